@@ -0,0 +1,85 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::codex::CodexSession;
+use crate::time_window::TimeWindow;
+use crate::transcript::{claude_project_dir, parse_timestamp, read_json_lines};
+
+// Pauses between messages shorter than this count as continuous work
+const IDLE_GAP: Duration = Duration::from_secs(30 * 60);
+
+/// Wall-clock time spent working in a worktree inside `window`, estimated
+/// from the message timestamps of its Claude sessions and of
+/// `codex_sessions`. Sessions running side by side are counted once.
+pub fn active_time_in(
+    worktree_path: &Path,
+    codex_sessions: &[CodexSession],
+    window: &TimeWindow,
+) -> Result<Duration> {
+    let mut timestamps = Vec::new();
+    if let Some(entries) = claude_project_dir(worktree_path).and_then(|dir| fs::read_dir(dir).ok())
+    {
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_some_and(|ext| ext == "jsonl") && window.admits_file(&path) {
+                timestamps.extend(read_json_lines(&path)?.iter().filter_map(parse_timestamp));
+            }
+        }
+    }
+    for session in codex_sessions {
+        timestamps.extend(
+            read_json_lines(&session.path)?
+                .iter()
+                .filter_map(parse_timestamp),
+        );
+    }
+    timestamps.retain(|at| window.contains(Some(*at)));
+    Ok(active_time(timestamps, IDLE_GAP))
+}
+
+/// Sum of the gaps between consecutive timestamps that are shorter than
+/// `idle_gap`.
+fn active_time(mut timestamps: Vec<DateTime<Utc>>, idle_gap: Duration) -> Duration {
+    timestamps.sort();
+    timestamps
+        .windows(2)
+        .filter_map(|pair| (pair[1] - pair[0]).to_std().ok())
+        .filter(|gap| *gap < idle_gap)
+        .sum()
+}
+
+/// Active time for display, e.g. `45m` or `3h 05m`.
+pub fn format_active_time(active: Duration) -> String {
+    let minutes = active.as_secs() / 60;
+    if minutes < 60 {
+        format!("{minutes}m")
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_only_gaps_shorter_than_the_idle_gap() {
+        let at = |value: &str| value.parse::<DateTime<Utc>>().unwrap();
+        let timestamps = vec![
+            at("2025-01-01T10:20:00Z"),
+            at("2025-01-01T10:00:00Z"),
+            at("2025-01-01T10:10:00Z"),
+            // A lunch break is not work
+            at("2025-01-01T12:00:00Z"),
+            at("2025-01-01T12:05:00Z"),
+            // A concurrent session in the same minutes adds nothing
+            at("2025-01-01T12:03:00Z"),
+        ];
+        let active = active_time(timestamps, IDLE_GAP);
+        assert_eq!(active, Duration::from_secs(25 * 60));
+        assert_eq!(format_active_time(active), "25m");
+        assert_eq!(format_active_time(Duration::from_secs(185 * 60)), "3h 05m");
+    }
+}
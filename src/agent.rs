@@ -0,0 +1,426 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::claude::get_claude_sessions;
+use crate::codex;
+use crate::input::smart_confirm_destructive;
+use crate::interrupt::TempFile;
+use crate::state::XlaudeState;
+
+/// Agent CLIs xlaude knows how to talk to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AgentKind {
+    Claude,
+    Codex,
+    Gemini,
+    Other,
+}
+
+impl AgentKind {
+    pub fn from_program(program: &str) -> Self {
+        let name = Path::new(program)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(program)
+            .to_ascii_lowercase();
+
+        match name.as_str() {
+            "claude" => AgentKind::Claude,
+            "codex" => AgentKind::Codex,
+            "gemini" => AgentKind::Gemini,
+            _ => AgentKind::Other,
+        }
+    }
+}
+
+/// How to install a missing agent CLI.
+pub fn install_hint(program: &str) -> String {
+    match AgentKind::from_program(program) {
+        AgentKind::Claude => "Install it with: npm install -g @anthropic-ai/claude-code".into(),
+        AgentKind::Codex => "Install it with: npm install -g @openai/codex".into(),
+        AgentKind::Gemini => "Install it with: npm install -g @google/gemini-cli".into(),
+        AgentKind::Other => format!(
+            "Make sure '{program}' is on PATH, or change the `agent` setting with 'xlaude config'"
+        ),
+    }
+}
+
+/// Environment that passes an initial prompt to an agent.
+pub struct InitialPrompt {
+    pub envs: Vec<(String, String)>,
+    // The prompt file, deleted when this is dropped
+    pub file: Option<TempFile>,
+}
+
+/// Add an initial prompt to an agent command line.
+///
+/// Claude and Codex take the prompt as a positional argument, Gemini through
+/// `--prompt-interactive`. Every agent also receives `XLAUDE_PROMPT`; agents
+/// without a known flag additionally get the prompt written to a temp file
+/// exposed as `XLAUDE_PROMPT_FILE`, which lives as long as the returned
+/// `InitialPrompt`. A `preview` only names the file without writing it.
+pub fn apply_initial_prompt(
+    program: &str,
+    args: &mut Vec<String>,
+    prompt: &str,
+    preview: bool,
+) -> Result<InitialPrompt> {
+    let mut envs = vec![("XLAUDE_PROMPT".to_string(), prompt.to_string())];
+    let mut prompt_file = None;
+
+    match AgentKind::from_program(program) {
+        AgentKind::Claude | AgentKind::Codex => args.push(prompt.to_string()),
+        AgentKind::Gemini => {
+            args.push("--prompt-interactive".to_string());
+            args.push(prompt.to_string());
+        }
+        AgentKind::Other if preview => {
+            envs.push(("XLAUDE_PROMPT_FILE".to_string(), "<prompt>".to_string()));
+        }
+        AgentKind::Other => {
+            let file = TempFile::create("xlaude-prompt", "md", prompt)
+                .context("Failed to write prompt file")?;
+            envs.push((
+                "XLAUDE_PROMPT_FILE".to_string(),
+                file.path().display().to_string(),
+            ));
+            prompt_file = Some(file);
+        }
+    }
+
+    Ok(InitialPrompt {
+        envs,
+        file: prompt_file,
+    })
+}
+
+/// First line of `<program> --version`. `None` when the program is not on PATH;
+/// `Some(None)` when it runs but prints no usable version.
+pub fn agent_version(program: &str) -> Option<Option<String>> {
+    match Command::new(program).arg("--version").output() {
+        Err(err) if err.kind() == ErrorKind::NotFound => None,
+        Err(_) => Some(None),
+        Ok(output) => Some(
+            output
+                .status
+                .success()
+                .then(|| {
+                    String::from_utf8_lossy(&output.stdout)
+                        .lines()
+                        .next()
+                        .map(|line| line.trim().to_string())
+                })
+                .flatten()
+                .filter(|line| !line.is_empty()),
+        ),
+    }
+}
+
+/// Flags that let the agent run commands and change files without asking.
+pub fn permission_bypass_flags(program: &str) -> &'static [&'static str] {
+    match AgentKind::from_program(program) {
+        AgentKind::Claude => &["--dangerously-skip-permissions"],
+        AgentKind::Codex => &["--dangerously-bypass-approvals-and-sandbox", "--yolo"],
+        AgentKind::Gemini => &["-y", "--yolo"],
+        AgentKind::Other => &[],
+    }
+}
+
+/// Whether someone is at the terminal to answer for an agent launch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Launch {
+    Interactive,
+    // `run`, commit message drafts, the dashboard: nobody to ask
+    Headless,
+}
+
+/// In a repository that is not trusted, keep an agent from starting with its
+/// permission prompts turned off. Interactive launches ask first and keep the
+/// flags when allowed; headless ones drop them. Every launch path goes
+/// through here, so `trusted` is the only way to a full-permission agent.
+pub fn guard_permissions(
+    state: &XlaudeState,
+    repo_name: &str,
+    program: &str,
+    args: &mut Vec<String>,
+    launch: Launch,
+) -> Result<()> {
+    let bypass = permission_bypass_flags(program);
+    let used: Vec<String> = args
+        .iter()
+        .filter(|arg| bypass.contains(&arg.as_str()))
+        .cloned()
+        .collect();
+    if used.is_empty() || state.repo_config(repo_name).trusted {
+        return Ok(());
+    }
+    let used = used.join(" ");
+
+    if launch == Launch::Headless {
+        eprintln!(
+            "{} Repository '{}' is not trusted; running {} without `{}`",
+            "🔒".green(),
+            repo_name,
+            program,
+            used
+        );
+        args.retain(|arg| !bypass.contains(&arg.as_str()));
+        return Ok(());
+    }
+
+    println!(
+        "{} Repository '{}' is not trusted: `{}` lets {} run any command without asking",
+        "⚠️".yellow(),
+        repo_name,
+        used,
+        program
+    );
+    if smart_confirm_destructive(
+        "skip_permissions",
+        &format!("Launch {program} with `{used}` anyway?"),
+        false,
+    )? {
+        println!(
+            "  {} Run {} to stop asking",
+            "💡".cyan(),
+            format!("xlaude trust {repo_name}").cyan()
+        );
+        return Ok(());
+    }
+    args.retain(|arg| !bypass.contains(&arg.as_str()));
+    println!(
+        "{} Launching {} with its permission prompts",
+        "🔒".green(),
+        program
+    );
+    Ok(())
+}
+
+/// Ask an agent command line for `model`, e.g. from `open --model`.
+///
+/// Claude, Codex, and Gemini all take `--model`, which replaces a model flag
+/// already in the command. Every agent also receives `XLAUDE_MODEL`, the only
+/// way to reach agents without a known flag. Returns the environment to set.
+pub fn apply_model(program: &str, args: &mut Vec<String>, model: &str) -> Vec<(String, String)> {
+    let kind = AgentKind::from_program(program);
+    if kind != AgentKind::Other {
+        // Claude has no `-m` short form
+        let short = kind != AgentKind::Claude;
+        let mut kept = Vec::with_capacity(args.len() + 2);
+        let mut rest = std::mem::take(args).into_iter();
+        while let Some(arg) = rest.next() {
+            if arg == "--model" || (short && arg == "-m") {
+                rest.next();
+            } else if !arg.starts_with("--model=") {
+                kept.push(arg);
+            }
+        }
+        kept.push("--model".to_string());
+        kept.push(model.to_string());
+        *args = kept;
+    }
+    vec![("XLAUDE_MODEL".to_string(), model.to_string())]
+}
+
+/// An earlier session of an agent that can be resumed.
+#[derive(Debug, Clone)]
+pub struct PastSession {
+    pub id: String,
+    // The session file the agent keeps its transcript in
+    pub path: PathBuf,
+    pub last_timestamp: Option<DateTime<Utc>>,
+    pub last_user_message: Option<String>,
+}
+
+/// Up to `limit` sessions `program` had in `worktree_path`, newest first, or
+/// `None` for agents whose sessions xlaude cannot resume.
+pub fn past_sessions(
+    program: &str,
+    worktree_path: &Path,
+    limit: usize,
+) -> Result<Option<Vec<PastSession>>> {
+    Ok(match AgentKind::from_program(program) {
+        AgentKind::Claude => Some(
+            get_claude_sessions(worktree_path)
+                .into_iter()
+                .take(limit)
+                .map(|session| PastSession {
+                    id: session.id,
+                    path: session.path,
+                    last_timestamp: session.last_timestamp,
+                    last_user_message: Some(session.last_user_message),
+                })
+                .collect(),
+        ),
+        AgentKind::Codex => Some(
+            codex::recent_sessions(worktree_path, limit)?
+                .0
+                .into_iter()
+                .map(|session| PastSession {
+                    id: session.id,
+                    path: session.path,
+                    last_timestamp: session.last_timestamp,
+                    last_user_message: session.last_user_message,
+                })
+                .collect(),
+        ),
+        AgentKind::Gemini | AgentKind::Other => None,
+    })
+}
+
+/// Make an agent command line resume session `id`: `claude --resume <id>` or
+/// `codex resume <id>`. Other agents are left alone.
+pub fn apply_resume(program: &str, args: &mut Vec<String>, id: &str) {
+    match AgentKind::from_program(program) {
+        AgentKind::Claude => args.extend(["--resume".to_string(), id.to_string()]),
+        AgentKind::Codex => args.extend(["resume".to_string(), id.to_string()]),
+        AgentKind::Gemini | AgentKind::Other => {}
+    }
+}
+
+/// Agent command line for a non-interactive run of a single instruction.
+pub struct HeadlessCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    // Written to the agent's stdin when the agent has no prompt flag
+    pub stdin: Option<String>,
+}
+
+/// Build a headless invocation: `claude -p`, `codex exec`, `gemini -p`.
+/// Other agents receive the instruction on stdin.
+pub fn headless_command(program: String, args: Vec<String>, prompt: &str) -> HeadlessCommand {
+    let mut headless_args = Vec::with_capacity(args.len() + 2);
+    let mut stdin = None;
+
+    match AgentKind::from_program(&program) {
+        AgentKind::Claude | AgentKind::Gemini => {
+            headless_args.extend(args);
+            headless_args.push("-p".to_string());
+            headless_args.push(prompt.to_string());
+        }
+        AgentKind::Codex => {
+            headless_args.push("exec".to_string());
+            headless_args.extend(args);
+            headless_args.push(prompt.to_string());
+        }
+        AgentKind::Other => {
+            headless_args.extend(args);
+            stdin = Some(prompt.to_string());
+        }
+    }
+
+    HeadlessCommand {
+        program,
+        args: headless_args,
+        stdin,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn detects_agent_kind_from_program_path() {
+        assert_eq!(AgentKind::from_program("claude"), AgentKind::Claude);
+        assert_eq!(
+            AgentKind::from_program("/usr/local/bin/codex"),
+            AgentKind::Codex
+        );
+        assert_eq!(AgentKind::from_program("Gemini"), AgentKind::Gemini);
+        assert_eq!(AgentKind::from_program("aider"), AgentKind::Other);
+    }
+
+    #[test]
+    fn headless_command_uses_print_modes() {
+        let cmd = headless_command("codex".to_string(), vec!["--full-auto".to_string()], "go");
+        assert_eq!(cmd.args, ["exec", "--full-auto", "go"]);
+        assert!(cmd.stdin.is_none());
+
+        let cmd = headless_command("claude".to_string(), Vec::new(), "go");
+        assert_eq!(cmd.args, ["-p", "go"]);
+
+        let cmd = headless_command("./agent.sh".to_string(), Vec::new(), "go");
+        assert!(cmd.args.is_empty());
+        assert_eq!(cmd.stdin.as_deref(), Some("go"));
+    }
+
+    #[test]
+    fn model_flag_replaces_the_configured_one() {
+        let mut args = vec![
+            "-m".to_string(),
+            "o3".to_string(),
+            "--full-auto".to_string(),
+        ];
+        apply_model("codex", &mut args, "gpt-5");
+        assert_eq!(args, ["--full-auto", "--model", "gpt-5"]);
+
+        let mut args = vec!["--model=sonnet".to_string()];
+        apply_model("claude", &mut args, "opus");
+        assert_eq!(args, ["--model", "opus"]);
+
+        let mut args = Vec::new();
+        let envs = apply_model("./agent.sh", &mut args, "opus");
+        assert!(args.is_empty());
+        assert_eq!(envs, [("XLAUDE_MODEL".to_string(), "opus".to_string())]);
+    }
+
+    #[test]
+    fn initial_prompt_uses_agent_specific_flags() {
+        let mut args = vec!["--dangerously-skip-permissions".to_string()];
+        apply_initial_prompt("claude", &mut args, "fix the bug", false).unwrap();
+        assert_eq!(args, ["--dangerously-skip-permissions", "fix the bug"]);
+
+        let mut args = vec!["-y".to_string()];
+        apply_initial_prompt("gemini", &mut args, "fix the bug", false).unwrap();
+        assert_eq!(args, ["-y", "--prompt-interactive", "fix the bug"]);
+
+        let mut args = Vec::new();
+        let prompt = apply_initial_prompt("aider", &mut args, "fix the bug", false).unwrap();
+        assert!(args.is_empty());
+        let path = prompt
+            .envs
+            .iter()
+            .find(|(key, _)| key == "XLAUDE_PROMPT_FILE")
+            .map(|(_, path)| PathBuf::from(path))
+            .unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fix the bug");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        drop(prompt);
+        assert!(!path.exists());
+
+        let prompt = apply_initial_prompt("aider", &mut args, "fix the bug", true).unwrap();
+        assert!(prompt.file.is_none());
+        assert!(
+            prompt
+                .envs
+                .contains(&("XLAUDE_PROMPT_FILE".to_string(), "<prompt>".to_string()))
+        );
+    }
+
+    #[test]
+    fn resume_uses_agent_specific_arguments() {
+        let mut args = vec!["--model".to_string(), "opus".to_string()];
+        apply_resume("claude", &mut args, "abc");
+        apply_initial_prompt("claude", &mut args, "go on", false).unwrap();
+        assert_eq!(args, ["--model", "opus", "--resume", "abc", "go on"]);
+
+        let mut args = Vec::new();
+        apply_resume("/opt/bin/codex", &mut args, "abc");
+        assert_eq!(args, ["resume", "abc"]);
+
+        let mut args = Vec::new();
+        apply_resume("gemini", &mut args, "abc");
+        assert!(args.is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::agent::AgentKind;
+
+/// What an agent CLI's local configuration says about its credentials. Only
+/// environment variables and credential files are inspected; no requests are
+/// made to the provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthStatus {
+    // Credentials come from this environment variable
+    ApiKey(&'static str),
+    // A login is on file, with the account or plan when known
+    LoggedIn(Option<String>),
+    // The access token expired at this time but a refresh token is on file
+    Refreshable(DateTime<Utc>),
+    // The login expired at this time and cannot be refreshed
+    Expired(DateTime<Utc>),
+    LoggedOut,
+    // Agents xlaude has no credential layout for
+    Unknown,
+}
+
+impl AuthStatus {
+    /// Whether the agent is unable to start a session without a new login.
+    pub fn needs_login(&self) -> bool {
+        matches!(self, AuthStatus::Expired(_) | AuthStatus::LoggedOut)
+    }
+}
+
+/// Inspect the credentials of `kind` as seen by the current user.
+pub fn check(kind: AgentKind) -> AuthStatus {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+        return AuthStatus::Unknown;
+    };
+    check_in(kind, &home, |name| std::env::var(name).ok(), Utc::now())
+}
+
+fn check_in(
+    kind: AgentKind,
+    home: &Path,
+    env: impl Fn(&str) -> Option<String>,
+    now: DateTime<Utc>,
+) -> AuthStatus {
+    let env_key = |names: &[&'static str]| {
+        names
+            .iter()
+            .copied()
+            .find(|name| env(name).is_some_and(|value| !value.is_empty()))
+    };
+    match kind {
+        AgentKind::Claude => {
+            if let Some(name) = env_key(&[
+                "ANTHROPIC_API_KEY",
+                "ANTHROPIC_AUTH_TOKEN",
+                "CLAUDE_CODE_OAUTH_TOKEN",
+                "CLAUDE_CODE_USE_BEDROCK",
+                "CLAUDE_CODE_USE_VERTEX",
+            ]) {
+                return AuthStatus::ApiKey(name);
+            }
+            let config_dir = env("CLAUDE_CONFIG_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.join(".claude"));
+            let credentials = read_json(&config_dir.join(".credentials.json"));
+            if let Some(oauth) = credentials.as_ref().and_then(|c| c.get("claudeAiOauth")) {
+                let plan = oauth
+                    .get("subscriptionType")
+                    .and_then(Value::as_str)
+                    .map(|plan| format!("{plan} plan"));
+                return token_status(oauth, "expiresAt", "refreshToken", plan, now);
+            }
+            // On macOS the tokens live in the keychain; the account is still recorded here
+            let account = read_json(&home.join(".claude.json")).and_then(|config| {
+                config
+                    .pointer("/oauthAccount/emailAddress")
+                    .and_then(Value::as_str)
+                    .map(String::from)
+            });
+            match account {
+                Some(email) => AuthStatus::LoggedIn(Some(email)),
+                None => AuthStatus::LoggedOut,
+            }
+        }
+        AgentKind::Codex => {
+            if let Some(name) = env_key(&["OPENAI_API_KEY"]) {
+                return AuthStatus::ApiKey(name);
+            }
+            let codex_home = env("CODEX_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.join(".codex"));
+            let Some(auth) = read_json(&codex_home.join("auth.json")) else {
+                return AuthStatus::LoggedOut;
+            };
+            if auth
+                .get("OPENAI_API_KEY")
+                .and_then(Value::as_str)
+                .is_some_and(|key| !key.is_empty())
+            {
+                return AuthStatus::ApiKey("auth.json");
+            }
+            // ChatGPT logins refresh their access token on their own
+            if auth
+                .pointer("/tokens/refresh_token")
+                .and_then(Value::as_str)
+                .is_some_and(|token| !token.is_empty())
+            {
+                return AuthStatus::LoggedIn(None);
+            }
+            AuthStatus::LoggedOut
+        }
+        AgentKind::Gemini => {
+            if let Some(name) = env_key(&[
+                "GEMINI_API_KEY",
+                "GOOGLE_API_KEY",
+                "GOOGLE_GENAI_USE_VERTEXAI",
+            ]) {
+                return AuthStatus::ApiKey(name);
+            }
+            match read_json(&home.join(".gemini").join("oauth_creds.json")) {
+                Some(creds) => token_status(&creds, "expiry_date", "refresh_token", None, now),
+                None => AuthStatus::LoggedOut,
+            }
+        }
+        AgentKind::Other => AuthStatus::Unknown,
+    }
+}
+
+/// Classify an OAuth token record whose expiry is stored in epoch milliseconds.
+fn token_status(
+    token: &Value,
+    expiry_field: &str,
+    refresh_field: &str,
+    detail: Option<String>,
+    now: DateTime<Utc>,
+) -> AuthStatus {
+    let expires_at = token
+        .get(expiry_field)
+        .and_then(Value::as_i64)
+        .and_then(DateTime::from_timestamp_millis);
+    let refreshable = token
+        .get(refresh_field)
+        .and_then(Value::as_str)
+        .is_some_and(|refresh| !refresh.is_empty());
+    match expires_at {
+        Some(expires_at) if expires_at <= now && refreshable => AuthStatus::Refreshable(expires_at),
+        Some(expires_at) if expires_at <= now => AuthStatus::Expired(expires_at),
+        _ => AuthStatus::LoggedIn(detail),
+    }
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn write(path: PathBuf, value: Value) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, value.to_string()).unwrap();
+    }
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn classifies_claude_and_gemini_logins() {
+        let home = tempfile::tempdir().unwrap();
+        let now = Utc::now();
+        let at = |offset_ms: i64| now.timestamp_millis() + offset_ms;
+
+        assert_eq!(
+            check_in(AgentKind::Claude, home.path(), no_env, now),
+            AuthStatus::LoggedOut
+        );
+        assert_eq!(
+            check_in(
+                AgentKind::Claude,
+                home.path(),
+                |name: &str| (name == "ANTHROPIC_API_KEY").then(|| "sk".to_string()),
+                now
+            ),
+            AuthStatus::ApiKey("ANTHROPIC_API_KEY")
+        );
+
+        let credentials = home.path().join(".claude").join(".credentials.json");
+        write(
+            credentials.clone(),
+            json!({ "claudeAiOauth": { "expiresAt": at(60_000), "subscriptionType": "max" } }),
+        );
+        assert_eq!(
+            check_in(AgentKind::Claude, home.path(), no_env, now),
+            AuthStatus::LoggedIn(Some("max plan".into()))
+        );
+        write(
+            credentials,
+            json!({ "claudeAiOauth": { "expiresAt": at(-60_000), "refreshToken": "" } }),
+        );
+        assert!(check_in(AgentKind::Claude, home.path(), no_env, now).needs_login());
+
+        write(
+            home.path().join(".gemini").join("oauth_creds.json"),
+            json!({ "expiry_date": at(-60_000), "refresh_token": "r" }),
+        );
+        assert!(matches!(
+            check_in(AgentKind::Gemini, home.path(), no_env, now),
+            AuthStatus::Refreshable(_)
+        ));
+    }
+
+    #[test]
+    fn reads_codex_auth_file() {
+        let home = tempfile::tempdir().unwrap();
+        let auth = home.path().join(".codex").join("auth.json");
+
+        write(
+            auth.clone(),
+            json!({ "OPENAI_API_KEY": null, "tokens": { "refresh_token": "r" } }),
+        );
+        assert_eq!(
+            check_in(AgentKind::Codex, home.path(), no_env, Utc::now()),
+            AuthStatus::LoggedIn(None)
+        );
+        write(auth, json!({ "OPENAI_API_KEY": null }));
+        assert_eq!(
+            check_in(AgentKind::Codex, home.path(), no_env, Utc::now()),
+            AuthStatus::LoggedOut
+        );
+    }
+}
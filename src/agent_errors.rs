@@ -0,0 +1,281 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::claude::SessionInfo;
+use crate::codex::CodexSession;
+use crate::transcript::{message_text, parse_timestamp, read_json_lines};
+
+// Longest error message kept, in characters
+const MESSAGE_LIMIT: usize = 160;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentErrorKind {
+    RateLimit,
+    Overloaded,
+    Auth,
+    Other,
+}
+
+impl AgentErrorKind {
+    /// Sort an error message by the HTTP status or the words it contains.
+    fn classify(message: &str) -> Self {
+        let text = message.to_lowercase();
+        let status = |code: &str| {
+            text.split(|c: char| !c.is_ascii_digit())
+                .any(|part| part == code)
+        };
+        let mentions = |words: &[&str]| words.iter().any(|word| text.contains(word));
+        if status("429")
+            || mentions(&[
+                "rate limit",
+                "rate_limit",
+                "too many requests",
+                "usage limit",
+                "quota",
+            ])
+        {
+            AgentErrorKind::RateLimit
+        } else if status("529") || mentions(&["overloaded"]) {
+            AgentErrorKind::Overloaded
+        } else if status("401")
+            || status("403")
+            || mentions(&[
+                "authentication",
+                "unauthorized",
+                "invalid api key",
+                "/login",
+                "oauth token",
+            ])
+        {
+            AgentErrorKind::Auth
+        } else {
+            AgentErrorKind::Other
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AgentErrorKind::RateLimit => "rate_limit",
+            AgentErrorKind::Overloaded => "overloaded",
+            AgentErrorKind::Auth => "auth",
+            AgentErrorKind::Other => "other",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AgentErrorKind::RateLimit => "hit rate limits",
+            AgentErrorKind::Overloaded => "found the API overloaded",
+            AgentErrorKind::Auth => "failed to authenticate",
+            AgentErrorKind::Other => "ended on an API error",
+        }
+    }
+}
+
+/// The API error an agent's session ended on, with no reply from the model
+/// after it: the reason the agent stopped making progress.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentError {
+    // "claude" or "codex"
+    pub agent: String,
+    pub session_id: String,
+    pub kind: AgentErrorKind,
+    // First line of the last error, as the agent logged it
+    pub message: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    // Errors since the model last replied, retries included
+    pub count: usize,
+}
+
+impl AgentError {
+    /// "Last run hit rate limits", with the number of attempts when retried.
+    pub fn summary(&self) -> String {
+        let mut summary = format!("Last run {}", self.kind.label());
+        if self.count > 1 {
+            summary.push_str(&format!(" ({} attempts)", self.count));
+        }
+        summary
+    }
+}
+
+pub(crate) enum Event {
+    Reply,
+    Error(String),
+}
+
+/// The error the most recent of a worktree's sessions (newest first, as
+/// `list` and the dashboard load them) ended on, if any.
+pub fn last_run_error(claude: &[SessionInfo], codex: &[CodexSession]) -> Option<AgentError> {
+    let (agent, session_id, path, event): (_, _, _, fn(&Value) -> Option<Event>) =
+        match (claude.first(), codex.first()) {
+            (Some(claude), Some(codex)) if codex.last_timestamp > claude.last_timestamp => {
+                ("codex", &codex.id, &codex.path, codex_event)
+            }
+            (Some(claude), _) => ("claude", &claude.id, &claude.path, claude_event),
+            (None, Some(codex)) => ("codex", &codex.id, &codex.path, codex_event),
+            (None, None) => return None,
+        };
+    let lines = read_json_lines(path).ok()?;
+    let (message, timestamp, count) = ending_error(&lines, event)?;
+    Some(AgentError {
+        agent: agent.to_string(),
+        session_id: session_id.clone(),
+        kind: AgentErrorKind::classify(&message),
+        message: message
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .take(MESSAGE_LIMIT)
+            .collect(),
+        timestamp,
+        count,
+    })
+}
+
+/// The last error in `lines` when no reply follows it, when it happened, and
+/// how many errors came since the last reply.
+fn ending_error(
+    lines: &[Value],
+    event: fn(&Value) -> Option<Event>,
+) -> Option<(String, Option<DateTime<Utc>>, usize)> {
+    let mut last = None;
+    let mut count = 0;
+    for line in lines {
+        match event(line) {
+            Some(Event::Reply) => {
+                last = None;
+                count = 0;
+            }
+            Some(Event::Error(message)) => {
+                count += 1;
+                last = Some((message, parse_timestamp(line)));
+            }
+            None => {}
+        }
+    }
+    last.map(|(message, timestamp)| (message, timestamp, count))
+}
+
+/// Claude logs failed requests as assistant messages flagged
+/// `isApiErrorMessage` ("API Error: 429 ...") and retries as `api_error`
+/// system entries.
+pub(crate) fn claude_event(value: &Value) -> Option<Event> {
+    match value.get("type")?.as_str()? {
+        "assistant" => {
+            let failed = value.get("isApiErrorMessage").and_then(Value::as_bool) == Some(true)
+                || value.get("error").is_some();
+            if !failed {
+                return Some(Event::Reply);
+            }
+            let text = value
+                .get("message")
+                .and_then(|message| message.get("content"))
+                .and_then(message_text)
+                .unwrap_or_else(|| error_text(&value["error"]));
+            Some(Event::Error(text))
+        }
+        "system"
+            if value.get("subtype").and_then(Value::as_str) == Some("api_error")
+                || value.get("level").and_then(Value::as_str) == Some("error") =>
+        {
+            let text = value
+                .get("content")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| error_text(&value["error"]));
+            Some(Event::Error(text))
+        }
+        _ => None,
+    }
+}
+
+/// Codex logs `error` and `stream_error` events; older logs have no
+/// `event_msg` wrapper.
+pub(crate) fn codex_event(value: &Value) -> Option<Event> {
+    let item = match value.get("type")?.as_str()? {
+        "event_msg" | "response_item" => value.get("payload")?,
+        _ => value,
+    };
+    match item.get("type")?.as_str()? {
+        "error" | "stream_error" => Some(Event::Error(
+            item.get("message").map(error_text).unwrap_or_default(),
+        )),
+        "agent_message" => Some(Event::Reply),
+        "message" if item.get("role").and_then(Value::as_str) == Some("assistant") => {
+            Some(Event::Reply)
+        }
+        _ => None,
+    }
+}
+
+/// A readable message from an error that is a string or an API error object.
+fn error_text(error: &Value) -> String {
+    if let Some(text) = error.as_str() {
+        return text.to_string();
+    }
+    let status = error.get("status").and_then(Value::as_u64);
+    let message = ["/error/error/message", "/error/message", "/message"]
+        .iter()
+        .find_map(|pointer| error.pointer(pointer).and_then(Value::as_str));
+    match (status, message) {
+        (Some(status), Some(message)) => format!("{status} {message}"),
+        (None, Some(message)) => message.to_string(),
+        _ => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn sessions_ending_on_errors_are_classified() {
+        let reply = json!({"type": "assistant", "message": {"content": "Done"}});
+        let rate_limited = json!({
+            "type": "assistant",
+            "isApiErrorMessage": true,
+            "timestamp": "2025-01-05T10:00:00Z",
+            "message": {"content": "API Error: 429 {\"type\":\"error\",\"error\":{\"type\":\"rate_limit_error\"}}"}
+        });
+        let retry = json!({
+            "type": "system",
+            "subtype": "api_error",
+            "error": {"status": 529, "error": {"error": {"message": "Overloaded"}}}
+        });
+
+        let (message, timestamp, count) = ending_error(
+            &[reply.clone(), retry.clone(), rate_limited.clone()],
+            claude_event,
+        )
+        .unwrap();
+        assert_eq!(
+            AgentErrorKind::classify(&message),
+            AgentErrorKind::RateLimit
+        );
+        assert!(timestamp.is_some());
+        assert_eq!(count, 2);
+        // A reply after the errors means the agent recovered
+        assert!(ending_error(&[rate_limited, retry.clone(), reply], claude_event).is_none());
+        let (message, ..) = ending_error(&[retry], claude_event).unwrap();
+        assert_eq!(message, "529 Overloaded");
+        assert_eq!(
+            AgentErrorKind::classify(&message),
+            AgentErrorKind::Overloaded
+        );
+
+        let codex = [
+            json!({"type": "event_msg", "payload": {"type": "agent_message", "message": "ok"}}),
+            json!({"type": "event_msg", "payload": {"type": "error", "message": "unexpected status 401 Unauthorized"}}),
+        ];
+        let (message, ..) = ending_error(&codex, codex_event).unwrap();
+        assert_eq!(AgentErrorKind::classify(&message), AgentErrorKind::Auth);
+        assert_eq!(
+            AgentErrorKind::classify("request 14290 failed"),
+            AgentErrorKind::Other
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::state::get_config_dir;
+
+// Snapshots kept when `state_backups` is not configured
+pub const DEFAULT_KEEP: usize = 20;
+// Snapshot ids sort in the order they were taken
+const ID_FORMAT: &str = "%Y%m%d-%H%M%S%.3f";
+
+/// A copy of the state file taken before it was overwritten.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub id: String,
+    pub path: PathBuf,
+    pub taken_at: DateTime<Utc>,
+}
+
+fn backup_dir() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("backups"))
+}
+
+/// Save `content`, the state file as it was before a save, as a new snapshot
+/// and delete all but the newest `keep`. Nothing is kept when `keep` is 0.
+pub fn snapshot(content: &str, keep: usize) -> Result<()> {
+    snapshot_in(&backup_dir()?, content, keep)
+}
+
+fn snapshot_in(dir: &Path, content: &str, keep: usize) -> Result<()> {
+    if keep == 0 {
+        return Ok(());
+    }
+    fs::create_dir_all(dir).context("Failed to create the backup directory")?;
+    let id = Utc::now().format(ID_FORMAT).to_string();
+    let path = dir.join(format!("state-{id}.json"));
+    // Several saves within a millisecond: the first snapshot holds the oldest state
+    if !path.exists() {
+        fs::write(&path, content).context("Failed to write state backup")?;
+    }
+    for old in list_in(dir)?.iter().skip(keep) {
+        let _ = fs::remove_file(&old.path);
+    }
+    Ok(())
+}
+
+/// All snapshots, newest first.
+pub fn list() -> Result<Vec<Snapshot>> {
+    list_in(&backup_dir()?)
+}
+
+fn list_in(dir: &Path) -> Result<Vec<Snapshot>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).context("Failed to read the backup directory"),
+    };
+    let mut snapshots: Vec<Snapshot> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name();
+            let id = file_name
+                .to_str()?
+                .strip_prefix("state-")?
+                .strip_suffix(".json")?
+                .to_string();
+            let taken_at = NaiveDateTime::parse_from_str(&id, ID_FORMAT)
+                .ok()?
+                .and_utc();
+            Some(Snapshot {
+                id,
+                path: entry.path(),
+                taken_at,
+            })
+        })
+        .collect();
+    snapshots.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(snapshots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_only_the_newest_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        for n in 0..4 {
+            snapshot_in(dir.path(), &format!("{{\"n\": {n}}}"), 2).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let snapshots = list_in(dir.path()).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert!(snapshots[0].taken_at >= snapshots[1].taken_at);
+        assert_eq!(
+            fs::read_to_string(&snapshots[0].path).unwrap(),
+            "{\"n\": 3}"
+        );
+
+        snapshot_in(dir.path(), "{}", 0).unwrap();
+        assert_eq!(list_in(dir.path()).unwrap().len(), 2);
+        assert!(list_in(&dir.path().join("missing")).unwrap().is_empty());
+    }
+}
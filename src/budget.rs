@@ -0,0 +1,269 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Local, TimeZone, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::codex;
+use crate::error::{ErrorCategory, WithCategory};
+use crate::state::{UsageBudget, XlaudeState};
+use crate::time_window::TimeWindow;
+use crate::usage::{self, TokenUsage, format_dollars, format_tokens};
+
+// Share of a budget used before warnings start, unless configured
+const DEFAULT_WARN_AT: f64 = 0.8;
+
+/// Usage this month measured against one configured budget.
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    // Repository the budget applies to; `None` for the budget across all of them
+    pub repo: Option<String>,
+    pub used: TokenUsage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dollar_limit: Option<f64>,
+    pub warn_at: f64,
+}
+
+impl BudgetStatus {
+    /// The larger of the used shares of the token and dollar limits.
+    pub fn fraction_used(&self) -> f64 {
+        let tokens = self
+            .token_limit
+            .map(|limit| self.used.total() as f64 / limit.max(1) as f64);
+        let dollars = self
+            .dollar_limit
+            .map(|limit| self.used.cost_usd / limit.max(f64::EPSILON));
+        tokens.into_iter().chain(dollars).fold(0.0, f64::max)
+    }
+
+    fn scope(&self) -> &str {
+        self.repo.as_deref().unwrap_or("All repositories")
+    }
+
+    /// One line such as `api: 12.3M of 20.0M tokens (62%), $41.20 of $100.00 (41%)`.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(limit) = self.token_limit {
+            parts.push(format!(
+                "{} of {} tokens ({:.0}%)",
+                format_tokens(self.used.total()),
+                format_tokens(limit),
+                percent(self.used.total() as f64, limit as f64)
+            ));
+        }
+        if let Some(limit) = self.dollar_limit {
+            parts.push(format!(
+                "{} of {} ({:.0}%)",
+                format_dollars(self.used.cost_usd),
+                format_dollars(limit),
+                percent(self.used.cost_usd, limit)
+            ));
+        }
+        format!("{}: {}", self.scope(), parts.join(", "))
+    }
+
+    /// Warning once the warn_at threshold of the budget is crossed.
+    pub fn warning(&self) -> Option<String> {
+        let fraction = self.fraction_used();
+        if fraction >= 1.0 {
+            Some(format!(
+                "{} exceeded its monthly usage budget: {}",
+                self.scope(),
+                self.summary()
+            ))
+        } else if fraction >= self.warn_at {
+            Some(format!(
+                "{} has used {:.0}% of its monthly usage budget: {}",
+                self.scope(),
+                fraction * 100.0,
+                self.summary()
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+fn percent(used: f64, limit: f64) -> f64 {
+    if limit > 0.0 {
+        used / limit * 100.0
+    } else {
+        100.0
+    }
+}
+
+/// Every configured budget with this month's usage of managed worktrees, the
+/// global budget first. Nothing is scanned when no budget is configured.
+pub fn check(state: &XlaudeState) -> Result<Vec<BudgetStatus>> {
+    let mut configured: Vec<(Option<String>, &UsageBudget)> = Vec::new();
+    if let Some(budget) = &state.usage_budget {
+        configured.push((None, budget));
+    }
+    let mut repo_budgets: Vec<_> = state
+        .repos
+        .iter()
+        .filter_map(|(repo, config)| config.usage_budget.as_ref().map(|budget| (repo, budget)))
+        .collect();
+    repo_budgets.sort_by(|a, b| a.0.cmp(b.0));
+    configured.extend(
+        repo_budgets
+            .into_iter()
+            .map(|(repo, budget)| (Some(repo.clone()), budget)),
+    );
+    if configured.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let by_repo = month_usage_by_repo(state, Utc::now())?;
+    configured
+        .into_iter()
+        .map(|(repo, budget)| {
+            let used = match &repo {
+                Some(repo) => by_repo.get(repo).copied().unwrap_or_default(),
+                None => by_repo
+                    .values()
+                    .fold(TokenUsage::default(), |mut sum, used| {
+                        sum += *used;
+                        sum
+                    }),
+            };
+            status(repo, budget, used)
+        })
+        .collect()
+}
+
+/// Warnings for every budget whose threshold is crossed.
+pub fn warnings(statuses: &[BudgetStatus]) -> Vec<String> {
+    statuses.iter().filter_map(BudgetStatus::warning).collect()
+}
+
+fn status(repo: Option<String>, budget: &UsageBudget, used: TokenUsage) -> Result<BudgetStatus> {
+    let scope = repo
+        .as_deref()
+        .map(|repo| format!("repos.{repo}.usage_budget"))
+        .unwrap_or_else(|| "usage_budget".to_string());
+    let token_limit = budget
+        .tokens
+        .as_deref()
+        .map(parse_token_count)
+        .transpose()
+        .with_category(ErrorCategory::Config, || {
+            format!("Invalid {scope}.tokens in state.json")
+        })?;
+    let warn_at = budget.warn_at.unwrap_or(DEFAULT_WARN_AT);
+    if !(0.0..=1.0).contains(&warn_at) {
+        crate::bail_with!(
+            ErrorCategory::Config,
+            "Invalid {}.warn_at in state.json: {} is not between 0 and 1",
+            scope,
+            warn_at
+        );
+    }
+    Ok(BudgetStatus {
+        repo,
+        used,
+        token_limit,
+        dollar_limit: budget.dollars,
+        warn_at,
+    })
+}
+
+/// Usage since the first of the current local month, per repository.
+fn month_usage_by_repo(
+    state: &XlaudeState,
+    now: DateTime<Utc>,
+) -> Result<HashMap<String, TokenUsage>> {
+    let window = TimeWindow {
+        since: Some(month_start(now)),
+        until: None,
+    };
+    let paths: Vec<PathBuf> = state
+        .worktrees
+        .values()
+        .map(|info| info.path.clone())
+        .collect();
+    let codex_sessions = codex::collect_sessions_for_paths_in(&paths, usize::MAX, &window)?;
+
+    let mut by_repo: HashMap<String, TokenUsage> = HashMap::new();
+    for info in state.worktrees.values() {
+        let mut used = usage::claude_usage_in(&info.path, &window)?;
+        let key = codex::normalized_worktree_path(&info.path);
+        for session in codex_sessions.get(&key).into_iter().flatten() {
+            used += usage::codex_usage_in(&session.path, &window)?;
+        }
+        *by_repo.entry(info.repo_name.clone()).or_default() += used;
+    }
+    Ok(by_repo)
+}
+
+fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    let local = now.with_timezone(&Local);
+    Local
+        .with_ymd_and_hms(local.year(), local.month(), 1, 0, 0, 0)
+        .earliest()
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or(now)
+}
+
+/// Parse a token count such as `500k`, `20M`, or `1.5B` (decimal units).
+pub fn parse_token_count(input: &str) -> Result<u64> {
+    let trimmed = input.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit() && c != '.' && c != '_')
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let value: f64 = number
+        .replace('_', "")
+        .parse()
+        .with_context(|| format!("Invalid token count: {input}"))?;
+    let multiplier = match unit.trim().to_ascii_lowercase().as_str() {
+        "" => 1.0,
+        "k" => 1e3,
+        "m" => 1e6,
+        "b" | "g" => 1e9,
+        other => anyhow::bail!("Unknown token unit '{}' in '{}'", other, input),
+    };
+    Ok((value * multiplier) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget_status(tokens: u64, cost_usd: f64) -> BudgetStatus {
+        BudgetStatus {
+            repo: Some("api".into()),
+            used: TokenUsage {
+                input_tokens: tokens,
+                output_tokens: 0,
+                cost_usd,
+            },
+            token_limit: Some(1_000_000),
+            dollar_limit: Some(10.0),
+            warn_at: 0.8,
+        }
+    }
+
+    #[test]
+    fn parses_token_counts() {
+        assert_eq!(parse_token_count("500k").unwrap(), 500_000);
+        assert_eq!(parse_token_count("1.5M").unwrap(), 1_500_000);
+        assert_eq!(parse_token_count("2B").unwrap(), 2_000_000_000);
+        assert_eq!(parse_token_count("1_000").unwrap(), 1_000);
+        assert!(parse_token_count("lots").is_err());
+        assert!(parse_token_count("5T").is_err());
+    }
+
+    #[test]
+    fn warns_past_the_threshold_of_either_limit() {
+        assert_eq!(budget_status(500_000, 1.0).warning(), None);
+        // The dollar limit is closer to being reached
+        let warning = budget_status(500_000, 9.0).warning().unwrap();
+        assert!(warning.starts_with("api has used 90% of its monthly usage budget"));
+        assert!(warning.contains("500.0k of 1.0M tokens (50%), $9.00 of $10.00 (90%)"));
+        let exceeded = budget_status(1_200_000, 1.0).warning().unwrap();
+        assert!(exceeded.starts_with("api exceeded its monthly usage budget"));
+    }
+}
@@ -1,33 +1,59 @@
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::fs;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug)]
+use crate::outcome::{SessionOutcome, claude_outcome};
+use crate::time_window::TimeWindow;
+use crate::transcript::{
+    Role, claude_project_dir, is_system_message, message_text, parse_timestamp,
+};
+
+// Flags Claude Code sets on lines that are not part of the conversation:
+// subagent messages (written to the session itself by versions before 2.0,
+// and to `agent-*.jsonl` files next to it since), lines it adds itself, and
+// the summary a compacted session continues from
+const NON_CONVERSATION_FLAGS: &[&str] = &[
+    "isSidechain",
+    "isMeta",
+    "isCompactSummary",
+    "isVisibleInTranscriptOnly",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SessionInfo {
+    // The session file's name, which `claude --resume` takes
+    pub id: String,
+    pub path: PathBuf,
     pub last_user_message: String,
+    pub first_timestamp: Option<DateTime<Utc>>,
     pub last_timestamp: Option<DateTime<Utc>>,
+    // How the session ended, from its trailing events
+    pub outcome: Option<SessionOutcome>,
 }
 
-pub fn get_claude_sessions(project_path: &Path) -> Vec<SessionInfo> {
-    // Get home directory
-    let Ok(home) = std::env::var("HOME") else {
-        return vec![];
-    };
+/// What one pass over a session file found.
+#[derive(Debug, Default)]
+struct SessionScan {
+    last_user_message: Option<String>,
+    first_timestamp: Option<DateTime<Utc>>,
+    last_timestamp: Option<DateTime<Utc>>,
+    outcome: Option<SessionOutcome>,
+}
 
-    // Construct path to Claude projects directory
-    let claude_projects_dir = Path::new(&home).join(".claude").join("projects");
+pub fn get_claude_sessions(project_path: &Path) -> Vec<SessionInfo> {
+    get_claude_sessions_in(project_path, &TimeWindow::default())
+}
 
-    // Get canonical path of the project
-    let Ok(canonical_path) = project_path.canonicalize() else {
+/// Claude sessions of a project that were active inside `window`, newest first.
+pub fn get_claude_sessions_in(project_path: &Path, window: &TimeWindow) -> Vec<SessionInfo> {
+    let Some(project_dir) = claude_project_dir(project_path) else {
         return vec![];
     };
 
-    // Convert path to Claude's format (replace / with -)
-    let encoded_path = canonical_path.to_string_lossy().replace('/', "-");
-
-    let project_dir = claude_projects_dir.join(&encoded_path);
-
     // List session files (.jsonl files)
     let mut sessions = vec![];
     if let Ok(entries) = fs::read_dir(&project_dir) {
@@ -36,72 +62,20 @@ pub fn get_claude_sessions(project_path: &Path) -> Vec<SessionInfo> {
                 && std::path::Path::new(name)
                     .extension()
                     .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"))
+                && window.admits_file(&entry.path())
             {
-                // Read session data from the file
-                let mut last_user_message = String::new();
-                let mut last_timestamp = None;
-
-                if let Ok(file) = fs::File::open(entry.path()) {
-                    let reader = BufReader::new(file);
-                    let mut user_messages = Vec::new();
-
-                    for line in reader.lines().map_while(Result::ok) {
-                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&line)
-                            && json.get("type").and_then(|t| t.as_str()) == Some("user")
-                        {
-                            // Extract timestamp
-                            if let Some(ts_str) = json.get("timestamp").and_then(|t| t.as_str())
-                                && let Ok(ts) = DateTime::parse_from_rfc3339(ts_str)
-                            {
-                                last_timestamp = Some(ts.with_timezone(&Utc));
-                            }
-
-                            // Extract message content
-                            if let Some(message) = json.get("message") {
-                                let content =
-                                    message.get("content").and_then(|c| c.as_str()).map_or_else(
-                                        || {
-                                            message
-                                                .get("content")
-                                                .and_then(|c| c.as_array())
-                                                .map_or_else(String::new, |content_arr| {
-                                                    content_arr
-                                                        .iter()
-                                                        .filter_map(|item| {
-                                                            item.get("text")
-                                                                .and_then(|t| t.as_str())
-                                                        })
-                                                        .collect::<Vec<_>>()
-                                                        .join(" ")
-                                                })
-                                        },
-                                        std::string::ToString::to_string,
-                                    );
-
-                                // Filter out system messages and empty content
-                                if !content.is_empty()
-                                    && !content.starts_with("<local-command")
-                                    && !content.starts_with("<command-")
-                                    && !content.starts_with("Caveat:")
-                                    && !content.contains("[Request interrupted")
-                                {
-                                    user_messages.push(content);
-                                }
-                            }
-                        }
-                    }
-
-                    // Get the last meaningful user message
-                    if let Some(msg) = user_messages.last() {
-                        last_user_message.clone_from(msg);
-                    }
-                }
-
-                // Only add sessions with user messages
-                if !last_user_message.is_empty() {
+                let scan = scan_session(&entry.path());
+                // Only add sessions with user messages; subagent files have none
+                if let Some(last_user_message) = scan.last_user_message
+                    && window.overlaps(scan.first_timestamp, scan.last_timestamp)
+                {
                     sessions.push(SessionInfo {
+                        id: name.trim_end_matches(".jsonl").to_string(),
+                        path: entry.path(),
                         last_user_message,
-                        last_timestamp,
+                        first_timestamp: scan.first_timestamp,
+                        last_timestamp: scan.last_timestamp,
+                        outcome: scan.outcome,
                     });
                 }
             }
@@ -117,3 +91,130 @@ pub fn get_claude_sessions(project_path: &Path) -> Vec<SessionInfo> {
     });
     sessions
 }
+
+fn scan_session(path: &Path) -> SessionScan {
+    let mut scan = SessionScan::default();
+    let Ok(file) = fs::File::open(path) else {
+        return scan;
+    };
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let Ok(json) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        scan.outcome = claude_outcome(&json).or(scan.outcome);
+        // Tool results are user lines too, so they keep tool-heavy sessions current
+        if json.get("type").and_then(Value::as_str) == Some("user")
+            && let Some(timestamp) = parse_timestamp(&json)
+        {
+            scan.last_timestamp = Some(timestamp);
+            scan.first_timestamp = scan.first_timestamp.or(scan.last_timestamp);
+        }
+        if let Some((Role::User, text)) = conversation_message(&json) {
+            scan.last_user_message = Some(text);
+        }
+    }
+    scan
+}
+
+/// Whether a session line belongs to the conversation between the user and
+/// the main agent.
+pub(crate) fn is_conversation_line(value: &Value) -> bool {
+    !NON_CONVERSATION_FLAGS
+        .iter()
+        .any(|flag| value.get(*flag).and_then(Value::as_bool) == Some(true))
+}
+
+/// Role and text of a session line that carries a message of the
+/// conversation. Summaries, snapshots, subagent and injected lines, tool
+/// calls and tool results, and user messages Claude Code wrote itself, such
+/// as slash command output, yield `None`.
+pub(crate) fn conversation_message(value: &Value) -> Option<(Role, String)> {
+    if !is_conversation_line(value) {
+        return None;
+    }
+    let message = value.get("message")?;
+    // The role is the line's type; older lines without one name it in the message
+    let role = match value
+        .get("type")
+        .or_else(|| message.get("role"))?
+        .as_str()?
+    {
+        "user" => Role::User,
+        "assistant" => Role::Assistant,
+        _ => return None,
+    };
+    let content = message.get("content")?;
+    let text = match (role, content.as_array()) {
+        // Newer versions add `<system-reminder>` blocks to the user's message
+        (Role::User, Some(blocks)) => {
+            let typed: Vec<Value> = blocks
+                .iter()
+                .filter(|block| {
+                    !block
+                        .get("text")
+                        .and_then(Value::as_str)
+                        .is_some_and(is_system_message)
+                })
+                .cloned()
+                .collect();
+            message_text(&Value::Array(typed))?
+        }
+        _ => message_text(content)?,
+    };
+    let text = text.trim();
+    if text.is_empty() || (role == Role::User && is_system_message(text)) {
+        return None;
+    }
+    Some((role, text.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/claude")
+            .join(name)
+    }
+
+    #[test]
+    fn reads_sessions_of_every_format_version() {
+        for (file, prompt, outcome, last) in [
+            (
+                "v0.2.jsonl",
+                "Add a retry to the upload client",
+                SessionOutcome::Completed,
+                "2025-03-20T09:02:10Z",
+            ),
+            (
+                "v1.0.jsonl",
+                "Now cover the retry with a test",
+                SessionOutcome::AwaitingInput,
+                "2025-07-02T14:05:30Z",
+            ),
+            (
+                "v2.0.jsonl",
+                "Make the backoff configurable",
+                SessionOutcome::Unfinished,
+                "2025-11-12T08:31:00Z",
+            ),
+        ] {
+            let scan = scan_session(&fixture(file));
+            assert_eq!(scan.last_user_message.as_deref(), Some(prompt), "{file}");
+            assert_eq!(scan.outcome, Some(outcome), "{file}");
+            assert_eq!(
+                scan.last_timestamp.map(|ts| ts.to_rfc3339()),
+                Some(last.replace('Z', "+00:00")),
+                "{file}"
+            );
+        }
+    }
+
+    #[test]
+    fn subagent_files_are_not_sessions() {
+        let scan = scan_session(&fixture("v2.0-agent.jsonl"));
+        assert_eq!(scan.last_user_message, None);
+        assert_eq!(scan.outcome, None);
+    }
+}
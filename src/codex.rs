@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
@@ -7,12 +8,22 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone)]
+use crate::outcome::{SessionOutcome, codex_outcome};
+use crate::time_window::TimeWindow;
+use crate::transcript::message_text;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CodexSession {
     pub id: String,
+    // Session log file under the Codex sessions directory
+    pub path: PathBuf,
     pub cwd: PathBuf,
+    pub started_at: Option<DateTime<Utc>>,
     pub last_timestamp: Option<DateTime<Utc>>,
     pub last_user_message: Option<String>,
+    // How the session ended, from its trailing events
+    pub outcome: Option<SessionOutcome>,
 }
 
 fn sessions_root() -> Option<PathBuf> {
@@ -127,11 +138,13 @@ fn parse_session_file(path: &Path) -> Result<Option<CodexSession>> {
 
     let mut last_user_message = None;
     let mut last_timestamp = start_timestamp;
+    let mut outcome = None;
 
     for line in lines {
         let Ok(value) = serde_json::from_str::<Value>(&line) else {
             continue;
         };
+        outcome = codex_outcome(&value).or(outcome);
 
         if value.get("type").and_then(|t| t.as_str()) != Some("response_item") {
             continue;
@@ -165,7 +178,7 @@ fn parse_session_file(path: &Path) -> Result<Option<CodexSession>> {
             last_timestamp = Some(ts);
         }
 
-        if let Some(msg) = extract_user_message(payload)
+        if let Some(msg) = payload.get("content").and_then(message_text)
             && !msg.trim().is_empty()
         {
             last_user_message = Some(msg);
@@ -174,37 +187,24 @@ fn parse_session_file(path: &Path) -> Result<Option<CodexSession>> {
 
     Ok(Some(CodexSession {
         id,
+        path: path.to_path_buf(),
         cwd,
+        started_at: start_timestamp,
         last_timestamp,
         last_user_message,
+        outcome,
     }))
 }
 
-fn extract_user_message(payload: &serde_json::Map<String, Value>) -> Option<String> {
-    let content = payload.get("content")?;
-
-    if let Some(text) = content.as_array() {
-        let mut segments = Vec::new();
-        for node in text {
-            if let Some(item) = node.as_object() {
-                if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
-                    segments.push(text);
-                } else if let Some(inner) = item.get("content").and_then(|c| c.as_str()) {
-                    segments.push(inner);
-                }
-            }
-        }
-        if segments.is_empty() {
-            None
-        } else {
-            Some(segments.join("\n"))
-        }
-    } else {
-        content.as_str().map(|s| s.to_string())
-    }
+pub fn iterate_session_files(descending: bool) -> Result<Vec<PathBuf>> {
+    iterate_session_files_in(&TimeWindow::default(), descending)
 }
 
-fn iterate_session_files(descending: bool) -> Result<Vec<PathBuf>> {
+/// Session files that may hold activity inside `window`. Sessions live under
+/// `YYYY/MM/DD` directories named after the day they started, so days after
+/// the window are never read, and files last written before it are skipped
+/// without being opened.
+pub fn iterate_session_files_in(window: &TimeWindow, descending: bool) -> Result<Vec<PathBuf>> {
     let Some(root) = sessions_root() else {
         return Ok(Vec::new());
     };
@@ -213,6 +213,7 @@ fn iterate_session_files(descending: bool) -> Result<Vec<PathBuf>> {
         return Ok(Vec::new());
     }
 
+    let last_day = window.last_day();
     let mut result = Vec::new();
 
     let mut years = read_sorted_directories(&root)?;
@@ -221,21 +222,32 @@ fn iterate_session_files(descending: bool) -> Result<Vec<PathBuf>> {
     }
 
     for year in years {
+        let year_number = dir_number(&year);
+        if after_last_day(last_day, [year_number]) {
+            continue;
+        }
         let mut months = read_sorted_directories(&year)?;
         if !descending {
             months.reverse();
         }
         for month in months {
+            let month_number = dir_number(&month);
+            if after_last_day(last_day, [year_number, month_number]) {
+                continue;
+            }
             let mut days = read_sorted_directories(&month)?;
             if !descending {
                 days.reverse();
             }
             for day in days {
+                if after_last_day(last_day, [year_number, month_number, dir_number(&day)]) {
+                    continue;
+                }
                 let mut files = read_sorted_files(&day)?;
                 if !descending {
                     files.reverse();
                 }
-                result.extend(files);
+                result.extend(files.into_iter().filter(|file| window.admits_file(file)));
             }
         }
     }
@@ -243,6 +255,27 @@ fn iterate_session_files(descending: bool) -> Result<Vec<PathBuf>> {
     Ok(result)
 }
 
+fn dir_number(path: &Path) -> Option<u32> {
+    path.file_name()?.to_str()?.parse().ok()
+}
+
+/// Whether a dated directory (year, year/month, or year/month/day) lies
+/// entirely after `last_day`. Directories with unexpected names are kept.
+fn after_last_day<const N: usize>(last_day: Option<NaiveDate>, parts: [Option<u32>; N]) -> bool {
+    let Some(last_day) = last_day else {
+        return false;
+    };
+    let limit = [last_day.year() as u32, last_day.month(), last_day.day()];
+    let mut parsed = Vec::with_capacity(N);
+    for part in parts {
+        match part {
+            Some(value) => parsed.push(value),
+            None => return false,
+        }
+    }
+    parsed.as_slice() > &limit[..N]
+}
+
 fn matches_worktree(session_path: &Path, target_canonical: &Path, fallback: &Path) -> bool {
     session_path
         .canonicalize()
@@ -273,7 +306,16 @@ pub fn find_latest_session(worktree_path: &Path) -> Result<Option<CodexSession>>
 }
 
 pub fn recent_sessions(worktree_path: &Path, limit: usize) -> Result<(Vec<CodexSession>, usize)> {
-    let files = iterate_session_files(true)?;
+    recent_sessions_in(worktree_path, limit, &TimeWindow::default())
+}
+
+/// Like [`recent_sessions`], counting only sessions active inside `window`.
+pub fn recent_sessions_in(
+    worktree_path: &Path,
+    limit: usize,
+    window: &TimeWindow,
+) -> Result<(Vec<CodexSession>, usize)> {
+    let files = iterate_session_files_in(window, true)?;
     if files.is_empty() {
         return Ok((Vec::new(), 0));
     }
@@ -287,7 +329,9 @@ pub fn recent_sessions(worktree_path: &Path, limit: usize) -> Result<(Vec<CodexS
             continue;
         };
 
-        if !matches_worktree(&session.cwd, &target_canonical, worktree_path) {
+        if !matches_worktree(&session.cwd, &target_canonical, worktree_path)
+            || !window.overlaps(session.started_at, session.last_timestamp)
+        {
             continue;
         }
 
@@ -303,12 +347,22 @@ pub fn recent_sessions(worktree_path: &Path, limit: usize) -> Result<(Vec<CodexS
 pub fn collect_recent_sessions_for_paths(
     worktree_paths: &[PathBuf],
     limit: usize,
+) -> Result<HashMap<PathBuf, Vec<CodexSession>>> {
+    collect_sessions_for_paths_in(worktree_paths, limit, &TimeWindow::default())
+}
+
+/// Up to `limit` sessions per worktree that were active inside `window`, keyed
+/// by normalized worktree path, from a single walk of the sessions directory.
+pub fn collect_sessions_for_paths_in(
+    worktree_paths: &[PathBuf],
+    limit: usize,
+    window: &TimeWindow,
 ) -> Result<HashMap<PathBuf, Vec<CodexSession>>> {
     if worktree_paths.is_empty() || limit == 0 {
         return Ok(HashMap::new());
     }
 
-    let files = iterate_session_files(true)?;
+    let files = iterate_session_files_in(window, true)?;
     if files.is_empty() {
         return Ok(HashMap::new());
     }
@@ -331,7 +385,9 @@ pub fn collect_recent_sessions_for_paths(
         };
 
         let normalized = normalized_path(&session.cwd);
-        if !targets.contains(&normalized) {
+        if !targets.contains(&normalized)
+            || !window.overlaps(session.started_at, session.last_timestamp)
+        {
             continue;
         }
 
@@ -1,9 +1,11 @@
 use anyhow::{Context, Result};
-use chrono::Utc;
 use colored::Colorize;
 use std::fs;
 
+use crate::bail_with;
+use crate::error::ErrorCategory;
 use crate::git::{get_current_branch, get_repo_name, is_in_worktree};
+use crate::hooks::{self, Event, EventKind};
 use crate::state::{WorktreeInfo, XlaudeState};
 use crate::utils::sanitize_branch_name;
 
@@ -42,7 +44,8 @@ pub fn handle_add(name: Option<String>) -> Result<()> {
         .values()
         .find(|info| normalize_path(&info.path) == current_dir_key)
     {
-        anyhow::bail!(
+        bail_with!(
+            ErrorCategory::Conflict,
             "Current directory '{}' is already managed by xlaude as '{}/{}'",
             current_dir.display(),
             existing.repo_name,
@@ -53,7 +56,8 @@ pub fn handle_add(name: Option<String>) -> Result<()> {
     // Check if already managed under the same name
     let key = XlaudeState::make_key(&repo_name, &worktree_name);
     if state.worktrees.contains_key(&key) {
-        anyhow::bail!(
+        bail_with!(
+            ErrorCategory::Conflict,
             "Worktree '{}/{}' is already managed by xlaude",
             repo_name,
             worktree_name
@@ -67,17 +71,15 @@ pub fn handle_add(name: Option<String>) -> Result<()> {
     );
 
     // Add to state
-    state.worktrees.insert(
-        key,
-        WorktreeInfo {
-            name: worktree_name.clone(),
-            branch: current_branch,
-            path: current_dir.clone(),
-            repo_name,
-            created_at: Utc::now(),
-        },
+    let worktree_info = WorktreeInfo::new(
+        worktree_name.clone(),
+        current_branch,
+        current_dir.clone(),
+        repo_name,
     );
+    state.worktrees.insert(key, worktree_info.clone());
     state.save()?;
+    hooks::emit(&state, Event::new(EventKind::WorktreeAdded, &worktree_info));
 
     println!(
         "{} Worktree '{}' added successfully",
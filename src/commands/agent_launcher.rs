@@ -1,57 +1,474 @@
 use anyhow::{Context, Result};
-use std::process::Command;
+use chrono::Utc;
+use colored::Colorize;
+use serde_json::json;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
 
+use crate::agent::{
+    AgentKind, Launch, PastSession, apply_initial_prompt, apply_model, apply_resume,
+    guard_permissions, install_hint, past_sessions,
+};
 use crate::commands::agent_prompt::{AgentSelection, option_info, prompt_agent_selection};
-use crate::state::WorktreeInfo;
-use crate::utils::split_command_line;
+use crate::commands::list::{PreviewStyle, format_message_preview, format_time_ago};
+use crate::dry_run;
+use crate::error::{ErrorCategory, WithCategory};
+use crate::history;
+use crate::hooks::{self, Event, EventKind};
+use crate::input::smart_select;
+use crate::processes;
+use crate::state::{MenuEntryKind, WorktreeInfo, XlaudeState};
+use crate::tr;
+use crate::utils::{
+    find_executable, missing_agent_error, normalize_agent_command, split_command_line,
+};
+
+// Sessions offered by `open --select-session`
+const SESSION_CHOICES: usize = 10;
+
+/// Which earlier session of the chosen agent to continue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resume {
+    Latest,
+    /// Pick one of the recent sessions
+    Select,
+    /// The session with this id
+    Session(String),
+}
+
+/// How to start the agent picked from the menu.
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions<'a> {
+    // Initial instruction to send to the agent
+    pub prompt: Option<&'a str>,
+    // Earlier session to continue instead of starting a new one
+    pub resume: Option<Resume>,
+    // Model to ask the agent for (see `apply_model`)
+    pub model: Option<&'a str>,
+    // Print the command instead of running it, like `--dry-run`
+    pub print_cmd: bool,
+}
 
 pub fn launch_with_menu(worktree: &WorktreeInfo, prompt: &str) -> Result<AgentSelection> {
+    launch_with_menu_and_prompt(worktree, prompt, LaunchOptions::default())
+}
+
+/// Like `launch_with_menu`, seeding the chosen agent with an initial
+/// instruction, a model, or one of its sessions to resume.
+pub fn launch_with_menu_and_prompt(
+    worktree: &WorktreeInfo,
+    prompt: &str,
+    options: LaunchOptions<'_>,
+) -> Result<AgentSelection> {
     let state = crate::state::XlaudeState::load()?;
-    let configured_agent = state
-        .agent
-        .as_ref()
-        .map(|value| value.trim())
-        .filter(|value| !value.is_empty())
-        .map(|value| value.to_string());
+    let configured_agent = state.agent_for(Some(&worktree.repo_name));
+    let default_choice = default_agent_selection_from_config(configured_agent);
+    let preview = |selection| preview_command(worktree, &state, selection, &options);
+    let selection = prompt_agent_selection(prompt, default_choice, &state.menu, &preview)?;
+    launch_selection(worktree, &state, selection, &options)
+}
+
+/// The command a menu choice would run, for the line confirming the choice.
+/// `None` for choices that do not launch an agent or describe themselves.
+fn preview_command(
+    worktree: &WorktreeInfo,
+    state: &XlaudeState,
+    selection: AgentSelection,
+    options: &LaunchOptions<'_>,
+) -> Option<String> {
+    let command = match selection {
+        AgentSelection::Custom(index) => {
+            let entry = state.menu.get(index)?;
+            (entry.kind == MenuEntryKind::Agent && entry.confirmation.is_none())
+                .then_some(entry.command.as_str())?
+        }
+        builtin => option_info(builtin).command?,
+    };
+    LaunchPlan::new(worktree, state, command, options)
+        .ok()
+        .map(|plan| plan.command_line())
+}
+
+/// Launch an agent chosen up front, e.g. with `create --agent`, without
+/// showing the menu. A missing agent still offers the others.
+pub fn launch_agent(
+    worktree: &WorktreeInfo,
+    selection: AgentSelection,
+    initial_prompt: Option<&str>,
+) -> Result<AgentSelection> {
+    let state = XlaudeState::load()?;
+    let options = LaunchOptions {
+        prompt: initial_prompt,
+        ..LaunchOptions::default()
+    };
+    launch_selection(worktree, &state, selection, &options)
+}
 
-    let default_choice = default_agent_selection_from_config(configured_agent.as_deref());
-    let selection = prompt_agent_selection(prompt, default_choice)?;
+fn launch_selection(
+    worktree: &WorktreeInfo,
+    state: &XlaudeState,
+    mut selection: AgentSelection,
+    options: &LaunchOptions<'_>,
+) -> Result<AgentSelection> {
+    let mut missing = Vec::new();
 
-    let option = option_info(selection);
+    loop {
+        let command = match selection {
+            AgentSelection::Custom(index) => {
+                let entry = &state.menu[index];
+                match entry.kind {
+                    MenuEntryKind::Agent => entry.command.as_str(),
+                    MenuEntryKind::Editor => {
+                        open_editor(worktree, &entry.command)?;
+                        return Ok(selection);
+                    }
+                    MenuEntryKind::Command => {
+                        run_in_worktree(worktree, &entry.command)?;
+                        return Ok(selection);
+                    }
+                }
+            }
+            builtin => match option_info(builtin).command {
+                Some(command) => command,
+                None => return Ok(selection),
+            },
+        };
+        if launch_installed(worktree, command, &state.agent_fallbacks, options)? {
+            return Ok(selection);
+        }
 
-    if let Some(command) = option.command {
-        let command_to_run = command.to_string();
-        spawn_agent(worktree, AgentCommand::Override(&command_to_run))?;
+        // Offer the remaining agents, defaulting to one that is installed
+        missing.push(selection);
+        let next_default = [
+            AgentSelection::Claude,
+            AgentSelection::Codex,
+            AgentSelection::Gemini,
+        ]
+        .into_iter()
+        .filter(|candidate| !missing.contains(candidate))
+        .find(|candidate| option_info(*candidate).command.is_some_and(is_installed))
+        .unwrap_or(AgentSelection::Skip);
+        println!();
+        let preview = |selection| preview_command(worktree, state, selection, options);
+        selection = prompt_agent_selection(
+            &tr!("Choose another agent:"),
+            next_default,
+            &state.menu,
+            &preview,
+        )?;
     }
+}
+
+/// Start an editor on the worktree without waiting for it.
+fn open_editor(worktree: &WorktreeInfo, command: &str) -> Result<()> {
+    let (program, args) = split_command_line(command)?;
+    if dry_run::is_enabled() {
+        dry_run::announce(&format!("{command} {}", worktree.path.display()));
+        return Ok(());
+    }
+    Command::new(&program)
+        .args(&args)
+        .arg(&worktree.path)
+        .current_dir(&worktree.path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to launch '{program}'"))?;
+    Ok(())
+}
+
+/// Run an interactive command, such as lazygit or a shell, in the worktree.
+/// Its exit status is not an error: a shell reports whatever ran last.
+fn run_in_worktree(worktree: &WorktreeInfo, command: &str) -> Result<()> {
+    let (program, args) = split_command_line(command)?;
+    if dry_run::is_enabled() {
+        dry_run::announce(command);
+        return Ok(());
+    }
+    Command::new(&program)
+        .args(&args)
+        .current_dir(worktree.working_dir())
+        .status()
+        .with_context(|| format!("Failed to run '{program}'"))?;
+    Ok(())
+}
+
+/// Launch `command`, or the first installed fallback when its program is not
+/// installed. Returns `false` when nothing could be launched.
+fn launch_installed(
+    worktree: &WorktreeInfo,
+    command: &str,
+    fallbacks: &[String],
+    options: &LaunchOptions<'_>,
+) -> Result<bool> {
+    if is_installed(command) {
+        spawn_agent(worktree, AgentCommand::Override(command), options)?;
+        return Ok(true);
+    }
+
+    let (program, _) = split_command_line(command)?;
+    println!(
+        "{}",
+        tr!("{} Agent '{}' is not installed", "❌".red(), program)
+    );
+    println!("   {}", install_hint(&program).bright_black());
+
+    for fallback in fallbacks {
+        let fallback = normalize_agent_command(fallback);
+        if is_installed(&fallback) {
+            println!("{}", tr!("{} Falling back to '{}'", "↪".yellow(), fallback));
+            spawn_agent(worktree, AgentCommand::Override(&fallback), options)?;
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
 
-    Ok(selection)
+fn is_installed(command: &str) -> bool {
+    split_command_line(command).is_ok_and(|(program, _)| find_executable(&program).is_some())
 }
 
 enum AgentCommand<'a> {
     Override(&'a str),
 }
 
-fn spawn_agent(worktree: &WorktreeInfo, command: AgentCommand<'_>) -> Result<()> {
-    std::env::set_current_dir(&worktree.path).context("Failed to change directory")?;
+/// What launching an agent runs: the command with everything xlaude adds to
+/// it, the directory it starts in, and the variables set on top of xlaude's
+/// own environment.
+struct LaunchPlan {
+    program: String,
+    args: Vec<String>,
+    envs: Vec<(String, String)>,
+    dir: PathBuf,
+}
 
-    let (program, args) = match command {
-        AgentCommand::Override(cmdline) => split_command_line(cmdline)?,
-    };
+impl LaunchPlan {
+    /// `cmdline` with the worktree's `agent_args` and the model applied: the
+    /// parts known before a session is picked or a prompt file is written.
+    fn new(
+        worktree: &WorktreeInfo,
+        state: &XlaudeState,
+        cmdline: &str,
+        options: &LaunchOptions<'_>,
+    ) -> Result<Self> {
+        let (program, mut args) = split_command_line(cmdline)?;
+        args.extend(state.agent_args_for(worktree)?);
+        let envs = match options.model {
+            Some(model) => apply_model(&program, &mut args, model),
+            None => Vec::new(),
+        };
+        // Agents keep sessions per directory, so resumed ones start at the
+        // root where they were found
+        let dir = match options.resume {
+            Some(_) => worktree.path.clone(),
+            None => worktree.working_dir(),
+        };
+        Ok(LaunchPlan {
+            program,
+            args,
+            envs,
+            dir,
+        })
+    }
+
+    fn command_line(&self) -> String {
+        shell_words::join(std::iter::once(&self.program).chain(&self.args))
+    }
+
+    /// The plan as shell commands that do the same when run by hand.
+    fn print(&self) {
+        println!("cd {}", shell_words::quote(&self.dir.to_string_lossy()));
+        let assignments: Vec<String> = self
+            .envs
+            .iter()
+            .map(|(name, value)| format!("{name}={}", shell_words::quote(value)))
+            .collect();
+        if assignments.is_empty() {
+            println!("{}", self.command_line());
+        } else {
+            println!("{} {}", assignments.join(" "), self.command_line());
+        }
+    }
+}
 
+fn spawn_agent(
+    worktree: &WorktreeInfo,
+    command: AgentCommand<'_>,
+    options: &LaunchOptions<'_>,
+) -> Result<()> {
+    let AgentCommand::Override(cmdline) = command;
+    let state = XlaudeState::load()?;
+    let mut plan = LaunchPlan::new(worktree, &state, cmdline, options)?;
+    guard_permissions(
+        &state,
+        &worktree.repo_name,
+        &plan.program,
+        &mut plan.args,
+        Launch::Interactive,
+    )?;
+    if let Some(resume) = &options.resume
+        && let Some(session) = choose_session(worktree, &plan.program, resume)?
+    {
+        apply_resume(&plan.program, &mut plan.args, &session);
+    }
+    let preview = options.print_cmd || dry_run::is_enabled();
+    let mut prompt_file = None;
+    if let Some(text) = options.prompt {
+        let prompt = apply_initial_prompt(&plan.program, &mut plan.args, text, preview)?;
+        plan.envs.extend(prompt.envs);
+        prompt_file = prompt.file;
+    }
+    if preview {
+        plan.print();
+        return Ok(());
+    }
+
+    std::env::set_current_dir(&plan.dir).context("Failed to change directory")?;
+    let LaunchPlan {
+        program,
+        args,
+        envs,
+        ..
+    } = plan;
     let mut cmd = Command::new(&program);
     cmd.args(&args);
 
     cmd.envs(std::env::vars());
+    cmd.envs(envs);
 
-    let status = cmd.status().context("Failed to launch agent")?;
+    let started_at = Utc::now();
+    let key = XlaudeState::make_key(&worktree.repo_name, &worktree.name);
+    if let Err(err) = history::record_open(&key, cmdline) {
+        eprintln!(
+            "{}",
+            tr!(
+                "{} Failed to record the launch: {}",
+                "⚠️".yellow(),
+                format!("{err:#}")
+            )
+        );
+    }
+    hooks::emit(
+        &state,
+        Event::new(EventKind::AgentStarted, worktree).agent(cmdline),
+    );
+    let child = match cmd.spawn() {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(missing_agent_error(&program));
+        }
+        spawned => {
+            spawned.with_category(ErrorCategory::AgentFailure, || "Failed to launch agent")?
+        }
+    };
+    let exit = processes::track(child, key, cmdline, 1)
+        .with_category(ErrorCategory::AgentFailure, || "Failed to wait for agent")?;
+    drop(prompt_file);
+    let status = exit.status;
+    hooks::emit(
+        &state,
+        Event::new(EventKind::AgentExited, worktree)
+            .agent(cmdline)
+            .details(json!({
+                "started_at": started_at,
+                "exit_code": status.code(),
+                "signal": processes::exit_signal(&status),
+                "success": status.success(),
+            })),
+    );
 
+    if let Some(signal) = exit.interrupted {
+        return Err(processes::interrupted_error(signal));
+    }
     if !status.success() {
-        anyhow::bail!("Agent exited with error");
+        return Err(processes::exit_error(&status));
     }
 
     Ok(())
 }
 
+/// The id of the session to resume, or `None` to start a new one when the
+/// agent has no sessions in the worktree or xlaude cannot resume them.
+fn choose_session(
+    worktree: &WorktreeInfo,
+    program: &str,
+    resume: &Resume,
+) -> Result<Option<String>> {
+    if let Resume::Session(id) = resume {
+        return Ok(Some(id.clone()));
+    }
+    let Some(sessions) = past_sessions(program, &worktree.path, SESSION_CHOICES)? else {
+        println!(
+            "{}",
+            tr!(
+                "{} xlaude cannot resume sessions of '{}'; starting a new one",
+                "⚠️".yellow(),
+                program
+            )
+        );
+        return Ok(None);
+    };
+    if sessions.is_empty() {
+        println!(
+            "{}",
+            tr!(
+                "{} No earlier sessions in '{}'; starting a new one",
+                "ℹ️".blue(),
+                worktree.name
+            )
+        );
+        return Ok(None);
+    }
+    let preview = PreviewStyle::from_state(&XlaudeState::load()?);
+    let index = match resume {
+        Resume::Latest | Resume::Session(_) => 0,
+        // Without anyone to ask, continue the latest
+        Resume::Select => smart_select(
+            "session",
+            &tr!("Select a session to resume"),
+            &sessions,
+            |session| describe_session(session, preview),
+        )?
+        .unwrap_or(0),
+    };
+    Ok(Some(sessions[index].id.clone()))
+}
+
+/// Resume session `id` of `agent` in the worktree. The configured agent
+/// command is used when it runs that agent, the built-in one otherwise.
+pub fn resume_session(worktree: &WorktreeInfo, agent: AgentKind, id: &str) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let builtin = match agent {
+        AgentKind::Codex => AgentSelection::Codex,
+        _ => AgentSelection::Claude,
+    };
+    let command = state
+        .agent_for(Some(&worktree.repo_name))
+        .map(normalize_agent_command)
+        .filter(|command| {
+            split_command_line(command)
+                .is_ok_and(|(program, _)| AgentKind::from_program(&program) == agent)
+        })
+        .unwrap_or_else(|| option_info(builtin).command.unwrap_or_default().to_string());
+    let options = LaunchOptions {
+        resume: Some(Resume::Session(id.to_string())),
+        ..LaunchOptions::default()
+    };
+    spawn_agent(worktree, AgentCommand::Override(&command), &options)
+}
+
+fn describe_session(session: &PastSession, preview: PreviewStyle) -> String {
+    format!(
+        "{}  {}",
+        format_time_ago(session.last_timestamp),
+        session
+            .last_user_message
+            .as_deref()
+            .map(|message| format_message_preview(message, preview))
+            .unwrap_or_else(|| "(no user message)".to_string())
+    )
+}
+
 fn default_agent_selection_from_config(agent_config: Option<&str>) -> AgentSelection {
     let Some(config) = agent_config
         .map(str::trim)
@@ -1,39 +1,28 @@
 use anyhow::Result;
 use colored::Colorize;
+use std::borrow::Cow;
 
-use crate::input::smart_choice_with_formatter;
+use crate::bail_with;
+use crate::error::ErrorCategory;
+use crate::i18n::translate;
+use crate::input::smart_choice_with_display;
+use crate::interaction::ChoiceDisplay;
+use crate::state::MenuEntry;
+use crate::tr;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum AgentSelection {
     Codex,
     Claude,
     Gemini,
+    /// The configured `menu` entry at this index
+    Custom(usize),
     Skip,
 }
 
-impl AgentSelection {
-    fn as_key(self) -> &'static str {
-        match self {
-            AgentSelection::Codex => "1",
-            AgentSelection::Claude => "2",
-            AgentSelection::Gemini => "3",
-            AgentSelection::Skip => "n",
-        }
-    }
-
-    fn from_key(key: &str) -> Option<Self> {
-        match key {
-            "1" => Some(AgentSelection::Codex),
-            "2" => Some(AgentSelection::Claude),
-            "3" => Some(AgentSelection::Gemini),
-            "n" | "N" => Some(AgentSelection::Skip),
-            _ => None,
-        }
-    }
-}
-
 struct AgentMenuOption {
     selection: AgentSelection,
+    key: &'static str,
     title: &'static str,
     command: &'static str,
     description: &'static str,
@@ -43,6 +32,7 @@ struct AgentMenuOption {
 const AGENT_MENU_OPTIONS: [AgentMenuOption; 4] = [
     AgentMenuOption {
         selection: AgentSelection::Codex,
+        key: "1",
         title: "Open with codex",
         command: "codex",
         description: "Open the worktree in the codex CLI.",
@@ -50,6 +40,7 @@ const AGENT_MENU_OPTIONS: [AgentMenuOption; 4] = [
     },
     AgentMenuOption {
         selection: AgentSelection::Claude,
+        key: "2",
         title: "Open with Claude",
         command: "claude --dangerously-skip-permissions",
         description: "Launch using the configured Claude command.",
@@ -57,6 +48,7 @@ const AGENT_MENU_OPTIONS: [AgentMenuOption; 4] = [
     },
     AgentMenuOption {
         selection: AgentSelection::Gemini,
+        key: "3",
         title: "Open with Gemini",
         command: "gemini -y",
         description: "Open the worktree in the Gemini CLI.",
@@ -64,6 +56,7 @@ const AGENT_MENU_OPTIONS: [AgentMenuOption; 4] = [
     },
     AgentMenuOption {
         selection: AgentSelection::Skip,
+        key: "n",
         title: "Skip launch",
         command: "",
         description: "Keep the worktree open without launching an agent.",
@@ -71,18 +64,192 @@ const AGENT_MENU_OPTIONS: [AgentMenuOption; 4] = [
     },
 ];
 
+/// A line of the menu: a built-in option or a configured entry.
+struct MenuRow<'a> {
+    selection: AgentSelection,
+    key: String,
+    title: Cow<'a, str>,
+    command: &'a str,
+    description: Cow<'a, str>,
+    confirmation: String,
+}
+
+impl From<&'static AgentMenuOption> for MenuRow<'_> {
+    fn from(option: &'static AgentMenuOption) -> Self {
+        MenuRow {
+            selection: option.selection,
+            key: option.key.to_string(),
+            title: Cow::Owned(translate(option.title, &[])),
+            command: option.command,
+            description: Cow::Owned(translate(option.description, &[])),
+            confirmation: translate(option.confirmation, &[]),
+        }
+    }
+}
+
+/// The built-in agents, then the configured entries, then "Skip launch".
+/// Entries without a key are numbered after the built-in agents (4, 5, ...);
+/// entries whose key is taken or not usable are left out with a warning.
+fn menu_rows(custom: &[MenuEntry]) -> Vec<MenuRow<'_>> {
+    let (skip, agents) = AGENT_MENU_OPTIONS
+        .split_last()
+        .expect("the menu has built-in options");
+    let mut taken: Vec<String> = AGENT_MENU_OPTIONS
+        .iter()
+        .map(|option| option.key.to_string())
+        .collect();
+    let mut keys: Vec<Option<String>> = Vec::new();
+    for entry in custom {
+        let key = entry.key.as_deref().map(|key| key.trim().to_lowercase());
+        match key {
+            Some(key)
+                if key.is_empty()
+                    || key == "?"
+                    || key.contains(char::is_whitespace)
+                    || taken.contains(&key) =>
+            {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        "{} Ignoring menu entry '{}': its key '{}' is taken or not usable",
+                        "⚠️".yellow(),
+                        entry.title,
+                        key
+                    )
+                );
+                keys.push(None);
+            }
+            Some(key) => {
+                taken.push(key.clone());
+                keys.push(Some(key));
+            }
+            None => keys.push(Some(String::new())),
+        }
+    }
+    let mut next_number = agents.len() + 1;
+    for key in keys.iter_mut().flatten().filter(|key| key.is_empty()) {
+        while taken.contains(&next_number.to_string()) {
+            next_number += 1;
+        }
+        *key = next_number.to_string();
+        taken.push(key.clone());
+    }
+
+    let mut rows: Vec<MenuRow> = agents.iter().map(MenuRow::from).collect();
+    for (index, (entry, key)) in custom.iter().zip(keys).enumerate() {
+        let Some(key) = key else {
+            continue;
+        };
+        rows.push(MenuRow {
+            selection: AgentSelection::Custom(index),
+            key,
+            title: Cow::Borrowed(&entry.title),
+            command: &entry.command,
+            description: Cow::Borrowed(entry.description.as_deref().unwrap_or_default()),
+            confirmation: entry
+                .confirmation
+                .clone()
+                .unwrap_or_else(|| tr!("Running `{}`", entry.command)),
+        });
+    }
+    rows.push(MenuRow::from(skip));
+    rows
+}
+
+/// The agent named on the command line: a built-in agent by its program
+/// (`claude`, `codex`, `gemini`), or a `menu` entry by its key or title.
+pub fn agent_named(name: &str, custom: &[MenuEntry]) -> Result<AgentSelection> {
+    let name = name.trim();
+    let builtin = AGENT_MENU_OPTIONS.iter().find(|option| {
+        option
+            .command
+            .split_whitespace()
+            .next()
+            .is_some_and(|program| program.eq_ignore_ascii_case(name))
+    });
+    if let Some(option) = builtin {
+        return Ok(option.selection);
+    }
+    let entry = custom.iter().position(|entry| {
+        entry.title.eq_ignore_ascii_case(name)
+            || entry
+                .key
+                .as_deref()
+                .is_some_and(|key| key.trim().eq_ignore_ascii_case(name))
+    });
+    match entry {
+        Some(index) => Ok(AgentSelection::Custom(index)),
+        None => bail_with!(
+            ErrorCategory::Usage,
+            "Unknown agent '{}'. Use claude, codex, gemini, or the key or title of a menu entry",
+            name
+        ),
+    }
+}
+
+/// Show the launch menu and return the choice. `preview` gives the command an
+/// agent choice would run, shown in the line confirming it.
 pub fn prompt_agent_selection(
     prompt: &str,
     default_choice: AgentSelection,
+    custom: &[MenuEntry],
+    preview: &dyn Fn(AgentSelection) -> Option<String>,
 ) -> Result<AgentSelection> {
     if !prompt.is_empty() {
         println!("{}", prompt.bold());
         println!();
     }
 
-    for (index, option) in AGENT_MENU_OPTIONS.iter().enumerate() {
+    let rows = menu_rows(custom);
+    print_menu(&rows, default_choice);
+
+    let prompt_indicator = format!("{} ", "›".bright_black());
+    let valid_keys: Vec<&str> = rows.iter().map(|row| row.key.as_str()).collect();
+    let default_key = rows
+        .iter()
+        .find(|row| row.selection == default_choice)
+        .map(|row| row.key.as_str())
+        .expect("default option is in the menu");
+    let find_row = |key: &str| {
+        rows.iter()
+            .find(|row| row.key.eq_ignore_ascii_case(key))
+            .expect("invalid agent selection key")
+    };
+    let display = ChoiceDisplay {
+        label: &|key| {
+            let option = find_row(key);
+            format!("[{}] {}", option.key.to_uppercase(), option.title)
+        },
+        print: &|| print_menu(&rows, default_choice),
+    };
+
+    let choice = smart_choice_with_display(
+        "agent",
+        &prompt_indicator,
+        &valid_keys,
+        default_key,
+        Some(&display),
+        |key| {
+            let option = find_row(key);
+            match option.selection {
+                AgentSelection::Skip => format!("{} {}", "⏭".yellow(), option.confirmation),
+                selection => {
+                    let confirmation = preview(selection)
+                        .map(|command| tr!("Launching `{}`", command))
+                        .unwrap_or_else(|| option.confirmation.clone());
+                    format!("{} {}", "✔".green(), confirmation.cyan())
+                }
+            }
+        },
+    )?;
+
+    Ok(find_row(&choice).selection)
+}
+
+fn print_menu(rows: &[MenuRow], default_choice: AgentSelection) {
+    for (index, option) in rows.iter().enumerate() {
         let is_default = option.selection == default_choice;
-        let key_label = format!("[{}]", option.selection.as_key().to_uppercase());
+        let key_label = format!("[{}]", option.key.to_uppercase());
         let key_display = if is_default {
             key_label.green().bold()
         } else {
@@ -91,7 +258,7 @@ pub fn prompt_agent_selection(
 
         let mut title = option.title.to_string();
         if is_default {
-            title.push_str(" (default)");
+            title.push_str(&tr!(" (default)"));
         }
 
         let title_display = if is_default {
@@ -105,7 +272,7 @@ pub fn prompt_agent_selection(
         if !option.command.is_empty() {
             println!(
                 "      {} {}",
-                "Command:".bright_black(),
+                tr!("Command:").bright_black(),
                 format!("`{}`", option.command).cyan()
             );
         }
@@ -114,51 +281,41 @@ pub fn prompt_agent_selection(
             println!("      {}", option.description.bright_black());
         }
 
-        if index + 1 != AGENT_MENU_OPTIONS.len() {
+        if index + 1 != rows.len() {
             println!();
         }
     }
 
+    let labels: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            format!("[{}]", row.key.to_uppercase())
+                .bright_black()
+                .to_string()
+        })
+        .collect();
+    let (last, others) = labels.split_last().expect("the menu has options");
     println!();
     println!(
-        "  Press {}, {}, {} or {}; Enter accepts the default.",
-        "[1]".bright_black(),
-        "[2]".bright_black(),
-        "[3]".bright_black(),
-        "[N]".bright_black()
+        "{}",
+        tr!(
+            "  Press {} or {}; Enter accepts the default.",
+            others.join(", "),
+            last
+        )
+    );
+    println!(
+        "  {}",
+        tr!("↑/↓ move between options and ? shows this list again.").bright_black()
     );
     println!();
-
-    let prompt_indicator = format!("{} ", "›".bright_black());
-    let valid_keys = ["1", "2", "3", "n"];
-
-    let choice = smart_choice_with_formatter(
-        &prompt_indicator,
-        &valid_keys,
-        default_choice.as_key(),
-        |key| {
-            let selection = AgentSelection::from_key(key).expect("invalid agent selection key");
-            let option = AGENT_MENU_OPTIONS
-                .iter()
-                .find(|opt| opt.selection == selection)
-                .expect("missing agent option");
-
-            match selection {
-                AgentSelection::Codex | AgentSelection::Claude | AgentSelection::Gemini => {
-                    format!("{} {}", "✔".green(), option.confirmation.cyan())
-                }
-                AgentSelection::Skip => format!("{} {}", "⏭".yellow(), option.confirmation),
-            }
-        },
-    )?;
-
-    Ok(AgentSelection::from_key(&choice).expect("invalid agent choice"))
 }
 
 pub struct AgentOptionInfo {
     pub command: Option<&'static str>,
 }
 
+/// Details of a built-in option; configured entries carry their own.
 pub fn option_info(selection: AgentSelection) -> AgentOptionInfo {
     let option = AGENT_MENU_OPTIONS
         .iter()
@@ -173,3 +330,45 @@ pub fn option_info(selection: AgentSelection) -> AgentOptionInfo {
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::MenuEntryKind;
+
+    fn entry(key: Option<&str>, title: &str) -> MenuEntry {
+        MenuEntry {
+            key: key.map(String::from),
+            kind: MenuEntryKind::Command,
+            title: title.into(),
+            command: "lazygit".into(),
+            description: None,
+            confirmation: None,
+        }
+    }
+
+    #[test]
+    fn configured_entries_come_before_skip_and_need_free_keys() {
+        let mut custom = vec![
+            entry(Some("G"), "Open lazygit"),
+            entry(Some("2"), "Clashes with Claude"),
+            entry(Some("n"), "Clashes with skip"),
+            entry(Some("g"), "Clashes with lazygit"),
+            entry(Some("vs"), "Open in VS Code"),
+            entry(Some("5"), "Numbered by hand"),
+        ];
+        custom.extend((0..6).map(|_| entry(None, "Numbered")));
+        let rows = menu_rows(&custom);
+        let keys: Vec<&str> = rows.iter().map(|row| row.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "1", "2", "3", "g", "vs", "5", "4", "6", "7", "8", "9", "10", "n"
+            ]
+        );
+        assert_eq!(rows[3].selection, AgentSelection::Custom(0));
+        assert_eq!(rows[4].selection, AgentSelection::Custom(4));
+        assert_eq!(rows[11].selection, AgentSelection::Custom(11));
+        assert_eq!(rows[4].confirmation, "Running `lazygit`");
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+
+use crate::bail_with;
+use crate::commands::create::initialize_worktree;
+use crate::commands::lock::format_reason;
+use crate::disk::{dir_size, format_bytes};
+use crate::error::{ErrorCategory, WithCategory};
+use crate::git::{
+    self, apply_sparse_checkout, execute_git, find_main_repo, has_commits_outside_refs,
+    is_working_tree_clean, worktree_lock_reason,
+};
+use crate::hooks::{self, Event, EventKind};
+use crate::processes::ProcessRegistry;
+use crate::state::{Archive, DirtyWorktreePolicy, XlaudeState};
+use crate::utils::execute_in_dir;
+
+/// Delete a worktree's checkout to free disk space, keeping its branch and its
+/// state entry so `xlaude restore` can bring it back.
+pub fn handle_archive(name: String) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let (key, info) = state
+        .find_worktree(&name)
+        .map(|(key, info)| (key.clone(), info.clone()))
+        .with_category(ErrorCategory::NotFound, || {
+            format!("Worktree '{name}' not found")
+        })?;
+    let size = archive_worktree(&mut state, &key)?;
+    state.save()?;
+    hooks::emit(
+        &state,
+        Event::new(EventKind::WorktreeArchived, &state.worktrees[&key]),
+    );
+
+    println!(
+        "{} Archived '{}', freeing {}",
+        "🗄️".green(),
+        info.name.cyan(),
+        format_bytes(size)
+    );
+    if !info.detached {
+        println!("  {} Branch '{}' is kept", "›".bright_black(), info.branch);
+    }
+    println!(
+        "  {} {}",
+        "›".bright_black(),
+        format!("Run 'xlaude restore {}' to check it out again", info.name).bright_black()
+    );
+    Ok(())
+}
+
+/// Remove the checkout of the worktree at `key` and mark it archived, without
+/// saving the state. Fails with the conflict or dirty-worktree exit code when
+/// the worktree is locked, has an agent running, or has work only the
+/// checkout holds. Returns the disk space freed.
+pub(crate) fn archive_worktree(state: &mut XlaudeState, key: &str) -> Result<u64> {
+    let info = state.worktrees[key].clone();
+    if info.archived.is_some() {
+        bail_with!(
+            ErrorCategory::Conflict,
+            "Worktree '{}' is already archived",
+            info.name
+        );
+    }
+    if !info.path.exists() {
+        bail_with!(
+            ErrorCategory::NotFound,
+            "Directory of worktree '{}' is missing: {}. Run 'xlaude delete {}' to remove it",
+            info.name,
+            info.path.display(),
+            info.name
+        );
+    }
+    if let Some(reason) = worktree_lock_reason(&info.path, &info.repo_name) {
+        bail_with!(
+            ErrorCategory::Conflict,
+            "Worktree '{}' is locked{}. Run 'xlaude unlock {}' first",
+            info.name,
+            format_reason(&reason),
+            info.name
+        );
+    }
+    let running = ProcessRegistry::load_live()
+        .map(|registry| !registry.for_worktree(key).is_empty())
+        .unwrap_or(false);
+    if running {
+        bail_with!(
+            ErrorCategory::Conflict,
+            "An agent is running in worktree '{}'. Run 'xlaude kill {}' first",
+            info.name,
+            info.name
+        );
+    }
+
+    // Only committed work survives in the branch
+    let (clean, unreachable, head) = execute_in_dir(&info.path, || {
+        Ok((
+            is_working_tree_clean()?,
+            info.detached && has_commits_outside_refs(),
+            execute_git(&["rev-parse", "HEAD"])?,
+        ))
+    })?;
+    let stash_changes = !clean && state.dirty_worktree == Some(DirtyWorktreePolicy::Stash);
+    if !clean && !stash_changes {
+        bail_with!(
+            ErrorCategory::DirtyWorktree,
+            "Worktree '{}' has uncommitted changes; commit or stash them before archiving, or set \"dirty_worktree\" to \"stash\"",
+            info.name
+        );
+    }
+    if unreachable {
+        bail_with!(
+            ErrorCategory::DirtyWorktree,
+            "Worktree '{}' has commits no branch or tag contains; create a branch for them before archiving",
+            info.name
+        );
+    }
+
+    let repo_path = find_main_repo(&info.path, &info.repo_name)
+        .with_category(ErrorCategory::NotFound, || {
+            format!("Main repository of worktree '{}' not found", info.name)
+        })?;
+    let stash = if stash_changes {
+        let stash = git::stash_changes(&info.path, &format!("xlaude archive {key}"), None)?;
+        println!(
+            "{} Stashed the uncommitted changes of '{}'; restoring it applies them again",
+            "📦".blue(),
+            info.name
+        );
+        Some(stash)
+    } else {
+        None
+    };
+    let size = dir_size(&info.path);
+    let path = info.path.display().to_string();
+    let repo = repo_path.display().to_string();
+    // Submodules make the plain removal refuse; the tree is known to be clean
+    if execute_git(&["-C", &repo, "worktree", "remove", &path]).is_err() {
+        execute_git(&["-C", &repo, "worktree", "remove", "--force", &path])
+            .context("Failed to remove worktree")?;
+    }
+
+    if let Some(info) = state.worktrees.get_mut(key) {
+        info.archived = Some(Archive {
+            archived_at: Utc::now(),
+            repo_path,
+            head,
+            stash,
+        });
+        info.archive_notice_at = None;
+    }
+    Ok(size)
+}
+
+/// Check an archived worktree out again at its recorded path.
+pub fn handle_restore(name: String) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let (key, info) = state
+        .find_worktree(&name)
+        .map(|(key, info)| (key.clone(), info.clone()))
+        .with_category(ErrorCategory::NotFound, || {
+            format!("Worktree '{name}' not found")
+        })?;
+    let Some(archive) = &info.archived else {
+        bail_with!(
+            ErrorCategory::Conflict,
+            "Worktree '{}' is not archived",
+            info.name
+        );
+    };
+    if info.path.exists() {
+        bail_with!(
+            ErrorCategory::Conflict,
+            "Cannot restore '{}': {} already exists",
+            info.name,
+            info.path.display()
+        );
+    }
+    if !archive.repo_path.exists() {
+        bail_with!(
+            ErrorCategory::NotFound,
+            "Repository of worktree '{}' is gone: {}",
+            info.name,
+            archive.repo_path.display()
+        );
+    }
+
+    let repo = archive.repo_path.display().to_string();
+    let path = info.path.display().to_string();
+    let rev = if info.detached {
+        archive.head.clone()
+    } else {
+        let branch_ref = format!("refs/heads/{}", info.branch);
+        if execute_git(&["-C", &repo, "rev-parse", "--verify", "--quiet", &branch_ref]).is_err() {
+            bail_with!(
+                ErrorCategory::NotFound,
+                "Branch '{}' of worktree '{}' no longer exists",
+                info.branch,
+                info.name
+            );
+        }
+        info.branch.clone()
+    };
+
+    let repo_config = state.repo_config(&info.repo_name);
+    let sparse_directories = info
+        .sparse
+        .as_ref()
+        .and_then(|profile| repo_config.sparse_profiles.get(profile));
+    if let Some(profile) = &info.sparse
+        && sparse_directories.is_none()
+    {
+        println!(
+            "{} Sparse profile '{}' is no longer configured; checking out every file",
+            "⚠️".yellow(),
+            profile
+        );
+    }
+
+    // Drop git's record of the removed checkout if one is left
+    execute_git(&["-C", &repo, "worktree", "prune"]).context("Failed to prune worktrees")?;
+    let mut add_args = vec!["-C", &repo, "worktree", "add"];
+    if sparse_directories.is_some() {
+        add_args.push("--no-checkout");
+    }
+    if info.detached {
+        add_args.push("--detach");
+    }
+    add_args.extend([path.as_str(), rev.as_str()]);
+    execute_git(&add_args).context("Failed to restore worktree")?;
+    if let Some(directories) = sparse_directories {
+        apply_sparse_checkout(&info.path, directories)?;
+    }
+    initialize_worktree(&info.path, &info.repo_name, &archive.repo_path, false)?;
+    if let Some(stash) = &archive.stash
+        && let Err(err) = git::pop_stash(&info.path, stash)
+    {
+        eprintln!(
+            "{} Could not apply the changes stashed when '{}' was archived: {err:#}. They are kept as stash {}",
+            "⚠️".yellow(),
+            info.name,
+            stash
+        );
+    }
+
+    if let Some(info) = state.worktrees.get_mut(&key) {
+        info.archived = None;
+    }
+    state.save()?;
+
+    println!(
+        "{} Restored '{}' at {}",
+        "♻️".green(),
+        info.name.cyan(),
+        info.path.display()
+    );
+    Ok(())
+}
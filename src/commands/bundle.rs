@@ -0,0 +1,193 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::json;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use crate::bail_with;
+use crate::dry_run;
+use crate::env_snapshot::{self, EnvSnapshot};
+use crate::error::{ErrorCategory, WithCategory};
+use crate::redact::Redactor;
+use crate::state::XlaudeState;
+use crate::tasks::TaskQueue;
+use crate::transcript;
+
+pub const DEFAULT_SESSIONS: usize = 5;
+
+/// Zip up what it takes to understand an agent's work in a worktree (the
+/// current one if `name` is `None`): its recent session transcripts, the diff
+/// against the base branch, its entry in the state, and its task logs. Secrets
+/// are redacted from everything.
+pub fn handle_bundle(name: Option<String>, output: Option<PathBuf>, sessions: usize) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let info = match &name {
+        Some(name) => {
+            state
+                .find_worktree(name)
+                .with_category(ErrorCategory::NotFound, || {
+                    format!("Worktree '{name}' not found")
+                })?
+                .1
+        }
+        None => {
+            let dir = std::env::current_dir().context("Failed to read the current directory")?;
+            let Some(info) = state.worktree_containing(&dir) else {
+                bail_with!(
+                    ErrorCategory::NotFound,
+                    "Not inside a managed worktree. Name the worktree to bundle."
+                );
+            };
+            info
+        }
+    };
+    let key = XlaudeState::make_key(&info.repo_name, &info.name);
+    let output = output.unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "xlaude-bundle-{}-{}-{}.zip",
+            info.repo_name,
+            info.name,
+            Utc::now().format("%Y%m%d-%H%M%S")
+        ))
+    });
+
+    if dry_run::is_enabled() {
+        dry_run::announce(&format!("write bundle of '{key}' to {}", output.display()));
+        return Ok(());
+    }
+    let mut bundle = Bundle {
+        zip: ZipWriter::new(
+            File::create(&output)
+                .with_context(|| format!("Failed to create {}", output.display()))?,
+        ),
+        redactor: Redactor::from_state(&state)?,
+        redactions: 0,
+        files: Vec::new(),
+    };
+
+    let tasks: Vec<_> = TaskQueue::load()?
+        .tasks
+        .into_iter()
+        .filter(|task| task.worktree == key)
+        .collect();
+    bundle.add_json(
+        "state.json",
+        &json!({
+            "key": key,
+            "worktree": info,
+            "repo": state.repos.get(&info.repo_name),
+            "agent": state.agent_for(Some(&info.repo_name)),
+            "tasks": tasks,
+        }),
+    )?;
+    if let Ok(snapshot) = EnvSnapshot::for_worktree(&state, Some(info), &info.path) {
+        bundle.add_json("environment.json", &snapshot)?;
+    }
+
+    let repo_config = state.repo_config(&info.repo_name);
+    let base = crate::git::base_ref(&info.path, &info.branch, repo_config.base_remote());
+    if info.path.exists() {
+        let from = base
+            .as_deref()
+            .and_then(|base| crate::git::merge_base(&info.path, base))
+            .unwrap_or_else(|| "HEAD".to_string());
+        if let Some(diff) = git(&info.path, &["diff", "--no-color", &from]) {
+            bundle.add("git/diff.patch", &diff)?;
+        }
+        if let Some(log) = git(&info.path, &["log", "--oneline", &format!("{from}..HEAD")]) {
+            bundle.add("git/log.txt", &log)?;
+        }
+        if let Some(status) = git(&info.path, &["status", "--short", "--branch"]) {
+            bundle.add("git/status.txt", &status)?;
+        }
+    }
+
+    for transcript in transcript::recent_transcripts(&info.path, sessions)? {
+        bundle.add_json(
+            &format!(
+                "sessions/{}-{}.json",
+                transcript.provider.to_lowercase(),
+                transcript.session_id
+            ),
+            &transcript,
+        )?;
+    }
+
+    for task in &tasks {
+        let Some(log_path) = &task.log_path else {
+            continue;
+        };
+        for path in [log_path.clone(), env_snapshot::path_for_log(log_path)] {
+            if let Ok(content) = fs::read_to_string(&path)
+                && let Some(file_name) = path.file_name()
+            {
+                bundle.add(&format!("logs/{}", file_name.to_string_lossy()), &content)?;
+            }
+        }
+    }
+
+    let manifest = json!({
+        "created_at": Utc::now(),
+        "xlaude_version": env!("CARGO_PKG_VERSION"),
+        "worktree": key,
+        "base": base,
+        "redactions": bundle.redactions,
+        "files": bundle.files,
+    });
+    bundle.add_json("manifest.json", &manifest)?;
+    let redactions = bundle.redactions;
+    let files = bundle.files.len();
+    bundle.zip.finish().context("Failed to write the bundle")?;
+
+    println!(
+        "{} Bundled '{}' into {} ({} files, {} secrets redacted)",
+        "✅".green(),
+        key,
+        output.display(),
+        files,
+        redactions
+    );
+    Ok(())
+}
+
+struct Bundle {
+    zip: ZipWriter<File>,
+    redactor: Redactor,
+    redactions: usize,
+    files: Vec<String>,
+}
+
+impl Bundle {
+    fn add(&mut self, name: &str, content: &str) -> Result<()> {
+        let (content, redactions) = self.redactor.redact_counting(content);
+        self.redactions += redactions;
+        self.zip
+            .start_file(name, SimpleFileOptions::default())
+            .and_then(|()| Ok(self.zip.write_all(content.as_bytes())?))
+            .with_context(|| format!("Failed to add {name} to the bundle"))?;
+        self.files.push(name.to_string());
+        Ok(())
+    }
+
+    fn add_json(&mut self, name: &str, value: &impl Serialize) -> Result<()> {
+        let content = serde_json::to_string_pretty(value)
+            .with_context(|| format!("Failed to serialize {name}"))?;
+        self.add(name, &content)
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+}
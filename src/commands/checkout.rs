@@ -1,15 +1,18 @@
-use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
-use chrono::Utc;
 use colored::Colorize;
 
+use crate::bail_with;
+use crate::commands::agent_launcher::{LaunchOptions, launch_with_menu};
+use crate::commands::agent_prompt::AgentSelection;
+use crate::commands::create::initialize_worktree;
 use crate::commands::open::handle_open;
-use crate::git::{execute_git, get_repo_name, update_submodules};
+use crate::error::ErrorCategory;
+use crate::git::{execute_git, get_repo_name, main_repo_dir, worktree_with_branch};
+use crate::hooks::{self, Event, EventKind};
 use crate::input::{get_command_arg, smart_confirm};
-use crate::state::{WorktreeInfo, XlaudeState};
-use crate::utils::sanitize_branch_name;
+use crate::state::{RepoConfig, WorktreeInfo, XlaudeState};
 
 pub fn handle_checkout(target: Option<String>) -> Result<()> {
     let raw_target = get_command_arg(target)?
@@ -17,15 +20,13 @@ pub fn handle_checkout(target: Option<String>) -> Result<()> {
         .filter(|s| !s.is_empty())
         .context("Please provide a branch name or pull request number")?;
 
-    let checkout_target = CheckoutTarget::parse(&raw_target)?;
-    let repo_root_str = execute_git(&["rev-parse", "--show-toplevel"])?
-        .trim()
-        .to_string();
-    let repo_root = PathBuf::from(&repo_root_str);
+    let checkout_target = resolve_target(&raw_target)?;
+    // The main checkout, or the repository itself when it is bare
+    let repo_root = main_repo_dir()?;
     let repo_name = get_repo_name().context("Not in a git repository")?;
 
     let branch_name = checkout_target.branch_name();
-    let worktree_name = sanitize_branch_name(&branch_name);
+    let worktree_name = XlaudeState::load()?.worktree_name_for_branch(&repo_name, &branch_name);
 
     if let Some(existing) = find_existing_worktree(&repo_name, &branch_name)? {
         println!(
@@ -42,12 +43,13 @@ pub fn handle_checkout(target: Option<String>) -> Result<()> {
         );
 
         let should_open = smart_confirm(
+            "open_existing",
             "Worktree already exists. Open it now with 'xlaude open'?",
             false,
         )?;
 
         if should_open {
-            handle_open(Some(existing.name.clone()))?;
+            handle_open(Some(existing.name.clone()), LaunchOptions::default())?;
             return Ok(());
         }
 
@@ -58,7 +60,12 @@ pub fn handle_checkout(target: Option<String>) -> Result<()> {
         );
     }
 
-    ensure_branch_ready(&checkout_target, &branch_name)?;
+    if guard_branch_checked_out(&repo_root, &branch_name)?.is_some() {
+        return Ok(());
+    }
+
+    let repo_config = XlaudeState::load()?.repo_config(&repo_name);
+    ensure_branch_ready(&checkout_target, &branch_name, &repo_config)?;
 
     println!(
         "{} Checking out {} into worktree '{}'...",
@@ -67,23 +74,61 @@ pub fn handle_checkout(target: Option<String>) -> Result<()> {
         worktree_name.cyan()
     );
 
-    let created_path = create_worktree(&repo_root, &repo_name, &branch_name, &worktree_name)?;
+    let worktree_info = create_worktree(&repo_root, &repo_name, &branch_name, &worktree_name)?;
 
     println!(
         "{} Worktree created at: {}",
         "✅".green(),
-        created_path.display()
-    );
-    println!(
-        "  {} To open it later, run: {} {}",
-        "💡".cyan(),
-        "xlaude open".cyan(),
-        worktree_name.cyan()
+        worktree_info.path.display()
     );
 
+    let hint = || {
+        println!(
+            "  {} To open it later, run: {} {}",
+            "💡".cyan(),
+            "xlaude open".cyan(),
+            worktree_name.cyan()
+        );
+    };
+
+    if std::env::var("XLAUDE_TEST_MODE").is_ok() || std::env::var("XLAUDE_NO_AUTO_OPEN").is_ok() {
+        hint();
+    } else {
+        let selection =
+            launch_with_menu(&worktree_info, "Would you like to open the worktree now?")
+                .context("Failed to launch agent")?;
+        if selection == AgentSelection::Skip && std::env::var("XLAUDE_NON_INTERACTIVE").is_err() {
+            hint();
+        }
+    }
+
     Ok(())
 }
 
+// Interpret `origin/feature` as the remote branch `feature` when no local branch has that name
+fn resolve_target(input: &str) -> Result<CheckoutTarget> {
+    let target = CheckoutTarget::parse(input)?;
+    let CheckoutTarget::Branch(ref name) = target else {
+        return Ok(target);
+    };
+    if branch_exists(name) {
+        return Ok(target);
+    }
+
+    let Some((remote, branch)) = name.split_once('/') else {
+        return Ok(target);
+    };
+    let remotes = execute_git(&["remote"]).unwrap_or_default();
+    if branch.is_empty() || !remotes.lines().any(|r| r.trim() == remote) {
+        return Ok(target);
+    }
+
+    Ok(CheckoutTarget::RemoteBranch {
+        remote: remote.to_string(),
+        branch: branch.to_string(),
+    })
+}
+
 fn find_existing_worktree(repo_name: &str, branch_name: &str) -> Result<Option<ExistingWorktree>> {
     let state = XlaudeState::load()?;
     Ok(state
@@ -94,54 +139,133 @@ fn find_existing_worktree(repo_name: &str, branch_name: &str) -> Result<Option<E
         .map(ExistingWorktree))
 }
 
-fn ensure_branch_ready(target: &CheckoutTarget, branch_name: &str) -> Result<()> {
+/// Git checks a branch out in one worktree at a time. When `branch_name` is
+/// already checked out, say where and offer to open that worktree if xlaude
+/// manages it; returns its name once opened, and fails when nothing was opened.
+pub(crate) fn guard_branch_checked_out(
+    repo_root: &Path,
+    branch_name: &str,
+) -> Result<Option<String>> {
+    let Some(holder) = worktree_with_branch(repo_root, branch_name) else {
+        return Ok(None);
+    };
+    println!(
+        "{} Branch '{}' is already checked out at {}",
+        "⚠️".yellow(),
+        branch_name.cyan(),
+        holder.display()
+    );
+
+    let state = XlaudeState::load()?;
+    let Some(existing) = state.worktrees.values().find(|info| info.path == holder) else {
+        bail_with!(
+            ErrorCategory::Conflict,
+            "Branch '{}' is already checked out at '{}'; switch that checkout to another branch first",
+            branch_name,
+            holder.display()
+        );
+    };
+    println!(
+        "  {} It belongs to worktree '{}'",
+        "💡".cyan(),
+        existing.name.cyan()
+    );
+    if smart_confirm(
+        "open_existing",
+        "Open that worktree with 'xlaude open' instead?",
+        false,
+    )? {
+        handle_open(Some(existing.name.clone()), LaunchOptions::default())?;
+        return Ok(Some(existing.name.clone()));
+    }
+    bail_with!(
+        ErrorCategory::Conflict,
+        "Branch '{}' is already checked out in worktree '{}'",
+        branch_name,
+        existing.name
+    );
+}
+
+fn ensure_branch_ready(
+    target: &CheckoutTarget,
+    branch_name: &str,
+    repo_config: &RepoConfig,
+) -> Result<()> {
     match target {
-        CheckoutTarget::Branch(_) => ensure_branch_available(branch_name),
-        CheckoutTarget::PullRequest(pr_number) => fetch_pull_request(*pr_number, branch_name),
+        CheckoutTarget::Branch(_) => {
+            ensure_branch_available(branch_name, repo_config.push_remote())
+        }
+        // Pull requests live on the repository they target
+        CheckoutTarget::PullRequest(pr_number) => {
+            fetch_pull_request(*pr_number, branch_name, repo_config.base_remote())
+        }
+        CheckoutTarget::RemoteBranch { remote, branch } => ensure_remote_branch(remote, branch),
     }
 }
 
-fn ensure_branch_available(branch_name: &str) -> Result<()> {
+fn ensure_remote_branch(remote: &str, branch: &str) -> Result<()> {
+    if branch_exists(branch) {
+        return Ok(());
+    }
+
+    println!(
+        "{} Fetching branch '{}' from {}...",
+        "🌐".blue(),
+        branch.cyan(),
+        remote
+    );
+    execute_git(&["fetch", remote, branch])
+        .with_context(|| format!("Failed to fetch branch '{branch}' from {remote}"))?;
+    execute_git(&["branch", "--track", branch, &format!("{remote}/{branch}")])
+        .with_context(|| format!("Failed to create local branch '{branch}'"))?;
+
+    Ok(())
+}
+
+fn ensure_branch_available(branch_name: &str, remote: &str) -> Result<()> {
     if branch_exists(branch_name) {
         return Ok(());
     }
 
     println!(
-        "{} Branch '{}' not found locally. Attempting to fetch from origin...",
+        "{} Branch '{}' not found locally. Attempting to fetch from {}...",
         "🌐".blue(),
-        branch_name.cyan()
+        branch_name.cyan(),
+        remote
     );
 
-    ensure_origin_remote()?;
+    ensure_remote(remote)?;
     let fetch_spec = format!("{branch_name}:{branch_name}");
-    execute_git(&["fetch", "origin", &fetch_spec])
-        .with_context(|| format!("Failed to fetch branch '{branch_name}' from origin"))?;
+    execute_git(&["fetch", remote, &fetch_spec])
+        .with_context(|| format!("Failed to fetch branch '{branch_name}' from {remote}"))?;
 
     if branch_exists(branch_name) {
         Ok(())
     } else {
-        bail!("Branch '{branch_name}' does not exist locally or on origin");
+        bail!("Branch '{branch_name}' does not exist locally or on {remote}");
     }
 }
 
-fn fetch_pull_request(pr_number: u64, branch_name: &str) -> Result<()> {
-    ensure_origin_remote()?;
+fn fetch_pull_request(pr_number: u64, branch_name: &str, remote: &str) -> Result<()> {
+    ensure_remote(remote)?;
     println!(
-        "{} Fetching pull request #{} from origin...",
+        "{} Fetching pull request #{} from {}...",
         "🌐".blue(),
-        pr_number
+        pr_number,
+        remote
     );
 
     let fetch_ref = format!("pull/{pr_number}/head:refs/heads/{branch_name}");
-    execute_git(&["fetch", "origin", &fetch_ref])
-        .with_context(|| format!("Failed to fetch pull request #{pr_number} from origin"))?;
+    execute_git(&["fetch", remote, &fetch_ref])
+        .with_context(|| format!("Failed to fetch pull request #{pr_number} from {remote}"))?;
 
     Ok(())
 }
 
-fn ensure_origin_remote() -> Result<()> {
-    execute_git(&["remote", "get-url", "origin"])
-        .context("Remote 'origin' is not configured. Please add a remote before using checkout.")?;
+fn ensure_remote(remote: &str) -> Result<()> {
+    execute_git(&["remote", "get-url", remote]).with_context(|| {
+        format!("Remote '{remote}' is not configured. Please add a remote before using checkout.")
+    })?;
     Ok(())
 }
 
@@ -154,7 +278,7 @@ fn create_worktree(
     repo_name: &str,
     branch_name: &str,
     worktree_name: &str,
-) -> Result<PathBuf> {
+) -> Result<WorktreeInfo> {
     let repo_root_str = repo_root
         .to_str()
         .context("Repository path contains invalid UTF-8")?;
@@ -165,7 +289,8 @@ fn create_worktree(
     let worktree_path = worktree_parent.join(format!("{repo_name}-{worktree_name}"));
 
     if worktree_path.exists() {
-        bail!(
+        bail_with!(
+            ErrorCategory::Conflict,
             "Directory '{}' already exists. Please remove it or choose another branch.",
             worktree_path.display()
         );
@@ -173,7 +298,8 @@ fn create_worktree(
 
     let existing_worktrees = list_worktrees_for_repo(repo_root)?;
     if existing_worktrees.iter().any(|w| w == &worktree_path) {
-        bail!(
+        bail_with!(
+            ErrorCategory::Conflict,
             "A git worktree already exists at '{}'. Remove it or pick a different branch.",
             worktree_path.display()
         );
@@ -182,7 +308,8 @@ fn create_worktree(
     let mut state = XlaudeState::load()?;
     let key = XlaudeState::make_key(repo_name, worktree_name);
     if state.worktrees.contains_key(&key) {
-        bail!(
+        bail_with!(
+            ErrorCategory::Conflict,
             "A worktree named '{}' is already tracked for '{}'.",
             worktree_name,
             repo_name
@@ -203,39 +330,22 @@ fn create_worktree(
     ])
     .context("Failed to create worktree")?;
 
-    if let Err(e) = update_submodules(&worktree_path) {
-        println!(
-            "{} Warning: Failed to update submodules: {}",
-            "⚠️".yellow(),
-            e
-        );
-    } else {
-        let gitmodules = worktree_path.join(".gitmodules");
-        if gitmodules.exists() {
-            println!("{} Updated submodules", "📦".green());
-        }
-    }
+    initialize_worktree(&worktree_path, repo_name, repo_root, false)?;
 
-    let claude_local = repo_root.join("CLAUDE.local.md");
-    if claude_local.exists() {
-        let target = worktree_path.join("CLAUDE.local.md");
-        fs::copy(&claude_local, &target).context("Failed to copy CLAUDE.local.md")?;
-        println!("{} Copied CLAUDE.local.md to worktree", "📄".green());
-    }
-
-    state.worktrees.insert(
-        key,
-        WorktreeInfo {
-            name: worktree_name.to_string(),
-            branch: branch_name.to_string(),
-            path: worktree_path.clone(),
-            repo_name: repo_name.to_string(),
-            created_at: Utc::now(),
-        },
+    let worktree_info = WorktreeInfo::new(
+        worktree_name.to_string(),
+        branch_name.to_string(),
+        worktree_path,
+        repo_name.to_string(),
     );
+    state.worktrees.insert(key, worktree_info.clone());
     state.save()?;
+    hooks::emit(
+        &state,
+        Event::new(EventKind::WorktreeCreated, &worktree_info),
+    );
 
-    Ok(worktree_path)
+    Ok(worktree_info)
 }
 
 fn list_worktrees_for_repo(repo_root: &Path) -> Result<Vec<PathBuf>> {
@@ -267,6 +377,7 @@ impl std::ops::Deref for ExistingWorktree {
 
 enum CheckoutTarget {
     Branch(String),
+    RemoteBranch { remote: String, branch: String },
     PullRequest(u64),
 }
 
@@ -291,6 +402,7 @@ impl CheckoutTarget {
     fn branch_name(&self) -> String {
         match self {
             Self::Branch(name) => name.clone(),
+            Self::RemoteBranch { branch, .. } => branch.clone(),
             Self::PullRequest(number) => format!("pr/{number}"),
         }
     }
@@ -298,6 +410,7 @@ impl CheckoutTarget {
     fn describe(&self) -> String {
         match self {
             Self::Branch(name) => format!("branch '{name}'"),
+            Self::RemoteBranch { remote, branch } => format!("branch '{remote}/{branch}'"),
             Self::PullRequest(number) => format!("pull request #{number}"),
         }
     }
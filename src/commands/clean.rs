@@ -1,39 +1,114 @@
 use anyhow::Result;
 use colored::Colorize;
+use serde_json::json;
 use std::collections::HashSet;
 use std::path::PathBuf;
 
-use crate::git::list_worktrees;
+use crate::commands::delete::delete_confirmed;
+use crate::commands::list::format_time_ago;
+use crate::commands::lock::format_reason;
+use crate::git::{
+    find_main_repo, has_unpushed_commits, is_working_tree_clean, list_worktrees,
+    worktree_lock_reason,
+};
+use crate::hooks::{self, Event, EventKind};
+use crate::input::smart_confirm_destructive;
+use crate::processes::ProcessRegistry;
+use crate::stale;
 use crate::state::XlaudeState;
+use crate::tr;
 use crate::utils::execute_in_dir;
 
-pub fn handle_clean() -> Result<()> {
+pub fn handle_clean(stale: bool) -> Result<()> {
     let mut state = XlaudeState::load()?;
 
     if state.worktrees.is_empty() {
-        println!("{} No worktrees in state", "✨".green());
+        println!("{}", tr!("{} No worktrees in state", "✨".green()));
         return Ok(());
     }
+    if stale {
+        return clean_stale(&mut state);
+    }
 
-    println!("{} Checking for invalid worktrees...", "🔍".cyan());
+    remove_invalid_worktrees(&mut state, false)?;
+    Ok(())
+}
+
+/// Drop worktrees that git no longer lists from the state, except locked,
+/// kept, and archived ones. Returns the keys of the removed entries; `quiet`
+/// leaves the reporting to the caller.
+pub(crate) fn remove_invalid_worktrees(
+    state: &mut XlaudeState,
+    quiet: bool,
+) -> Result<Vec<String>> {
+    if !quiet {
+        println!(
+            "{}",
+            tr!("{} Checking for invalid worktrees...", "🔍".cyan())
+        );
+    }
 
     // Collect all actual worktrees from all repositories
-    let actual_worktrees = collect_all_worktrees(&state)?;
+    let actual_worktrees = collect_all_worktrees(state)?;
 
     // Find and remove invalid worktrees
-    let mut removed_count = 0;
     let worktrees_to_remove: Vec<_> = state
         .worktrees
         .iter()
         .filter_map(|(name, info)| {
+            if let Some(reason) = worktree_lock_reason(&info.path, &info.repo_name) {
+                if !quiet {
+                    println!(
+                        "{}",
+                        tr!(
+                            "  {} Skipping locked worktree: {}{}",
+                            "🔒".yellow(),
+                            name.yellow(),
+                            format_reason(&reason)
+                        )
+                    );
+                }
+                return None;
+            }
+            if state.is_kept(info) {
+                if !quiet {
+                    println!(
+                        "{}",
+                        tr!(
+                            "  {} Skipping kept worktree: {}",
+                            "📌".blue(),
+                            name.yellow()
+                        )
+                    );
+                }
+                return None;
+            }
+            // Its checkout was removed on purpose
+            if info.archived.is_some() {
+                if !quiet {
+                    println!(
+                        "{}",
+                        tr!(
+                            "  {} Skipping archived worktree: {}",
+                            "🗄️".blue(),
+                            name.yellow()
+                        )
+                    );
+                }
+                return None;
+            }
             if !actual_worktrees.contains(&info.path) {
-                println!(
-                    "  {} Found invalid worktree: {} ({})",
-                    "❌".red(),
-                    name.yellow(),
-                    info.path.display()
-                );
-                removed_count += 1;
+                if !quiet {
+                    println!(
+                        "{}",
+                        tr!(
+                            "  {} Found invalid worktree: {} ({})",
+                            "❌".red(),
+                            name.yellow(),
+                            info.path.display()
+                        )
+                    );
+                }
                 Some(name.clone())
             } else {
                 None
@@ -42,22 +117,115 @@ pub fn handle_clean() -> Result<()> {
         .collect();
 
     // Remove invalid worktrees from state
-    for name in worktrees_to_remove {
-        state.worktrees.remove(&name);
-    }
+    let removed: Vec<_> = worktrees_to_remove
+        .iter()
+        .filter_map(|name| state.remove_worktree(name))
+        .collect();
 
-    if removed_count > 0 {
+    if !removed.is_empty() {
         state.save()?;
+        for info in &removed {
+            hooks::emit(
+                state,
+                Event::new(EventKind::WorktreeDeleted, info).details(json!({ "reason": "clean" })),
+            );
+        }
+    }
+    if !quiet && removed.is_empty() {
+        println!("{}", tr!("{} All worktrees are valid", "✨".green()));
+    } else if !quiet {
+        let message = if removed.len() == 1 {
+            tr!(
+                "{} Removed {} invalid worktree",
+                "✅".green(),
+                removed.len()
+            )
+        } else {
+            tr!(
+                "{} Removed {} invalid worktrees",
+                "✅".green(),
+                removed.len()
+            )
+        };
+        println!("{message}");
+    }
+
+    Ok(worktrees_to_remove)
+}
+
+/// Offer every stale worktree for deletion with a single confirmation.
+/// Worktrees on the repository's `keep` list are not considered. Locked worktrees, worktrees with pending work, and worktrees with a
+/// running agent are listed but never deleted.
+fn clean_stale(state: &mut XlaudeState) -> Result<()> {
+    let Some(threshold) = stale::stale_after(state)? else {
+        println!(
+            "{}",
+            tr!(
+                "{} Stale detection is turned off (stale_after in state.json)",
+                "ℹ️".blue()
+            )
+        );
+        return Ok(());
+    };
+
+    println!("{}", tr!("{} Checking for stale worktrees...", "🔍".cyan()));
+    let registry = ProcessRegistry::load_live().unwrap_or_default();
+    let mut keys: Vec<_> = state.worktrees.keys().cloned().collect();
+    keys.sort();
+
+    let mut candidates = Vec::new();
+    for key in keys {
+        let info = &state.worktrees[&key];
+        if !info.path.exists() || state.is_kept(info) {
+            continue;
+        }
+        let Some(since) = stale::stale_since(info, threshold) else {
+            continue;
+        };
+        let skip_reason = if let Some(reason) = worktree_lock_reason(&info.path, &info.repo_name) {
+            Some(tr!("locked{}", format_reason(&reason)))
+        } else if !registry.for_worktree(&key).is_empty() {
+            Some(tr!("an agent is running"))
+        } else if execute_in_dir(&info.path, || {
+            Ok(!is_working_tree_clean()? || has_unpushed_commits())
+        })? {
+            Some(tr!("uncommitted changes or unpushed commits"))
+        } else {
+            None
+        };
+
         println!(
-            "{} Removed {} invalid worktree{}",
-            "✅".green(),
-            removed_count,
-            if removed_count == 1 { "" } else { "s" }
+            "{}",
+            tr!(
+                "  {} {} (last activity {})",
+                "💤".yellow(),
+                key.yellow(),
+                format_time_ago(Some(since))
+            )
         );
-    } else {
-        println!("{} All worktrees are valid", "✨".green());
+        match skip_reason {
+            Some(reason) => println!("{}", tr!("      {} Skipped: {}", "ℹ️".blue(), reason)),
+            None => candidates.push(key),
+        }
+    }
+
+    if candidates.is_empty() {
+        println!("{}", tr!("{} No stale worktrees to delete", "✨".green()));
+        return Ok(());
+    }
+    if !smart_confirm_destructive(
+        "confirm_clean",
+        &tr!("Delete {} stale worktree(s)?", candidates.len()),
+        false,
+    )? {
+        println!("{}", tr!("{} Cancelled", "❌".red()));
+        return Ok(());
     }
 
+    for key in candidates {
+        let info = state.worktrees[&key].clone();
+        delete_confirmed(state, &key, &info)?;
+    }
     Ok(())
 }
 
@@ -68,7 +236,7 @@ fn collect_all_worktrees(state: &XlaudeState) -> Result<HashSet<PathBuf>> {
     let repo_paths: HashSet<_> = state
         .worktrees
         .values()
-        .filter_map(|info| info.path.parent().map(|p| p.join(&info.repo_name)))
+        .filter_map(|info| find_main_repo(&info.path, &info.repo_name))
         .collect();
 
     // Collect worktrees from each repository
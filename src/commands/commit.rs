@@ -0,0 +1,246 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::agent::{Launch, guard_permissions, headless_command};
+use crate::bail_with;
+use crate::dry_run;
+use crate::error::{ErrorCategory, WithCategory};
+use crate::git::execute_git;
+use crate::input::is_piped_input;
+use crate::interrupt::TempFile;
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::transcript::{Role, is_system_message, recent_transcripts};
+use crate::utils::resolve_available_agent_command;
+
+// The diff sent to the agent is cut to this many bytes
+const MAX_DIFF_BYTES: usize = 20_000;
+// Latest user instructions from the session included in the prompt
+const INSTRUCTION_COUNT: usize = 3;
+const MAX_SUBJECT_CHARS: usize = 72;
+
+pub fn handle_commit(name: String, no_verify: bool, amend: bool) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let (_, info) = state
+        .find_worktree(&name)
+        .with_category(ErrorCategory::NotFound, || {
+            format!("Worktree '{name}' not found")
+        })?;
+    let path = info.path.display().to_string();
+
+    execute_git(&["-C", &path, "add", "-A"])?;
+    // When amending, the draft describes the amended commit as a whole
+    let base = if amend
+        && execute_git(&["-C", &path, "rev-parse", "--verify", "--quiet", "HEAD^"]).is_ok()
+    {
+        "HEAD^"
+    } else {
+        "HEAD"
+    };
+    let files = execute_git(&["-C", &path, "diff", "--cached", "--name-only", base])?;
+    if files.is_empty() && !amend && !dry_run::is_enabled() {
+        bail_with!(
+            ErrorCategory::General,
+            "Nothing to commit in worktree '{}'",
+            info.name
+        );
+    }
+
+    let mut args = vec!["-C", path.as_str(), "commit"];
+    if amend {
+        args.push("--amend");
+    }
+    if no_verify {
+        args.push("--no-verify");
+    }
+    if dry_run::is_enabled() {
+        dry_run::announce("draft a commit message with the agent");
+        args.extend(["-F", "<draft>"]);
+        dry_run::announce(&shell_words::join(std::iter::once("git").chain(args)));
+        return Ok(());
+    }
+
+    let diff = execute_git(&["-C", &path, "diff", "--cached", base])?;
+    let instructions = recent_instructions(&info.path);
+    let message = draft_message(info, &diff, &files, &instructions);
+    println!("{} Drafted commit message:", "📝".cyan());
+    for line in message.lines() {
+        println!("  {}", line.bright_black());
+    }
+
+    let message_file = TempFile::create("xlaude-commit", "txt", &format!("{message}\n"))
+        .context("Failed to write the commit message")?;
+    let message_arg = message_file.path().display().to_string();
+    args.extend(["-F", message_arg.as_str()]);
+    // Let the user edit the draft in their git editor when there is a terminal
+    if std::env::var("XLAUDE_NON_INTERACTIVE").is_err() && !is_piped_input() {
+        args.push("--edit");
+    }
+    let status = Command::new("git").args(&args).status();
+    drop(message_file);
+    if !status.context("Failed to execute git command")?.success() {
+        bail_with!(ErrorCategory::Git, "git commit failed in '{}'", info.name);
+    }
+
+    let sha = execute_git(&["-C", &path, "rev-parse", "--short", "HEAD"])?;
+    println!(
+        "{} Committed {} in '{}'",
+        "✅".green(),
+        sha.cyan(),
+        info.name.cyan()
+    );
+    Ok(())
+}
+
+/// The last few instructions the user typed in the worktree's most recent
+/// session, oldest first.
+fn recent_instructions(worktree_path: &Path) -> Vec<String> {
+    let Some(transcript) = recent_transcripts(worktree_path, 1)
+        .ok()
+        .and_then(|transcripts| transcripts.into_iter().next())
+    else {
+        return Vec::new();
+    };
+    let mut instructions: Vec<String> = transcript
+        .turns
+        .into_iter()
+        .filter(|turn| turn.role == Role::User && !is_system_message(&turn.text))
+        .map(|turn| turn.text.trim().to_string())
+        .filter(|text| !text.is_empty())
+        .collect();
+    let skip = instructions.len().saturating_sub(INSTRUCTION_COUNT);
+    instructions.drain(..skip);
+    instructions
+}
+
+/// Ask the agent for a message; fall back to one built from the latest
+/// instruction and the changed files when it cannot provide one.
+fn draft_message(info: &WorktreeInfo, diff: &str, files: &str, instructions: &[String]) -> String {
+    match agent_draft(info, diff, instructions) {
+        Ok(Some(message)) => return message,
+        Ok(None) => {}
+        Err(err) => eprintln!(
+            "{} Could not draft a message with the agent: {err:#}",
+            "⚠️".yellow()
+        ),
+    }
+    fallback_message(info, files, instructions)
+}
+
+fn agent_draft(info: &WorktreeInfo, diff: &str, instructions: &[String]) -> Result<Option<String>> {
+    let (program, mut args) = resolve_available_agent_command(info)?;
+    guard_permissions(
+        &XlaudeState::load()?,
+        &info.repo_name,
+        &program,
+        &mut args,
+        Launch::Headless,
+    )?;
+    let headless = headless_command(program, args, &draft_prompt(diff, instructions));
+    let mut child = Command::new(&headless.program)
+        .args(&headless.args)
+        .current_dir(&info.path)
+        .stdin(if headless.stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to launch agent '{}'", headless.program))?;
+    if let (Some(input), Some(mut stdin)) = (headless.stdin, child.stdin.take()) {
+        // The agent may exit without reading its input; that is not our error
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail_with!(
+            ErrorCategory::AgentFailure,
+            "Agent '{}' exited with {}",
+            headless.program,
+            output.status
+        );
+    }
+    let message = clean_agent_output(&String::from_utf8_lossy(&output.stdout));
+    Ok((!message.is_empty()).then_some(message))
+}
+
+fn draft_prompt(diff: &str, instructions: &[String]) -> String {
+    let mut prompt = String::from(
+        "Write a git commit message for the staged changes below. Reply with the message \
+         only: a subject line of at most 72 characters in the imperative mood, then a blank \
+         line and a short body if the change needs explaining.\n",
+    );
+    if !instructions.is_empty() {
+        prompt.push_str("\nThe changes were made for these instructions:\n");
+        for instruction in instructions {
+            prompt.push_str(&format!("- {instruction}\n"));
+        }
+    }
+    prompt.push_str("\nDiff:\n");
+    prompt.push_str(truncate_at_char_boundary(diff, MAX_DIFF_BYTES));
+    if diff.len() > MAX_DIFF_BYTES {
+        prompt.push_str("\n[diff truncated]");
+    }
+    prompt
+}
+
+/// Agents sometimes wrap their answer in a code fence.
+fn clean_agent_output(output: &str) -> String {
+    let trimmed = output.trim();
+    let unfenced = trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|inner| inner.split_once('\n').map_or("", |(_, body)| body))
+        .unwrap_or(trimmed);
+    unfenced.trim().to_string()
+}
+
+fn fallback_message(info: &WorktreeInfo, files: &str, instructions: &[String]) -> String {
+    let subject = instructions
+        .last()
+        .and_then(|instruction| instruction.lines().next())
+        .map(|line| {
+            if line.chars().count() > MAX_SUBJECT_CHARS {
+                let cut: String = line.chars().take(MAX_SUBJECT_CHARS - 3).collect();
+                format!("{}...", cut.trim_end())
+            } else {
+                line.to_string()
+            }
+        })
+        .unwrap_or_else(|| format!("Update {}", info.name));
+    if files.is_empty() {
+        return subject;
+    }
+    let list: Vec<String> = files.lines().map(|file| format!("- {file}")).collect();
+    format!("{subject}\n\nChanged files:\n{}", list.join("\n"))
+}
+
+fn truncate_at_char_boundary(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let end = (0..=max_bytes)
+        .rev()
+        .find(|&index| text.is_char_boundary(index))
+        .unwrap_or(0);
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_code_fences_from_agent_output() {
+        assert_eq!(
+            clean_agent_output("```text\nAdd login\n\nUses OAuth.\n```\n"),
+            "Add login\n\nUses OAuth."
+        );
+        assert_eq!(clean_agent_output("  Add login\n"), "Add login");
+        assert_eq!(truncate_at_char_boundary("héllo", 2), "h");
+    }
+}
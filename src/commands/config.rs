@@ -3,6 +3,11 @@ use std::process::Command;
 
 use anyhow::{Context, Result, anyhow, bail};
 
+use crate::error::{ErrorCategory, WithCategory};
+use crate::interrupt::TempFile;
+use crate::state::XlaudeState;
+use crate::store::Backend;
+
 pub fn handle_config() -> Result<()> {
     let editor = std::env::var("EDITOR")
         .context("EDITOR environment variable is not set; please export your preferred editor")?;
@@ -14,7 +19,18 @@ pub fn handle_config() -> Result<()> {
         bail!("EDITOR command is empty");
     }
 
-    let state_path = crate::state::get_state_path()?;
+    // The SQLite backend is edited through a JSON export that is saved back afterwards
+    let export = if Backend::current()? == Backend::Sqlite {
+        let content = serde_json::to_string_pretty(&XlaudeState::load()?)
+            .context("Failed to serialize state")?;
+        Some(TempFile::create("xlaude-state", "json", &content).context("Failed to export state")?)
+    } else {
+        None
+    };
+    let state_path = match &export {
+        Some(export) => export.path().to_path_buf(),
+        None => crate::state::get_state_path()?,
+    };
     if let Some(parent) = state_path.parent() {
         fs::create_dir_all(parent)
             .with_context(|| format!("Failed to create config directory: {}", parent.display()))?;
@@ -40,5 +56,20 @@ pub fn handle_config() -> Result<()> {
         );
     }
 
+    if let Some(export) = export {
+        let content = fs::read_to_string(&state_path).context("Failed to read edited state")?;
+        let state: XlaudeState = match serde_json::from_str(&content) {
+            Ok(state) => state,
+            // The edits stay in the file for another try
+            Err(err) => {
+                export.keep();
+                return Err(err).with_category(ErrorCategory::Config, || {
+                    format!("Edited state in {} is not valid", state_path.display())
+                });
+            }
+        };
+        state.save()?;
+    }
+
     Ok(())
 }
@@ -0,0 +1,202 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::bail_with;
+use crate::error::ErrorCategory;
+use crate::git;
+use crate::overlap::{self, Overlap};
+use crate::state::{WorktreeInfo, XlaudeState};
+
+#[derive(Debug, Serialize)]
+struct RepoConflicts {
+    repo: String,
+    // Branch the worktrees are checked against; `None` when there is none
+    base: Option<String>,
+    conflicts: Vec<Conflict>,
+    // Worktrees with commits that merge cleanly with the base and each other
+    clean: Vec<String>,
+    // Worktrees without commits of their own, which cannot conflict
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unchanged: Vec<String>,
+    // Files several worktrees change, committed or not, whether or not they
+    // conflict; merging those worktrees one after another is safest
+    overlaps: Vec<Overlap>,
+}
+
+#[derive(Debug, Serialize)]
+struct Conflict {
+    // Worktree name
+    left: String,
+    // Another worktree's name, or the base branch
+    right: String,
+    files: Vec<String>,
+}
+
+/// Trial-merge every worktree branch with its repository's base branch and
+/// with every other worktree branch of the repository, and report the pairs
+/// that would conflict. Fails with the conflict exit code when any would.
+pub fn handle_conflicts(repo: Option<String>, json: bool) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let mut repos: BTreeMap<&str, Vec<&WorktreeInfo>> = BTreeMap::new();
+    for info in state.worktrees.values() {
+        if repo.as_deref().is_none_or(|repo| repo == info.repo_name) && info.path.is_dir() {
+            repos.entry(&info.repo_name).or_default().push(info);
+        }
+    }
+    if let Some(repo) = &repo
+        && repos.is_empty()
+    {
+        bail_with!(
+            ErrorCategory::NotFound,
+            "No worktrees of repository '{repo}' found"
+        );
+    }
+
+    let mut reports = Vec::new();
+    for (repo_name, mut worktrees) in repos {
+        worktrees.sort_by(|a, b| a.name.cmp(&b.name));
+        reports.push(check_repo(&state, repo_name, &worktrees)?);
+    }
+    let conflicts: usize = reports.iter().map(|report| report.conflicts.len()).sum();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        print_reports(&reports);
+    }
+    if conflicts > 0 {
+        bail_with!(
+            ErrorCategory::Conflict,
+            "{} pair(s) of branches would conflict",
+            conflicts
+        );
+    }
+    Ok(())
+}
+
+fn check_repo(
+    state: &XlaudeState,
+    repo_name: &str,
+    worktrees: &[&WorktreeInfo],
+) -> Result<RepoConflicts> {
+    // Worktrees share their refs, so any of them can run the merges
+    let git_dir = &worktrees[0].path;
+    let remote = state.repo_config(repo_name).base_remote().to_string();
+    let base = git::base_ref(git_dir, &worktrees[0].branch, &remote);
+
+    let mut changed = Vec::new();
+    let mut unchanged = Vec::new();
+    for info in worktrees {
+        let ahead = base
+            .as_deref()
+            .and_then(|base| git::ahead_behind(&info.path, base))
+            .map(|(ahead, _)| ahead);
+        if ahead == Some(0) || base.as_deref() == Some(info.branch.as_str()) {
+            unchanged.push(info.name.clone());
+        } else {
+            changed.push(*info);
+        }
+    }
+
+    let mut conflicts = Vec::new();
+    if let Some(base) = &base {
+        for info in &changed {
+            let files = git::merge_conflicts(git_dir, base, &info.branch)?;
+            if !files.is_empty() {
+                conflicts.push(Conflict {
+                    left: info.name.clone(),
+                    right: base.clone(),
+                    files,
+                });
+            }
+        }
+    }
+    for (index, left) in changed.iter().enumerate() {
+        for right in &changed[index + 1..] {
+            let files = git::merge_conflicts(git_dir, &left.branch, &right.branch)?;
+            if !files.is_empty() {
+                conflicts.push(Conflict {
+                    left: left.name.clone(),
+                    right: right.name.clone(),
+                    files,
+                });
+            }
+        }
+    }
+
+    let clean = changed
+        .iter()
+        .filter(|info| {
+            !conflicts
+                .iter()
+                .any(|conflict| conflict.left == info.name || conflict.right == info.name)
+        })
+        .map(|info| info.name.clone())
+        .collect();
+    Ok(RepoConflicts {
+        repo: repo_name.to_string(),
+        base,
+        conflicts,
+        clean,
+        unchanged,
+        overlaps: overlap::file_overlaps(state, worktrees.iter().copied()),
+    })
+}
+
+fn print_reports(reports: &[RepoConflicts]) {
+    if reports.is_empty() {
+        println!("{} No worktrees to check", "📭".yellow());
+        return;
+    }
+    for report in reports {
+        let against = report
+            .base
+            .as_deref()
+            .map(|base| format!(" (base {base})"))
+            .unwrap_or_default();
+        println!(
+            "  {} {}{}",
+            "📦".blue(),
+            report.repo.bold(),
+            against.bright_black()
+        );
+        for conflict in &report.conflicts {
+            println!(
+                "    {} {} {} {}: {}",
+                "⚠️".yellow(),
+                conflict.left.cyan(),
+                "↔".bright_black(),
+                conflict.right.cyan(),
+                conflict.files.join(", ").yellow()
+            );
+        }
+        for name in &report.clean {
+            println!(
+                "    {} {} {}",
+                "✅".green(),
+                name.cyan(),
+                "merges cleanly".bright_black()
+            );
+        }
+        if !report.unchanged.is_empty() {
+            println!(
+                "    {} {}",
+                "·".bright_black(),
+                format!("No commits yet: {}", report.unchanged.join(", ")).bright_black()
+            );
+        }
+        if !report.overlaps.is_empty() {
+            println!("    {} Changed in several worktrees:", "🔥".red());
+            for overlap in &report.overlaps {
+                println!(
+                    "      {} {}  {}",
+                    format!("{}×", overlap.worktrees.len()).red(),
+                    overlap.path,
+                    overlap.worktrees.join(", ").bright_black()
+                );
+            }
+        }
+    }
+}
@@ -1,33 +1,151 @@
 use anyhow::{Context, Result};
-use chrono::Utc;
 use colored::Colorize;
+use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::commands::agent_launcher::launch_with_menu;
-use crate::commands::agent_prompt::AgentSelection;
+use crate::bail_with;
+use crate::commands::agent_launcher::{
+    LaunchOptions, launch_agent, launch_with_menu, launch_with_menu_and_prompt,
+};
+use crate::commands::agent_prompt::{AgentSelection, agent_named};
+use crate::commands::checkout::guard_branch_checked_out;
+use crate::dry_run;
+use crate::error::ErrorCategory;
 use crate::git::{
-    execute_git, extract_repo_name_from_url, get_repo_name, list_worktrees, update_submodules,
+    apply_sparse_checkout, execute_git, extract_repo_name_from_url, get_repo_name,
+    is_bare_repository, lfs_pull, list_worktrees, main_repo_dir, repo_name_from_dir,
+    update_submodules, uses_lfs,
 };
+use crate::hooks::{self, Event, EventKind};
 use crate::input::get_command_arg;
-use crate::state::{WorktreeInfo, XlaudeState};
-use crate::utils::{generate_random_name, sanitize_branch_name};
+use crate::state::{NameCollisionPolicy, WorktreeInfo, XlaudeState};
+use crate::tr;
+use crate::tracker::{fetch_github_issue, fetch_ticket, parse_issue_number, parse_ticket_key};
+use crate::utils::{generate_random_name, with_numeric_suffix};
+
+// Random words tried before falling back to a numeric suffix
+const NEW_WORD_ATTEMPTS: usize = 10;
+
+#[derive(Debug, Default)]
+pub struct CreateOptions {
+    pub name: Option<String>,
+    // GitHub issue number used for naming and linking
+    pub issue: Option<String>,
+    // Jira/Linear ticket key used for naming and linking
+    pub ticket: Option<String>,
+    // Sparse-checkout profile from the repository config
+    pub sparse: Option<String>,
+    // Always generate a name instead of reading one from piped input
+    pub generate_name: bool,
+    // Start point for a new branch; defaults to the current or default branch
+    pub base: Option<String>,
+    // Remote branch such as `origin/feature-x` for the new branch to track
+    pub from: Option<String>,
+    // Check out `base` as a detached HEAD instead of creating a branch
+    pub detach: bool,
+    // Launch an agent right away instead of asking whether to open the worktree
+    pub open: bool,
+    // Agent to launch with `open` instead of showing the menu
+    pub agent: Option<String>,
+    // Initial instruction for the agent launched with `open`
+    pub prompt: Option<String>,
+}
+
+pub fn handle_create(options: CreateOptions) -> Result<()> {
+    handle_create_in_dir(options, None)
+}
+
+pub fn handle_create_in_dir(options: CreateOptions, repo_path: Option<PathBuf>) -> Result<()> {
+    handle_create_in_dir_quiet(options, repo_path, false)?;
+    Ok(())
+}
 
-pub fn handle_create(name: Option<String>) -> Result<()> {
-    handle_create_in_dir(name, None)
+#[derive(Serialize)]
+struct CreatedWorktree {
+    name: String,
+    branch: String,
+    path: PathBuf,
 }
 
-pub fn handle_create_in_dir(name: Option<String>, repo_path: Option<PathBuf>) -> Result<()> {
-    handle_create_in_dir_quiet(name, repo_path, false)?;
+/// Create `count` worktrees with generated names from the current branch and print
+/// them as JSON, e.g. to fan the same task out to several agents.
+pub fn handle_create_many(count: usize, sparse: Option<String>) -> Result<()> {
+    if count == 0 {
+        anyhow::bail!("--count must be at least 1");
+    }
+    let repo_name = get_repo_name().context("Not in a git repository")?;
+
+    let mut created = Vec::with_capacity(count);
+    for index in 1..=count {
+        eprintln!(
+            "{}",
+            tr!("{} Creating worktree {}/{}...", "✨".green(), index, count)
+        );
+        let name = handle_create_in_dir_quiet(
+            CreateOptions {
+                sparse: sparse.clone(),
+                generate_name: true,
+                ..Default::default()
+            },
+            None,
+            true,
+        )?;
+        let state = XlaudeState::load()?;
+        let info = state
+            .worktrees
+            .get(&XlaudeState::make_key(&repo_name, &name))
+            .context("Created worktree is missing from state")?;
+        created.push(CreatedWorktree {
+            name,
+            branch: info.branch.clone(),
+            path: info.path.clone(),
+        });
+    }
+
+    println!("{}", serde_json::to_string_pretty(&created)?);
     Ok(())
 }
 
 // Create worktree quietly without prompting for open, returns the created worktree name
 pub fn handle_create_in_dir_quiet(
-    name: Option<String>,
+    options: CreateOptions,
     repo_path: Option<PathBuf>,
     quiet: bool,
 ) -> Result<String> {
+    let CreateOptions {
+        name,
+        issue,
+        ticket,
+        sparse,
+        generate_name,
+        base,
+        from,
+        detach,
+        open,
+        agent,
+        prompt,
+    } = options;
+    // Resolve the agent first so that a typo does not leave a worktree behind
+    let agent = agent
+        .map(|name| agent_named(&name, &XlaudeState::load()?.menu))
+        .transpose()?;
+    if issue.is_some() && ticket.is_some() {
+        anyhow::bail!("--issue and --ticket cannot be used together");
+    }
+    if from.is_some() && base.is_some() {
+        anyhow::bail!("--from cannot be combined with a base branch");
+    }
+    // The commit or tag a detached worktree is checked out at
+    let detached_at = match (detach, &base) {
+        (false, _) => None,
+        (true, None) => bail_with!(
+            ErrorCategory::Usage,
+            "--detach needs a commit or tag to check out (--at)"
+        ),
+        (true, Some(rev)) => Some(rev.clone()),
+    };
+
     // Helper to execute git in the right directory using git -C
     let exec_git = |args: &[&str]| -> Result<String> {
         if let Some(ref path) = repo_path {
@@ -43,28 +161,96 @@ pub fn handle_create_in_dir_quiet(
     // Get repo name from the target directory
     let repo_name = if let Some(ref path) = repo_path {
         // Get repo name from the specified path using git -C
-        let output = execute_git(&["-C", path.to_str().unwrap(), "remote", "get-url", "origin"])?;
-        if let Some(name) = extract_repo_name_from_url(&output) {
+        let origin = execute_git(&["-C", path.to_str().unwrap(), "remote", "get-url", "origin"]);
+        if let Some(name) = origin.ok().and_then(|url| extract_repo_name_from_url(&url)) {
             name
         } else {
             // Fallback to directory name
-            path.file_name()
-                .and_then(|n| n.to_str())
-                .map(String::from)
-                .context("Failed to get repository name")?
+            repo_name_from_dir(path).context("Failed to get repository name")?
         }
     } else {
         get_repo_name().context("Not in a git repository")?
     };
 
+    let repo_config = XlaudeState::load()?.repo_config(&repo_name);
+    let base_remote = repo_config.base_remote();
+    let default_branch = exec_git(&["symbolic-ref", &format!("refs/remotes/{base_remote}/HEAD")])
+        .ok()
+        .and_then(|s| {
+            s.strip_prefix(&format!("refs/remotes/{base_remote}/"))
+                .map(String::from)
+        })
+        .unwrap_or_else(|| "main".to_string());
+
+    // Fetch the remote branch up front so a typo fails before anything is created
+    let from = match from {
+        Some(ref spec) => {
+            let remotes = exec_git(&["remote"])?;
+            let Some((remote, branch)) = spec.split_once('/').filter(|(remote, branch)| {
+                !branch.is_empty() && remotes.lines().any(|r| r.trim() == *remote)
+            }) else {
+                bail_with!(
+                    ErrorCategory::Usage,
+                    "'{}' is not a remote branch; expected <remote>/<branch> with a configured remote",
+                    spec
+                );
+            };
+            if !quiet {
+                println!(
+                    "{}",
+                    tr!(
+                        "{} Fetching branch '{}' from {}...",
+                        "🌐".blue(),
+                        branch.cyan(),
+                        remote
+                    )
+                );
+            }
+            exec_git(&["fetch", remote, branch])
+                .with_context(|| format!("Failed to fetch branch '{branch}' from {remote}"))?;
+            Some((spec.clone(), branch.to_string()))
+        }
+        None => None,
+    };
+
+    if let Some(rev) = &detached_at
+        && exec_git(&[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{rev}^{{commit}}"),
+        ])
+        .is_err()
+    {
+        bail_with!(ErrorCategory::NotFound, "Revision '{}' not found", rev);
+    }
+
+    // Bare repositories have no primary checkout: new branches start from the branch
+    // their HEAD points to, whether create runs in the repository or in one of its worktrees
+    let repo_dir = match &repo_path {
+        Some(path) => path.clone(),
+        None => main_repo_dir()?,
+    };
+    let bare_head = if is_bare_repository(&repo_dir) {
+        let repo_dir = repo_dir
+            .to_str()
+            .context("Repository path contains invalid UTF-8")?;
+        Some(execute_git(&[
+            "-C",
+            repo_dir,
+            "symbolic-ref",
+            "--short",
+            "HEAD",
+        ])?)
+    } else {
+        None
+    };
+
     // Only check base branch if no repo_path is provided (i.e., running from CLI in current directory)
-    // Clients that pass repo_path are expected to enforce their own branch safety checks
-    if repo_path.is_none() {
+    // Clients that pass repo_path are expected to enforce their own branch safety checks.
+    // Work with an explicit start point does not come from the current branch, so it needs no check.
+    if repo_path.is_none() && from.is_none() && base.is_none() && bare_head.is_none() {
         let current_branch = exec_git(&["branch", "--show-current"])?;
-        let default_branch = exec_git(&["symbolic-ref", "refs/remotes/origin/HEAD"])
-            .ok()
-            .and_then(|s| s.strip_prefix("refs/remotes/origin/").map(String::from))
-            .unwrap_or_else(|| "main".to_string());
 
         let base_branches = ["main", "master", "develop", &default_branch];
         if !base_branches.contains(&current_branch.as_str()) {
@@ -75,47 +261,78 @@ pub fn handle_create_in_dir_quiet(
         }
     }
 
-    // Get name from CLI args or pipe, generate if not provided
-    let branch_name = match get_command_arg(name)? {
-        Some(n) => n,
-        None => generate_random_name()?,
+    // Resolve the sparse profile up front so a typo fails before any branch is created
+    let sparse_directories = match sparse {
+        Some(ref profile) => {
+            let Some(directories) = repo_config.sparse_profiles.get(profile) else {
+                let mut known: Vec<_> = repo_config.sparse_profiles.keys().cloned().collect();
+                known.sort();
+                bail_with!(
+                    ErrorCategory::Config,
+                    "Sparse profile '{}' is not configured for repository '{}' (available: {})",
+                    profile,
+                    repo_name,
+                    if known.is_empty() {
+                        "none".to_string()
+                    } else {
+                        known.join(", ")
+                    }
+                );
+            };
+            Some(directories.clone())
+        }
+        None => None,
     };
 
-    // Sanitize the branch name for use in directory names
-    let worktree_name = sanitize_branch_name(&branch_name);
+    // Resolve the linked GitHub issue before picking a name so its title can seed the branch
+    let issue = match issue {
+        Some(ref input) => {
+            let number = parse_issue_number(input)?;
+            if !quiet {
+                println!("{}", tr!("{} Fetching issue #{}...", "🔍".cyan(), number));
+            }
+            Some(fetch_github_issue(number, repo_path.as_deref())?)
+        }
+        None => None,
+    };
+    let ticket = match ticket {
+        Some(ref input) => {
+            let key = parse_ticket_key(input)?;
+            if !quiet {
+                println!("{}", tr!("{} Fetching ticket {}...", "🔍".cyan(), key));
+            }
+            Some(fetch_ticket(&key, &XlaudeState::load()?.ticket_providers)?)
+        }
+        None => None,
+    };
 
-    // Check if a worktree with this name already exists in xlaude state
-    let state = XlaudeState::load()?;
-    let key = XlaudeState::make_key(&repo_name, &worktree_name);
-    if state.worktrees.contains_key(&key) {
-        anyhow::bail!(
-            "A worktree named '{}' already exists for repository '{}' (tracked by xlaude). Please choose a different name.",
-            worktree_name,
-            repo_name
-        );
-    }
+    // A tracked remote branch or a detached revision names the worktree unless
+    // another name is given
+    let name = name
+        .or_else(|| from.as_ref().map(|(_, branch)| branch.clone()))
+        .or_else(|| detached_at.clone());
 
-    // Check if the worktree directory will be created
-    let worktree_dir_path = if let Some(ref path) = repo_path {
-        path.parent()
-            .unwrap()
-            .join(format!("{repo_name}-{worktree_name}"))
+    // Get name from CLI args or pipe, fall back to the issue/ticket title, generate if not provided
+    let name = if generate_name {
+        name
     } else {
-        std::env::current_dir()?
-            .parent()
-            .unwrap()
-            .join(format!("{repo_name}-{worktree_name}"))
+        get_command_arg(name)?
+    };
+    let (branch_name, generated) = match name {
+        Some(n) => (n, false),
+        None => match (&issue, &ticket) {
+            (Some(issue), _) => (issue.branch_name(), false),
+            (_, Some(ticket)) => (ticket.branch_name(), false),
+            _ => (generate_random_name()?, true),
+        },
     };
 
-    // Check if the directory already exists
-    if worktree_dir_path.exists() {
-        anyhow::bail!(
-            "Directory '{}' already exists. Please choose a different name or remove the existing directory.",
-            worktree_dir_path.display()
-        );
-    }
+    let state = XlaudeState::load()?;
+    let worktree_parent = match repo_path {
+        Some(ref path) => path.parent().unwrap().to_path_buf(),
+        None => std::env::current_dir()?.parent().unwrap().to_path_buf(),
+    };
 
-    // Check if a git worktree already exists at this path
     // Need to run git worktree list in the correct directory
     let existing_worktrees = if let Some(ref path) = repo_path {
         // Parse git worktree list output from the specified directory
@@ -137,13 +354,84 @@ pub fn handle_create_in_dir_quiet(
         list_worktrees()?
     };
 
-    if existing_worktrees.iter().any(|w| w == &worktree_dir_path) {
-        anyhow::bail!(
-            "A git worktree already exists at '{}'. Please choose a different name or remove the existing worktree.",
-            worktree_dir_path.display()
+    // Why a branch name cannot be used, if it can't. An existing branch is only a
+    // collision for names xlaude picked; a typed name adopts the existing branch.
+    let collision = |branch: &str, branch_taken: bool| -> Option<String> {
+        let worktree_name = state.worktree_name_for_branch(&repo_name, branch);
+        let worktree_dir_path = worktree_parent.join(format!("{repo_name}-{worktree_name}"));
+        if state
+            .worktrees
+            .contains_key(&XlaudeState::make_key(&repo_name, &worktree_name))
+        {
+            Some(format!(
+                "A worktree named '{worktree_name}' already exists for repository '{repo_name}' (tracked by xlaude). Please choose a different name."
+            ))
+        } else if worktree_dir_path.exists() {
+            Some(format!(
+                "Directory '{}' already exists. Please choose a different name or remove the existing directory.",
+                worktree_dir_path.display()
+            ))
+        } else if existing_worktrees.iter().any(|w| w == &worktree_dir_path) {
+            Some(format!(
+                "A git worktree already exists at '{}'. Please choose a different name or remove the existing worktree.",
+                worktree_dir_path.display()
+            ))
+        } else if branch_taken
+            && exec_git(&["show-ref", "--verify", &format!("refs/heads/{branch}")]).is_ok()
+        {
+            Some(format!("Branch '{branch}' already exists."))
+        } else {
+            None
+        }
+    };
+
+    let branch_name = match collision(&branch_name, generated) {
+        None => branch_name,
+        Some(reason) => {
+            let policy = state.name_collision.unwrap_or(if generated {
+                NameCollisionPolicy::NewWord
+            } else {
+                NameCollisionPolicy::Suffix
+            });
+            let replacement = match policy {
+                NameCollisionPolicy::Error => bail_with!(ErrorCategory::Conflict, "{reason}"),
+                NameCollisionPolicy::NewWord if generated => (0..NEW_WORD_ATTEMPTS)
+                    .filter_map(|_| generate_random_name().ok())
+                    .find(|candidate| collision(candidate, true).is_none()),
+                _ => None,
+            }
+            .unwrap_or_else(|| {
+                with_numeric_suffix(&branch_name, |candidate| {
+                    collision(candidate, true).is_some()
+                })
+            });
+            if !quiet {
+                println!(
+                    "{}",
+                    tr!(
+                        "{} '{}' is already taken, using '{}' instead",
+                        "ℹ️".blue(),
+                        branch_name,
+                        replacement.cyan()
+                    )
+                );
+            }
+            replacement
+        }
+    };
+
+    // Scripts piping names in must not be able to check out a base or release branch
+    if detached_at.is_none() && state.is_protected_branch(&branch_name) {
+        bail_with!(
+            ErrorCategory::Conflict,
+            "Branch '{}' is protected (see protected_branches in state.json); choose another name",
+            branch_name
         );
     }
 
+    // Sanitize the branch name for use in directory names
+    let worktree_name = state.worktree_name_for_branch(&repo_name, &branch_name);
+
     // Check if the branch already exists
     let branch_already_exists = exec_git(&[
         "show-ref",
@@ -152,39 +440,68 @@ pub fn handle_create_in_dir_quiet(
     ])
     .is_ok();
 
-    if branch_already_exists {
+    // Git would refuse a branch that another worktree has checked out
+    if branch_already_exists
+        && detached_at.is_none()
+        && let Some(opened) = guard_branch_checked_out(&repo_dir, &branch_name)?
+    {
+        return Ok(opened);
+    }
+
+    if let Some(rev) = &detached_at {
+        if !quiet {
+            println!(
+                "{}",
+                tr!(
+                    "{} Creating detached worktree '{}' at '{}'...",
+                    "✨".green(),
+                    worktree_name.cyan(),
+                    rev.cyan()
+                )
+            );
+        }
+    } else if branch_already_exists {
         if !quiet {
             println!(
-                "{} Creating worktree '{}' from existing branch '{}'...",
-                "✨".green(),
-                worktree_name.cyan(),
-                branch_name.cyan()
+                "{}",
+                tr!(
+                    "{} Creating worktree '{}' from existing branch '{}'...",
+                    "✨".green(),
+                    worktree_name.cyan(),
+                    branch_name.cyan()
+                )
             );
         }
     } else {
         if !quiet {
             println!(
-                "{} Creating worktree '{}' with new branch '{}'...",
-                "✨".green(),
-                worktree_name.cyan(),
-                branch_name.cyan()
+                "{}",
+                tr!(
+                    "{} Creating worktree '{}' with new branch '{}'...",
+                    "✨".green(),
+                    worktree_name.cyan(),
+                    branch_name.cyan()
+                )
             );
         }
 
-        // When repo_path is provided, create branch from the default branch
-        // Otherwise create from current branch
-        if repo_path.is_some() {
-            // Get the default branch
-            let default_branch = exec_git(&["symbolic-ref", "refs/remotes/origin/HEAD"])
-                .ok()
-                .and_then(|s| s.strip_prefix("refs/remotes/origin/").map(String::from))
-                .unwrap_or_else(|| "main".to_string());
-
-            // Create branch from the default branch
+        // An explicit base wins. Otherwise, when repo_path is provided, create the
+        // branch from the default branch, and from the current branch for the CLI
+        if let Some((remote_branch, _)) = &from {
+            exec_git(&["branch", "--track", &branch_name, remote_branch])
+                .with_context(|| format!("Failed to create a branch tracking '{remote_branch}'"))?;
+        } else if let Some(base) = &base {
+            exec_git(&["branch", &branch_name, base])
+                .with_context(|| format!("Failed to create branch from '{base}'"))?;
+        } else if let Some(head) = &bare_head {
+            exec_git(&["branch", &branch_name, head])
+                .with_context(|| format!("Failed to create branch from '{head}'"))?;
+        } else if repo_path.is_some() {
+            // Create branch from the base remote's default branch
             exec_git(&[
                 "branch",
                 &branch_name,
-                &format!("origin/{}", default_branch),
+                &format!("{base_remote}/{default_branch}"),
             ])
             .context("Failed to create branch from default branch")?;
         } else {
@@ -193,98 +510,146 @@ pub fn handle_create_in_dir_quiet(
         }
     }
 
-    // Create worktree with sanitized directory name
+    // Create worktree with sanitized directory name; sparse worktrees are populated after
+    // the cone is configured so the full tree is never written to disk
     let worktree_dir = format!("../{repo_name}-{worktree_name}");
-    exec_git(&["worktree", "add", &worktree_dir, &branch_name])
-        .context("Failed to create worktree")?;
+    let mut add_args = vec!["worktree", "add"];
+    if sparse_directories.is_some() {
+        add_args.push("--no-checkout");
+    }
+    match &detached_at {
+        Some(rev) => add_args.extend(["--detach", worktree_dir.as_str(), rev.as_str()]),
+        None => add_args.extend([worktree_dir.as_str(), branch_name.as_str()]),
+    }
+    exec_git(&add_args).context("Failed to create worktree")?;
+
+    // Pushes from the new branch go to the configured push remote
+    if detached_at.is_none()
+        && let Some(push_remote) = &repo_config.push_remote
+    {
+        exec_git(&[
+            "config",
+            &format!("branch.{branch_name}.pushRemote"),
+            push_remote,
+        ])
+        .context("Failed to set the push remote of the new branch")?;
+    }
 
     // Get absolute path
-    let worktree_path = if let Some(ref path) = repo_path {
-        path.parent()
-            .unwrap()
-            .join(format!("{repo_name}-{worktree_name}"))
-    } else {
-        std::env::current_dir()?
-            .parent()
-            .unwrap()
-            .join(format!("{repo_name}-{worktree_name}"))
-    };
+    let worktree_path = worktree_parent.join(format!("{repo_name}-{worktree_name}"));
 
-    // Update submodules if they exist
-    if let Err(e) = update_submodules(&worktree_path) {
+    let repo_root = match repo_path {
+        Some(ref path) => path.clone(),
+        None => std::env::current_dir()?,
+    };
+    if let Some(directories) = &sparse_directories {
+        apply_sparse_checkout(&worktree_path, directories)?;
         if !quiet {
             println!(
-                "{} Warning: Failed to update submodules: {}",
-                "⚠️".yellow(),
-                e
+                "{}",
+                tr!(
+                    "{} Applied sparse profile '{}' ({})",
+                    "🌿".green(),
+                    sparse.as_deref().unwrap_or_default(),
+                    directories.join(", ")
+                )
             );
         }
-    } else {
-        // Check if submodules were actually updated
-        let gitmodules = worktree_path.join(".gitmodules");
-        if gitmodules.exists() && !quiet {
-            println!("{} Updated submodules", "📦".green());
-        }
-    }
-
-    // Copy CLAUDE.local.md if it exists
-    let claude_local_md = if let Some(ref path) = repo_path {
-        path.join("CLAUDE.local.md")
-    } else {
-        PathBuf::from("CLAUDE.local.md")
-    };
-    if claude_local_md.exists() {
-        let target_path = worktree_path.join("CLAUDE.local.md");
-        fs::copy(claude_local_md, &target_path).context("Failed to copy CLAUDE.local.md")?;
-        if !quiet {
-            println!("{} Copied CLAUDE.local.md to worktree", "📄".green());
-        }
     }
+    initialize_worktree(&worktree_path, &repo_name, &repo_root, quiet)?;
 
     // Save state
     let mut state = XlaudeState::load()?;
     let key = XlaudeState::make_key(&repo_name, &worktree_name);
-    let worktree_info = WorktreeInfo {
-        name: worktree_name.clone(),
-        branch: branch_name.clone(),
-        path: worktree_path.clone(),
+    let mut worktree_info = WorktreeInfo::new(
+        worktree_name.clone(),
+        detached_at.clone().unwrap_or_else(|| branch_name.clone()),
+        worktree_path.clone(),
         repo_name,
-        created_at: Utc::now(),
-    };
+    );
+    worktree_info.detached = detached_at.is_some();
+    worktree_info.issue = issue.as_ref().map(|issue| issue.link());
+    worktree_info.note = ticket.as_ref().map(|ticket| ticket.summary.clone());
+    worktree_info.sparse = sparse;
+    worktree_info.ticket = ticket;
 
     state.worktrees.insert(key, worktree_info.clone());
     state.save()?;
+    hooks::emit(
+        &state,
+        Event::new(EventKind::WorktreeCreated, &worktree_info),
+    );
 
     if !quiet {
         println!(
-            "{} Worktree created at: {}",
-            "✅".green(),
-            worktree_path.display()
+            "{}",
+            tr!(
+                "{} Worktree created at: {}",
+                "✅".green(),
+                worktree_path.display()
+            )
         );
     }
 
+    // An agent opened on an issue starts from the issue unless told otherwise
+    let prompt = prompt.or_else(|| issue.as_ref().map(|issue| issue.prompt()));
+
     // Ask if user wants to open the worktree (skip in quiet mode)
     if !quiet {
-        if std::env::var("XLAUDE_TEST_MODE").is_ok() || std::env::var("XLAUDE_NO_AUTO_OPEN").is_ok()
+        if open && !dry_run::is_enabled() {
+            let selection = match agent {
+                Some(agent) => launch_agent(&worktree_info, agent, prompt.as_deref()),
+                None => launch_with_menu_and_prompt(
+                    &worktree_info,
+                    &tr!("Choose an agent for the new worktree:"),
+                    LaunchOptions {
+                        prompt: prompt.as_deref(),
+                        ..LaunchOptions::default()
+                    },
+                ),
+            }
+            .context("Failed to launch agent")?;
+            if selection == AgentSelection::Skip {
+                println!(
+                    "{}",
+                    tr!(
+                        "  {} To open it later, run: {} {}",
+                        "💡".cyan(),
+                        "xlaude open".cyan(),
+                        worktree_name.cyan()
+                    )
+                );
+            }
+        } else if std::env::var("XLAUDE_TEST_MODE").is_ok()
+            || std::env::var("XLAUDE_NO_AUTO_OPEN").is_ok()
+            || dry_run::is_enabled()
         {
             println!(
-                "  {} To open it, run: {} {}",
-                "💡".cyan(),
-                "xlaude open".cyan(),
-                worktree_name.cyan()
+                "{}",
+                tr!(
+                    "  {} To open it, run: {} {}",
+                    "💡".cyan(),
+                    "xlaude open".cyan(),
+                    worktree_name.cyan()
+                )
             );
         } else {
-            let selection =
-                launch_with_menu(&worktree_info, "Would you like to open the worktree now?")
-                    .context("Failed to launch agent")?;
+            let selection = launch_with_menu(
+                &worktree_info,
+                &tr!("Would you like to open the worktree now?"),
+            )
+            .context("Failed to launch agent")?;
 
             if selection == AgentSelection::Skip && std::env::var("XLAUDE_NON_INTERACTIVE").is_err()
             {
                 println!(
-                    "  {} To open it later, run: {} {}",
-                    "💡".cyan(),
-                    "xlaude open".cyan(),
-                    worktree_name.cyan()
+                    "{}",
+                    tr!(
+                        "  {} To open it later, run: {} {}",
+                        "💡".cyan(),
+                        "xlaude open".cyan(),
+                        worktree_name.cyan()
+                    )
                 );
             }
         }
@@ -292,3 +657,66 @@ pub fn handle_create_in_dir_quiet(
 
     Ok(worktree_name)
 }
+
+/// Prepare a freshly added worktree: submodules, LFS objects, and CLAUDE.local.md.
+pub(crate) fn initialize_worktree(
+    worktree_path: &Path,
+    repo_name: &str,
+    repo_root: &Path,
+    quiet: bool,
+) -> Result<()> {
+    let config = XlaudeState::load()?.repo_config(repo_name);
+
+    let has_submodules = worktree_path.join(".gitmodules").exists();
+    if config.submodules.unwrap_or(true) && has_submodules {
+        match update_submodules(worktree_path) {
+            Ok(()) if !quiet => println!("{}", tr!("{} Updated submodules", "📦".green())),
+            Err(e) if !quiet => println!(
+                "{}",
+                tr!(
+                    "{} Warning: Failed to update submodules: {}",
+                    "⚠️".yellow(),
+                    e
+                )
+            ),
+            _ => {}
+        }
+    }
+
+    if config.lfs.unwrap_or_else(|| uses_lfs(worktree_path)) {
+        match lfs_pull(worktree_path) {
+            Ok(()) if !quiet => println!("{}", tr!("{} Pulled LFS objects", "📦".green())),
+            Err(e) if !quiet => println!(
+                "{}",
+                tr!(
+                    "{} Warning: Failed to pull LFS objects: {}",
+                    "⚠️".yellow(),
+                    format!("{e:#}")
+                )
+            ),
+            _ => {}
+        }
+    }
+
+    let claude_local_md = repo_root.join("CLAUDE.local.md");
+    if claude_local_md.exists() {
+        let target_path = worktree_path.join("CLAUDE.local.md");
+        if dry_run::is_enabled() {
+            dry_run::announce(&format!(
+                "copy {} -> {}",
+                claude_local_md.display(),
+                target_path.display()
+            ));
+            return Ok(());
+        }
+        fs::copy(claude_local_md, &target_path).context("Failed to copy CLAUDE.local.md")?;
+        if !quiet {
+            println!(
+                "{}",
+                tr!("{} Copied CLAUDE.local.md to worktree", "📄".green())
+            );
+        }
+    }
+
+    Ok(())
+}
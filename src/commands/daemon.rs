@@ -0,0 +1,59 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::commands::list::format_time_ago;
+use crate::daemon;
+
+pub fn handle_daemon(addr: Option<String>, readonly: bool) -> Result<()> {
+    daemon::run_daemon(addr, readonly)
+}
+
+pub fn handle_daemon_status(json: bool) -> Result<()> {
+    let status = daemon::status();
+
+    if json {
+        let value = match &status {
+            Some(status) => serde_json::to_value(status)?,
+            None => serde_json::json!({ "running": false }),
+        };
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    let Some(status) = status else {
+        println!(
+            "{} No daemon is running on {}",
+            "💤".yellow(),
+            daemon::socket_path()?.display()
+        );
+        return Ok(());
+    };
+
+    println!(
+        "{} Daemon running (pid {}), started {}",
+        "🛰️".green(),
+        status.pid,
+        format_time_ago(Some(status.started_at))
+    );
+    println!(
+        "  Indexed {} session(s) across {} worktree(s), refreshed {}",
+        status.indexed_sessions,
+        status.indexed_worktrees,
+        format_time_ago(status.refreshed_at)
+    );
+    if status.agents.is_empty() {
+        println!("  No running agents");
+    } else {
+        println!("  Running agents:");
+        for agent in &status.agents {
+            println!(
+                "    {} {} (pid {}, started {})",
+                agent.worktree.cyan(),
+                agent.command,
+                agent.pid,
+                format_time_ago(Some(agent.started_at))
+            );
+        }
+    }
+    Ok(())
+}
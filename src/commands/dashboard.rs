@@ -2,6 +2,6 @@ use anyhow::Result;
 
 use crate::dashboard;
 
-pub fn handle_dashboard(addr: Option<String>, no_browser: bool) -> Result<()> {
-    dashboard::run_dashboard(addr, !no_browser)
+pub fn handle_dashboard(addr: Option<String>, no_browser: bool, readonly: bool) -> Result<()> {
+    dashboard::run_dashboard(addr, !no_browser, readonly)
 }
@@ -1,10 +1,26 @@
 use anyhow::{Context, Result};
+use chrono::Utc;
 use colored::Colorize;
 
-use crate::git::{execute_git, has_unpushed_commits, is_working_tree_clean};
-use crate::input::{get_command_arg, smart_confirm};
+use crate::bail_with;
+use crate::commands::list::format_time_ago;
+use crate::commands::lock::format_reason;
+use crate::error::{ErrorCategory, WithCategory};
+use crate::git::{
+    execute_git, find_main_repo, has_commits_outside_refs, has_unpushed_commits,
+    is_working_tree_clean, worktree_lock_reason,
+};
+use crate::history::{self, DeletedWorktree};
+use crate::hooks::{self, Event, EventKind};
+use crate::input::{get_command_arg, smart_confirm_destructive};
+use crate::processes::ProcessRegistry;
 use crate::state::{WorktreeInfo, XlaudeState};
-use crate::utils::execute_in_dir;
+use crate::tr;
+use crate::transcript::last_session_activity;
+use crate::utils::{execute_in_dir, parse_duration};
+
+// Agent activity more recent than this needs an extra confirmation
+const DEFAULT_ACTIVITY_WINDOW: chrono::Duration = chrono::Duration::minutes(30);
 
 /// Represents the result of various checks performed before deletion
 struct DeletionChecks {
@@ -24,21 +40,33 @@ impl DeletionChecks {
     }
 }
 
+/// What happened to the branch of a deleted worktree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BranchDeletion {
+    // `git branch -d` accepted it, so it was merged
+    Merged,
+    Forced,
+    Kept,
+}
+
 /// Configuration for deletion behavior
 struct DeletionConfig {
     is_interactive: bool,
     worktree_exists: bool,
     is_current_directory: bool,
+    // The branch matches `protected_branches` and must be kept
+    branch_protected: bool,
 }
 
 impl DeletionConfig {
-    fn from_env(worktree_info: &WorktreeInfo) -> Result<Self> {
+    fn from_env(state: &XlaudeState, worktree_info: &WorktreeInfo) -> Result<Self> {
         let current_dir = std::env::current_dir()?;
 
         Ok(Self {
             is_interactive: std::env::var("XLAUDE_NON_INTERACTIVE").is_err(),
             worktree_exists: worktree_info.path.exists(),
             is_current_directory: current_dir == worktree_info.path,
+            branch_protected: state.is_protected_branch(&worktree_info.branch),
         })
     }
 }
@@ -49,48 +77,130 @@ pub fn handle_delete(name: Option<String>) -> Result<()> {
     // Get name from CLI args or pipe
     let target_name = get_command_arg(name)?;
     let (key, worktree_info) = find_worktree_to_delete(&state, target_name)?;
-    let config = DeletionConfig::from_env(&worktree_info)?;
+    let config = DeletionConfig::from_env(&state, &worktree_info)?;
+
+    if let Some(reason) = worktree_lock_reason(&worktree_info.path, &worktree_info.repo_name) {
+        bail_with!(
+            ErrorCategory::Conflict,
+            "Worktree '{}' is locked{}. Run 'xlaude unlock {}' first",
+            worktree_info.name,
+            format_reason(&reason),
+            worktree_info.name
+        );
+    }
 
     println!(
-        "{} Checking worktree '{}'...",
-        "🔍".yellow(),
-        worktree_info.name.cyan()
+        "{}",
+        tr!(
+            "{} Checking worktree '{}'...",
+            "🔍".yellow(),
+            worktree_info.name.cyan()
+        )
     );
 
+    // Whether the branch was merged is unknown when the directory is gone
+    let mut merged = false;
+
     // Handle case where worktree directory doesn't exist
     if !config.worktree_exists {
         if !handle_missing_worktree(&worktree_info, &config)? {
-            println!("{} Cancelled", "❌".red());
+            println!("{}", tr!("{} Cancelled", "❌".red()));
             return Ok(());
         }
     } else {
         // Check branch status first (for output consistency)
-        println!(
-            "{} Checking branch '{}'...",
-            "🔍".yellow(),
-            worktree_info.branch
-        );
+        if !worktree_info.detached {
+            println!(
+                "{}",
+                tr!(
+                    "{} Checking branch '{}'...",
+                    "🔍".yellow(),
+                    worktree_info.branch
+                )
+            );
+        }
 
         // Perform deletion checks
         let checks = perform_deletion_checks(&worktree_info)?;
+        merged = checks.branch_is_merged();
+
+        if !confirm_agent_activity(&state, &worktree_info)? {
+            println!("{}", tr!("{} Cancelled", "❌".red()));
+            bail_with!(
+                ErrorCategory::Conflict,
+                "An agent was recently active in worktree '{}'",
+                worktree_info.name
+            );
+        }
 
         if !confirm_deletion(&worktree_info, &checks, &config)? {
-            println!("{} Cancelled", "❌".red());
+            println!("{}", tr!("{} Cancelled", "❌".red()));
+            // Scripts need to tell "kept because of pending work" from a deliberate no
+            if checks.has_pending_work() {
+                bail_with!(
+                    ErrorCategory::DirtyWorktree,
+                    "Worktree '{}' has uncommitted changes or unpushed commits",
+                    worktree_info.name
+                );
+            }
             return Ok(());
         }
     }
 
     // Execute deletion
-    perform_deletion(&worktree_info, &config)?;
+    let branch = perform_deletion(&worktree_info, &config)?;
+    finish_deletion(&mut state, &key, &worktree_info, merged, branch)
+}
 
-    // Update state
-    state.worktrees.remove(&key);
+/// Delete a worktree that was confirmed as part of a batch, without asking
+/// about it again. Branches that are not fully merged are kept.
+pub(crate) fn delete_confirmed(
+    state: &mut XlaudeState,
+    key: &str,
+    worktree_info: &WorktreeInfo,
+) -> Result<()> {
+    let config = DeletionConfig {
+        is_interactive: false,
+        ..DeletionConfig::from_env(state, worktree_info)?
+    };
+    let branch = perform_deletion(worktree_info, &config)?;
+    finish_deletion(state, key, worktree_info, false, branch)
+}
+
+/// Update state and history once the worktree and its branch are gone
+fn finish_deletion(
+    state: &mut XlaudeState,
+    key: &str,
+    worktree_info: &WorktreeInfo,
+    merged: bool,
+    branch: BranchDeletion,
+) -> Result<()> {
+    state.remove_worktree(key);
     state.save()?;
+    // The history only feeds reports, so failing to write it is not fatal
+    if let Err(err) = history::record_deletion(&DeletedWorktree::new(
+        worktree_info,
+        merged || branch == BranchDeletion::Merged,
+        branch != BranchDeletion::Kept,
+    )) {
+        eprintln!(
+            "{}",
+            tr!(
+                "{} Failed to record deletion: {}",
+                "⚠️".yellow(),
+                format!("{err:#}")
+            )
+        );
+    }
+    hooks::emit(state, Event::new(EventKind::WorktreeDeleted, worktree_info));
 
     println!(
-        "{} Worktree '{}' deleted successfully",
-        "✅".green(),
-        worktree_info.name.cyan()
+        "{}",
+        tr!(
+            "{} Worktree '{}' deleted successfully",
+            "✅".green(),
+            worktree_info.name.cyan()
+        )
     );
     Ok(())
 }
@@ -107,7 +217,9 @@ fn find_worktree_to_delete(
             .iter()
             .find(|(_, w)| w.name == n)
             .map(|(k, w)| (k.clone(), w.clone()))
-            .context(format!("Worktree '{n}' not found"))
+            .with_category(ErrorCategory::NotFound, || {
+                format!("Worktree '{n}' not found")
+            })
     } else {
         // Find worktree by current directory
         find_current_worktree(state)
@@ -127,34 +239,55 @@ fn find_current_worktree(state: &XlaudeState) -> Result<(String, WorktreeInfo)>
         .iter()
         .find(|(_, w)| w.path.file_name().and_then(|n| n.to_str()) == Some(dir_name))
         .map(|(k, w)| (k.clone(), w.clone()))
-        .context("Current directory is not a managed worktree")
+        .with_category(
+            ErrorCategory::NotFound,
+            || "Current directory is not a managed worktree",
+        )
 }
 
 /// Handle the case where worktree directory doesn't exist
 fn handle_missing_worktree(worktree_info: &WorktreeInfo, _config: &DeletionConfig) -> Result<bool> {
     println!(
-        "{} Worktree directory not found at {}",
-        "⚠️ ".yellow(),
-        worktree_info.path.display()
+        "{}",
+        tr!(
+            "{} Worktree directory not found at {}",
+            "⚠️ ".yellow(),
+            worktree_info.path.display()
+        )
     );
     println!(
-        "  {} The worktree may have been manually deleted",
-        "ℹ️".blue()
+        "{}",
+        tr!(
+            "  {} The worktree may have been manually deleted",
+            "ℹ️".blue()
+        )
     );
 
-    smart_confirm("Remove this worktree from xlaude management?", true)
+    smart_confirm_destructive(
+        "remove_missing",
+        &tr!("Remove this worktree from xlaude management?"),
+        true,
+    )
 }
 
 /// Perform all checks needed before deletion
 fn perform_deletion_checks(worktree_info: &WorktreeInfo) -> Result<DeletionChecks> {
     execute_in_dir(&worktree_info.path, || {
         let has_uncommitted_changes = !is_working_tree_clean()?;
-        let has_unpushed_commits = has_unpushed_commits();
-
-        // Check branch merge status in main repo
-        let main_repo_path = get_main_repo_path(worktree_info)?;
-        let (branch_merged_via_git, branch_merged_via_pr) =
-            check_branch_merge_status(&main_repo_path, &worktree_info.branch)?;
+        // Commits made on a detached HEAD are only reachable from the worktree
+        let has_unpushed_commits = if worktree_info.detached {
+            has_commits_outside_refs()
+        } else {
+            has_unpushed_commits()
+        };
+
+        // Check branch merge status in main repo; a detached worktree has no branch
+        let (branch_merged_via_git, branch_merged_via_pr) = if worktree_info.detached {
+            (false, false)
+        } else {
+            let main_repo_path = get_main_repo_path(worktree_info)?;
+            check_branch_merge_status(&main_repo_path, &worktree_info.branch)?
+        };
 
         Ok(DeletionChecks {
             has_uncommitted_changes,
@@ -204,6 +337,63 @@ fn check_branch_merged_via_pr(branch: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Show when an agent last worked in the worktree. If that was recent, or an
+/// agent is still running there, ask for an explicit confirmation first.
+fn confirm_agent_activity(state: &XlaudeState, worktree_info: &WorktreeInfo) -> Result<bool> {
+    let window = match &state.recent_activity_window {
+        Some(window) => parse_duration(window).with_category(ErrorCategory::Config, || {
+            format!("Invalid recent_activity_window '{window}'")
+        })?,
+        None => DEFAULT_ACTIVITY_WINDOW,
+    };
+
+    let key = XlaudeState::make_key(&worktree_info.repo_name, &worktree_info.name);
+    let running: Vec<_> = ProcessRegistry::load_live()
+        .map(|registry| registry.for_worktree(&key).into_iter().cloned().collect())
+        .unwrap_or_default();
+    let last_activity = last_session_activity(&worktree_info.path);
+
+    if let Some(timestamp) = last_activity {
+        println!(
+            "{}",
+            tr!(
+                "  {} Last agent activity {}",
+                "🤖".cyan(),
+                format_time_ago(Some(timestamp))
+            )
+        );
+    }
+    let recent = window > chrono::Duration::zero()
+        && last_activity.is_some_and(|timestamp| Utc::now() - timestamp < window);
+    if running.is_empty() && !recent {
+        return Ok(true);
+    }
+
+    println!();
+    for agent in &running {
+        println!(
+            "{}",
+            tr!(
+                "{} An agent is still running in this worktree: {} (pid {})",
+                "⚠️ ".red(),
+                agent.command,
+                agent.pid
+            )
+        );
+    }
+    if recent {
+        println!(
+            "{}",
+            tr!(
+                "{} An agent wrote to this worktree's session {}; it may still be working here",
+                "⚠️ ".red(),
+                format_time_ago(last_activity)
+            )
+        );
+    }
+    smart_confirm_destructive("delete_active", &tr!("Delete it anyway?"), false)
+}
+
 /// Confirm deletion with the user based on checks
 fn confirm_deletion(
     worktree_info: &WorktreeInfo,
@@ -214,43 +404,69 @@ fn confirm_deletion(
     if checks.has_pending_work() {
         show_pending_work_warnings(checks);
 
-        return smart_confirm("Are you sure you want to delete this worktree?", false);
+        return smart_confirm_destructive(
+            "confirm_delete",
+            &tr!("Are you sure you want to delete this worktree?"),
+            false,
+        );
     }
 
     // Show branch merge status
-    if !checks.branch_is_merged() {
+    if worktree_info.detached {
+        println!(
+            "{}",
+            tr!(
+                "  {} Detached at '{}'; there is no branch to delete",
+                "ℹ️".blue(),
+                worktree_info.branch
+            )
+        );
+    } else if !checks.branch_is_merged() {
         show_unmerged_branch_warning(worktree_info);
     } else if checks.branch_merged_via_pr && !checks.branch_merged_via_git {
-        println!("  {} Branch was merged via PR", "ℹ️".blue());
+        println!("{}", tr!("  {} Branch was merged via PR", "ℹ️".blue()));
     }
 
     // Ask for confirmation
-    smart_confirm(&format!("Delete worktree '{}'?", worktree_info.name), true)
+    smart_confirm_destructive(
+        "confirm_delete",
+        &tr!("Delete worktree '{}'?", worktree_info.name),
+        true,
+    )
 }
 
 /// Show warnings for uncommitted changes or unpushed commits
 fn show_pending_work_warnings(checks: &DeletionChecks) {
     println!();
     if checks.has_uncommitted_changes {
-        println!("{} You have uncommitted changes", "⚠️ ".red());
+        println!("{}", tr!("{} You have uncommitted changes", "⚠️ ".red()));
     }
     if checks.has_unpushed_commits {
-        println!("{} You have unpushed commits", "⚠️ ".red());
+        println!("{}", tr!("{} You have unpushed commits", "⚠️ ".red()));
     }
 }
 
 /// Show warning for unmerged branch
 fn show_unmerged_branch_warning(worktree_info: &WorktreeInfo) {
     println!(
-        "{} Branch '{}' is not fully merged",
-        "⚠️ ".yellow(),
-        worktree_info.branch.cyan()
+        "{}",
+        tr!(
+            "{} Branch '{}' is not fully merged",
+            "⚠️ ".yellow(),
+            worktree_info.branch.cyan()
+        )
+    );
+    println!(
+        "{}",
+        tr!("  {} No merged PR found for this branch", "ℹ️".blue())
     );
-    println!("  {} No merged PR found for this branch", "ℹ️".blue());
 }
 
 /// Perform the actual deletion of worktree and branch
-fn perform_deletion(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Result<()> {
+fn perform_deletion(
+    worktree_info: &WorktreeInfo,
+    config: &DeletionConfig,
+) -> Result<BranchDeletion> {
     let main_repo_path = get_main_repo_path(worktree_info)?;
 
     // Change to main repo if we're deleting current directory
@@ -264,16 +480,14 @@ fn perform_deletion(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Re
         remove_worktree(worktree_info, config)?;
 
         // Delete branch
-        delete_branch(worktree_info, config)?;
-
-        Ok(())
+        delete_branch(worktree_info, config)
     })
 }
 
 /// Remove the worktree from git
 fn remove_worktree(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Result<()> {
     if config.worktree_exists {
-        println!("{} Removing worktree...", "🗑️ ".yellow());
+        println!("{}", tr!("{} Removing worktree...", "🗑️ ".yellow()));
 
         // First attempt: try normal removal
         let result = execute_git(&["worktree", "remove", worktree_info.path.to_str().unwrap()]);
@@ -281,8 +495,11 @@ fn remove_worktree(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Res
         // If failed, might be due to submodules - try with force flag
         if result.is_err() {
             println!(
-                "{} Standard removal failed, trying force removal...",
-                "⚠️ ".yellow()
+                "{}",
+                tr!(
+                    "{} Standard removal failed, trying force removal...",
+                    "⚠️ ".yellow()
+                )
             );
             execute_git(&[
                 "worktree",
@@ -293,47 +510,75 @@ fn remove_worktree(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Res
             .context("Failed to force remove worktree")?;
         }
     } else {
-        println!("{} Pruning non-existent worktree...", "🗑️ ".yellow());
+        println!(
+            "{}",
+            tr!("{} Pruning non-existent worktree...", "🗑️ ".yellow())
+        );
         execute_git(&["worktree", "prune"]).context("Failed to prune worktree")?;
     }
     Ok(())
 }
 
 /// Delete the branch from git
-fn delete_branch(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Result<()> {
+fn delete_branch(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Result<BranchDeletion> {
+    if worktree_info.detached {
+        return Ok(BranchDeletion::Kept);
+    }
+    if config.branch_protected {
+        println!(
+            "{}",
+            tr!(
+                "{} Branch '{}' is protected; kept",
+                "ℹ️ ".blue(),
+                worktree_info.branch
+            )
+        );
+        return Ok(BranchDeletion::Kept);
+    }
+
     println!(
-        "{} Deleting branch '{}'...",
-        "🗑️ ".yellow(),
-        worktree_info.branch
+        "{}",
+        tr!(
+            "{} Deleting branch '{}'...",
+            "🗑️ ".yellow(),
+            worktree_info.branch
+        )
     );
 
     // First try safe delete
     if execute_git(&["branch", "-d", &worktree_info.branch]).is_ok() {
-        println!("{} Branch deleted", "✅".green());
-        return Ok(());
+        println!("{}", tr!("{} Branch deleted", "✅".green()));
+        return Ok(BranchDeletion::Merged);
     }
 
     // Branch is not fully merged, ask for force delete
     if !config.is_interactive {
-        println!("{} Branch kept (not fully merged)", "ℹ️ ".blue());
-        return Ok(());
+        println!("{}", tr!("{} Branch kept (not fully merged)", "ℹ️ ".blue()));
+        return Ok(BranchDeletion::Kept);
     }
 
-    let force_delete = smart_confirm("Branch is not fully merged. Force delete?", false)?;
+    let force_delete = smart_confirm_destructive(
+        "force_delete_branch",
+        &tr!("Branch is not fully merged. Force delete?"),
+        false,
+    )?;
 
     if force_delete {
         execute_git(&["branch", "-D", &worktree_info.branch])
             .context("Failed to force delete branch")?;
-        println!("{} Branch force deleted", "✅".green());
+        println!("{}", tr!("{} Branch force deleted", "✅".green()));
+        Ok(BranchDeletion::Forced)
     } else {
-        println!("{} Branch kept", "ℹ️ ".blue());
+        println!("{}", tr!("{} Branch kept", "ℹ️ ".blue()));
+        Ok(BranchDeletion::Kept)
     }
-
-    Ok(())
 }
 
-/// Get the path to the main repository from worktree info
+/// Get the path to the main repository (or bare repository) from worktree info
 fn get_main_repo_path(worktree_info: &WorktreeInfo) -> Result<std::path::PathBuf> {
+    if let Some(main_repo) = find_main_repo(&worktree_info.path, &worktree_info.repo_name) {
+        return Ok(main_repo);
+    }
     let parent = worktree_info
         .path
         .parent()
@@ -1,13 +1,22 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 
+use crate::bail_with;
+use crate::error::{ErrorCategory, WithCategory};
+use crate::history;
 use crate::input::{get_command_arg, smart_select};
-use crate::state::{WorktreeInfo, XlaudeState};
+use crate::state::XlaudeState;
 
-pub fn handle_dir(name: Option<String>) -> Result<()> {
-    let state = XlaudeState::load()?;
+pub fn handle_dir(name: Option<String>, root: bool) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    if state.remember_current_dir() {
+        state.save_without_backup()?;
+    }
 
     if state.worktrees.is_empty() {
-        anyhow::bail!("No worktrees found. Create one first with 'xlaude create'");
+        bail_with!(
+            ErrorCategory::NotFound,
+            "No worktrees found. Create one first with 'xlaude create'"
+        );
     }
 
     // Get name from CLI args or pipe
@@ -21,18 +30,19 @@ pub fn handle_dir(name: Option<String>) -> Result<()> {
             .iter()
             .find(|(_, w)| w.name == n)
             .map(|(k, w)| (k.clone(), w.clone()))
-            .context(format!("Worktree '{n}' not found"))?
+            .with_category(ErrorCategory::NotFound, || {
+                format!("Worktree '{n}' not found")
+            })?
     } else {
-        // Interactive selection - show repo/name format
-        let worktree_list: Vec<(String, WorktreeInfo)> = state
-            .worktrees
-            .iter()
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect();
+        // Interactive selection - show repo/name format, most recently opened first
+        let worktree_list = history::by_recent_use(&state);
 
-        let selection = smart_select("Select a worktree", &worktree_list, |(_, info)| {
-            format!("{}/{}", info.repo_name, info.name)
-        })?;
+        let selection = smart_select(
+            "worktree",
+            "Select a worktree",
+            &worktree_list,
+            |(_, info)| format!("{}/{}", info.repo_name, info.name),
+        )?;
 
         match selection {
             Some(idx) => worktree_list[idx].clone(),
@@ -42,9 +52,23 @@ pub fn handle_dir(name: Option<String>) -> Result<()> {
         }
     };
 
+    if worktree_info.archived.is_some() {
+        bail_with!(
+            ErrorCategory::NotFound,
+            "Worktree '{}' is archived. Run 'xlaude restore {}' first",
+            worktree_info.name,
+            worktree_info.name
+        );
+    }
+
     // Output only the path - no decorations, no colors
     // This makes it easy to use in shell commands: cd $(xlaude dir name)
-    println!("{}", worktree_info.path.display());
+    let path = if root {
+        worktree_info.path.clone()
+    } else {
+        worktree_info.working_dir()
+    };
+    println!("{}", path.display());
 
     Ok(())
 }
@@ -0,0 +1,397 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde_json::json;
+use std::process::Command;
+
+use crate::agent::{AgentKind, agent_version};
+use crate::agent_auth::{self, AuthStatus};
+use crate::bail_with;
+use crate::error::ErrorCategory;
+use crate::git::{
+    WorktreeHealth, branch_in, check_worktree_health, find_main_repo, repair_worktree,
+};
+use crate::hooks::{self, Event, EventKind};
+use crate::input::smart_confirm;
+use crate::integrity::{check_state, repair};
+use crate::state::{WorktreeInfo, XlaudeState, get_state_path};
+use crate::utils::{resolve_agent_command, sanitize_branch_name};
+
+pub fn handle_doctor(fix: bool) -> Result<()> {
+    println!("{} Running xlaude diagnostics...", "🩺".cyan());
+    println!();
+
+    let mut problems = 0usize;
+
+    match tool_version("git", &["--version"]) {
+        Some(version) => println!("  {} git: {}", "✅".green(), version),
+        None => {
+            println!("  {} git: not found on PATH", "❌".red());
+            problems += 1;
+        }
+    }
+    match tool_version("gh", &["--version"]) {
+        Some(version) => println!("  {} gh: {}", "✅".green(), version),
+        None => println!(
+            "  {} gh: not found (needed for --issue and merged-PR detection)",
+            "⚠️".yellow()
+        ),
+    }
+
+    let mut state = match XlaudeState::load() {
+        Ok(state) => {
+            println!(
+                "  {} State file: {} ({} worktree(s))",
+                "✅".green(),
+                get_state_path()?.display(),
+                state.worktrees.len()
+            );
+            state
+        }
+        Err(err) => {
+            println!("  {} State file: {:#}", "❌".red(), err);
+            anyhow::bail!("doctor found a problem that must be fixed first");
+        }
+    };
+
+    problems += check_agents();
+    problems += check_state_entries(&mut state, fix)?;
+
+    let mut worktrees: Vec<_> = state.worktrees.iter().collect();
+    worktrees.sort_by_key(|(key, _)| key.as_str());
+
+    let mut broken = Vec::new();
+    let mut renamed = Vec::new();
+    if !worktrees.is_empty() {
+        println!();
+        println!("  {}", "Worktrees:".bold());
+    }
+    for (key, info) in worktrees {
+        if info.archived.is_some() {
+            println!("    {} {}: archived", "🗄️".blue(), key);
+            continue;
+        }
+        match check_worktree_health(&info.path) {
+            WorktreeHealth::Healthy => match renamed_branch(info) {
+                Some(branch) => renamed.push((key.clone(), branch)),
+                None => println!("    {} {}", "✅".green(), key),
+            },
+            WorktreeHealth::MissingDirectory => {
+                problems += 1;
+                println!(
+                    "    {} {}: directory missing (run 'xlaude clean')",
+                    "❌".red(),
+                    key
+                );
+            }
+            WorktreeHealth::BrokenLink(reason) => {
+                println!("    {} {}: broken git link: {}", "⚠️".yellow(), key, reason);
+                broken.push(info);
+            }
+        }
+    }
+
+    let broken: Vec<WorktreeInfo> = broken.into_iter().cloned().collect();
+    for info in &broken {
+        if !offer_repair(info)? {
+            problems += 1;
+        }
+    }
+    for (key, branch) in renamed {
+        let updated = offer_branch_update(&mut state, &key, &branch)?;
+        if state.worktrees[&updated].branch != branch {
+            problems += 1;
+        }
+    }
+
+    println!();
+    if problems > 0 {
+        anyhow::bail!("doctor found {} problem(s)", problems);
+    }
+    println!("{} No problems found", "✅".green());
+    Ok(())
+}
+
+/// Validate the state entries themselves and, with `fix`, offer to repair
+/// each problem. Returns the number of problems left.
+fn check_state_entries(state: &mut XlaudeState, fix: bool) -> Result<usize> {
+    let issues = check_state(state);
+    if issues.is_empty() {
+        return Ok(0);
+    }
+    println!();
+    println!("  {}", "State entries:".bold());
+    if !fix {
+        for issue in &issues {
+            println!("    {} {}", "⚠️".yellow(), issue.describe(state));
+        }
+        println!(
+            "    {} Run 'xlaude doctor --fix' to repair them",
+            "💡".cyan()
+        );
+        return Ok(issues.len());
+    }
+
+    let declined = repair(state, |issue, state| {
+        println!("    {} {}", "⚠️".yellow(), issue.describe(state));
+        smart_confirm("fix_state", &issue.fix_prompt(), true)
+    })?;
+    state.save()?;
+    let fixed = issues.len().saturating_sub(declined.len());
+    if fixed > 0 {
+        println!(
+            "    {} Repaired {} state entr{}",
+            "✅".green(),
+            fixed,
+            if fixed == 1 { "y" } else { "ies" }
+        );
+    }
+    Ok(declined.len())
+}
+
+/// Offer to run `git worktree repair` for a worktree whose git link is broken.
+/// Returns whether the worktree is healthy afterwards.
+pub(crate) fn offer_repair(info: &WorktreeInfo) -> Result<bool> {
+    let Some(main_repo) = find_main_repo(&info.path, &info.repo_name) else {
+        println!(
+            "  {} Cannot repair '{}': main repository '{}' was not found next to it",
+            "⚠️".yellow(),
+            info.name,
+            info.repo_name
+        );
+        return Ok(false);
+    };
+
+    let confirmed = smart_confirm(
+        "repair_worktree",
+        &format!(
+            "Run 'git worktree repair' for '{}/{}'?",
+            info.repo_name, info.name
+        ),
+        true,
+    )?;
+    if !confirmed {
+        return Ok(false);
+    }
+
+    if let Err(err) = repair_worktree(&main_repo, &info.path) {
+        println!("  {} {:#}", "❌".red(), err);
+    }
+
+    match check_worktree_health(&info.path) {
+        WorktreeHealth::Healthy => {
+            println!(
+                "  {} Repaired '{}/{}'",
+                "✅".green(),
+                info.repo_name,
+                info.name
+            );
+            Ok(true)
+        }
+        _ => {
+            println!(
+                "  {} '{}/{}' is still broken. Remove the directory and recreate it with 'xlaude checkout {}'",
+                "❌".red(),
+                info.repo_name,
+                info.name,
+                info.branch
+            );
+            Ok(false)
+        }
+    }
+}
+
+/// The branch checked out in a worktree when it differs from the one xlaude
+/// recorded, as after a `git branch -m` outside xlaude.
+pub(crate) fn renamed_branch(info: &WorktreeInfo) -> Option<String> {
+    if info.detached {
+        return None;
+    }
+    branch_in(&info.path).filter(|branch| *branch != info.branch)
+}
+
+/// Offer to record `branch` as the branch of the worktree at `key`. A worktree
+/// named after its old branch is renamed after the new one as well. Returns the
+/// worktree's key afterwards.
+pub(crate) fn offer_branch_update(
+    state: &mut XlaudeState,
+    key: &str,
+    branch: &str,
+) -> Result<String> {
+    let info = &state.worktrees[key];
+    let old_branch = info.branch.clone();
+    let old_name = info.name.clone();
+    println!(
+        "    {} {}: branch '{}' is now checked out instead of '{}' (renamed outside xlaude?)",
+        "⚠️".yellow(),
+        key,
+        branch,
+        old_branch
+    );
+    let new_name = if old_name == sanitize_branch_name(&old_branch) {
+        state.worktree_name_for_branch(&info.repo_name, branch)
+    } else {
+        old_name.clone()
+    };
+    let prompt = if new_name == old_name {
+        format!("Record branch '{branch}' for '{key}'?")
+    } else {
+        format!("Record branch '{branch}' for '{key}' and rename the worktree to '{new_name}'?")
+    };
+    if !smart_confirm("update_branch", &prompt, true)? {
+        return Ok(key.to_string());
+    }
+
+    if let Some(info) = state.worktrees.get_mut(key) {
+        info.branch = branch.to_string();
+    }
+    let new_key = if new_name == old_name {
+        key.to_string()
+    } else {
+        state
+            .rename_worktree(key, &new_name)
+            .unwrap_or_else(|| key.to_string())
+    };
+    state.save()?;
+    hooks::emit(
+        state,
+        Event::new(EventKind::WorktreeRenamed, &state.worktrees[&new_key])
+            .details(json!({ "old_name": old_name, "old_branch": old_branch })),
+    );
+    println!(
+        "  {} Updated '{}' to branch '{}'",
+        "✅".green(),
+        new_key,
+        branch
+    );
+    Ok(new_key)
+}
+
+/// Make sure a worktree can be used, offering a repair when its git link is broken.
+pub(crate) fn ensure_worktree_usable(info: &WorktreeInfo) -> Result<()> {
+    if info.archived.is_some() {
+        bail_with!(
+            ErrorCategory::NotFound,
+            "Worktree '{}' is archived. Run 'xlaude restore {}' first",
+            info.name,
+            info.name
+        );
+    }
+    match check_worktree_health(&info.path) {
+        WorktreeHealth::Healthy => Ok(()),
+        WorktreeHealth::MissingDirectory => bail_with!(
+            ErrorCategory::NotFound,
+            "Worktree directory '{}' no longer exists. Run 'xlaude clean' to remove it from state",
+            info.path.display()
+        ),
+        WorktreeHealth::BrokenLink(reason) => {
+            println!(
+                "{} Worktree '{}' has a broken git link: {}",
+                "⚠️".yellow(),
+                info.name,
+                reason
+            );
+            if offer_repair(info)? {
+                Ok(())
+            } else {
+                anyhow::bail!(
+                    "Worktree '{}' is broken. Run 'xlaude doctor' for details",
+                    info.name
+                )
+            }
+        }
+    }
+}
+
+/// Check that the configured agent CLI runs and is logged in, and report on the
+/// other known agents that are installed. Returns the number of problems.
+fn check_agents() -> usize {
+    let configured = resolve_agent_command(None).ok().map(|(program, _)| program);
+    let configured_kind = configured.as_deref().map(AgentKind::from_program);
+
+    let mut programs: Vec<String> = configured.iter().cloned().collect();
+    for known in ["claude", "codex", "gemini"] {
+        if configured_kind != Some(AgentKind::from_program(known)) {
+            programs.push(known.to_string());
+        }
+    }
+
+    println!();
+    println!("  {}", "Agents:".bold());
+    let mut problems = 0;
+    for program in programs {
+        let is_configured = configured.as_deref() == Some(program.as_str());
+        let Some(version) = agent_version(&program) else {
+            // Other agents are optional
+            if is_configured {
+                println!(
+                    "    {} {}: not found on PATH (configured agent)",
+                    "❌".red(),
+                    program
+                );
+                problems += 1;
+            }
+            continue;
+        };
+        let label = if is_configured {
+            format!("{program} (configured)")
+        } else {
+            program.clone()
+        };
+        println!(
+            "    {} {}: {}",
+            "✅".green(),
+            label,
+            version.as_deref().unwrap_or("installed")
+        );
+
+        let status = agent_auth::check(AgentKind::from_program(&program));
+        let summary = match &status {
+            AuthStatus::Unknown => continue,
+            AuthStatus::ApiKey(source) => format!("credentials from {source}"),
+            AuthStatus::LoggedIn(Some(detail)) => format!("logged in ({detail})"),
+            AuthStatus::LoggedIn(None) => "logged in".to_string(),
+            AuthStatus::Refreshable(expired) => format!(
+                "access token expired {}; it is refreshed on the next launch",
+                expired.format("%Y-%m-%d %H:%M UTC")
+            ),
+            AuthStatus::Expired(expired) => format!(
+                "login expired {}; log in again with '{}'",
+                expired.format("%Y-%m-%d %H:%M UTC"),
+                login_command(&program)
+            ),
+            AuthStatus::LoggedOut => {
+                format!("not logged in; log in with '{}'", login_command(&program))
+            }
+        };
+        let icon = if !status.needs_login() {
+            "✅".green()
+        } else if is_configured {
+            problems += 1;
+            "❌".red()
+        } else {
+            "⚠️".yellow()
+        };
+        println!("       {icon} {summary}");
+    }
+    problems
+}
+
+fn login_command(program: &str) -> &'static str {
+    match AgentKind::from_program(program) {
+        AgentKind::Claude => "claude /login",
+        AgentKind::Codex => "codex login",
+        AgentKind::Gemini => "gemini",
+        AgentKind::Other => "",
+    }
+}
+
+fn tool_version(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+}
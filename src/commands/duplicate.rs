@@ -0,0 +1,191 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde_json::json;
+
+use crate::bail_with;
+use crate::commands::create::initialize_worktree;
+use crate::dry_run;
+use crate::error::{ErrorCategory, WithCategory};
+use crate::git::execute_git;
+use crate::hooks::{self, Event, EventKind};
+use crate::state::{WorktreeInfo, XlaudeState};
+
+pub fn handle_duplicate(name: String, new_name: String, with_changes: bool) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let source = state
+        .find_worktree(&name)
+        .map(|(_, info)| info.clone())
+        .with_category(ErrorCategory::NotFound, || {
+            format!("Worktree '{name}' not found")
+        })?;
+
+    if !source.path.exists() {
+        bail!(
+            "Worktree directory '{}' no longer exists",
+            source.path.display()
+        );
+    }
+    let source_str = source
+        .path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+
+    let branch_name = new_name;
+    let repo_name = source.repo_name.clone();
+    let worktree_name = state.worktree_name_for_branch(&repo_name, &branch_name);
+
+    let key = XlaudeState::make_key(&repo_name, &worktree_name);
+    if state.worktrees.contains_key(&key) {
+        bail_with!(
+            ErrorCategory::Conflict,
+            "A worktree named '{}' already exists for repository '{}' (tracked by xlaude). Please choose a different name.",
+            worktree_name,
+            repo_name
+        );
+    }
+
+    let worktree_path = source
+        .path
+        .parent()
+        .context("Worktree has no parent directory")?
+        .join(format!("{repo_name}-{worktree_name}"));
+    if worktree_path.exists() {
+        bail_with!(
+            ErrorCategory::Conflict,
+            "Directory '{}' already exists. Please choose a different name or remove the existing directory.",
+            worktree_path.display()
+        );
+    }
+
+    if execute_git(&[
+        "-C",
+        source_str,
+        "show-ref",
+        "--verify",
+        &format!("refs/heads/{branch_name}"),
+    ])
+    .is_ok()
+    {
+        bail_with!(
+            ErrorCategory::Conflict,
+            "Branch '{}' already exists",
+            branch_name
+        );
+    }
+
+    // Snapshot uncommitted changes without touching the source worktree
+    let stash = if with_changes {
+        let sha = execute_git(&["-C", source_str, "stash", "create"])?;
+        (!sha.is_empty()).then_some(sha)
+    } else {
+        None
+    };
+
+    println!(
+        "{} Duplicating '{}' into '{}' with new branch '{}'...",
+        "✨".green(),
+        source.name.cyan(),
+        worktree_name.cyan(),
+        branch_name.cyan()
+    );
+
+    let worktree_arg = worktree_path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+    execute_git(&[
+        "-C",
+        source_str,
+        "worktree",
+        "add",
+        "-b",
+        &branch_name,
+        worktree_arg,
+        "HEAD",
+    ])
+    .context("Failed to create worktree")?;
+
+    initialize_worktree(&worktree_path, &repo_name, &source.path, false)?;
+
+    if with_changes {
+        if let Some(sha) = &stash {
+            execute_git(&["-C", worktree_arg, "stash", "apply", sha])
+                .context("Failed to apply uncommitted changes to the new worktree")?;
+        }
+        let copied = copy_untracked_files(&source.path, &worktree_path)?;
+        if stash.is_some() || copied > 0 {
+            println!(
+                "{} Carried over uncommitted changes ({} untracked file(s))",
+                "📋".green(),
+                copied
+            );
+        } else {
+            println!("{} No uncommitted changes to carry over", "ℹ️".blue());
+        }
+    }
+
+    let worktree_info = WorktreeInfo::new(
+        worktree_name.clone(),
+        branch_name,
+        worktree_path.clone(),
+        repo_name,
+    );
+    state.worktrees.insert(key, worktree_info.clone());
+    state.save()?;
+    hooks::emit(
+        &state,
+        Event::new(EventKind::WorktreeCreated, &worktree_info)
+            .details(json!({ "duplicated_from": source.name })),
+    );
+
+    println!(
+        "{} Worktree created at: {}",
+        "✅".green(),
+        worktree_path.display()
+    );
+    println!(
+        "  {} To open it, run: {} {}",
+        "💡".cyan(),
+        "xlaude open".cyan(),
+        worktree_name.cyan()
+    );
+
+    Ok(())
+}
+
+fn copy_untracked_files(source: &Path, target: &Path) -> Result<usize> {
+    let source_str = source
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+    let output = execute_git(&[
+        "-C",
+        source_str,
+        "ls-files",
+        "--others",
+        "--exclude-standard",
+    ])?;
+
+    let mut copied = 0;
+    for relative in output.lines().filter(|line| !line.is_empty()) {
+        let from = source.join(relative);
+        let to = target.join(relative);
+        if !from.is_file() {
+            continue;
+        }
+        if dry_run::is_enabled() {
+            dry_run::announce(&format!("copy {} -> {}", from.display(), to.display()));
+            copied += 1;
+            continue;
+        }
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::copy(&from, &to).with_context(|| format!("Failed to copy {relative}"))?;
+        copied += 1;
+    }
+
+    Ok(copied)
+}
@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::bail_with;
+use crate::env_snapshot::{self, EnvSnapshot};
+use crate::error::{ErrorCategory, WithCategory};
+use crate::processes;
+use crate::state::XlaudeState;
+use crate::tasks::get_task_log_path;
+
+/// Snapshot the environment, agent, and checkout of a worktree (the current
+/// one if `name` is `None`), to `output` or stdout.
+pub fn handle_env_capture(name: Option<String>, output: Option<PathBuf>) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let snapshot = match name {
+        Some(name) => {
+            let (_, info) = state
+                .find_worktree(&name)
+                .with_category(ErrorCategory::NotFound, || {
+                    format!("Worktree '{name}' not found")
+                })?;
+            EnvSnapshot::for_worktree(&state, Some(info), &info.path)?
+        }
+        None => {
+            let dir = std::env::current_dir().context("Failed to read the current directory")?;
+            let Some(info) = state.worktree_containing(&dir) else {
+                bail_with!(
+                    ErrorCategory::NotFound,
+                    "Not inside a managed worktree. Name the worktree to capture."
+                );
+            };
+            EnvSnapshot::for_worktree(&state, Some(info), &dir)?
+        }
+    };
+
+    match output {
+        Some(path) => {
+            snapshot.save(&path)?;
+            println!(
+                "{} Saved the environment of '{}' to {} ({} variables, {} redacted)",
+                "✅".green(),
+                snapshot.worktree.as_deref().unwrap_or_default(),
+                path.display(),
+                snapshot.env.len(),
+                snapshot.redacted()
+            );
+        }
+        None => println!(
+            "{}",
+            serde_json::to_string_pretty(&snapshot).context("Failed to serialize the snapshot")?
+        ),
+    }
+    Ok(())
+}
+
+/// Bring back the environment of a snapshot file or of a task run by
+/// `xlaude run` (by id). Prints shell commands that set it up, or with `run`
+/// runs the recorded agent command again.
+pub fn handle_env_apply(snapshot: String, run: bool) -> Result<()> {
+    let path = match snapshot.trim_start_matches("task-").parse::<u64>() {
+        Ok(id) if !Path::new(&snapshot).exists() => {
+            env_snapshot::path_for_log(&get_task_log_path(id)?)
+        }
+        _ => PathBuf::from(&snapshot),
+    };
+    if !path.exists() {
+        bail_with!(
+            ErrorCategory::NotFound,
+            "No environment snapshot at {}",
+            path.display()
+        );
+    }
+    let snapshot = EnvSnapshot::load(&path)?;
+
+    for difference in snapshot.drift() {
+        eprintln!("{} {difference}", "⚠️".yellow());
+    }
+    if snapshot.redacted() > 0 {
+        eprintln!(
+            "{} {} redacted variable(s) keep their current values",
+            "ℹ️".blue(),
+            snapshot.redacted()
+        );
+    }
+
+    if !run {
+        println!("cd {}", shell_words::quote(&snapshot.dir.to_string_lossy()));
+        for (name, value) in snapshot.variables() {
+            println!("export {name}={}", shell_words::quote(value));
+        }
+        println!("# {}", shell_words::join(&snapshot.command));
+        return Ok(());
+    }
+
+    let Some((program, args)) = snapshot.command.split_first() else {
+        bail_with!(ErrorCategory::Usage, "The snapshot records no command");
+    };
+    let mut child = Command::new(program)
+        .args(args)
+        .current_dir(&snapshot.dir)
+        .envs(snapshot.variables())
+        .stdin(match snapshot.stdin {
+            Some(_) => Stdio::piped(),
+            None => Stdio::inherit(),
+        })
+        .spawn()
+        .with_category(ErrorCategory::AgentFailure, || {
+            format!("Failed to run '{program}'")
+        })?;
+    if let (Some(input), Some(mut stdin)) = (&snapshot.stdin, child.stdin.take()) {
+        // The agent may exit without reading its input
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    let status = child
+        .wait()
+        .with_category(ErrorCategory::AgentFailure, || "Failed to wait for agent")?;
+    if !status.success() {
+        return Err(processes::exit_error(&status));
+    }
+    Ok(())
+}
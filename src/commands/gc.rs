@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::bail_with;
+use crate::commands::clean::remove_invalid_worktrees;
+use crate::disk::format_bytes;
+use crate::dry_run;
+use crate::error::ErrorCategory;
+use crate::git::{execute_git, find_main_repo, main_repo_dir};
+use crate::state::XlaudeState;
+
+/// Prune git's records of worktrees whose directories are gone in every
+/// repository xlaude manages, optionally `git gc` them, and drop the state
+/// entries git no longer knows about.
+pub fn handle_gc(objects: bool) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let repos = repositories(&state);
+    if repos.is_empty() {
+        println!("{} No repositories to clean up", "✨".green());
+        return Ok(());
+    }
+
+    let mut pruned = 0;
+    let mut freed = 0;
+    for repo in &repos {
+        println!("{} {}", "📦".blue(), repo.display().to_string().bold());
+        let removed = prune_worktrees(repo)?;
+        if removed.is_empty() {
+            println!("  {} No orphaned worktree entries", "✨".green());
+        }
+        for entry in &removed {
+            let verb = if dry_run::is_enabled() {
+                "Would remove"
+            } else {
+                "Removed"
+            };
+            println!("  {} {} {}", "🗑️ ".yellow(), verb, entry);
+        }
+        pruned += removed.len();
+
+        if objects {
+            let repo_str = repo
+                .to_str()
+                .context("Repository path contains invalid UTF-8")?;
+            let before = object_bytes(repo);
+            execute_git(&["-C", repo_str, "gc", "--quiet"])
+                .with_context(|| format!("git gc failed in {}", repo.display()))?;
+            if !dry_run::is_enabled() {
+                let after = object_bytes(repo);
+                let saved = before.saturating_sub(after);
+                freed += saved;
+                println!(
+                    "  {} Collected garbage: {} -> {} of objects",
+                    "🧹".green(),
+                    format_bytes(before),
+                    format_bytes(after)
+                );
+            }
+        }
+    }
+
+    println!();
+    let removed = remove_invalid_worktrees(&mut state, false)?.len();
+    println!(
+        "{} {} orphaned worktree entr{} pruned, {} state entr{} removed{}",
+        "✅".green(),
+        pruned,
+        if pruned == 1 { "y" } else { "ies" },
+        removed,
+        if removed == 1 { "y" } else { "ies" },
+        if objects && !dry_run::is_enabled() {
+            format!(", {} freed", format_bytes(freed))
+        } else {
+            String::new()
+        }
+    );
+    Ok(())
+}
+
+/// Main repositories of all managed worktrees, plus the one the command runs in.
+pub(crate) fn repositories(state: &XlaudeState) -> BTreeSet<PathBuf> {
+    state
+        .worktrees
+        .values()
+        .filter_map(|info| find_main_repo(&info.path, &info.repo_name))
+        .chain(main_repo_dir().ok())
+        .collect()
+}
+
+/// Run `git worktree prune` and return the administrative entries it removed,
+/// e.g. `worktrees/feature: gitdir file points to non-existent location`.
+/// In dry-run mode git only reports what it would remove.
+pub(crate) fn prune_worktrees(repo: &Path) -> Result<Vec<String>> {
+    let mut args = vec!["worktree", "prune", "--verbose"];
+    if dry_run::is_enabled() {
+        args.push("--dry-run");
+    }
+    let output = Command::new("git")
+        .args(&args)
+        .current_dir(repo)
+        .output()
+        .context("Failed to execute git command")?;
+    // Verbose prune reports on stderr
+    let report = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        bail_with!(
+            ErrorCategory::Git,
+            "git worktree prune failed in {}: {}",
+            repo.display(),
+            report.trim()
+        );
+    }
+    Ok(report
+        .lines()
+        .filter_map(|line| line.strip_prefix("Removing "))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Loose and packed object storage of a repository, from `git count-objects`.
+fn object_bytes(repo: &Path) -> u64 {
+    let Some(output) = repo
+        .to_str()
+        .and_then(|repo| execute_git(&["-C", repo, "count-objects", "-v"]).ok())
+    else {
+        return 0;
+    };
+    output
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(": ")?;
+            matches!(key, "size" | "size-pack" | "size-garbage")
+                .then(|| value.trim().parse::<u64>().ok())
+                .flatten()
+        })
+        .map(|kib| kib * 1024)
+        .sum()
+}
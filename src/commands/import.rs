@@ -0,0 +1,243 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use crate::commands::list::format_time_ago;
+use crate::git::{branch_in, execute_git, get_repo_name, head_sha};
+use crate::hooks::{self, Event, EventKind};
+use crate::input::smart_confirm;
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::transcript::claude_projects_dir;
+use crate::utils::{execute_in_dir, sanitize_branch_name, with_numeric_suffix};
+
+// Lines of a session file searched for the directory it was started in
+const CWD_SCAN_LINES: usize = 50;
+
+/// A git checkout Claude sessions were recorded in.
+#[derive(Default)]
+struct Checkout {
+    // Sessions started at the top of the checkout, the ones xlaude shows
+    sessions: usize,
+    // Sessions started in one of its subdirectories
+    nested: usize,
+    last_active: Option<DateTime<Utc>>,
+}
+
+/// Find the git checkouts Claude Code sessions were recorded in and offer to
+/// manage the ones xlaude does not know yet, so their history shows up in
+/// `list`, `sessions`, and the dashboard.
+pub fn handle_import() -> Result<()> {
+    let Some(projects) = claude_projects_dir().filter(|dir| dir.is_dir()) else {
+        println!("{} No Claude Code history found", "📭".yellow());
+        return Ok(());
+    };
+    let mut state = XlaudeState::load()?;
+    let managed: HashSet<PathBuf> = state
+        .worktrees
+        .values()
+        .map(|info| canonical(&info.path))
+        .collect();
+
+    let mut checkouts: BTreeMap<PathBuf, Checkout> = BTreeMap::new();
+    let mut unreachable = 0;
+    for (cwd, (count, last_active)) in session_dirs(&projects) {
+        let Some(root) = checkout_root(&cwd) else {
+            unreachable += count;
+            continue;
+        };
+        let checkout = checkouts.entry(root.clone()).or_default();
+        if canonical(&cwd) == root {
+            checkout.sessions += count;
+        } else {
+            checkout.nested += count;
+        }
+        checkout.last_active = checkout.last_active.max(last_active);
+    }
+
+    let already_managed = checkouts
+        .keys()
+        .filter(|root| managed.contains(*root))
+        .count();
+    let mut candidates: Vec<(PathBuf, Checkout)> = checkouts
+        .into_iter()
+        .filter(|(root, _)| !managed.contains(root))
+        .collect();
+    candidates.sort_by_key(|(_, checkout)| Reverse(checkout.last_active));
+
+    let mut adopted = Vec::new();
+    let mut nested_only = Vec::new();
+    for (root, checkout) in candidates {
+        if checkout.sessions == 0 {
+            nested_only.push((root, checkout.nested));
+            continue;
+        }
+        let Ok(repo_name) = execute_in_dir(&root, get_repo_name) else {
+            continue;
+        };
+        let (branch, detached) = match branch_in(&root) {
+            Some(branch) => (branch, false),
+            None => match head_sha(&root) {
+                Some(sha) => (sha[..sha.len().min(7)].to_string(), true),
+                // An unborn branch has nothing to work on yet
+                None => continue,
+            },
+        };
+        let base_name = if detached {
+            root.file_name()
+                .map(|name| sanitize_branch_name(&name.to_string_lossy()))
+                .unwrap_or_else(|| branch.clone())
+        } else {
+            sanitize_branch_name(&branch)
+        };
+        let name = with_numeric_suffix(&base_name, |name| {
+            state
+                .worktrees
+                .contains_key(&XlaudeState::make_key(&repo_name, name))
+        });
+
+        println!(
+            "{} {}/{} {}",
+            "📦".blue(),
+            repo_name.bold(),
+            name.cyan(),
+            format!(
+                "{} ({}, {} session(s), last active {})",
+                root.display(),
+                branch,
+                checkout.sessions,
+                format_time_ago(checkout.last_active)
+            )
+            .bright_black()
+        );
+        if !smart_confirm(
+            "import_worktree",
+            &format!("Adopt {} as '{}/{}'?", root.display(), repo_name, name),
+            true,
+        )? {
+            continue;
+        }
+        let mut info = WorktreeInfo::new(name, branch, root, repo_name.clone());
+        info.detached = detached;
+        state
+            .worktrees
+            .insert(XlaudeState::make_key(&repo_name, &info.name), info.clone());
+        adopted.push(info);
+    }
+
+    if !adopted.is_empty() {
+        state.save()?;
+        for info in &adopted {
+            hooks::emit(&state, Event::new(EventKind::WorktreeAdded, info));
+        }
+    }
+
+    if adopted.is_empty() {
+        println!("{} No checkouts adopted", "✨".green());
+    } else {
+        println!(
+            "{} Adopted {} checkout(s); their sessions now show in 'xlaude list'",
+            "✅".green(),
+            adopted.len()
+        );
+    }
+    if already_managed > 0 {
+        println!(
+            "  {} {}",
+            "›".bright_black(),
+            format!("{already_managed} checkout(s) with sessions are already managed")
+                .bright_black()
+        );
+    }
+    for (root, count) in &nested_only {
+        println!(
+            "  {} {}",
+            "›".bright_black(),
+            format!(
+                "{} has {} session(s), all started in subdirectories; run 'xlaude add' at its top to manage it",
+                root.display(),
+                count
+            )
+            .bright_black()
+        );
+    }
+    if unreachable > 0 {
+        println!(
+            "  {} {}",
+            "›".bright_black(),
+            format!(
+                "{unreachable} session(s) were started in directories that are gone or not git checkouts"
+            )
+            .bright_black()
+        );
+    }
+    Ok(())
+}
+
+/// The directories Claude sessions were started in, with how many sessions
+/// each has and when one was last written to.
+fn session_dirs(projects: &Path) -> BTreeMap<PathBuf, (usize, Option<DateTime<Utc>>)> {
+    let mut dirs: BTreeMap<PathBuf, (usize, Option<DateTime<Utc>>)> = BTreeMap::new();
+    let Ok(projects) = fs::read_dir(projects) else {
+        return dirs;
+    };
+    for project in projects.flatten().map(|entry| entry.path()) {
+        let Ok(files) = fs::read_dir(&project) else {
+            continue;
+        };
+        for path in files.flatten().map(|entry| entry.path()) {
+            if path.extension().is_none_or(|ext| ext != "jsonl") {
+                continue;
+            }
+            // The project directory's name encodes the path lossily, so the
+            // path is taken from the session itself
+            let Some(cwd) = session_cwd(&path) else {
+                continue;
+            };
+            let modified = fs::metadata(&path)
+                .and_then(|meta| meta.modified())
+                .ok()
+                .map(DateTime::<Utc>::from);
+            let entry = dirs.entry(cwd).or_default();
+            entry.0 += 1;
+            entry.1 = entry.1.max(modified);
+        }
+    }
+    dirs
+}
+
+/// The `cwd` recorded near the top of a Claude session file.
+fn session_cwd(path: &Path) -> Option<PathBuf> {
+    let file = File::open(path).ok()?;
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .take(CWD_SCAN_LINES)
+        .filter_map(|line| serde_json::from_str::<Value>(&line).ok())
+        .find_map(|value| value.get("cwd")?.as_str().map(PathBuf::from))
+}
+
+/// Top of the git checkout `dir` belongs to, or `None` when it is gone or not
+/// in one.
+fn checkout_root(dir: &Path) -> Option<PathBuf> {
+    if !dir.is_dir() {
+        return None;
+    }
+    let top = execute_git(&[
+        "-C",
+        &dir.display().to_string(),
+        "rev-parse",
+        "--show-toplevel",
+    ])
+    .ok()?;
+    Some(canonical(Path::new(&top)))
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,82 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+use std::time::Duration;
+
+use crate::bail_with;
+use crate::dry_run;
+use crate::error::{ErrorCategory, WithCategory};
+use crate::processes::{self, JobOutcome, JobRecord, ProcessRegistry};
+use crate::state::XlaudeState;
+use crate::utils::parse_duration;
+
+// Time the agent gets to exit after each signal before the next one is sent
+const DEFAULT_GRACE: Duration = Duration::from_secs(5);
+
+pub fn handle_kill(name: String, grace: Option<String>) -> Result<()> {
+    let grace = match grace.as_deref() {
+        Some(value) => parse_duration(value)?
+            .to_std()
+            .context("Grace period must not be negative")?,
+        None => DEFAULT_GRACE,
+    };
+
+    let state = XlaudeState::load()?;
+    let (key, info) = state
+        .find_worktree(&name)
+        .with_category(ErrorCategory::NotFound, || {
+            format!("Worktree '{name}' not found")
+        })?;
+    let registry = ProcessRegistry::load_live()?;
+    let agents: Vec<_> = registry.for_worktree(key).into_iter().cloned().collect();
+    if agents.is_empty() {
+        bail_with!(
+            ErrorCategory::NotFound,
+            "No agent is running in worktree '{}'",
+            info.name
+        );
+    }
+
+    for agent in agents {
+        if dry_run::is_enabled() {
+            dry_run::announce(&format!(
+                "stop {} (pid {}) with SIGINT, then SIGTERM/SIGKILL after {}s each",
+                agent.command,
+                agent.pid,
+                grace.as_secs()
+            ));
+            continue;
+        }
+
+        println!(
+            "{} Stopping {} (pid {}) in '{}'...",
+            "⏹️".yellow(),
+            agent.command,
+            agent.pid,
+            info.name.cyan()
+        );
+        // Take the entry first so the launching process does not log a normal exit
+        processes::unregister(agent.pid)?;
+        let signal = match processes::terminate(agent.pid, grace) {
+            Ok(signal) => signal,
+            Err(err) => {
+                // Still running, so keep it visible as active
+                let _ = processes::register(agent);
+                return Err(err);
+            }
+        };
+
+        processes::log_job(&JobRecord {
+            agent,
+            finished_at: Utc::now(),
+            outcome: JobOutcome::Killed,
+            exit_code: None,
+            signal: signal.map(String::from),
+        })?;
+        match signal {
+            Some(signal) => println!("{} Agent stopped ({})", "✅".green(), signal),
+            None => println!("{} Agent had already exited", "ℹ️".blue()),
+        }
+    }
+    Ok(())
+}
@@ -1,18 +1,38 @@
 use anyhow::Result;
 use chrono::{DateTime, Local, Utc};
-use colored::Colorize;
+use colored::{ColoredString, Colorize};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::claude::get_claude_sessions;
-use crate::codex;
-use crate::state::XlaudeState;
+use crate::activity::{active_time_in, format_active_time};
+use crate::agent::AgentKind;
+use crate::agent_errors::{AgentError, last_run_error};
+use crate::budget::{self, BudgetStatus};
+use crate::claude::SessionInfo;
+use crate::codex::CodexSession;
+use crate::commands::doctor::{offer_repair, renamed_branch};
+use crate::commands::lock::format_reason;
+use crate::daemon;
+use crate::disk::{DiskUsageCache, budget_warning, disk_budget, format_bytes};
+use crate::git::{WorktreeHealth, check_worktree_health, worktree_lock_reason};
+use crate::input::smart_confirm;
+use crate::outcome::SessionOutcome;
+use crate::processes::{AgentProcess, ProcessRegistry};
+use crate::stale;
+use crate::state::{IssueLink, TicketLink, WorktreeInfo, XlaudeState, current_profile};
+use crate::tabular::{Column, OutputFormat, print_columns, print_rows};
+use crate::time_window::TimeWindow;
+use crate::tr;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct JsonSessionInfo {
     last_user_message: String,
     last_timestamp: Option<DateTime<Utc>>,
     time_ago: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    outcome: Option<SessionOutcome>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,13 +42,63 @@ struct JsonWorktreeInfo {
     path: String,
     repo_name: String,
     created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    issue: Option<IssueLink>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ticket: Option<TicketLink>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sparse: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    disk_usage_bytes: Option<u64>,
+    // Lock reason when the worktree is locked (empty if none was given)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    locked: Option<String>,
+    // Agents xlaude launched here that are still running
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    running_agents: Vec<AgentProcess>,
+    // No session or commit activity for longer than `stale_after`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    stale: bool,
+    // Checked out at the commit or tag in `branch` without a branch
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    detached: bool,
+    // API error (rate limit, overload, auth) the latest session ended on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_run_error: Option<AgentError>,
+    // Wall-clock time of agent activity, see `activity::active_time_in`
+    #[serde(default)]
+    active_seconds: u64,
     sessions: Vec<JsonSessionInfo>,
     codex_sessions: Vec<JsonCodexSessionInfo>,
+    // Session counts before `--offset`/`--limit`, set only when they are given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    session_total: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    codex_session_total: Option<usize>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize)]
 struct JsonOutput {
     worktrees: Vec<JsonWorktreeInfo>,
+    // Worktrees whose checkout was removed with `xlaude archive`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    archived: Vec<JsonArchivedWorktree>,
+    // Configured usage budgets with this month's consumption
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    budgets: Vec<BudgetStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonArchivedWorktree {
+    name: String,
+    branch: String,
+    path: String,
+    repo_name: String,
+    archived_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -37,79 +107,581 @@ struct JsonCodexSessionInfo {
     last_user_message: Option<String>,
     last_timestamp: Option<DateTime<Utc>>,
     time_ago: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    outcome: Option<SessionOutcome>,
 }
 
-fn format_time_ago(timestamp: Option<DateTime<Utc>>) -> String {
-    timestamp.map_or_else(
-        || "unknown".to_string(),
-        |ts| {
-            let now = Utc::now();
-            let diff = now.signed_duration_since(ts);
+// Set by `--absolute`: times are shown as local timestamps instead of "3h ago"
+static ABSOLUTE_TIMES: AtomicBool = AtomicBool::new(false);
+// How far ahead a timestamp may be and still count as clock skew
+const CLOCK_SKEW_MINUTES: i64 = 5;
 
-            if diff.num_minutes() < 60 {
-                format!("{}m ago", diff.num_minutes())
-            } else if diff.num_hours() < 24 {
-                format!("{}h ago", diff.num_hours())
-            } else {
-                format!("{}d ago", diff.num_days())
-            }
-        },
-    )
+pub fn set_absolute_times(enabled: bool) {
+    ABSOLUTE_TIMES.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether Claude Code has recorded sessions in any project.
+fn has_claude_history() -> bool {
+    crate::transcript::claude_projects_dir()
+        .and_then(|dir| std::fs::read_dir(dir).ok())
+        .is_some_and(|mut entries| entries.next().is_some())
+}
+
+/// "3h ago", "2w ago", or "1y ago"; with `--absolute`, and for times too far in
+/// the future to be clock skew, the local date and time instead.
+pub(crate) fn format_time_ago(timestamp: Option<DateTime<Utc>>) -> String {
+    let Some(ts) = timestamp else {
+        return tr!("unknown");
+    };
+    let diff = Utc::now().signed_duration_since(ts);
+    if ABSOLUTE_TIMES.load(Ordering::Relaxed) || diff.num_minutes() < -CLOCK_SKEW_MINUTES {
+        return format_absolute_time(ts);
+    }
+
+    let days = diff.num_days();
+    if diff.num_minutes() < 60 {
+        tr!("{}m ago", diff.num_minutes().max(0))
+    } else if diff.num_hours() < 24 {
+        tr!("{}h ago", diff.num_hours())
+    } else if days < 7 {
+        tr!("{}d ago", days)
+    } else if days < 30 {
+        tr!("{}w ago", days / 7)
+    } else if days < 365 {
+        tr!("{}mo ago", days / 30)
+    } else {
+        tr!("{}y ago", days / 365)
+    }
+}
+
+pub(crate) fn format_absolute_time(timestamp: DateTime<Utc>) -> String {
+    timestamp
+        .with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+fn table_row(info: &JsonWorktreeInfo) -> Vec<String> {
+    let last_session_at = info
+        .sessions
+        .iter()
+        .map(|session| session.last_timestamp)
+        .chain(
+            info.codex_sessions
+                .iter()
+                .map(|session| session.last_timestamp),
+        )
+        .max()
+        .flatten();
+    vec![
+        info.repo_name.clone(),
+        info.name.clone(),
+        info.branch.clone(),
+        info.path.clone(),
+        info.created_at.to_rfc3339(),
+        last_session_at
+            .map(|timestamp| timestamp.to_rfc3339())
+            .unwrap_or_default(),
+        info.sessions.len().to_string(),
+        info.codex_sessions.len().to_string(),
+        info.running_agents.len().to_string(),
+        info.disk_usage_bytes
+            .map(|bytes| bytes.to_string())
+            .unwrap_or_default(),
+        if info.locked.is_some() { "yes" } else { "" }.to_string(),
+        info.tags.join(" "),
+        info.note.clone().unwrap_or_default(),
+        info.active_seconds.to_string(),
+        if info.stale { "yes" } else { "" }.to_string(),
+        if info.detached { "yes" } else { "" }.to_string(),
+        info.last_run_error
+            .as_ref()
+            .map(|error| error.kind.as_str().to_string())
+            .unwrap_or_default(),
+    ]
+}
+
+// Sessions previewed per worktree in text output unless `--limit` says otherwise
+const PREVIEWS: usize = 3;
+
+/// The sessions of each worktree that `list` shows, from `--offset` and
+/// `--limit`. Text output previews `PREVIEWS` sessions by default and JSON
+/// includes all of them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SessionPage {
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+impl SessionPage {
+    fn is_set(&self) -> bool {
+        self.offset > 0 || self.limit.is_some()
+    }
+
+    pub(crate) fn select<'a, T>(&self, items: &'a [T], default_limit: usize) -> &'a [T] {
+        let start = self.offset.min(items.len());
+        let end = start
+            .saturating_add(self.limit.unwrap_or(default_limit))
+            .min(items.len());
+        &items[start..end]
+    }
+
+    /// How many of `total` sessions come after the page.
+    fn remaining(&self, total: usize, default_limit: usize) -> usize {
+        total.saturating_sub(
+            self.offset
+                .saturating_add(self.limit.unwrap_or(default_limit)),
+        )
+    }
+}
+
+/// Sessions of one worktree shown by `list`, limited to the requested window.
+struct WorktreeSessions {
+    claude: Vec<SessionInfo>,
+    // The `codex_limit` most recent Codex sessions out of `codex_total`
+    codex: Vec<CodexSession>,
+    codex_total: usize,
+}
+
+impl WorktreeSessions {
+    fn load(info: &WorktreeInfo, codex_limit: usize, window: &TimeWindow) -> Result<Self> {
+        let sessions = daemon::sessions_in(info, window)?;
+        let codex_total = sessions.codex.len();
+        Ok(Self {
+            claude: sessions.claude,
+            codex: sessions.codex.into_iter().take(codex_limit).collect(),
+            codex_total,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.claude.is_empty() && self.codex_total == 0
+    }
+
+    /// Sessions of both agents in one list, newest first. A session listed
+    /// more than once, as Codex does for resumed sessions, appears once with
+    /// its latest activity.
+    fn recent_activity(&self) -> Vec<Activity<'_>> {
+        let mut entries: Vec<Activity> = self
+            .claude
+            .iter()
+            .map(|session| Activity {
+                agent: AgentKind::Claude,
+                id: &session.id,
+                timestamp: session.last_timestamp,
+                message: Some(&session.last_user_message),
+                outcome: session.outcome,
+            })
+            .chain(self.codex.iter().map(|session| Activity {
+                agent: AgentKind::Codex,
+                id: &session.id,
+                timestamp: session.last_timestamp,
+                message: session.last_user_message.as_deref(),
+                outcome: session.outcome,
+            }))
+            .collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+        let mut seen = HashSet::new();
+        entries.retain(|entry| seen.insert((entry.agent == AgentKind::Codex, entry.id)));
+        entries
+    }
+}
+
+/// A session in a worktree's "Recent activity".
+struct Activity<'a> {
+    agent: AgentKind,
+    id: &'a str,
+    timestamp: Option<DateTime<Utc>>,
+    message: Option<&'a str>,
+    outcome: Option<SessionOutcome>,
 }
 
-fn format_message_preview(message: &str, limit: usize) -> String {
-    if message.len() <= limit {
-        return message.to_string();
+impl Activity<'_> {
+    fn icon(&self) -> ColoredString {
+        match self.agent {
+            AgentKind::Codex => "◆".blue(),
+            _ => "✻".yellow(),
+        }
+    }
+}
+
+/// The glyph of a session's outcome, blank when it is unknown.
+fn outcome_glyph(outcome: Option<SessionOutcome>) -> ColoredString {
+    outcome.map_or_else(|| " ".normal(), SessionOutcome::glyph)
+}
+
+/// One line per worktree, ordered by repository and name, without decoration.
+fn print_plain(state: &XlaudeState, field: PlainField, window: &TimeWindow) -> Result<()> {
+    let mut worktrees = Vec::new();
+    for info in state
+        .worktrees
+        .values()
+        .filter(|info| info.archived.is_none())
+    {
+        if !window.is_bounded() || !WorktreeSessions::load(info, 0, window)?.is_empty() {
+            worktrees.push(info);
+        }
+    }
+    worktrees.sort_by(|a, b| {
+        a.repo_name
+            .cmp(&b.repo_name)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    for info in worktrees {
+        match field {
+            PlainField::Name => println!("{}", info.name),
+            PlainField::Key => println!("{}/{}", info.repo_name, info.name),
+            PlainField::Path => println!("{}", info.path.display()),
+        }
+    }
+    Ok(())
+}
+
+fn print_more(count: usize) {
+    if count > 0 {
+        println!(
+            "        {}",
+            tr!("{} ... and {} more", "-".bright_black(), count)
+        );
+    }
+}
+
+/// The latest sessions of a worktree, whichever agent ran them.
+fn print_recent_activity(sessions: &WorktreeSessions, page: SessionPage, preview: PreviewStyle) {
+    let activity = sessions.recent_activity();
+    if activity.is_empty() {
+        return;
+    }
+    println!(
+        "      {} {}",
+        tr!("Recent activity:").bright_black(),
+        tr!("{} session(s):", activity.len())
+    );
+    for entry in page.select(&activity, PREVIEWS) {
+        let message = entry
+            .message
+            .map(|msg| format_message_preview(msg, preview))
+            .unwrap_or_else(|| tr!("(no user message)"));
+        println!(
+            "        {} {} {} {}",
+            entry.icon(),
+            outcome_glyph(entry.outcome),
+            format_time_ago(entry.timestamp).bright_black(),
+            message.bright_black()
+        );
+    }
+    print_more(page.remaining(activity.len(), PREVIEWS));
+}
+
+/// The latest sessions of each agent, in separate blocks.
+fn print_sessions_by_agent(sessions: &WorktreeSessions, page: SessionPage, preview: PreviewStyle) {
+    let claude_sessions = &sessions.claude;
+    if !claude_sessions.is_empty() {
+        println!(
+            "      {} {}",
+            "Claude:".bright_black(),
+            tr!("{} session(s):", claude_sessions.len())
+        );
+        for session in page.select(claude_sessions, PREVIEWS) {
+            let time_str = format_time_ago(session.last_timestamp);
+            let message = format_message_preview(&session.last_user_message, preview);
+
+            println!(
+                "        {} {} {} {}",
+                "-".bright_black(),
+                outcome_glyph(session.outcome),
+                time_str.bright_black(),
+                message.bright_black()
+            );
+        }
+        print_more(page.remaining(claude_sessions.len(), PREVIEWS));
+    }
+
+    let (codex_sessions, codex_total) = (&sessions.codex, sessions.codex_total);
+    if codex_total > 0 {
+        println!(
+            "      {} {}",
+            "Codex:".bright_black(),
+            tr!("{} session(s):", codex_total)
+        );
+        for session in page.select(codex_sessions, PREVIEWS) {
+            let time_str = format_time_ago(session.last_timestamp);
+            let message = session
+                .last_user_message
+                .as_deref()
+                .map(|msg| format_message_preview(msg, preview))
+                .unwrap_or_else(|| tr!("(no user message)"));
+
+            println!(
+                "        {} {} {} {}",
+                "-".bright_black(),
+                outcome_glyph(session.outcome),
+                time_str.bright_black(),
+                message.bright_black()
+            );
+        }
+        print_more(page.remaining(codex_total, PREVIEWS));
+    }
+}
+
+// Display width of message previews when `preview_width` is not set
+const DEFAULT_PREVIEW_WIDTH: usize = 60;
+
+/// How session messages are previewed, from the `preview_width` and
+/// `preview_first_line` settings.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PreviewStyle {
+    pub width: usize,
+    pub first_line: bool,
+}
+
+impl PreviewStyle {
+    pub fn from_state(state: &XlaudeState) -> Self {
+        Self {
+            width: state.preview_width.unwrap_or(DEFAULT_PREVIEW_WIDTH),
+            first_line: state.preview_first_line,
+        }
+    }
+}
+
+/// One line of `message` at most `style.width` columns wide, ending in "..."
+/// when cut. The lines of the message are joined, or with `first_line` only the
+/// first non-blank one is kept.
+pub(crate) fn format_message_preview(message: &str, style: PreviewStyle) -> String {
+    let mut lines = message.lines().filter(|line| !line.trim().is_empty());
+    let (text, cut) = if style.first_line {
+        let first = lines.next().unwrap_or_default();
+        let text = first.split_whitespace().collect::<Vec<_>>().join(" ");
+        (text, lines.next().is_some())
+    } else {
+        (
+            message.split_whitespace().collect::<Vec<_>>().join(" "),
+            false,
+        )
+    };
+    if !cut && text.width() <= style.width {
+        return text;
     }
 
     let mut truncated = String::new();
-    let safe_limit = limit.saturating_sub(3);
-    for ch in message.chars() {
-        if truncated.len() + ch.len_utf8() > safe_limit {
+    let mut width = 0;
+    let safe_width = style.width.saturating_sub(3);
+    for ch in text.chars() {
+        let ch_width = ch.width().unwrap_or(0);
+        if width + ch_width > safe_width {
             break;
         }
+        width += ch_width;
         truncated.push(ch);
     }
     truncated.push_str("...");
     truncated
 }
 
-pub fn handle_list(json: bool) -> Result<()> {
+/// Minimal one-value-per-line output for scripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlainField {
+    Name,
+    // `repo/name`, the state key
+    Key,
+    Path,
+}
+
+// Columns of `--format tsv|csv`; append new ones at the end to keep imports working
+const TABLE_COLUMNS: &[Column] = &[
+    Column {
+        name: "repo",
+        description: "Repository name",
+    },
+    Column {
+        name: "name",
+        description: "Worktree name",
+    },
+    Column {
+        name: "branch",
+        description: "Checked-out branch",
+    },
+    Column {
+        name: "path",
+        description: "Absolute worktree path",
+    },
+    Column {
+        name: "created_at",
+        description: "Creation time (RFC 3339, UTC)",
+    },
+    Column {
+        name: "last_session_at",
+        description: "Latest Claude or Codex message time (RFC 3339, UTC; empty if none)",
+    },
+    Column {
+        name: "claude_sessions",
+        description: "Number of Claude sessions",
+    },
+    Column {
+        name: "codex_sessions",
+        description: "Number of Codex sessions",
+    },
+    Column {
+        name: "running_agents",
+        description: "Number of agents xlaude launched here that are still running",
+    },
+    Column {
+        name: "disk_usage_bytes",
+        description: "Disk usage in bytes (only with --du)",
+    },
+    Column {
+        name: "locked",
+        description: "\"yes\" when the worktree is locked, otherwise empty",
+    },
+    Column {
+        name: "tags",
+        description: "Tags separated by spaces",
+    },
+    Column {
+        name: "note",
+        description: "Free-form note",
+    },
+    Column {
+        name: "active_seconds",
+        description: "Wall-clock seconds of agent activity (pauses under 30 minutes count as work)",
+    },
+    Column {
+        name: "stale",
+        description: "\"yes\" when there was no session or commit activity for stale_after, otherwise empty",
+    },
+    Column {
+        name: "detached",
+        description: "\"yes\" when checked out at a commit or tag without a branch, otherwise empty",
+    },
+    Column {
+        name: "last_run_error",
+        description: "rate_limit, overloaded, auth, or other when the latest session ended on an API error, otherwise empty",
+    },
+];
+
+pub fn handle_list(
+    format: OutputFormat,
+    du: bool,
+    plain: Option<PlainField>,
+    since: Option<String>,
+    until: Option<String>,
+    full: bool,
+    page: SessionPage,
+) -> Result<()> {
+    if format == OutputFormat::Help {
+        print_columns(TABLE_COLUMNS);
+        return Ok(());
+    }
+    let window = TimeWindow::parse(since.as_deref(), until.as_deref())?;
+    let json = format == OutputFormat::Json;
+    let table = matches!(format, OutputFormat::Tsv | OutputFormat::Csv);
     let state = XlaudeState::load()?;
+    if let Some(field) = plain {
+        return print_plain(&state, field, &window);
+    }
+    let (mut du_cache, disk_limit) = if du {
+        (
+            Some(DiskUsageCache::load()?),
+            disk_budget(state.disk_budget.as_deref())?,
+        )
+    } else {
+        (None, None)
+    };
+    let mut du_total = 0u64;
+    // The registry only affects what is displayed, so a broken one is not fatal
+    let registry = ProcessRegistry::load_live().unwrap_or_default();
 
     if state.worktrees.is_empty() {
         if json {
-            let output = JsonOutput { worktrees: vec![] };
+            let output = JsonOutput {
+                worktrees: vec![],
+                archived: vec![],
+                budgets: vec![],
+            };
             println!("{}", serde_json::to_string_pretty(&output)?);
+        } else if table {
+            print_rows(format, TABLE_COLUMNS, &[]);
         } else {
-            println!("{} No active worktrees", "📭".yellow());
+            println!("{}", tr!("{} No active worktrees", "📭".yellow()));
+            // On a first run, point at the sessions recorded before xlaude
+            if has_claude_history() {
+                println!(
+                    "  {} {}",
+                    "›".bright_black(),
+                    tr!("Run 'xlaude import' to adopt the checkouts of your Claude Code sessions")
+                        .bright_black()
+                );
+            }
         }
         return Ok(());
     }
 
-    if json {
-        // JSON output
+    // Archived worktrees have no checkout to report on; they are listed on
+    // their own, and only when no time range narrows the list
+    let mut archived: Vec<&WorktreeInfo> = state
+        .worktrees
+        .values()
+        .filter(|info| info.archived.is_some() && !window.is_bounded())
+        .collect();
+    archived.sort_by(|a, b| {
+        a.repo_name
+            .cmp(&b.repo_name)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let stale_after = stale::stale_after(&state)?;
+    let preview = PreviewStyle::from_state(&state);
+    let budgets = budget::check(&state)?;
+    let budget_warnings = budget::warnings(&budgets);
+
+    if json || table {
+        // JSON and table output
         let mut worktrees = Vec::new();
 
-        for info in state.worktrees.values() {
-            let claude_sessions = get_claude_sessions(&info.path);
-            let json_sessions: Vec<JsonSessionInfo> = claude_sessions
-                .into_iter()
+        for info in state
+            .worktrees
+            .values()
+            .filter(|info| info.archived.is_none())
+        {
+            let sessions = WorktreeSessions::load(info, usize::MAX, &window)?;
+            if window.is_bounded() && sessions.is_empty() {
+                continue;
+            }
+            let disk_usage_bytes = du_cache.as_mut().and_then(|cache| {
+                cache.size_of(
+                    &XlaudeState::make_key(&info.repo_name, &info.name),
+                    &info.path,
+                )
+            });
+            du_total += disk_usage_bytes.unwrap_or_default();
+            let active = active_time_in(&info.path, &sessions.codex, &window)?;
+            // TSV and CSV count every session
+            let page = if json { page } else { SessionPage::default() };
+            let (session_total, codex_session_total) = if page.is_set() {
+                (Some(sessions.claude.len()), Some(sessions.codex.len()))
+            } else {
+                (None, None)
+            };
+            let json_sessions: Vec<JsonSessionInfo> = page
+                .select(&sessions.claude, usize::MAX)
+                .iter()
                 .map(|session| JsonSessionInfo {
-                    last_user_message: session.last_user_message,
+                    last_user_message: session.last_user_message.clone(),
                     last_timestamp: session.last_timestamp,
                     time_ago: format_time_ago(session.last_timestamp),
+                    outcome: session.outcome,
                 })
                 .collect();
 
-            let (codex_sessions, _) = codex::recent_sessions(&info.path, usize::MAX)?;
-            let json_codex_sessions: Vec<JsonCodexSessionInfo> = codex_sessions
-                .into_iter()
+            let json_codex_sessions: Vec<JsonCodexSessionInfo> = page
+                .select(&sessions.codex, usize::MAX)
+                .iter()
                 .map(|session| JsonCodexSessionInfo {
-                    id: session.id,
-                    last_user_message: session.last_user_message,
+                    id: session.id.clone(),
+                    last_user_message: session.last_user_message.clone(),
                     last_timestamp: session.last_timestamp,
                     time_ago: format_time_ago(session.last_timestamp),
+                    outcome: session.outcome,
                 })
                 .collect();
 
@@ -119,8 +691,28 @@ pub fn handle_list(json: bool) -> Result<()> {
                 path: info.path.display().to_string(),
                 repo_name: info.repo_name.clone(),
                 created_at: info.created_at,
+                issue: info.issue.clone(),
+                ticket: info.ticket.clone(),
+                note: info.note.clone(),
+                sparse: info.sparse.clone(),
+                tags: info.tags.clone(),
+                disk_usage_bytes,
+                locked: worktree_lock_reason(&info.path, &info.repo_name),
+                running_agents: registry
+                    .for_worktree(&XlaudeState::make_key(&info.repo_name, &info.name))
+                    .into_iter()
+                    .cloned()
+                    .collect(),
+                stale: !state.is_kept(info)
+                    && stale_after
+                        .is_some_and(|threshold| stale::stale_since(info, threshold).is_some()),
+                detached: info.detached,
+                last_run_error: last_run_error(&sessions.claude, &sessions.codex),
+                active_seconds: active.as_secs(),
                 sessions: json_sessions,
                 codex_sessions: json_codex_sessions,
+                session_total,
+                codex_session_total,
             });
         }
 
@@ -131,102 +723,399 @@ pub fn handle_list(json: bool) -> Result<()> {
                 .then_with(|| a.name.cmp(&b.name))
         });
 
-        let output = JsonOutput { worktrees };
-        println!("{}", serde_json::to_string_pretty(&output)?);
+        if table {
+            let rows: Vec<Vec<String>> = worktrees.iter().map(table_row).collect();
+            print_rows(format, TABLE_COLUMNS, &rows);
+        } else {
+            let archived = archived
+                .iter()
+                .filter_map(|info| {
+                    Some(JsonArchivedWorktree {
+                        name: info.name.clone(),
+                        branch: info.branch.clone(),
+                        path: info.path.display().to_string(),
+                        repo_name: info.repo_name.clone(),
+                        archived_at: info.archived.as_ref()?.archived_at,
+                    })
+                })
+                .collect();
+            let output = JsonOutput {
+                worktrees,
+                archived,
+                budgets,
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        if let Some(warning) = budget_warning(du_total, disk_limit) {
+            eprintln!("{} {}", "⚠️".yellow(), warning);
+        }
+        for warning in &budget_warnings {
+            eprintln!("{} {}", "⚠️".yellow(), warning);
+        }
     } else {
-        // Original colored output
-        println!("{} Active worktrees:", "📋".cyan());
-        println!();
-
         // Group worktrees by repository
         let mut grouped: BTreeMap<String, Vec<_>> = BTreeMap::new();
-        for info in state.worktrees.values() {
+        for info in state
+            .worktrees
+            .values()
+            .filter(|info| info.archived.is_none())
+        {
+            let sessions = WorktreeSessions::load(info, usize::MAX, &window)?;
+            if window.is_bounded() && sessions.is_empty() {
+                continue;
+            }
+            let active = active_time_in(&info.path, &sessions.codex, &window)?;
             grouped
                 .entry(info.repo_name.clone())
                 .or_default()
-                .push(info);
+                .push((info, sessions, active));
+        }
+
+        if grouped.is_empty() && archived.is_empty() {
+            println!(
+                "{}",
+                tr!(
+                    "{} No worktrees with sessions in that time range",
+                    "📭".yellow()
+                )
+            );
+            return Ok(());
         }
 
+        for warning in &budget_warnings {
+            println!("{} {}", "⚠️".yellow(), warning.yellow());
+        }
+        if !budget_warnings.is_empty() {
+            println!();
+        }
+
+        // Original colored output
+        if !grouped.is_empty() {
+            match current_profile() {
+                Some(profile) => println!(
+                    "{}",
+                    tr!(
+                        "{} Active worktrees {}:",
+                        "📋".cyan(),
+                        tr!("(profile {})", profile).bright_black()
+                    )
+                ),
+                None => println!("{}", tr!("{} Active worktrees:", "📋".cyan())),
+            }
+            println!();
+        }
+
+        let mut broken = Vec::new();
+        let mut stale_count = 0;
+
         // Display grouped by repository
         for (repo_name, mut worktrees) in grouped {
             println!("  {} {}", "📦".blue(), repo_name.bold());
 
             // Sort worktrees within each repo by name
-            worktrees.sort_by_key(|w| &w.name);
+            worktrees.sort_by(|(a, ..), (b, ..)| a.name.cmp(&b.name));
 
-            for info in worktrees {
-                println!("    {} {}", "•".green(), info.name.cyan());
-                println!("      {} {}", "Path:".bright_black(), info.path.display());
+            for (info, sessions, active) in worktrees {
+                let stale_since = stale_after
+                    .filter(|_| !state.is_kept(info))
+                    .and_then(|threshold| stale::stale_since(info, threshold));
+                let mut labels = Vec::new();
+                if info.detached {
+                    labels.push(tr!("(detached at {})", info.branch).bright_black());
+                }
+                if stale_since.is_some() {
+                    stale_count += 1;
+                    labels.push(tr!("(stale)").yellow());
+                }
+                if let Some(outcome) = sessions
+                    .recent_activity()
+                    .first()
+                    .and_then(|latest| latest.outcome)
+                    .filter(|outcome| outcome.needs_attention())
+                {
+                    labels.push(format!("({})", outcome.label()).yellow());
+                }
+                print!("    {} {}", "•".green(), info.name.cyan());
+                for label in labels {
+                    print!(" {label}");
+                }
+                println!();
                 println!(
                     "      {} {}",
-                    "Created:".bright_black(),
-                    info.created_at
-                        .with_timezone(&Local)
-                        .format("%Y-%m-%d %H:%M:%S")
+                    tr!("Path:").bright_black(),
+                    info.path.display()
                 );
-
-                // Get Claude sessions for this worktree
-                let claude_sessions = get_claude_sessions(&info.path);
-                if !claude_sessions.is_empty() {
+                if let Some(cache) = du_cache.as_mut()
+                    && let Some(bytes) = cache.size_of(
+                        &XlaudeState::make_key(&info.repo_name, &info.name),
+                        &info.path,
+                    )
+                {
+                    du_total += bytes;
                     println!(
-                        "      {} {} session(s):",
-                        "Claude:".bright_black(),
-                        claude_sessions.len()
+                        "      {} {}",
+                        tr!("Disk:").bright_black(),
+                        format_bytes(bytes)
                     );
-                    for session in claude_sessions.iter().take(3) {
-                        let time_str = format_time_ago(session.last_timestamp);
-                        let message = format_message_preview(&session.last_user_message, 60);
-
-                        println!(
-                            "        {} {} {}",
-                            "-".bright_black(),
-                            time_str.bright_black(),
-                            message.bright_black()
-                        );
+                }
+                if let Some(reason) = worktree_lock_reason(&info.path, &info.repo_name) {
+                    println!(
+                        "      {} {}{}",
+                        "🔒".yellow(),
+                        tr!("Locked").yellow(),
+                        format_reason(&reason)
+                    );
+                }
+                if let Some(since) = stale_since {
+                    println!(
+                        "      {} {}",
+                        "💤".yellow(),
+                        tr!("Stale: last activity {}", format_time_ago(Some(since))).yellow()
+                    );
+                }
+                for agent in
+                    registry.for_worktree(&XlaudeState::make_key(&info.repo_name, &info.name))
+                {
+                    let attempt = if agent.attempt > 1 {
+                        tr!(", attempt {}", agent.attempt)
+                    } else {
+                        String::new()
+                    };
+                    println!(
+                        "      {} {} {}",
+                        "🟢".green(),
+                        tr!("Running: {}", agent.command).green(),
+                        tr!(
+                            "(pid {}, started {}{})",
+                            agent.pid,
+                            format_time_ago(Some(agent.started_at)),
+                            attempt
+                        )
+                    );
+                }
+                if let Some(error) = last_run_error(&sessions.claude, &sessions.codex) {
+                    println!(
+                        "      {} {}",
+                        "⛔".red(),
+                        format!(
+                            "{} {} ({}): {}",
+                            error.summary(),
+                            format_time_ago(error.timestamp),
+                            error.agent,
+                            error.message
+                        )
+                        .yellow()
+                    );
+                }
+                match check_worktree_health(&info.path) {
+                    WorktreeHealth::Healthy => {
+                        if let Some(branch) = renamed_branch(info) {
+                            println!(
+                                "      {} {}",
+                                "⚠️".yellow(),
+                                tr!(
+                                    "Branch renamed to '{}' outside xlaude (run 'xlaude doctor' to update)",
+                                    branch
+                                )
+                                .yellow()
+                            );
+                        }
                     }
-                    if claude_sessions.len() > 3 {
+                    WorktreeHealth::MissingDirectory => println!(
+                        "      {} {}",
+                        "⚠️".yellow(),
+                        tr!("Directory missing (run 'xlaude clean')").yellow()
+                    ),
+                    WorktreeHealth::BrokenLink(reason) => {
                         println!(
-                            "        {} ... and {} more",
-                            "-".bright_black(),
-                            claude_sessions.len() - 3
+                            "      {} {}",
+                            "⚠️".yellow(),
+                            tr!("Broken git link: {}", reason).yellow()
                         );
+                        broken.push(info);
                     }
                 }
-
-                let (codex_sessions, codex_total) = codex::recent_sessions(&info.path, 3)?;
-                if codex_total > 0 {
+                println!(
+                    "      {} {}",
+                    tr!("Created:").bright_black(),
+                    info.created_at
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                );
+                if let Some(issue) = &info.issue {
+                    println!(
+                        "      {} #{} {}",
+                        tr!("Issue:").bright_black(),
+                        issue.number,
+                        issue.title
+                    );
+                }
+                if let Some(ticket) = &info.ticket {
+                    println!("      {} {}", tr!("Ticket:").bright_black(), ticket.key);
+                }
+                if let Some(note) = &info.note {
+                    println!("      {} {}", tr!("Note:").bright_black(), note);
+                }
+                if let Some(profile) = &info.sparse {
+                    println!("      {} {}", tr!("Sparse:").bright_black(), profile);
+                }
+                if !info.tags.is_empty() {
+                    println!(
+                        "      {} {}",
+                        tr!("Tags:").bright_black(),
+                        info.tags.join(", ")
+                    );
+                }
+                if !active.is_zero() {
                     println!(
-                        "      {} {} session(s):",
-                        "Codex:".bright_black(),
-                        codex_total
+                        "      {} {}",
+                        tr!("Active:").bright_black(),
+                        format_active_time(active)
                     );
-                    for session in &codex_sessions {
-                        let time_str = format_time_ago(session.last_timestamp);
-                        let message = session
-                            .last_user_message
-                            .as_deref()
-                            .map(|msg| format_message_preview(msg, 60))
-                            .unwrap_or_else(|| "(no user message)".to_string());
+                }
 
-                        println!(
-                            "        {} {} {}",
-                            "-".bright_black(),
-                            time_str.bright_black(),
-                            message.bright_black()
-                        );
-                    }
-                    if codex_total > codex_sessions.len() {
-                        println!(
-                            "        {} ... and {} more",
-                            "-".bright_black(),
-                            codex_total - codex_sessions.len()
-                        );
-                    }
+                if full {
+                    print_sessions_by_agent(&sessions, page, preview);
+                } else {
+                    print_recent_activity(&sessions, page, preview);
                 }
             }
             println!();
         }
+
+        if !archived.is_empty() {
+            println!("{}", tr!("{} Archived worktrees:", "🗄️".blue()));
+            for info in &archived {
+                let archived_at = info.archived.as_ref().map(|archive| archive.archived_at);
+                let branch = if info.detached {
+                    tr!("detached at {}", info.branch)
+                } else {
+                    info.branch.clone()
+                };
+                println!(
+                    "    {} {}/{} {}",
+                    "•".bright_black(),
+                    info.repo_name,
+                    info.name.cyan(),
+                    tr!("({}, archived {})", branch, format_time_ago(archived_at)).bright_black()
+                );
+            }
+            println!(
+                "  {} {}",
+                "›".bright_black(),
+                tr!("Run 'xlaude restore <name>' to check one out again").bright_black()
+            );
+            println!();
+        }
+
+        if du_cache.is_some() {
+            println!(
+                "{}",
+                tr!(
+                    "{} Total disk usage: {}",
+                    "💾".cyan(),
+                    format_bytes(du_total).bold()
+                )
+            );
+            if let Some(warning) = budget_warning(du_total, disk_limit) {
+                println!("{} {}", "⚠️".yellow(), warning.yellow());
+            }
+        }
+
+        if stale_count > 0 {
+            println!(
+                "{}",
+                tr!(
+                    "{} {} stale worktree(s); run 'xlaude clean --stale' to review them",
+                    "💤".yellow(),
+                    stale_count
+                )
+            );
+        }
+
+        if !budgets.is_empty() {
+            println!("{}", tr!("{} Agent usage this month:", "💰".cyan()));
+            for status in &budgets {
+                println!("  {}", status.summary());
+            }
+        }
+
+        if !broken.is_empty()
+            && smart_confirm(
+                "repair_worktrees",
+                &tr!(
+                    "Repair {} broken worktree(s) with 'git worktree repair'?",
+                    broken.len()
+                ),
+                false,
+            )?
+        {
+            for info in broken {
+                offer_repair(info)?;
+            }
+        }
+    }
+
+    if let Some(mut cache) = du_cache {
+        cache.retain_keys(
+            state
+                .worktrees
+                .iter()
+                .filter(|(_, info)| info.archived.is_none())
+                .map(|(key, _)| key),
+        );
+        cache.save()?;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_times_use_larger_units() {
+        let ago = |duration: chrono::Duration| format_time_ago(Some(Utc::now() - duration));
+        assert_eq!(ago(chrono::Duration::minutes(5)), "5m ago");
+        assert_eq!(ago(chrono::Duration::hours(3)), "3h ago");
+        assert_eq!(ago(chrono::Duration::days(3)), "3d ago");
+        assert_eq!(ago(chrono::Duration::days(15)), "2w ago");
+        assert_eq!(ago(chrono::Duration::days(95)), "3mo ago");
+        assert_eq!(ago(chrono::Duration::days(3500)), "9y ago");
+        // Slightly ahead is clock skew; far ahead shows the time itself
+        assert_eq!(ago(chrono::Duration::minutes(-2)), "0m ago");
+        let ahead = Utc::now() + chrono::Duration::days(2);
+        assert_eq!(format_time_ago(Some(ahead)), format_absolute_time(ahead));
+        assert_eq!(format_time_ago(None), "unknown");
+    }
+
+    #[test]
+    fn previews_fit_the_display_width() {
+        let style = PreviewStyle {
+            width: 10,
+            first_line: false,
+        };
+        assert_eq!(format_message_preview("fix it", style), "fix it");
+        assert_eq!(
+            format_message_preview("fix\n\n  the   parser", style),
+            "fix the..."
+        );
+        // Each of these characters takes two columns
+        assert_eq!(
+            format_message_preview("修复解析器的错误", style),
+            "修复解..."
+        );
+        assert_eq!(format_message_preview("naïve café", style), "naïve café");
+
+        let first_line = PreviewStyle {
+            width: 20,
+            first_line: true,
+        };
+        assert_eq!(
+            format_message_preview("\nfix the parser\nthen add tests", first_line),
+            "fix the parser..."
+        );
+        assert_eq!(format_message_preview("one line", first_line), "one line");
+    }
+}
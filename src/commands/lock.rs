@@ -0,0 +1,79 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use crate::error::{ErrorCategory, WithCategory};
+use crate::git::{execute_git, find_main_repo, worktree_lock_reason};
+use crate::state::{WorktreeInfo, XlaudeState};
+
+pub fn handle_lock(name: String, reason: Option<String>) -> Result<()> {
+    let info = find_worktree(&name)?;
+    if let Some(existing) = worktree_lock_reason(&info.path, &info.repo_name) {
+        anyhow::bail!(
+            "Worktree '{}' is already locked{}",
+            info.name,
+            format_reason(&existing)
+        );
+    }
+
+    let (main, path) = git_paths(&info)?;
+    let mut args = vec!["-C", main.as_str(), "worktree", "lock"];
+    if let Some(reason) = reason.as_deref() {
+        args.extend(["--reason", reason]);
+    }
+    args.push(path.as_str());
+    execute_git(&args).context("Failed to lock worktree")?;
+
+    println!(
+        "{} Locked worktree '{}'{}",
+        "🔒".green(),
+        info.name.cyan(),
+        format_reason(reason.as_deref().unwrap_or_default())
+    );
+    Ok(())
+}
+
+pub fn handle_unlock(name: String) -> Result<()> {
+    let info = find_worktree(&name)?;
+    if worktree_lock_reason(&info.path, &info.repo_name).is_none() {
+        anyhow::bail!("Worktree '{}' is not locked", info.name);
+    }
+
+    let (main, path) = git_paths(&info)?;
+    execute_git(&["-C", &main, "worktree", "unlock", &path])
+        .context("Failed to unlock worktree")?;
+
+    println!("{} Unlocked worktree '{}'", "🔓".green(), info.name.cyan());
+    Ok(())
+}
+
+pub(crate) fn format_reason(reason: &str) -> String {
+    if reason.is_empty() {
+        String::new()
+    } else {
+        format!(" ({reason})")
+    }
+}
+
+fn find_worktree(name: &str) -> Result<WorktreeInfo> {
+    let state = XlaudeState::load()?;
+    state
+        .find_worktree(name)
+        .map(|(_, info)| info.clone())
+        .with_category(ErrorCategory::NotFound, || {
+            format!("Worktree '{name}' not found")
+        })
+}
+
+fn git_paths(info: &WorktreeInfo) -> Result<(String, String)> {
+    let main = find_main_repo(&info.path, &info.repo_name)
+        .with_context(|| format!("Main repository for '{}' not found", info.name))?;
+    Ok((
+        main.to_str()
+            .context("Repository path contains invalid UTF-8")?
+            .to_string(),
+        info.path
+            .to_str()
+            .context("Worktree path contains invalid UTF-8")?
+            .to_string(),
+    ))
+}
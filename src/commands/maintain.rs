@@ -0,0 +1,642 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use clap::ValueEnum;
+use colored::Colorize;
+use serde::Serialize;
+use std::fs;
+
+use crate::backup;
+use crate::commands::archive::archive_worktree;
+use crate::commands::clean::remove_invalid_worktrees;
+use crate::commands::gc::{prune_worktrees, repositories};
+use crate::commands::list::format_time_ago;
+use crate::disk::{DiskUsageCache, budget_warning, disk_budget, format_bytes};
+use crate::dry_run;
+use crate::error::{ErrorCategory, WithCategory};
+use crate::hooks::{self, Event, EventKind};
+use crate::stale;
+use crate::state::{MaintainTask, XlaudeState};
+use crate::tasks::{QueueLock, TaskQueue, TaskStatus, get_task_log_path};
+use crate::utils::parse_duration;
+
+// How long after the warning an idle worktree is archived
+const ARCHIVE_NOTICE: chrono::Duration = chrono::Duration::days(1);
+
+// Finished tasks are kept this long unless `task_retention` says otherwise
+const DEFAULT_TASK_RETENTION: chrono::Duration = chrono::Duration::days(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MaintainFormat {
+    Text,
+    /// Markdown, e.g. for cron to mail
+    #[value(alias = "markdown")]
+    Md,
+    Json,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct MaintenanceReport {
+    finished_at: DateTime<Utc>,
+    // One section per task that ran; tasks left out of `maintain_tasks` or
+    // that failed have none
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backup: Option<BackupReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prune: Option<PruneReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    archive: Option<ArchiveReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stale: Option<StaleReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tasks: Option<TaskReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    disk_usage: Option<DiskUsageReport>,
+    // Tasks that failed; the ones after them still ran
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<TaskError>,
+}
+
+#[derive(Debug, Serialize)]
+struct BackupReport {
+    // False when `state_backups` is 0
+    enabled: bool,
+    // Id of the snapshot taken, for `xlaude state restore`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snapshot: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PruneReport {
+    // Entries `git worktree prune` removed, as "<repository>: <entry>"
+    git_entries: Vec<String>,
+    // Keys of state entries of worktrees git no longer knows about
+    state_entries: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct ArchiveReport {
+    // False unless `auto_archive_after` is set
+    enabled: bool,
+    pending: Vec<PendingArchive>,
+    archived: Vec<ArchivedWorktree>,
+    // Due worktrees that cannot be archived, e.g. with uncommitted changes
+    skipped: Vec<SkippedWorktree>,
+    // Worktrees used again after the notice, which was withdrawn
+    reactivated: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PendingArchive {
+    worktree: String,
+    last_active: DateTime<Utc>,
+    archive_after: DateTime<Utc>,
+    // This run sent the notice
+    announced: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ArchivedWorktree {
+    worktree: String,
+    freed_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct SkippedWorktree {
+    worktree: String,
+    reason: String,
+}
+
+#[derive(Debug, Serialize)]
+struct StaleReport {
+    // False when `stale_after` is 0
+    enabled: bool,
+    worktrees: Vec<StaleWorktree>,
+}
+
+#[derive(Debug, Serialize)]
+struct StaleWorktree {
+    worktree: String,
+    last_active: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct TaskReport {
+    // False when `task_retention` is 0
+    enabled: bool,
+    // Ids of the finished tasks dropped with their logs
+    removed: Vec<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct DiskUsageReport {
+    total_bytes: u64,
+    worktrees: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    budget_warning: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TaskError {
+    task: MaintainTask,
+    message: String,
+}
+
+/// Run the housekeeping tasks selected by `maintain_tasks` and print one report
+/// of what they did, so a single cron entry keeps worktrees tidy. A failing task
+/// does not stop the others; the command fails afterwards.
+pub fn handle_maintain(format: MaintainFormat) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let selected = state
+        .maintain_tasks
+        .clone()
+        .unwrap_or_else(|| MaintainTask::ALL.to_vec());
+
+    let mut report = MaintenanceReport::default();
+    for task in MaintainTask::ALL
+        .into_iter()
+        .filter(|task| selected.contains(task))
+    {
+        let result = match task {
+            MaintainTask::Backup => take_backup(&state).map(|done| report.backup = Some(done)),
+            MaintainTask::Prune => prune(&mut state).map(|done| report.prune = Some(done)),
+            MaintainTask::Archive => {
+                auto_archive(&mut state).map(|done| report.archive = Some(done))
+            }
+            MaintainTask::Stale => find_stale(&state).map(|done| report.stale = Some(done)),
+            MaintainTask::Tasks => prune_tasks(&state).map(|done| report.tasks = Some(done)),
+            MaintainTask::DiskUsage => {
+                refresh_disk_usage(&state).map(|done| report.disk_usage = Some(done))
+            }
+        };
+        if let Err(err) = result {
+            report.errors.push(TaskError {
+                task,
+                message: format!("{err:#}"),
+            });
+        }
+    }
+    report.finished_at = Utc::now();
+
+    match format {
+        MaintainFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        MaintainFormat::Md => print!("{}", render_markdown(&report)),
+        MaintainFormat::Text => print_text(&report),
+    }
+    if !report.errors.is_empty() {
+        anyhow::bail!("{} maintenance task(s) failed", report.errors.len());
+    }
+    Ok(())
+}
+
+fn take_backup(state: &XlaudeState) -> Result<BackupReport> {
+    let keep = state.state_backups.unwrap_or(backup::DEFAULT_KEEP);
+    if keep == 0 || dry_run::is_enabled() {
+        if keep > 0 {
+            dry_run::announce("snapshot the state file");
+        }
+        return Ok(BackupReport {
+            enabled: keep > 0,
+            snapshot: None,
+        });
+    }
+    let content = serde_json::to_string_pretty(state).context("Failed to serialize state")?;
+    backup::snapshot(&content, keep)?;
+    Ok(BackupReport {
+        enabled: true,
+        snapshot: backup::list()?.first().map(|snapshot| snapshot.id.clone()),
+    })
+}
+
+fn prune(state: &mut XlaudeState) -> Result<PruneReport> {
+    let mut git_entries = Vec::new();
+    for repo in repositories(state) {
+        let name = repo
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| repo.display().to_string());
+        git_entries.extend(
+            prune_worktrees(&repo)?
+                .into_iter()
+                .map(|entry| format!("{name}: {entry}")),
+        );
+    }
+    let state_entries = remove_invalid_worktrees(state, true)?;
+    Ok(PruneReport {
+        git_entries,
+        state_entries,
+    })
+}
+
+/// Apply the `auto_archive_after` policy. An idle worktree is announced on the
+/// first run that finds it idle and archived by the first run a day or more
+/// later, unless it was used in between. Kept, locked, and dirty worktrees and
+/// ones with a running agent are left alone.
+fn auto_archive(state: &mut XlaudeState) -> Result<ArchiveReport> {
+    let Some(threshold) = stale::auto_archive_after(state)? else {
+        return Ok(ArchiveReport::default());
+    };
+    let mut report = ArchiveReport {
+        enabled: true,
+        ..ArchiveReport::default()
+    };
+    let mut keys: Vec<_> = state
+        .worktrees
+        .iter()
+        .filter(|(_, info)| info.archived.is_none() && info.path.exists())
+        .map(|(key, _)| key.clone())
+        .collect();
+    keys.sort();
+
+    let now = Utc::now();
+    for key in keys {
+        let info = &state.worktrees[&key];
+        let idle_since = stale::stale_since(info, threshold).filter(|_| !state.is_kept(info));
+        let notice_at = info.archive_notice_at;
+        let Some(idle_since) = idle_since else {
+            // Used again since the warning, or kept after all
+            if notice_at.is_some() {
+                state.worktrees.get_mut(&key).unwrap().archive_notice_at = None;
+                state.save()?;
+                report.reactivated.push(key);
+            }
+            continue;
+        };
+
+        match notice_at {
+            Some(notice_at) if now - notice_at >= ARCHIVE_NOTICE => {
+                match archive_worktree(state, &key) {
+                    Ok(size) => {
+                        state.save()?;
+                        hooks::emit(
+                            state,
+                            Event::new(EventKind::WorktreeArchived, &state.worktrees[&key]),
+                        );
+                        report.archived.push(ArchivedWorktree {
+                            worktree: key,
+                            freed_bytes: size,
+                        });
+                    }
+                    Err(err) => report.skipped.push(SkippedWorktree {
+                        worktree: key,
+                        reason: format!("{err:#}"),
+                    }),
+                }
+            }
+            Some(notice_at) => report.pending.push(PendingArchive {
+                worktree: key,
+                last_active: idle_since,
+                archive_after: notice_at + ARCHIVE_NOTICE,
+                announced: false,
+            }),
+            None => {
+                state.worktrees.get_mut(&key).unwrap().archive_notice_at = Some(now);
+                state.save()?;
+                hooks::emit(
+                    state,
+                    Event::new(EventKind::WorktreeArchivePending, &state.worktrees[&key])
+                        .details(serde_json::json!({ "idle_since": idle_since })),
+                );
+                report.pending.push(PendingArchive {
+                    worktree: key,
+                    last_active: idle_since,
+                    archive_after: now + ARCHIVE_NOTICE,
+                    announced: true,
+                });
+            }
+        }
+    }
+    Ok(report)
+}
+
+fn find_stale(state: &XlaudeState) -> Result<StaleReport> {
+    let Some(threshold) = stale::stale_after(state)? else {
+        return Ok(StaleReport {
+            enabled: false,
+            worktrees: Vec::new(),
+        });
+    };
+    let mut worktrees: Vec<StaleWorktree> = state
+        .worktrees
+        .iter()
+        .filter(|(_, info)| info.archived.is_none() && info.path.exists() && !state.is_kept(info))
+        .filter_map(|(key, info)| {
+            Some(StaleWorktree {
+                worktree: key.clone(),
+                last_active: stale::stale_since(info, threshold)?,
+            })
+        })
+        .collect();
+    worktrees.sort_by_key(|worktree| worktree.last_active);
+    Ok(StaleReport {
+        enabled: true,
+        worktrees,
+    })
+}
+
+/// Drop `xlaude run` tasks that finished more than `task_retention` ago, along
+/// with their logs.
+fn prune_tasks(state: &XlaudeState) -> Result<TaskReport> {
+    let retention = match &state.task_retention {
+        Some(value) => parse_duration(value).with_category(ErrorCategory::Config, || {
+            format!("Invalid task_retention '{value}'")
+        })?,
+        None => DEFAULT_TASK_RETENTION,
+    };
+    if retention <= chrono::Duration::zero() {
+        return Ok(TaskReport {
+            enabled: false,
+            removed: Vec::new(),
+        });
+    }
+
+    // A retention reaching back past the earliest date keeps every task
+    let cutoff = Utc::now()
+        .checked_sub_signed(retention)
+        .unwrap_or(DateTime::<Utc>::MIN_UTC);
+    let _lock = QueueLock::acquire()?;
+    let mut queue = TaskQueue::load()?;
+    let (expired, kept) = std::mem::take(&mut queue.tasks)
+        .into_iter()
+        .partition::<Vec<_>, _>(|task| {
+            matches!(task.status, TaskStatus::Succeeded | TaskStatus::Failed)
+                && task.finished_at.is_some_and(|at| at < cutoff)
+        });
+    queue.tasks = kept;
+    if !expired.is_empty() {
+        if dry_run::is_enabled() {
+            dry_run::announce(&format!(
+                "remove {} finished task(s) and their logs",
+                expired.len()
+            ));
+        } else {
+            queue.save()?;
+            for task in &expired {
+                let log = match &task.log_path {
+                    Some(path) => path.clone(),
+                    None => get_task_log_path(task.id)?,
+                };
+                // Tasks that never started have no log
+                let _ = fs::remove_file(log);
+            }
+        }
+    }
+    Ok(TaskReport {
+        enabled: true,
+        removed: expired.iter().map(|task| task.id).collect(),
+    })
+}
+
+fn refresh_disk_usage(state: &XlaudeState) -> Result<DiskUsageReport> {
+    let limit = disk_budget(state.disk_budget.as_deref())?;
+    let mut cache = DiskUsageCache::load()?;
+    let checked_out: Vec<_> = state
+        .worktrees
+        .iter()
+        .filter(|(_, info)| info.archived.is_none())
+        .collect();
+    let (mut total_bytes, mut worktrees) = (0, 0);
+    for (key, info) in &checked_out {
+        if let Some(bytes) = cache.refresh(key, &info.path) {
+            total_bytes += bytes;
+            worktrees += 1;
+        }
+    }
+    cache.retain_keys(checked_out.iter().map(|(key, _)| *key));
+    cache.save()?;
+    Ok(DiskUsageReport {
+        total_bytes,
+        worktrees,
+        budget_warning: budget_warning(total_bytes, limit),
+    })
+}
+
+/// What a task did, in a form both the text and the Markdown reports use.
+struct Section {
+    task: MaintainTask,
+    summary: String,
+    items: Vec<String>,
+}
+
+fn title(task: MaintainTask) -> &'static str {
+    match task {
+        MaintainTask::Backup => "Backup",
+        MaintainTask::Prune => "Prune",
+        MaintainTask::Archive => "Auto-archive",
+        MaintainTask::Stale => "Stale worktrees",
+        MaintainTask::Tasks => "Finished tasks",
+        MaintainTask::DiskUsage => "Disk usage",
+    }
+}
+
+fn icon(task: MaintainTask) -> &'static str {
+    match task {
+        MaintainTask::Backup => "💾",
+        MaintainTask::Prune => "🧹",
+        MaintainTask::Archive => "🗄️",
+        MaintainTask::Stale => "💤",
+        MaintainTask::Tasks => "📋",
+        MaintainTask::DiskUsage => "📊",
+    }
+}
+
+fn sections(report: &MaintenanceReport) -> Vec<Section> {
+    let mut sections = Vec::new();
+    if let Some(backup) = &report.backup {
+        sections.push(Section {
+            task: MaintainTask::Backup,
+            summary: match (&backup.snapshot, backup.enabled) {
+                (Some(id), _) => format!("snapshot {id}"),
+                (None, true) => "no snapshot in a dry run".to_string(),
+                (None, false) => "off (state_backups is 0)".to_string(),
+            },
+            items: Vec::new(),
+        });
+    }
+    if let Some(prune) = &report.prune {
+        sections.push(Section {
+            task: MaintainTask::Prune,
+            summary: format!(
+                "{} orphaned git entr{}, {} state entr{} removed",
+                prune.git_entries.len(),
+                if prune.git_entries.len() == 1 {
+                    "y"
+                } else {
+                    "ies"
+                },
+                prune.state_entries.len(),
+                if prune.state_entries.len() == 1 {
+                    "y"
+                } else {
+                    "ies"
+                }
+            ),
+            items: prune
+                .git_entries
+                .iter()
+                .cloned()
+                .chain(
+                    prune
+                        .state_entries
+                        .iter()
+                        .map(|key| format!("{key}: removed from state")),
+                )
+                .collect(),
+        });
+    }
+    if let Some(archive) = &report.archive {
+        let freed: u64 = archive.archived.iter().map(|done| done.freed_bytes).sum();
+        let mut items: Vec<String> = archive
+            .archived
+            .iter()
+            .map(|done| {
+                format!(
+                    "{}: archived, freeing {}",
+                    done.worktree,
+                    format_bytes(done.freed_bytes)
+                )
+            })
+            .collect();
+        items.extend(archive.pending.iter().map(|pending| {
+            if pending.announced {
+                format!(
+                    "{}: last active {}; it will be archived in a day unless used",
+                    pending.worktree,
+                    format_time_ago(Some(pending.last_active))
+                )
+            } else {
+                format!(
+                    "{}: will be archived after {}",
+                    pending.worktree,
+                    pending
+                        .archive_after
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M")
+                )
+            }
+        }));
+        items.extend(
+            archive
+                .skipped
+                .iter()
+                .map(|skipped| format!("{}: skipped: {}", skipped.worktree, skipped.reason)),
+        );
+        items.extend(
+            archive
+                .reactivated
+                .iter()
+                .map(|key| format!("{key}: active again, no longer scheduled for archiving")),
+        );
+        sections.push(Section {
+            task: MaintainTask::Archive,
+            summary: if archive.enabled {
+                format!(
+                    "{} archived ({} freed), {} pending, {} skipped",
+                    archive.archived.len(),
+                    format_bytes(freed),
+                    archive.pending.len(),
+                    archive.skipped.len()
+                )
+            } else {
+                "off (set auto_archive_after to turn it on)".to_string()
+            },
+            items,
+        });
+    }
+    if let Some(stale) = &report.stale {
+        sections.push(Section {
+            task: MaintainTask::Stale,
+            summary: match stale.worktrees.len() {
+                _ if !stale.enabled => "off (stale_after is 0)".to_string(),
+                0 => "none".to_string(),
+                count => format!("{count}; run 'xlaude clean --stale' to review them"),
+            },
+            items: stale
+                .worktrees
+                .iter()
+                .map(|worktree| {
+                    format!(
+                        "{}: last active {}",
+                        worktree.worktree,
+                        format_time_ago(Some(worktree.last_active))
+                    )
+                })
+                .collect(),
+        });
+    }
+    if let Some(tasks) = &report.tasks {
+        sections.push(Section {
+            task: MaintainTask::Tasks,
+            summary: if tasks.enabled {
+                format!("{} removed with their logs", tasks.removed.len())
+            } else {
+                "kept (task_retention is 0)".to_string()
+            },
+            items: Vec::new(),
+        });
+    }
+    if let Some(disk) = &report.disk_usage {
+        sections.push(Section {
+            task: MaintainTask::DiskUsage,
+            summary: format!(
+                "{} across {} worktree(s)",
+                format_bytes(disk.total_bytes),
+                disk.worktrees
+            ),
+            items: disk.budget_warning.iter().cloned().collect(),
+        });
+    }
+    sections
+}
+
+fn print_text(report: &MaintenanceReport) {
+    println!("{} Maintenance:", "🧰".cyan());
+    for section in sections(report) {
+        println!(
+            "  {} {}: {}",
+            icon(section.task),
+            title(section.task).bold(),
+            section.summary
+        );
+        for item in &section.items {
+            println!("      {}", item.bright_black());
+        }
+    }
+    for error in &report.errors {
+        println!(
+            "  {} {}: {}",
+            "❌".red(),
+            title(error.task).bold(),
+            format!("failed: {}", error.message).red()
+        );
+    }
+}
+
+fn render_markdown(report: &MaintenanceReport) -> String {
+    let mut out = format!(
+        "# Maintenance report: {}\n",
+        report
+            .finished_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+    );
+    for section in sections(report) {
+        out.push_str(&format!(
+            "\n## {}\n\n{}\n",
+            title(section.task),
+            section.summary
+        ));
+        if !section.items.is_empty() {
+            out.push('\n');
+            for item in &section.items {
+                out.push_str(&format!("- {item}\n"));
+            }
+        }
+    }
+    if !report.errors.is_empty() {
+        out.push_str("\n## Errors\n\n");
+        for error in &report.errors {
+            out.push_str(&format!("- {}: {}\n", title(error.task), error.message));
+        }
+    }
+    out
+}
@@ -1,27 +1,85 @@
 pub mod add;
 pub mod agent_launcher;
 pub mod agent_prompt;
+pub mod archive;
+pub mod bundle;
 pub mod checkout;
 pub mod clean;
+pub mod commit;
 pub mod complete;
 pub mod config;
+pub mod conflicts;
 pub mod create;
+pub mod daemon;
 pub mod dashboard;
 pub mod delete;
 pub mod dir;
+pub mod doctor;
+pub mod duplicate;
+pub mod env;
+pub mod gc;
+pub mod import;
+pub mod kill;
 pub mod list;
+pub mod lock;
+pub mod maintain;
 pub mod open;
+pub mod plugin;
+pub mod prompt_info;
+pub mod recent;
 pub mod rename;
+pub mod report;
+pub mod review;
+pub mod run;
+pub mod sessions;
+pub mod state;
+pub mod tag;
+pub mod task;
+pub mod trust;
+pub mod verify;
+pub mod workspace;
 
 pub use add::handle_add;
+pub use archive::{handle_archive, handle_restore};
+pub use bundle::handle_bundle;
 pub use checkout::handle_checkout;
 pub use clean::handle_clean;
+pub use commit::handle_commit;
 pub use complete::handle_complete_worktrees;
 pub use config::handle_config;
-pub use create::handle_create;
+pub use conflicts::handle_conflicts;
+pub use create::{CreateOptions, handle_create, handle_create_many};
+pub use daemon::{handle_daemon, handle_daemon_status};
 pub use dashboard::handle_dashboard;
 pub use delete::handle_delete;
 pub use dir::handle_dir;
-pub use list::handle_list;
+pub use doctor::handle_doctor;
+pub use duplicate::handle_duplicate;
+pub use env::{handle_env_apply, handle_env_capture};
+pub use gc::handle_gc;
+pub use import::handle_import;
+pub use kill::handle_kill;
+pub use list::{PlainField, handle_list};
+pub use lock::{handle_lock, handle_unlock};
+pub use maintain::{MaintainFormat, handle_maintain};
 pub use open::handle_open;
+pub use plugin::handle_external;
+pub use prompt_info::handle_prompt_info;
+pub use recent::handle_recent;
 pub use rename::handle_rename;
+pub use report::{ReportFormat, handle_report};
+pub use review::handle_review;
+pub use run::handle_run;
+pub use sessions::{
+    handle_sessions_export, handle_sessions_list, handle_sessions_resume, handle_sessions_search,
+    handle_sessions_show,
+};
+pub use state::{handle_state_backups, handle_state_migrate, handle_state_restore};
+pub use tag::handle_tag;
+pub use task::{handle_task_add, handle_task_list, handle_task_remove};
+pub use trust::{handle_trust, handle_untrust};
+pub use verify::handle_verify;
+pub use workspace::{
+    handle_workspace_create, handle_workspace_delete, handle_workspace_list, handle_workspace_open,
+    handle_workspace_status,
+};
@@ -1,15 +1,24 @@
 use anyhow::{Context, Result};
-use chrono::Utc;
 use colored::Colorize;
 
-use crate::commands::agent_launcher::launch_with_menu;
+use crate::bail_with;
+use crate::commands::agent_launcher::{LaunchOptions, launch_with_menu_and_prompt};
+use crate::commands::doctor::{ensure_worktree_usable, offer_branch_update, renamed_branch};
+use crate::error::{ErrorCategory, WithCategory};
 use crate::git::{get_current_branch, get_repo_name, is_base_branch, is_in_worktree};
+use crate::history;
+use crate::hooks::{self, Event, EventKind};
 use crate::input::{get_command_arg, is_piped_input, smart_confirm, smart_select};
 use crate::state::{WorktreeInfo, XlaudeState};
+use crate::tr;
 use crate::utils::sanitize_branch_name;
 
-pub fn handle_open(name: Option<String>) -> Result<()> {
+pub fn handle_open(name: Option<String>, options: LaunchOptions<'_>) -> Result<()> {
     let mut state = XlaudeState::load()?;
+    // Printing the command leaves everything as it was
+    if !options.print_cmd && state.remember_current_dir() {
+        state.save_without_backup()?;
+    }
 
     // Check if current path is a worktree when no name is provided
     // Note: base branches (main/master/develop) are not considered worktrees
@@ -36,20 +45,28 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
             } else {
                 // Not managed, ask if user wants to add it
                 println!(
-                    "{} Current directory is a worktree but not managed by xlaude",
-                    "ℹ️".blue()
+                    "{}",
+                    tr!(
+                        "{} Current directory is a worktree but not managed by xlaude",
+                        "ℹ️".blue()
+                    )
                 );
                 println!(
                     "  {} {}/{}",
-                    "Worktree:".bright_black(),
+                    tr!("Worktree:").bright_black(),
                     repo_name,
                     current_branch
                 );
-                println!("  {} {}", "Path:".bright_black(), current_dir.display());
+                println!(
+                    "  {} {}",
+                    tr!("Path:").bright_black(),
+                    current_dir.display()
+                );
 
                 // Use smart confirm for pipe support
                 let should_add = smart_confirm(
-                    "Would you like to add this worktree to xlaude and open it?",
+                    "add_worktree",
+                    &tr!("Would you like to add this worktree to xlaude and open it?"),
                     true,
                 )?;
 
@@ -59,70 +76,64 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
 
                 // Add to state
                 println!(
-                    "{} Adding worktree '{}' to xlaude management...",
-                    "➕".green(),
-                    worktree_name.cyan()
+                    "{}",
+                    tr!(
+                        "{} Adding worktree '{}' to xlaude management...",
+                        "➕".green(),
+                        worktree_name.cyan()
+                    )
                 );
 
-                state.worktrees.insert(
-                    key.clone(),
-                    WorktreeInfo {
-                        name: worktree_name.clone(),
-                        branch: current_branch.clone(),
-                        path: current_dir.clone(),
-                        repo_name: repo_name.clone(),
-                        created_at: Utc::now(),
-                    },
-                );
+                let info = WorktreeInfo::new(worktree_name, current_branch, current_dir, repo_name);
+                state.worktrees.insert(key, info.clone());
                 state.save()?;
+                hooks::emit(&state, Event::new(EventKind::WorktreeAdded, &info));
 
-                println!("{} Worktree added successfully", "✅".green());
-                state.worktrees.get(&key).cloned().unwrap_or(WorktreeInfo {
-                    name: worktree_name,
-                    branch: current_branch,
-                    path: current_dir,
-                    repo_name,
-                    created_at: Utc::now(),
-                })
+                println!("{}", tr!("{} Worktree added successfully", "✅".green()));
+                info
             };
 
-            let _ = launch_with_menu(
+            let _ = launch_with_menu_and_prompt(
                 &worktree_info,
-                "Select an agent to open the current worktree with:",
-            )
-            .context("Failed to launch agent")?;
+                &tr!("Select an agent to open the current worktree with:"),
+                options,
+            )?;
 
             return Ok(());
         }
     }
 
     if state.worktrees.is_empty() {
-        anyhow::bail!("No worktrees found. Create one first with 'xlaude create'");
+        bail_with!(
+            ErrorCategory::NotFound,
+            "No worktrees found. Create one first with 'xlaude create'"
+        );
     }
 
     // Get the name from CLI args or pipe
     let target_name = get_command_arg(name)?;
 
     // Determine which worktree to open
-    let (_key, worktree_info) = if let Some(n) = target_name {
+    let (key, worktree_info) = if let Some(n) = target_name {
         // Find worktree by name across all projects
         state
             .worktrees
             .iter()
             .find(|(_, w)| w.name == n)
             .map(|(k, w)| (k.clone(), w.clone()))
-            .context(format!("Worktree '{n}' not found"))?
+            .with_category(ErrorCategory::NotFound, || {
+                format!("Worktree '{n}' not found")
+            })?
     } else {
-        // Interactive selection - show repo/name format
-        let worktree_list: Vec<(String, WorktreeInfo)> = state
-            .worktrees
-            .iter()
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect();
+        // Interactive selection - show repo/name format, most recently opened first
+        let worktree_list = history::by_recent_use(&state);
 
-        let selection = smart_select("Select a worktree to open", &worktree_list, |(_, info)| {
-            format!("{}/{}", info.repo_name, info.name)
-        })?;
+        let selection = smart_select(
+            "worktree",
+            &tr!("Select a worktree to open"),
+            &worktree_list,
+            |(_, info)| format!("{}/{}", info.repo_name, info.name),
+        )?;
 
         match selection {
             Some(idx) => worktree_list[idx].clone(),
@@ -132,8 +143,28 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
         }
     };
 
-    let _ = launch_with_menu(&worktree_info, "Select an agent to open the worktree with:")
-        .context("Failed to launch agent")?;
+    open_worktree(state, key, worktree_info, options)
+}
+
+/// Launch an agent in a worktree picked by name or from a list, after making
+/// sure it is still usable.
+pub fn open_worktree(
+    mut state: XlaudeState,
+    mut key: String,
+    mut worktree_info: WorktreeInfo,
+    options: LaunchOptions<'_>,
+) -> Result<()> {
+    ensure_worktree_usable(&worktree_info)?;
+    if let Some(branch) = renamed_branch(&worktree_info) {
+        key = offer_branch_update(&mut state, &key, &branch)?;
+        worktree_info = state.worktrees[&key].clone();
+    }
+
+    let _ = launch_with_menu_and_prompt(
+        &worktree_info,
+        "Select an agent to open the worktree with:",
+        options,
+    )?;
 
     Ok(())
 }
@@ -0,0 +1,153 @@
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+use crate::bail_with;
+use crate::dry_run;
+use crate::error::{self, ErrorCategory, WithCategory};
+use crate::state::{WorktreeInfo, XlaudeState, data_dir_env, get_config_dir, get_state_path};
+use crate::utils::xlaude_exe;
+
+/// Run an unknown subcommand `name` as the `xlaude-<name>` executable found on PATH.
+///
+/// `--worktree <name>` selects the worktree exposed to the plugin; otherwise the
+/// managed worktree containing the current directory is used, if any.
+pub fn handle_external(args: Vec<String>) -> Result<()> {
+    let Some((name, rest)) = args.split_first() else {
+        bail_with!(ErrorCategory::Usage, "Missing command");
+    };
+    let Some(program) = find_plugin(name) else {
+        bail_with!(
+            ErrorCategory::Usage,
+            "Unknown command '{}'. No 'xlaude-{}' executable was found on PATH",
+            name,
+            name
+        );
+    };
+
+    let (selected, plugin_args) = split_worktree_flag(rest)?;
+    let state = XlaudeState::load()?;
+    let worktree = match selected {
+        Some(selected) => Some(
+            state
+                .find_worktree(&selected)
+                .map(|(_, info)| info.clone())
+                .with_category(ErrorCategory::NotFound, || {
+                    format!("Worktree '{selected}' not found")
+                })?,
+        ),
+        None => env::current_dir()
+            .ok()
+            .and_then(|cwd| worktree_containing(&state, &cwd)),
+    };
+
+    let mut cmd = Command::new(&program);
+    cmd.args(&plugin_args)
+        .env("XLAUDE_CONFIG_DIR", get_config_dir()?)
+        .envs(data_dir_env()?)
+        .env("XLAUDE_STATE_PATH", get_state_path()?);
+    if let Ok(exe) = xlaude_exe() {
+        cmd.env("XLAUDE_BIN", exe);
+    }
+    if dry_run::is_enabled() {
+        cmd.env("XLAUDE_DRY_RUN", "1");
+    }
+    if let Some(info) = &worktree {
+        cmd.env("XLAUDE_WORKTREE_NAME", &info.name)
+            .env("XLAUDE_WORKTREE_PATH", &info.path)
+            .env("XLAUDE_WORKTREE_BRANCH", &info.branch)
+            .env("XLAUDE_WORKTREE_REPO", &info.repo_name);
+    }
+
+    let status = cmd
+        .status()
+        .with_context(|| format!("Failed to run {}", program.display()))?;
+    if !status.success() {
+        // Exit with the plugin's own status, like git does for its external commands
+        return Err(error::exit_status(status.code().unwrap_or(1)));
+    }
+    Ok(())
+}
+
+fn find_plugin(name: &str) -> Option<PathBuf> {
+    let file_name = format!("xlaude-{name}{}", env::consts::EXE_SUFFIX);
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Pull `--worktree <name>` / `--worktree=<name>` out of the plugin arguments.
+fn split_worktree_flag(args: &[String]) -> Result<(Option<String>, Vec<OsString>)> {
+    let mut selected = None;
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            rest.push(arg.into());
+            rest.extend(iter.by_ref().map(OsString::from));
+        } else if arg == "--worktree" {
+            let value = iter.next().with_category(
+                ErrorCategory::Usage,
+                || "--worktree requires a worktree name",
+            )?;
+            selected = Some(value.clone());
+        } else if let Some(value) = arg.strip_prefix("--worktree=") {
+            selected = Some(value.to_string());
+        } else {
+            rest.push(arg.into());
+        }
+    }
+    Ok((selected, rest))
+}
+
+fn worktree_containing(state: &XlaudeState, dir: &Path) -> Option<WorktreeInfo> {
+    let dir = dir.canonicalize().ok()?;
+    state
+        .worktrees
+        .values()
+        .filter_map(|info| Some((info.path.canonicalize().ok()?, info)))
+        .filter(|(path, _)| dir.starts_with(path))
+        // Prefer the deepest match in case worktrees are nested
+        .max_by_key(|(path, _)| path.components().count())
+        .map(|(_, info)| info.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn extracts_worktree_flag() {
+        let (selected, rest) =
+            split_worktree_flag(&strings(&["--worktree", "feat", "-v", "x"])).unwrap();
+        assert_eq!(selected.as_deref(), Some("feat"));
+        assert_eq!(rest, vec![OsString::from("-v"), OsString::from("x")]);
+
+        let (selected, rest) =
+            split_worktree_flag(&strings(&["--worktree=feat", "--", "--worktree", "y"])).unwrap();
+        assert_eq!(selected.as_deref(), Some("feat"));
+        assert_eq!(rest.len(), 3);
+
+        assert!(split_worktree_flag(&strings(&["--worktree"])).is_err());
+    }
+}
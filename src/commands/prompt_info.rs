@@ -0,0 +1,36 @@
+use anyhow::Result;
+
+use crate::git::prompt_status;
+use crate::state::XlaudeState;
+
+/// Print `repo:name` for the managed worktree the current directory is in,
+/// followed by `*` for uncommitted changes and `↑N`/`↓N` for commits ahead of
+/// or behind the upstream branch. Prints nothing anywhere else, and never
+/// fails, so it can be embedded in PS1 or a starship module. Sessions are not
+/// scanned to keep it fast.
+pub fn handle_prompt_info(git: bool) -> Result<()> {
+    let Ok(state) = XlaudeState::load() else {
+        return Ok(());
+    };
+    let Some(info) = std::env::current_dir()
+        .ok()
+        .and_then(|dir| state.worktree_containing(&dir))
+    else {
+        return Ok(());
+    };
+
+    let mut prompt = format!("{}:{}", info.repo_name, info.name);
+    if git && let Some(status) = prompt_status(&info.path) {
+        if status.dirty {
+            prompt.push('*');
+        }
+        if status.ahead > 0 {
+            prompt.push_str(&format!("↑{}", status.ahead));
+        }
+        if status.behind > 0 {
+            prompt.push_str(&format!("↓{}", status.behind));
+        }
+    }
+    println!("{prompt}");
+    Ok(())
+}
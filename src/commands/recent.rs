@@ -0,0 +1,102 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+
+use crate::bail_with;
+use crate::commands::agent_launcher::LaunchOptions;
+use crate::commands::list::format_time_ago;
+use crate::commands::open::open_worktree;
+use crate::daemon;
+use crate::error::ErrorCategory;
+use crate::history;
+use crate::input::smart_choice_with_display;
+use crate::interaction::ChoiceDisplay;
+use crate::state::{WorktreeInfo, XlaudeState};
+
+// One keystroke picks a worktree, so the list is capped at the available keys
+const KEYS: [&str; 35] = [
+    "1", "2", "3", "4", "5", "6", "7", "8", "9", "a", "b", "c", "d", "e", "f", "g", "h", "i", "j",
+    "k", "l", "m", "n", "o", "p", "q", "r", "s", "t", "u", "v", "w", "x", "y", "z",
+];
+
+struct RecentWorktree {
+    key: String,
+    info: WorktreeInfo,
+    active_at: DateTime<Utc>,
+}
+
+/// Offer the most recently active worktrees, by agent sessions and the open
+/// log, under a key each, and open the one picked right away.
+pub fn handle_recent(limit: usize, list: bool) -> Result<()> {
+    let state = XlaudeState::load()?;
+    if state.worktrees.is_empty() {
+        bail_with!(
+            ErrorCategory::NotFound,
+            "No worktrees found. Create one first with 'xlaude create'"
+        );
+    }
+
+    let opened = history::last_opened();
+    let mut recent: Vec<RecentWorktree> = state
+        .worktrees
+        .iter()
+        .map(|(key, info)| RecentWorktree {
+            key: key.clone(),
+            active_at: [daemon::last_activity(info), opened.get(key).copied()]
+                .into_iter()
+                .flatten()
+                .fold(info.created_at, DateTime::max),
+            info: info.clone(),
+        })
+        .collect();
+    recent.sort_by(|a, b| b.active_at.cmp(&a.active_at).then(a.key.cmp(&b.key)));
+    recent.truncate(limit.clamp(1, KEYS.len()));
+
+    if list {
+        for worktree in &recent {
+            println!("{}", describe(worktree));
+        }
+        return Ok(());
+    }
+
+    let keys = &KEYS[..recent.len()];
+    let print = || {
+        for (key, worktree) in keys.iter().zip(&recent) {
+            println!("  {} {}", format!("[{key}]").cyan(), describe(worktree));
+        }
+    };
+    let position = |key: &str| keys.iter().position(|candidate| *candidate == key);
+    let display = ChoiceDisplay {
+        label: &|key| {
+            let worktree = &recent[position(key).unwrap_or_default()];
+            format!("[{key}] {}/{}", worktree.info.repo_name, worktree.info.name)
+        },
+        print: &print,
+    };
+
+    println!("{}", "Recent worktrees:".bold());
+    print();
+    let choice = smart_choice_with_display(
+        "recent",
+        &format!("{} ", "›".bright_black()),
+        keys,
+        keys[0],
+        Some(&display),
+        |key| {
+            let worktree = &recent[position(key).unwrap_or_default()];
+            format!("{} Opening {}", "✔".green(), worktree.info.name.cyan())
+        },
+    )?;
+
+    let picked = recent.swap_remove(position(&choice).unwrap_or_default());
+    open_worktree(state, picked.key, picked.info, LaunchOptions::default())
+}
+
+fn describe(worktree: &RecentWorktree) -> String {
+    format!(
+        "{}/{}  {}",
+        worktree.info.repo_name,
+        worktree.info.name.cyan(),
+        format_time_ago(Some(worktree.active_at)).bright_black()
+    )
+}
@@ -1,7 +1,11 @@
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result};
 use colored::Colorize;
+use serde_json::json;
 
+use crate::bail_with;
+use crate::error::ErrorCategory;
 use crate::git;
+use crate::hooks::{self, Event, EventKind};
 use crate::state::XlaudeState;
 
 pub fn handle_rename(old_name: String, new_name: String) -> Result<()> {
@@ -12,27 +16,33 @@ pub fn handle_rename(old_name: String, new_name: String) -> Result<()> {
     let new_key = XlaudeState::make_key(&repo, &new_name);
 
     if !state.worktrees.contains_key(&old_key) {
-        bail!("Worktree '{}' not found in repository '{}'", old_name, repo);
+        bail_with!(
+            ErrorCategory::NotFound,
+            "Worktree '{}' not found in repository '{}'",
+            old_name,
+            repo
+        );
     }
 
     if state.worktrees.contains_key(&new_key) {
-        bail!(
+        bail_with!(
+            ErrorCategory::Conflict,
             "Worktree '{}' already exists in repository '{}'",
             new_name,
             repo
         );
     }
 
-    let mut worktree_data = state
-        .worktrees
-        .remove(&old_key)
+    state
+        .rename_worktree(&old_key, &new_name)
         .context("Failed to get worktree data")?;
-
-    // Update the name field in the worktree info
-    worktree_data.name = new_name.clone();
-
-    state.worktrees.insert(new_key, worktree_data);
+    let worktree_data = state.worktrees[&new_key].clone();
     state.save()?;
+    hooks::emit(
+        &state,
+        Event::new(EventKind::WorktreeRenamed, &worktree_data)
+            .details(json!({ "old_name": old_name })),
+    );
 
     println!(
         "{} {} {} {} {} {}",
@@ -0,0 +1,255 @@
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::activity::{active_time_in, format_active_time};
+use crate::claude::get_claude_sessions_in;
+use crate::codex;
+use crate::history::{self, DeletedWorktree};
+use crate::state::XlaudeState;
+use crate::time_window::TimeWindow;
+use crate::usage::{self, TokenUsage, format_dollars, format_tokens};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// Markdown, for pasting into an update
+    #[value(alias = "markdown")]
+    Md,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    since: Option<DateTime<Utc>>,
+    until: DateTime<Utc>,
+    repos: Vec<RepoReport>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct RepoReport {
+    repo_name: String,
+    // Managed worktrees that were created or had sessions during the period
+    worktrees: Vec<WorktreeActivity>,
+    // Worktrees deleted during the period, with what happened to their branch
+    deleted: Vec<DeletedWorktree>,
+    sessions: usize,
+    tokens: TokenUsage,
+    // Summed over worktrees, so parallel work in several of them adds up
+    active_seconds: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct WorktreeActivity {
+    name: String,
+    branch: String,
+    created: bool,
+    claude_sessions: usize,
+    codex_sessions: usize,
+    tokens: TokenUsage,
+    active_seconds: u64,
+}
+
+pub fn handle_report(since: String, until: Option<String>, format: ReportFormat) -> Result<()> {
+    let window = TimeWindow::parse(Some(&since), until.as_deref())?;
+    let report = build_report(&XlaudeState::load()?, &window)?;
+    match format {
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        ReportFormat::Md => print!("{}", render_markdown(&report)),
+    }
+    Ok(())
+}
+
+fn build_report(state: &XlaudeState, window: &TimeWindow) -> Result<Report> {
+    let mut repos: BTreeMap<String, RepoReport> = BTreeMap::new();
+
+    for info in state.worktrees.values() {
+        let created = window.contains(Some(info.created_at));
+        let claude_sessions = get_claude_sessions_in(&info.path, window).len();
+        let (codex_sessions, codex_total) =
+            codex::recent_sessions_in(&info.path, usize::MAX, window)?;
+        if !created && claude_sessions == 0 && codex_total == 0 {
+            continue;
+        }
+
+        let mut tokens = usage::claude_usage_in(&info.path, window)?;
+        for session in &codex_sessions {
+            tokens += usage::codex_usage_in(&session.path, window)?;
+        }
+        let active_seconds = active_time_in(&info.path, &codex_sessions, window)?.as_secs();
+
+        let repo = repos.entry(info.repo_name.clone()).or_default();
+        repo.sessions += claude_sessions + codex_total;
+        repo.tokens += tokens;
+        repo.active_seconds += active_seconds;
+        repo.worktrees.push(WorktreeActivity {
+            name: info.name.clone(),
+            branch: info.branch.clone(),
+            created,
+            claude_sessions,
+            codex_sessions: codex_total,
+            tokens,
+            active_seconds,
+        });
+    }
+
+    for deleted in history::deletions_in(window)? {
+        repos
+            .entry(deleted.repo_name.clone())
+            .or_default()
+            .deleted
+            .push(deleted);
+    }
+
+    let repos = repos
+        .into_iter()
+        .map(|(repo_name, mut repo)| {
+            repo.repo_name = repo_name;
+            repo.worktrees.sort_by(|a, b| a.name.cmp(&b.name));
+            repo
+        })
+        .collect();
+    Ok(Report {
+        since: window.since,
+        until: window.until.unwrap_or_else(Utc::now),
+        repos,
+    })
+}
+
+fn render_markdown(report: &Report) -> String {
+    let day = |at: DateTime<Utc>| at.with_timezone(&Local).format("%Y-%m-%d").to_string();
+    let mut out = match report.since {
+        Some(since) => format!(
+            "# Activity report: {} to {}\n",
+            day(since),
+            day(report.until)
+        ),
+        None => format!("# Activity report until {}\n", day(report.until)),
+    };
+
+    if report.repos.is_empty() {
+        out.push_str("\n_No activity recorded in this period._\n");
+        return out;
+    }
+
+    for repo in &report.repos {
+        out.push_str(&format!("\n## {}\n\n", repo.repo_name));
+        out.push_str(&format!(
+            "{} worktree(s) touched, {} session(s), {} active, {} tokens ({} in / {} out, about {})\n",
+            repo.worktrees.len() + repo.deleted.len(),
+            repo.sessions,
+            format_active_time(Duration::from_secs(repo.active_seconds)),
+            format_tokens(repo.tokens.total()),
+            format_tokens(repo.tokens.input_tokens),
+            format_tokens(repo.tokens.output_tokens),
+            format_dollars(repo.tokens.cost_usd)
+        ));
+
+        if !repo.worktrees.is_empty() {
+            out.push_str("\n| Worktree | Branch | Sessions | Active | Tokens |\n");
+            out.push_str("| --- | --- | ---: | ---: | ---: |\n");
+            for worktree in &repo.worktrees {
+                let name = if worktree.created {
+                    format!("{} (new)", worktree.name)
+                } else {
+                    worktree.name.clone()
+                };
+                out.push_str(&format!(
+                    "| {} | `{}` | {} | {} | {} |\n",
+                    table_cell(&name),
+                    table_cell(&worktree.branch),
+                    worktree.claude_sessions + worktree.codex_sessions,
+                    format_active_time(Duration::from_secs(worktree.active_seconds)),
+                    format_tokens(worktree.tokens.total())
+                ));
+            }
+        }
+
+        if !repo.deleted.is_empty() {
+            out.push_str("\nBranches merged/deleted:\n\n");
+            for deleted in &repo.deleted {
+                out.push_str(&format!(
+                    "- `{}` ({}): {}\n",
+                    deleted.branch,
+                    deleted.name,
+                    branch_outcome(deleted)
+                ));
+            }
+        }
+    }
+    out
+}
+
+fn branch_outcome(deleted: &DeletedWorktree) -> &'static str {
+    match (deleted.merged, deleted.branch_deleted) {
+        (true, true) => "merged, branch deleted",
+        (true, false) => "merged, branch kept",
+        (false, true) => "branch deleted without merging",
+        (false, false) => "worktree removed, branch kept",
+    }
+}
+
+/// Pipes would end a Markdown table cell early.
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_repos_as_markdown() {
+        let since = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let report = Report {
+            since: Some(since),
+            until: since + chrono::Duration::days(7),
+            repos: vec![RepoReport {
+                repo_name: "api".into(),
+                worktrees: vec![WorktreeActivity {
+                    name: "auth".into(),
+                    branch: "feat|auth".into(),
+                    created: true,
+                    claude_sessions: 2,
+                    codex_sessions: 1,
+                    tokens: TokenUsage {
+                        input_tokens: 1_500,
+                        output_tokens: 500,
+                        cost_usd: 0.012,
+                    },
+                    active_seconds: 5_400,
+                }],
+                deleted: vec![DeletedWorktree {
+                    deleted_at: since,
+                    repo_name: "api".into(),
+                    name: "old".into(),
+                    branch: "old".into(),
+                    merged: true,
+                    branch_deleted: true,
+                }],
+                sessions: 3,
+                tokens: TokenUsage {
+                    input_tokens: 1_500,
+                    output_tokens: 500,
+                    cost_usd: 0.012,
+                },
+                active_seconds: 5_400,
+            }],
+        };
+        let markdown = render_markdown(&report);
+        assert!(markdown.contains("## api\n"));
+        assert!(
+            markdown.contains("2 worktree(s) touched, 3 session(s), 1h 30m active, 2.0k tokens")
+        );
+        assert!(markdown.contains("| auth (new) | `feat\\|auth` | 3 | 1h 30m | 2.0k |"));
+        assert!(markdown.contains("- `old` (old): merged, branch deleted"));
+
+        let empty = Report {
+            repos: Vec::new(),
+            ..report
+        };
+        assert!(render_markdown(&empty).contains("No activity recorded"));
+    }
+}
@@ -0,0 +1,317 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::bail_with;
+use crate::dry_run;
+use crate::error::{ErrorCategory, WithCategory};
+use crate::git::{self, execute_git};
+use crate::input::{smart_choice_with_display, smart_confirm_destructive};
+use crate::interaction::ChoiceDisplay;
+use crate::state::{DirtyWorktreePolicy, XlaudeState};
+
+// Keys of the per-file prompt, with what they do
+const ACTIONS: &[(&str, &str)] = &[
+    ("a", "Accept: stage the file"),
+    ("r", "Revert: restore the file's base version"),
+    ("s", "Skip: leave the file as it is"),
+    ("v", "View the diff again"),
+    ("q", "Quit the review"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileStatus {
+    Added,
+    Modified,
+    Deleted,
+    // New and not yet known to git
+    Untracked,
+}
+
+impl FileStatus {
+    fn label(self) -> colored::ColoredString {
+        match self {
+            FileStatus::Added => "added".green(),
+            FileStatus::Modified => "modified".yellow(),
+            FileStatus::Deleted => "deleted".red(),
+            FileStatus::Untracked => "untracked".green(),
+        }
+    }
+}
+
+struct ChangedFile {
+    path: String,
+    status: FileStatus,
+}
+
+/// Step through the worktree's changes against its base branch one file at a
+/// time, showing each diff in a pager and asking whether to accept (stage) or
+/// revert it.
+pub fn handle_review(name: String) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let (_, info) = state
+        .find_worktree(&name)
+        .with_category(ErrorCategory::NotFound, || {
+            format!("Worktree '{name}' not found")
+        })?;
+    if !info.path.exists() {
+        bail_with!(
+            ErrorCategory::NotFound,
+            "Directory of worktree '{}' is missing: {}",
+            info.name,
+            info.path.display()
+        );
+    }
+
+    let repo_config = state.repo_config(&info.repo_name);
+    let base = git::base_ref(&info.path, &info.branch, repo_config.base_remote());
+    // Committed and uncommitted work alike
+    let from = base
+        .as_deref()
+        .and_then(|base| git::merge_base(&info.path, base))
+        .unwrap_or_else(|| "HEAD".to_string());
+    let files = changed_files(&info.path, &from)?;
+    let against = base.as_deref().unwrap_or("HEAD");
+    if files.is_empty() {
+        println!(
+            "{} No changes to review in '{}' against {}",
+            "✨".green(),
+            info.name.cyan(),
+            against
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} Reviewing {} file(s) in '{}' against {}",
+        "🔍".cyan(),
+        files.len(),
+        info.name.cyan(),
+        against
+    );
+    let keys: Vec<&str> = ACTIONS.iter().map(|(key, _)| *key).collect();
+    let print_actions = || {
+        for (key, description) in ACTIONS {
+            println!("  [{}] {}", key.cyan(), description);
+        }
+    };
+    let display = ChoiceDisplay {
+        label: &|key| {
+            let (key, description) = ACTIONS
+                .iter()
+                .find(|(action, _)| *action == key)
+                .expect("invalid review action");
+            format!("[{key}] {description}")
+        },
+        print: &print_actions,
+    };
+
+    // Reverted changes are kept in a stash instead of being lost
+    let stash_reverts = state.dirty_worktree == Some(DirtyWorktreePolicy::Stash);
+    let (mut accepted, mut reverted, mut skipped) = (0, 0, 0);
+    'files: for (index, file) in files.iter().enumerate() {
+        println!();
+        println!(
+            "{} {} ({})",
+            format!("[{}/{}]", index + 1, files.len()).bright_black(),
+            file.path.bold(),
+            file.status.label()
+        );
+        show_diff(&info.path, &from, file, state.review_pager.as_deref())?;
+        loop {
+            let action = smart_choice_with_display(
+                "review_file",
+                &format!(
+                    "{} ",
+                    "Accept, revert, skip, view, or quit? [a/r/s/v/q]".bright_black()
+                ),
+                &keys,
+                "s",
+                Some(&display),
+                |key| key.to_string(),
+            )?;
+            match action.as_str() {
+                "a" => {
+                    execute_git(&[
+                        "-C",
+                        &info.path.display().to_string(),
+                        "add",
+                        "--",
+                        &file.path,
+                    ])?;
+                    accepted += 1;
+                }
+                "r" if stash_reverts => {
+                    revert(&info.path, &from, file, true)?;
+                    reverted += 1;
+                }
+                "r" => {
+                    if smart_confirm_destructive(
+                        "confirm_revert",
+                        &format!(
+                            "Revert '{}' to its version on {against}? Its changes will be lost.",
+                            file.path
+                        ),
+                        false,
+                    )? {
+                        revert(&info.path, &from, file, false)?;
+                        reverted += 1;
+                    } else {
+                        skipped += 1;
+                    }
+                }
+                "v" => {
+                    show_diff(&info.path, &from, file, state.review_pager.as_deref())?;
+                    continue;
+                }
+                "q" => break 'files,
+                _ => skipped += 1,
+            }
+            break;
+        }
+    }
+
+    println!();
+    println!(
+        "{} Reviewed '{}': {} accepted, {} reverted, {} skipped",
+        "✅".green(),
+        info.name.cyan(),
+        accepted,
+        reverted,
+        skipped
+    );
+    if accepted + reverted > 0 {
+        println!(
+            "  {} {}",
+            "›".bright_black(),
+            format!(
+                "Accepted files are staged and reverts are uncommitted; run 'xlaude commit {}' to record them",
+                info.name
+            )
+            .bright_black()
+        );
+    }
+    Ok(())
+}
+
+/// Files that differ from `from`, tracked ones first, sorted by path.
+fn changed_files(worktree_path: &Path, from: &str) -> Result<Vec<ChangedFile>> {
+    let path = worktree_path.display().to_string();
+    let diff = execute_git(&[
+        "-C",
+        &path,
+        "-c",
+        "core.quotePath=false",
+        "diff",
+        "--name-status",
+        "--no-renames",
+        from,
+    ])?;
+    let mut files: Vec<ChangedFile> = diff
+        .lines()
+        .filter_map(|line| {
+            let (status, file) = line.split_once('\t')?;
+            let status = match status.chars().next()? {
+                'A' => FileStatus::Added,
+                'D' => FileStatus::Deleted,
+                _ => FileStatus::Modified,
+            };
+            Some(ChangedFile {
+                path: file.to_string(),
+                status,
+            })
+        })
+        .collect();
+    let untracked = execute_git(&[
+        "-C",
+        &path,
+        "-c",
+        "core.quotePath=false",
+        "ls-files",
+        "--others",
+        "--exclude-standard",
+    ])?;
+    files.extend(untracked.lines().map(|file| ChangedFile {
+        path: file.to_string(),
+        status: FileStatus::Untracked,
+    }));
+    Ok(files)
+}
+
+/// Show one file's diff through `pager`, or through git's configured pager
+/// (`core.pager`, e.g. delta) when none is set. Git pages only in a terminal.
+fn show_diff(
+    worktree_path: &Path,
+    from: &str,
+    file: &ChangedFile,
+    pager: Option<&str>,
+) -> Result<()> {
+    let mut command = Command::new("git");
+    command.current_dir(worktree_path);
+    if let Some(pager) = pager {
+        command.args(["-c", &format!("core.pager={pager}")]);
+    }
+    command.args(["--paginate", "diff"]);
+    match file.status {
+        // Untracked files have no index entry to diff against
+        FileStatus::Untracked => command.args(["--no-index", "--", "/dev/null", &file.path]),
+        _ => command.args([from, "--", &file.path]),
+    };
+    // `diff --no-index` exits with 1 when the files differ, which they do
+    command.status().context("Failed to execute git command")?;
+    Ok(())
+}
+
+/// Bring a file back to its state at `from`: restore it, or remove it when it
+/// did not exist there. With `stash`, its uncommitted changes are stashed
+/// first so they can be recovered.
+fn revert(worktree_path: &Path, from: &str, file: &ChangedFile, stash: bool) -> Result<()> {
+    let path = worktree_path.display().to_string();
+    if stash && !execute_git(&["-C", &path, "status", "--porcelain", "--", &file.path])?.is_empty()
+    {
+        git::stash_changes(
+            worktree_path,
+            &format!("xlaude review: revert {}", file.path),
+            Some(&file.path),
+        )?;
+        println!(
+            "  {} {}",
+            "›".bright_black(),
+            format!(
+                "Stashed the uncommitted changes of '{}'; 'git stash pop' brings them back",
+                file.path
+            )
+            .bright_black()
+        );
+    }
+    match file.status {
+        FileStatus::Modified | FileStatus::Deleted => {
+            execute_git(&["-C", &path, "checkout", from, "--", &file.path])?;
+        }
+        FileStatus::Added | FileStatus::Untracked => {
+            if file.status == FileStatus::Added {
+                execute_git(&[
+                    "-C",
+                    &path,
+                    "rm",
+                    "--cached",
+                    "--quiet",
+                    "-f",
+                    "--ignore-unmatch",
+                    "--",
+                    &file.path,
+                ])?;
+            }
+            let target = worktree_path.join(&file.path);
+            if dry_run::is_enabled() {
+                dry_run::announce(&format!("remove {}", target.display()));
+            } else if target.exists() {
+                fs::remove_file(&target)
+                    .with_context(|| format!("Failed to remove {}", target.display()))?;
+            }
+        }
+    }
+    Ok(())
+}
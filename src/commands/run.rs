@@ -0,0 +1,775 @@
+use anyhow::{Context, Result};
+use atty::Stream;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use crossterm::{cursor, execute, terminal};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{HashSet, VecDeque};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::agent::{Launch, apply_model, guard_permissions, headless_command};
+use crate::env_snapshot::{self, EnvSnapshot};
+use crate::error::{ErrorCategory, WithCategory, categorized, with_exit_code};
+use crate::hooks::{self, Event, EventKind};
+use crate::processes::{self, RestartPolicy};
+use crate::state::{WorktreeInfo, XlaudeState, get_config_dir};
+use crate::tasks::{QueueLock, Task, TaskQueue, TaskStatus, get_task_log_path};
+use crate::utils::{missing_agent_error, resolve_available_agent_command};
+
+pub fn handle_run(
+    worktree: Option<String>,
+    next: bool,
+    parallel: usize,
+    report: Option<PathBuf>,
+    restart: Option<String>,
+    model: Option<String>,
+) -> Result<()> {
+    let launch = AgentLaunch {
+        restart: restart
+            .as_deref()
+            .map(RestartPolicy::parse)
+            .transpose()?
+            .unwrap_or_default(),
+        model: model.as_deref(),
+    };
+    let state = XlaudeState::load()?;
+    let key = match worktree {
+        Some(name) => {
+            let (key, _) = state
+                .find_worktree(&name)
+                .with_category(ErrorCategory::NotFound, || {
+                    format!("Worktree '{name}' not found")
+                })?;
+            Some(key.clone())
+        }
+        None => None,
+    };
+
+    let started_at = Utc::now();
+    let finished = if parallel > 1 {
+        run_parallel(&state, key.as_deref(), parallel, launch)?
+    } else {
+        run_sequential(&state, key.as_deref(), next, launch)?
+    };
+
+    if finished.is_empty() {
+        println!("{} No pending tasks", "📭".yellow());
+        return Ok(());
+    }
+
+    let failed = finished
+        .iter()
+        .filter(|task| task.status != TaskStatus::Succeeded)
+        .count();
+
+    let report_path = match report {
+        Some(path) => path,
+        None => get_config_dir()?
+            .join("logs")
+            .join(format!("run-{}.json", started_at.format("%Y%m%dT%H%M%SZ"))),
+    };
+    write_report(
+        &report_path,
+        &RunReport {
+            started_at,
+            finished_at: Utc::now(),
+            parallel: parallel.max(1),
+            succeeded: finished.len() - failed,
+            failed,
+            tasks: finished.iter().collect(),
+        },
+    )?;
+    println!("{} Report: {}", "📝".cyan(), report_path.display());
+
+    if let Some(signal) = processes::received_signal() {
+        return Err(processes::interrupted_error(signal));
+    }
+
+    if failed > 0 {
+        let message = format!("{} of {} task(s) failed", failed, finished.len());
+        // Exit like the first failed agent did, so wrappers can tell crashes from interrupts
+        let agent_exit = finished
+            .iter()
+            .filter(|task| task.status != TaskStatus::Succeeded)
+            .find_map(|task| {
+                task.exit_code
+                    .or_else(|| task.signal.as_deref().and_then(processes::signal_exit_code))
+            });
+        return Err(match agent_exit {
+            Some(code) => with_exit_code(ErrorCategory::AgentFailure, code, message),
+            None => categorized(ErrorCategory::AgentFailure, message),
+        });
+    }
+
+    Ok(())
+}
+
+fn run_sequential(
+    state: &XlaudeState,
+    key: Option<&str>,
+    next: bool,
+    launch: AgentLaunch<'_>,
+) -> Result<Vec<Task>> {
+    let mut finished = Vec::new();
+
+    loop {
+        // Claim one at a time so tasks queued while we run are picked up
+        let claimed = claim_tasks(|queue| {
+            queue
+                .next_pending(key)
+                .map(|task| task.id)
+                .into_iter()
+                .collect()
+        })?;
+        let Some(task) = claimed.into_iter().next() else {
+            break;
+        };
+
+        let log_path = start_task(task.id)?;
+        println!(
+            "{} Task #{} in {}: {}",
+            "▶".cyan(),
+            task.id,
+            task.worktree.cyan(),
+            task.instruction
+        );
+
+        let result = run_queued_task(state, &task, &log_path, launch);
+        let entry = finish_task(task.id, result)?;
+
+        if entry.status == TaskStatus::Succeeded {
+            println!("{} Task #{} succeeded", "✅".green(), entry.id);
+        } else {
+            match (entry.exit_code, &entry.signal) {
+                (Some(code), _) => println!(
+                    "{} Task #{} failed (exit code {})",
+                    "❌".red(),
+                    entry.id,
+                    code
+                ),
+                (None, Some(signal)) => println!(
+                    "{} Task #{} failed (terminated by {})",
+                    "❌".red(),
+                    entry.id,
+                    signal
+                ),
+                (None, None) => println!("{} Task #{} failed", "❌".red(), entry.id),
+            }
+        }
+        if let Some(line) = &entry.last_line {
+            println!("  {} {}", "Last output:".bright_black(), line);
+        }
+        println!("  {} {}", "Log:".bright_black(), log_path.display());
+
+        finished.push(entry);
+        if next || processes::received_signal().is_some() {
+            break;
+        }
+    }
+
+    Ok(finished)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RowStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl RowStatus {
+    fn label(self) -> &'static str {
+        match self {
+            RowStatus::Queued => "queued",
+            RowStatus::Running => "running",
+            RowStatus::Succeeded => "succeeded",
+            RowStatus::Failed => "failed",
+        }
+    }
+}
+
+struct Row {
+    task: Task,
+    status: RowStatus,
+    started: Option<Instant>,
+    elapsed: Duration,
+    log_path: Option<PathBuf>,
+    last_line: Option<String>,
+}
+
+#[derive(Default)]
+struct Scheduler {
+    pending: VecDeque<usize>,
+    busy: HashSet<String>,
+}
+
+impl Scheduler {
+    // Take the first queued row whose worktree is idle, so each worktree runs one task at a time
+    fn take(&mut self, rows: &[Row]) -> Option<usize> {
+        let pos = self
+            .pending
+            .iter()
+            .position(|&idx| !self.busy.contains(&rows[idx].task.worktree))?;
+        let idx = self.pending.remove(pos)?;
+        self.busy.insert(rows[idx].task.worktree.clone());
+        Some(idx)
+    }
+}
+
+fn run_parallel(
+    state: &XlaudeState,
+    key: Option<&str>,
+    parallel: usize,
+    launch: AgentLaunch<'_>,
+) -> Result<Vec<Task>> {
+    let claimed = claim_tasks(|queue| {
+        queue
+            .tasks
+            .iter()
+            .filter(|task| {
+                task.status == TaskStatus::Pending && key.is_none_or(|k| task.worktree == k)
+            })
+            .map(|task| task.id)
+            .collect()
+    })?;
+    let rows: Vec<Row> = claimed
+        .into_iter()
+        .map(|task| Row {
+            task,
+            status: RowStatus::Queued,
+            started: None,
+            elapsed: Duration::ZERO,
+            log_path: None,
+            last_line: None,
+        })
+        .collect();
+
+    if rows.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let scheduler = Mutex::new(Scheduler {
+        pending: (0..rows.len()).collect(),
+        busy: HashSet::new(),
+    });
+    let rows = Mutex::new(rows);
+    let finished = Mutex::new(Vec::new());
+    let live = atty::is(Stream::Stdout);
+
+    let ran = thread::scope(|scope| -> Result<()> {
+        let workers: Vec<_> = (0..parallel)
+            .map(|_| scope.spawn(|| run_worker(state, &scheduler, &rows, &finished, live, launch)))
+            .collect();
+
+        let mut drawn = 0u16;
+        while !workers.iter().all(|w| w.is_finished()) {
+            if live {
+                drawn = draw_table(&mut rows.lock().unwrap(), drawn)?;
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+        if live {
+            draw_table(&mut rows.lock().unwrap(), drawn)?;
+        }
+
+        for worker in workers {
+            worker
+                .join()
+                .map_err(|_| anyhow::anyhow!("Task worker panicked"))??;
+        }
+        Ok(())
+    });
+    // Tasks never started, after a signal or an error, go back to the queue
+    let unstarted: Vec<u64> = rows
+        .into_inner()
+        .unwrap()
+        .iter()
+        .filter(|row| row.status == RowStatus::Queued)
+        .map(|row| row.task.id)
+        .collect();
+    release_tasks(&unstarted)?;
+    ran?;
+
+    let mut finished = finished.into_inner().unwrap();
+    finished.sort_by_key(|task| task.id);
+    Ok(finished)
+}
+
+fn run_worker(
+    state: &XlaudeState,
+    scheduler: &Mutex<Scheduler>,
+    rows: &Mutex<Vec<Row>>,
+    finished: &Mutex<Vec<Task>>,
+    live: bool,
+    launch: AgentLaunch<'_>,
+) -> Result<()> {
+    loop {
+        // Leave the remaining tasks queued once xlaude is asked to stop
+        if processes::received_signal().is_some() {
+            return Ok(());
+        }
+        let picked = {
+            let rows = rows.lock().unwrap();
+            let mut scheduler = scheduler.lock().unwrap();
+            if scheduler.pending.is_empty() {
+                return Ok(());
+            }
+            scheduler.take(&rows)
+        };
+        let Some(idx) = picked else {
+            thread::sleep(Duration::from_millis(100));
+            continue;
+        };
+
+        let task = rows.lock().unwrap()[idx].task.clone();
+        let result = run_row(state, rows, idx, &task, live, launch);
+        // Free the worktree even on errors so other workers do not wait on it forever
+        scheduler.lock().unwrap().busy.remove(&task.worktree);
+        finished.lock().unwrap().push(result?);
+    }
+}
+
+fn run_row(
+    state: &XlaudeState,
+    rows: &Mutex<Vec<Row>>,
+    idx: usize,
+    task: &Task,
+    live: bool,
+    launch: AgentLaunch<'_>,
+) -> Result<Task> {
+    let log_path = start_task(task.id)?;
+    {
+        let mut rows = rows.lock().unwrap();
+        rows[idx].status = RowStatus::Running;
+        rows[idx].started = Some(Instant::now());
+        rows[idx].log_path = Some(log_path.clone());
+    }
+    if !live {
+        println!(
+            "{} Task #{} started in {}",
+            "▶".cyan(),
+            task.id,
+            task.worktree
+        );
+    }
+
+    let result = run_queued_task(state, task, &log_path, launch);
+    let entry = finish_task(task.id, result)?;
+    let succeeded = entry.status == TaskStatus::Succeeded;
+
+    {
+        let mut rows = rows.lock().unwrap();
+        let row = &mut rows[idx];
+        row.status = if succeeded {
+            RowStatus::Succeeded
+        } else {
+            RowStatus::Failed
+        };
+        row.elapsed = row.started.map(|s| s.elapsed()).unwrap_or_default();
+        row.last_line = entry.last_line.clone();
+    }
+    if !live {
+        let icon = if succeeded {
+            "✅".green()
+        } else {
+            "❌".red()
+        };
+        println!("{} Task #{} {}", icon, entry.id, entry.status.label());
+    }
+
+    Ok(entry)
+}
+
+// Redraw the status table in place, returning the number of lines printed
+fn draw_table(rows: &mut [Row], previous_lines: u16) -> Result<u16> {
+    let mut stdout = std::io::stdout();
+    if previous_lines > 0 {
+        execute!(
+            stdout,
+            cursor::MoveUp(previous_lines),
+            terminal::Clear(terminal::ClearType::FromCursorDown)
+        )?;
+    }
+
+    let width = terminal::size()
+        .ok()
+        .filter(|(w, _)| *w > 0)
+        .map_or(100, |(w, _)| w as usize);
+    let worktree_width = rows
+        .iter()
+        .map(|row| row.task.worktree.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("WORKTREE".len());
+
+    println!(
+        "{}",
+        format!(
+            "{:<6} {:<worktree_width$} {:<10} {:>8}  LAST OUTPUT",
+            "TASK", "WORKTREE", "STATUS", "ELAPSED"
+        )
+        .bold()
+    );
+
+    let fixed = 6 + 1 + worktree_width + 1 + 10 + 1 + 8 + 2;
+    for row in rows.iter_mut() {
+        if row.status == RowStatus::Running {
+            if let Some(started) = row.started {
+                row.elapsed = started.elapsed();
+            }
+            row.last_line = row.log_path.as_deref().and_then(last_log_line);
+        }
+
+        let label = format!("{:<10}", row.status.label());
+        let status = match row.status {
+            RowStatus::Queued => label.bright_black(),
+            RowStatus::Running => label.cyan(),
+            RowStatus::Succeeded => label.green(),
+            RowStatus::Failed => label.red(),
+        };
+        let elapsed = if row.started.is_some() {
+            format_elapsed(row.elapsed)
+        } else {
+            "-".to_string()
+        };
+        let last_line: String = row
+            .last_line
+            .as_deref()
+            .unwrap_or("")
+            .chars()
+            .take(width.saturating_sub(fixed).max(20))
+            .collect();
+
+        println!(
+            "{:<6} {:<worktree_width$} {} {:>8}  {}",
+            format!("#{}", row.task.id),
+            row.task.worktree,
+            status,
+            elapsed,
+            last_line.bright_black()
+        );
+    }
+    stdout.flush()?;
+
+    Ok(rows.len() as u16 + 1)
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 60 {
+        format!("{secs}s")
+    } else if secs < 3600 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+#[derive(Serialize)]
+struct RunReport<'a> {
+    started_at: DateTime<Utc>,
+    finished_at: DateTime<Utc>,
+    parallel: usize,
+    succeeded: usize,
+    failed: usize,
+    tasks: Vec<&'a Task>,
+}
+
+fn write_report(path: &Path, report: &RunReport<'_>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create report directory")?;
+    }
+    let content = serde_json::to_string_pretty(report).context("Failed to serialize report")?;
+    fs::write(path, content).context("Failed to write run report")?;
+    Ok(())
+}
+
+/// Mark the still pending tasks among those `pick` selects as running, so no
+/// other `xlaude run` starts them too. Selecting and claiming happen under one
+/// hold of the queue lock.
+fn claim_tasks(pick: impl FnOnce(&TaskQueue) -> Vec<u64>) -> Result<Vec<Task>> {
+    let _lock = QueueLock::acquire()?;
+    let mut queue = TaskQueue::load()?;
+    let mut claimed = Vec::new();
+    for id in pick(&queue) {
+        let Some(entry) = queue.get_mut(id) else {
+            continue;
+        };
+        if entry.status != TaskStatus::Pending {
+            continue;
+        }
+        entry.status = TaskStatus::Running;
+        entry.log_path = Some(get_task_log_path(id)?);
+        claimed.push(entry.clone());
+    }
+    if !claimed.is_empty() {
+        queue.save()?;
+    }
+    Ok(claimed)
+}
+
+/// Put claimed tasks that were never started back in the queue.
+fn release_tasks(ids: &[u64]) -> Result<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let _lock = QueueLock::acquire()?;
+    let mut queue = TaskQueue::load()?;
+    for id in ids {
+        if let Some(entry) = queue.get_mut(*id)
+            && entry.status == TaskStatus::Running
+            && entry.started_at.is_none()
+        {
+            entry.status = TaskStatus::Pending;
+            entry.log_path = None;
+        }
+    }
+    queue.save()
+}
+
+/// Record that a claimed task's agent is starting, returning its log path.
+fn start_task(id: u64) -> Result<PathBuf> {
+    let _lock = QueueLock::acquire()?;
+    let mut queue = TaskQueue::load()?;
+    let entry = queue
+        .get_mut(id)
+        .with_context(|| format!("Task #{id} disappeared from the queue"))?;
+    entry.started_at = Some(Utc::now());
+    let log_path = match &entry.log_path {
+        Some(path) => path.clone(),
+        None => get_task_log_path(id)?,
+    };
+    entry.log_path = Some(log_path.clone());
+    queue.save()?;
+    Ok(log_path)
+}
+
+fn finish_task(id: u64, result: Result<TaskOutcome>) -> Result<Task> {
+    let _lock = QueueLock::acquire()?;
+    let mut queue = TaskQueue::load()?;
+    let entry = queue
+        .get_mut(id)
+        .with_context(|| format!("Task #{id} disappeared from the queue"))?;
+    entry.finished_at = Some(Utc::now());
+    match result {
+        Ok(outcome) => {
+            entry.status = if outcome.success {
+                TaskStatus::Succeeded
+            } else {
+                TaskStatus::Failed
+            };
+            entry.exit_code = outcome.exit_code;
+            entry.signal = outcome.signal;
+            entry.last_line = outcome.last_line;
+            entry.attempts = Some(outcome.attempts);
+        }
+        Err(err) => {
+            entry.status = TaskStatus::Failed;
+            entry.last_line = Some(err.to_string());
+        }
+    }
+    let entry = entry.clone();
+    queue.save()?;
+    Ok(entry)
+}
+
+fn run_queued_task(
+    state: &XlaudeState,
+    task: &Task,
+    log_path: &Path,
+    launch: AgentLaunch<'_>,
+) -> Result<TaskOutcome> {
+    let Some(info) = state.worktrees.get(&task.worktree) else {
+        return Err(missing_worktree(task));
+    };
+    let mut details = json!({ "task_id": task.id, "instruction": task.instruction });
+    hooks::emit(
+        state,
+        Event::new(EventKind::TaskStarted, info).details(details.clone()),
+    );
+    let outcome = run_task(&task.instruction, info, log_path, launch);
+    match &outcome {
+        Ok(result) => {
+            details["success"] = json!(result.success);
+            details["exit_code"] = json!(result.exit_code);
+            details["signal"] = json!(result.signal);
+            details["attempts"] = json!(result.attempts);
+        }
+        Err(err) => {
+            details["success"] = json!(false);
+            details["error"] = json!(format!("{err:#}"));
+        }
+    }
+    hooks::emit(
+        state,
+        Event::new(EventKind::TaskFinished, info).details(details),
+    );
+    outcome
+}
+
+fn missing_worktree(task: &Task) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Worktree '{}' is no longer managed by xlaude",
+        task.worktree
+    )
+}
+
+/// How the agent of every task is launched.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct AgentLaunch<'a> {
+    pub restart: RestartPolicy,
+    // Model to ask the agent for, from `run --model`
+    pub model: Option<&'a str>,
+}
+
+pub(crate) struct TaskOutcome {
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub signal: Option<String>,
+    pub last_line: Option<String>,
+    // Number of times the agent was launched, restarts included
+    pub attempts: u32,
+}
+
+/// Run one instruction through the configured agent in headless mode, capturing
+/// stdout and stderr into `log_path`. Failed runs are relaunched with backoff
+/// as long as its restart policy allows it.
+pub(crate) fn run_task(
+    instruction: &str,
+    worktree: &WorktreeInfo,
+    log_path: &Path,
+    launch: AgentLaunch<'_>,
+) -> Result<TaskOutcome> {
+    if let Some(parent) = log_path.parent() {
+        fs::create_dir_all(parent).context("Failed to create log directory")?;
+    }
+    let mut log = File::create(log_path).context("Failed to create task log")?;
+
+    let mut attempt = 1;
+    loop {
+        // The first attempt's environment is kept for `xlaude env apply`
+        let snapshot = (attempt == 1).then(|| env_snapshot::path_for_log(log_path));
+        let exit = run_attempt(
+            instruction,
+            worktree,
+            launch.model,
+            &log,
+            attempt,
+            snapshot.as_deref(),
+        )?;
+        let next = attempt + 1;
+        // Agents stopped with `xlaude kill` or along with xlaude stay stopped
+        if exit.status.success()
+            || exit.stopped
+            || exit.interrupted.is_some()
+            || !launch.restart.allows(next)
+        {
+            return Ok(TaskOutcome {
+                success: exit.status.success(),
+                exit_code: exit.status.code(),
+                signal: processes::exit_signal(&exit.status),
+                last_line: last_log_line(log_path),
+                attempts: attempt,
+            });
+        }
+
+        let delay = RestartPolicy::backoff(next);
+        let reason = match exit.status.code() {
+            Some(code) => format!("exit code {code}"),
+            None => exit.status.to_string(),
+        };
+        writeln!(
+            log,
+            "[xlaude] attempt {attempt} failed ({reason}); restarting in {}s",
+            delay.as_secs()
+        )
+        .context("Failed to write task log")?;
+        thread::sleep(delay);
+        attempt = next;
+    }
+}
+
+fn run_attempt(
+    instruction: &str,
+    worktree: &WorktreeInfo,
+    model: Option<&str>,
+    log: &File,
+    attempt: u32,
+    snapshot: Option<&Path>,
+) -> Result<processes::AgentExit> {
+    let (program, mut args) = resolve_available_agent_command(worktree)?;
+    guard_permissions(
+        &XlaudeState::load()?,
+        &worktree.repo_name,
+        &program,
+        &mut args,
+        Launch::Headless,
+    )?;
+    let model_envs = match model {
+        Some(model) => apply_model(&program, &mut args, model),
+        None => Vec::new(),
+    };
+    let headless = headless_command(program, args, instruction);
+    if let Some(path) = snapshot {
+        let command = std::iter::once(headless.program.clone())
+            .chain(headless.args.iter().cloned())
+            .collect();
+        let mut captured =
+            EnvSnapshot::capture(Some(worktree), &worktree.path, command, &model_envs);
+        captured.stdin = headless.stdin.clone();
+        if let Err(err) = captured.save(path) {
+            let mut log = log;
+            let _ = writeln!(log, "[xlaude] could not save the environment: {err:#}");
+        }
+    }
+
+    let mut cmd = Command::new(&headless.program);
+    // Own process group, so a signal forwarded by xlaude reaches the agent's children too
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    cmd.args(&headless.args)
+        .envs(model_envs)
+        .current_dir(&worktree.path)
+        .stdout(Stdio::from(log.try_clone()?))
+        .stderr(Stdio::from(log.try_clone()?))
+        .stdin(if headless.stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        });
+
+    let mut child = match cmd.spawn() {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(missing_agent_error(&headless.program));
+        }
+        spawned => spawned.with_category(ErrorCategory::AgentFailure, || {
+            format!("Failed to launch agent '{}'", headless.program)
+        })?,
+    };
+
+    if let (Some(input), Some(mut stdin)) = (headless.stdin, child.stdin.take()) {
+        // The agent may exit without reading its input; that is not our error
+        let _ = stdin.write_all(input.as_bytes());
+    }
+
+    let key = XlaudeState::make_key(&worktree.repo_name, &worktree.name);
+    processes::track(child, key, &headless.program, attempt).context("Failed to wait for agent")
+}
+
+pub(crate) fn last_log_line(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    content
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(String::from)
+}
@@ -0,0 +1,380 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::agent::{AgentKind, PastSession};
+use crate::bail_with;
+use crate::commands::agent_launcher::resume_session;
+use crate::commands::list::{PreviewStyle, SessionPage, format_message_preview, format_time_ago};
+use crate::daemon;
+use crate::error::ErrorCategory;
+use crate::redact::Redactor;
+use crate::search::{SessionQuery, WorktreeFilter, search_sessions};
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::time_window::TimeWindow;
+use crate::transcript::{Role, Transcript, read_claude_transcript, read_codex_transcript};
+
+// Shortest id prefix `sessions list` shows, like git's abbreviated SHAs
+const MIN_ABBREV: usize = 8;
+
+/// A recorded Claude or Codex session and the worktree it was run in.
+struct RecordedSession {
+    worktree: WorktreeInfo,
+    agent: AgentKind,
+    session: PastSession,
+}
+
+impl RecordedSession {
+    fn agent_name(&self) -> &'static str {
+        match self.agent {
+            AgentKind::Codex => "codex",
+            _ => "claude",
+        }
+    }
+
+    fn transcript(&self, redactor: Option<&Redactor>) -> Result<Option<Transcript>> {
+        let mut transcript = match self.agent {
+            AgentKind::Codex => read_codex_transcript(&self.session.path),
+            _ => read_claude_transcript(&self.session.path),
+        }?;
+        if let (Some(redactor), Some(transcript)) = (redactor, transcript.as_mut()) {
+            redactor.redact_transcript(transcript);
+        }
+        Ok(transcript)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonSession<'a> {
+    id: &'a str,
+    agent: &'static str,
+    worktree: String,
+    path: String,
+    last_activity: Option<DateTime<Utc>>,
+    last_user_message: Option<String>,
+}
+
+pub fn handle_sessions_search(
+    query: String,
+    repo: Option<String>,
+    tag: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    json: bool,
+) -> Result<()> {
+    let Some(parsed) = SessionQuery::parse(&query) else {
+        bail_with!(ErrorCategory::Usage, "Search query cannot be empty");
+    };
+    let filter = WorktreeFilter {
+        repo,
+        tag,
+        window: TimeWindow::parse(since.as_deref(), until.as_deref())?,
+    };
+
+    let state = XlaudeState::load()?;
+    let hits = search_sessions(&state, &parsed, &filter)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&hits)?);
+        return Ok(());
+    }
+
+    if hits.is_empty() {
+        println!("{} No sessions match '{}'", "🔍".yellow(), query);
+        return Ok(());
+    }
+
+    println!("{} {} match(es) for '{}':", "🔍".cyan(), hits.len(), query);
+    for hit in &hits {
+        let role = match hit.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        };
+        println!(
+            "  {} {} {} {}",
+            hit.worktree.cyan(),
+            hit.provider,
+            format_time_ago(hit.timestamp).bright_black(),
+            format!("({role})").bright_black()
+        );
+        println!("    {}", hit.snippet);
+    }
+    Ok(())
+}
+
+pub fn handle_sessions_list(
+    repo: Option<String>,
+    tag: Option<String>,
+    ids: bool,
+    page: SessionPage,
+    json: bool,
+) -> Result<()> {
+    let filter = WorktreeFilter {
+        repo,
+        tag,
+        window: TimeWindow::default(),
+    };
+    let state = XlaudeState::load()?;
+    let redactor = Redactor::from_state(&state)?;
+    let all = recorded_sessions(&state, &filter)?;
+    let sessions = page.select(&all, usize::MAX);
+
+    if ids {
+        for recorded in sessions {
+            println!("{}", recorded.session.id);
+        }
+        return Ok(());
+    }
+
+    if json {
+        let entries: Vec<JsonSession> = sessions
+            .iter()
+            .map(|recorded| JsonSession {
+                id: &recorded.session.id,
+                agent: recorded.agent_name(),
+                worktree: XlaudeState::make_key(
+                    &recorded.worktree.repo_name,
+                    &recorded.worktree.name,
+                ),
+                path: recorded.session.path.display().to_string(),
+                last_activity: recorded.session.last_timestamp,
+                last_user_message: recorded
+                    .session
+                    .last_user_message
+                    .as_deref()
+                    .map(|message| redactor.redact(message)),
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    if all.is_empty() {
+        println!("{} No recorded sessions", "📭".yellow());
+        return Ok(());
+    }
+    if sessions.is_empty() {
+        println!(
+            "{} No sessions past the first {} of {}",
+            "📭".yellow(),
+            page.offset,
+            all.len()
+        );
+        return Ok(());
+    }
+
+    let preview = PreviewStyle::from_state(&state);
+    // Abbreviations stay unique across pages
+    let all_ids: Vec<&str> = all.iter().map(|r| r.session.id.as_str()).collect();
+    let width = abbreviation_len(&all_ids);
+    if sessions.len() < all.len() {
+        println!(
+            "{} Sessions {}-{} of {}:",
+            "💬".cyan(),
+            page.offset + 1,
+            page.offset + sessions.len(),
+            all.len()
+        );
+    } else {
+        println!("{} {} session(s):", "💬".cyan(), sessions.len());
+    }
+    for recorded in sessions {
+        let id: String = recorded.session.id.chars().take(width).collect();
+        println!(
+            "  {}  {:<6} {} {}",
+            id.yellow(),
+            recorded.agent_name(),
+            XlaudeState::make_key(&recorded.worktree.repo_name, &recorded.worktree.name).cyan(),
+            format_time_ago(recorded.session.last_timestamp).bright_black()
+        );
+        if let Some(message) = &recorded.session.last_user_message {
+            let message = redactor.redact(message);
+            println!("    {}", format_message_preview(&message, preview));
+        }
+    }
+    Ok(())
+}
+
+/// Print a session's messages, with secrets redacted when `redact` is set.
+pub fn handle_sessions_show(id: String, redact: bool) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let recorded = find_session(&state, &id)?;
+    let redactor = redact.then(|| Redactor::from_state(&state)).transpose()?;
+    let transcript = recorded.transcript(redactor.as_ref())?;
+
+    println!(
+        "{} {} session {} in {}",
+        "💬".cyan(),
+        recorded.agent_name(),
+        recorded.session.id.yellow(),
+        XlaudeState::make_key(&recorded.worktree.repo_name, &recorded.worktree.name).cyan()
+    );
+    let Some(transcript) = transcript else {
+        println!("  {}", "(no messages)".bright_black());
+        return Ok(());
+    };
+    for turn in &transcript.turns {
+        let role = match turn.role {
+            Role::User => "user".green(),
+            Role::Assistant => "assistant".blue(),
+        };
+        println!();
+        println!(
+            "{} {}",
+            role,
+            format_time_ago(turn.timestamp).bright_black()
+        );
+        println!("{}", turn.text);
+    }
+    Ok(())
+}
+
+/// Print a session's transcript as JSON, with secrets redacted when `redact`
+/// is set.
+pub fn handle_sessions_export(id: String, redact: bool) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let recorded = find_session(&state, &id)?;
+    let redactor = redact.then(|| Redactor::from_state(&state)).transpose()?;
+    let transcript = recorded
+        .transcript(redactor.as_ref())?
+        .unwrap_or_else(|| Transcript {
+            provider: recorded.agent_name().to_string(),
+            session_id: recorded.session.id.clone(),
+            started_at: None,
+            updated_at: recorded.session.last_timestamp,
+            turns: Vec::new(),
+        });
+    println!("{}", serde_json::to_string_pretty(&transcript)?);
+    Ok(())
+}
+
+/// Continue a session with its agent, in the worktree it was run in.
+pub fn handle_sessions_resume(id: String) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let recorded = find_session(&state, &id)?;
+    println!(
+        "{} Resuming {} session {} in '{}'",
+        "🚀".green(),
+        recorded.agent_name(),
+        recorded.session.id.yellow(),
+        recorded.worktree.name.cyan()
+    );
+    resume_session(&recorded.worktree, recorded.agent, &recorded.session.id)
+}
+
+/// Claude and Codex sessions of every managed worktree that passes `filter`,
+/// newest first.
+fn recorded_sessions(state: &XlaudeState, filter: &WorktreeFilter) -> Result<Vec<RecordedSession>> {
+    let mut sessions = Vec::new();
+    for info in state.worktrees.values().filter(|info| filter.matches(info)) {
+        let recorded = daemon::sessions_in(info, &TimeWindow::default())?;
+        let claude = recorded.claude.into_iter().map(|session| {
+            let session = PastSession {
+                id: session.id,
+                path: session.path,
+                last_timestamp: session.last_timestamp,
+                last_user_message: Some(session.last_user_message),
+            };
+            (AgentKind::Claude, session)
+        });
+        let codex = recorded.codex.into_iter().map(|session| {
+            let session = PastSession {
+                id: session.id,
+                path: session.path,
+                last_timestamp: session.last_timestamp,
+                last_user_message: session.last_user_message,
+            };
+            (AgentKind::Codex, session)
+        });
+        for (agent, session) in claude.chain(codex) {
+            sessions.push(RecordedSession {
+                worktree: info.clone(),
+                agent,
+                session,
+            });
+        }
+    }
+    sessions.sort_by_key(|recorded| std::cmp::Reverse(recorded.session.last_timestamp));
+    Ok(sessions)
+}
+
+/// The session whose id is `id` or, like git with SHAs, the only one whose id
+/// starts with it.
+fn find_session(state: &XlaudeState, id: &str) -> Result<RecordedSession> {
+    let sessions = recorded_sessions(state, &WorktreeFilter::default())?;
+    let ids: Vec<&str> = sessions.iter().map(|r| r.session.id.as_str()).collect();
+    let index = resolve_prefix(&ids, id)?;
+    Ok(sessions.into_iter().nth(index).expect("resolved index"))
+}
+
+/// Index of `prefix` in `ids`, matched exactly or as a unique prefix.
+fn resolve_prefix(ids: &[&str], prefix: &str) -> Result<usize> {
+    if prefix.is_empty() {
+        bail_with!(ErrorCategory::Usage, "Session id cannot be empty");
+    }
+    if let Some(index) = ids.iter().position(|id| *id == prefix) {
+        return Ok(index);
+    }
+    let matches: Vec<usize> = (0..ids.len())
+        .filter(|&index| ids[index].starts_with(prefix))
+        .collect();
+    match matches.as_slice() {
+        [index] => Ok(*index),
+        [] => bail_with!(
+            ErrorCategory::NotFound,
+            "No session matches '{}'. See 'xlaude sessions list'",
+            prefix
+        ),
+        _ => {
+            let mut candidates: Vec<&str> = matches.iter().map(|&index| ids[index]).collect();
+            candidates.dedup();
+            bail_with!(
+                ErrorCategory::Usage,
+                "Session id '{}' is ambiguous; it matches {}",
+                prefix,
+                candidates.join(", ")
+            )
+        }
+    }
+}
+
+/// Length of the shortest prefix, at least `MIN_ABBREV`, that tells every id
+/// apart.
+fn abbreviation_len(ids: &[&str]) -> usize {
+    let mut sorted = ids.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    sorted
+        .windows(2)
+        .map(|pair| {
+            let common = pair[0]
+                .chars()
+                .zip(pair[1].chars())
+                .take_while(|(a, b)| a == b)
+                .count();
+            common + 1
+        })
+        .fold(MIN_ABBREV, usize::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_ids_resolve_from_unique_prefixes() {
+        let ids = ["0a1b2c3d-1", "0a1b2c3d-2", "ffee0011"];
+        assert_eq!(resolve_prefix(&ids, "ff").unwrap(), 2);
+        assert_eq!(resolve_prefix(&ids, "0a1b2c3d-2").unwrap(), 1);
+
+        let ambiguous = resolve_prefix(&ids, "0a1b").unwrap_err();
+        assert!(ambiguous.to_string().contains("0a1b2c3d-1, 0a1b2c3d-2"));
+        let missing = resolve_prefix(&ids, "99").unwrap_err();
+        assert!(missing.to_string().contains("No session matches '99'"));
+
+        assert_eq!(abbreviation_len(&ids), 10);
+        assert_eq!(abbreviation_len(&["ab", "cd"]), MIN_ABBREV);
+    }
+}
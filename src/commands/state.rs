@@ -0,0 +1,162 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+
+use crate::backup::{self, Snapshot};
+use crate::bail_with;
+use crate::commands::list::format_time_ago;
+use crate::dry_run;
+use crate::error::{ErrorCategory, WithCategory};
+use crate::input::{smart_confirm, smart_select};
+use crate::state::XlaudeState;
+use crate::store::{BACKEND_ENV, Backend};
+use crate::tr;
+
+pub fn handle_state_backups() -> Result<()> {
+    let snapshots = backup::list()?;
+    if snapshots.is_empty() {
+        println!("{} No state snapshots yet", "📭".yellow());
+        return Ok(());
+    }
+    println!("{} State snapshots (newest first):", "🗄️".cyan());
+    for snapshot in &snapshots {
+        println!("  {}", describe(snapshot));
+    }
+    Ok(())
+}
+
+/// Replace the state file with a snapshot. The state being replaced is backed
+/// up like any other save, so a restore can be undone.
+pub fn handle_state_restore(id: Option<String>) -> Result<()> {
+    let snapshots = backup::list()?;
+    if snapshots.is_empty() {
+        bail_with!(ErrorCategory::NotFound, "No state snapshots found");
+    }
+    let snapshot = match id {
+        Some(id) => snapshots
+            .iter()
+            .find(|snapshot| snapshot.id == id)
+            .with_category(ErrorCategory::NotFound, || {
+                format!("State snapshot '{id}' not found. See 'xlaude state backups'")
+            })?,
+        None => match smart_select(
+            "snapshot",
+            "Select a snapshot to restore",
+            &snapshots,
+            describe,
+        )? {
+            Some(idx) => &snapshots[idx],
+            None => bail_with!(
+                ErrorCategory::Usage,
+                "Interactive selection not available in non-interactive mode. Please specify a snapshot."
+            ),
+        },
+    };
+
+    let content = fs::read_to_string(&snapshot.path)
+        .with_context(|| format!("Failed to read {}", snapshot.path.display()))?;
+    let restored: XlaudeState = serde_json::from_str(&content)
+        .with_category(ErrorCategory::Config, || {
+            format!("Snapshot '{}' is not a valid state file", snapshot.id)
+        })?;
+
+    if !smart_confirm(
+        "confirm_restore",
+        &format!(
+            "Replace the current state with snapshot '{}' ({} worktree(s))?",
+            snapshot.id,
+            restored.worktrees.len()
+        ),
+        true,
+    )? {
+        println!("{}", tr!("{} Cancelled", "❌".red()));
+        return Ok(());
+    }
+
+    restored.save()?;
+    println!(
+        "{} Restored state snapshot '{}' ({} worktree(s)); the replaced state was backed up",
+        "✅".green(),
+        snapshot.id.cyan(),
+        restored.worktrees.len()
+    );
+    Ok(())
+}
+
+/// Copy the state into another backend's store. The old store is renamed
+/// with a `.migrated` suffix rather than deleted.
+pub fn handle_state_migrate(to: Backend) -> Result<()> {
+    let from = Backend::current()?;
+    if from == to {
+        println!(
+            "{} The state is already kept in the {} backend",
+            "ℹ️".blue(),
+            to.name()
+        );
+        return Ok(());
+    }
+    let state = XlaudeState::load()?;
+    let source = from.open()?;
+    let target = to.open()?;
+    let retired = source.path().with_extension(format!(
+        "{}.migrated",
+        source
+            .path()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+    ));
+    if dry_run::is_enabled() {
+        dry_run::announce(&format!(
+            "copy {} worktree(s) to {}",
+            state.worktrees.len(),
+            target.path().display()
+        ));
+        dry_run::announce(&format!(
+            "rename {} to {}",
+            source.path().display(),
+            retired.display()
+        ));
+        return Ok(());
+    }
+
+    let document = serde_json::to_value(&state).context("Failed to serialize state")?;
+    target.save(&document, None)?;
+    if source.path().exists() {
+        fs::rename(source.path(), &retired)
+            .with_context(|| format!("Failed to rename {}", source.path().display()))?;
+    }
+    println!(
+        "{} Moved {} worktree(s) from {} to {} ({})",
+        "✅".green(),
+        state.worktrees.len(),
+        from.name(),
+        to.name().cyan(),
+        target.path().display()
+    );
+    if std::env::var(BACKEND_ENV).is_ok() {
+        println!(
+            "{} {} is set; update it to '{}' as well",
+            "⚠️".yellow(),
+            BACKEND_ENV,
+            to.name()
+        );
+    }
+    Ok(())
+}
+
+fn describe(snapshot: &Snapshot) -> String {
+    let worktrees = fs::read_to_string(&snapshot.path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|state| state["worktrees"].as_object().map(|map| map.len()));
+    format!(
+        "{}  {}  {}",
+        snapshot.id,
+        format_time_ago(Some(snapshot.taken_at)).bright_black(),
+        match worktrees {
+            Some(count) => format!("{count} worktree(s)"),
+            None => "unreadable".red().to_string(),
+        }
+    )
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::error::{ErrorCategory, WithCategory};
+use crate::state::XlaudeState;
+
+pub fn handle_tag(name: String, tags: Vec<String>, remove: bool) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let key = state
+        .find_worktree(&name)
+        .map(|(key, _)| key.clone())
+        .with_category(ErrorCategory::NotFound, || {
+            format!("Worktree '{name}' not found")
+        })?;
+    let info = state
+        .worktrees
+        .get_mut(&key)
+        .expect("worktree key was just looked up");
+
+    if !tags.is_empty() {
+        for tag in tags
+            .iter()
+            .map(|tag| tag.trim())
+            .filter(|tag| !tag.is_empty())
+        {
+            if remove {
+                info.tags.retain(|existing| existing != tag);
+            } else if !info.tags.iter().any(|existing| existing == tag) {
+                info.tags.push(tag.to_string());
+            }
+        }
+        info.tags.sort();
+    }
+
+    let summary = if info.tags.is_empty() {
+        "no tags".to_string()
+    } else {
+        info.tags.join(", ")
+    };
+    println!("{} {}: {}", "🏷️".green(), info.name.cyan(), summary);
+
+    if !tags.is_empty() {
+        state.save()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::error::{ErrorCategory, WithCategory};
+use crate::state::XlaudeState;
+use crate::tasks::{QueueLock, TaskQueue, TaskStatus};
+
+pub fn handle_task_add(worktree: String, instruction: String) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let (key, _) = state
+        .find_worktree(&worktree)
+        .with_category(ErrorCategory::NotFound, || {
+            format!("Worktree '{worktree}' not found")
+        })?;
+
+    if instruction.trim().is_empty() {
+        anyhow::bail!("Task instruction cannot be empty");
+    }
+
+    let _lock = QueueLock::acquire()?;
+    let mut queue = TaskQueue::load()?;
+    let task = queue.push(key.clone(), instruction);
+    println!(
+        "{} Queued task #{} for {}",
+        "➕".green(),
+        task.id,
+        task.worktree.cyan()
+    );
+    queue.save()?;
+
+    Ok(())
+}
+
+pub fn handle_task_list(worktree: Option<String>, json: bool) -> Result<()> {
+    let key = match worktree {
+        Some(name) => {
+            let state = XlaudeState::load()?;
+            let (key, _) = state
+                .find_worktree(&name)
+                .with_category(ErrorCategory::NotFound, || {
+                    format!("Worktree '{name}' not found")
+                })?;
+            Some(key.clone())
+        }
+        None => None,
+    };
+
+    let queue = TaskQueue::load()?;
+    let tasks: Vec<_> = queue
+        .tasks
+        .iter()
+        .filter(|task| key.as_ref().is_none_or(|k| &task.worktree == k))
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&tasks)?);
+        return Ok(());
+    }
+
+    if tasks.is_empty() {
+        println!("{} No tasks queued", "📭".yellow());
+        return Ok(());
+    }
+
+    println!("{} Tasks:", "📋".cyan());
+    println!();
+    for task in tasks {
+        let status = match task.status {
+            TaskStatus::Pending => task.status.label().yellow(),
+            TaskStatus::Running => task.status.label().blue(),
+            TaskStatus::Succeeded => task.status.label().green(),
+            TaskStatus::Failed => task.status.label().red(),
+        };
+        println!(
+            "  {} #{} [{}] {}",
+            "•".green(),
+            task.id,
+            status,
+            task.worktree.cyan()
+        );
+        println!("      {}", task.instruction);
+        if let Some(line) = &task.last_line {
+            println!("      {} {}", "Last output:".bright_black(), line);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn handle_task_remove(id: u64) -> Result<()> {
+    let _lock = QueueLock::acquire()?;
+    let mut queue = TaskQueue::load()?;
+    let task = queue
+        .tasks
+        .iter()
+        .find(|task| task.id == id)
+        .with_category(ErrorCategory::NotFound, || format!("Task #{id} not found"))?;
+
+    if task.status == TaskStatus::Running {
+        anyhow::bail!("Task #{id} is running and cannot be removed");
+    }
+
+    queue.tasks.retain(|task| task.id != id);
+    queue.save()?;
+    println!("{} Removed task #{}", "🗑️".green(), id);
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::git::get_repo_name;
+use crate::state::XlaudeState;
+
+/// Let agents launch in `repo` (the current repository if `None`) with their
+/// permission prompts turned off, as the configured agent command asks.
+pub fn handle_trust(repo: Option<String>) -> Result<()> {
+    let repo = match repo {
+        Some(repo) => repo,
+        None => get_repo_name()?,
+    };
+    set_trusted(&repo, true)?;
+    println!(
+        "{} Trusted '{}': agents may launch there without permission prompts",
+        "✅".green(),
+        repo.cyan()
+    );
+    Ok(())
+}
+
+/// Ask again before agents launch in `repo` without permission prompts.
+pub fn handle_untrust(repo: Option<String>) -> Result<()> {
+    let repo = match repo {
+        Some(repo) => repo,
+        None => get_repo_name()?,
+    };
+    set_trusted(&repo, false)?;
+    println!(
+        "{} '{}' is no longer trusted: launching an agent without permission prompts needs confirmation",
+        "🔒".green(),
+        repo.cyan()
+    );
+    Ok(())
+}
+
+fn set_trusted(repo: &str, trusted: bool) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let config = state.repos.entry(repo.to_string()).or_default();
+    if config.trusted != trusted {
+        config.trusted = trusted;
+        state.save()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::process::Command;
+use std::time::Instant;
+
+use crate::bail_with;
+use crate::dry_run;
+use crate::error::{ErrorCategory, WithCategory};
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::utils::split_command_line;
+
+// Lines of a failed command's output kept in the error
+const OUTPUT_LINES: usize = 60;
+
+/// Run the repository's `verify_commands` in a worktree.
+pub fn handle_verify(name: String) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let (_, info) = state
+        .find_worktree(&name)
+        .with_category(ErrorCategory::NotFound, || {
+            format!("Worktree '{name}' not found")
+        })?;
+    let commands = state.repo_config(&info.repo_name).verify_commands;
+    if commands.is_empty() {
+        println!(
+            "{} No verify_commands configured for repository '{}'",
+            "ℹ️".blue(),
+            info.repo_name
+        );
+        return Ok(());
+    }
+
+    run_verify_commands(info, &commands)?;
+    println!(
+        "{} All {} verify command(s) passed in '{}'",
+        "✅".green(),
+        commands.len(),
+        info.name.cyan()
+    );
+    Ok(())
+}
+
+/// Run `commands` one after another inside the worktree, stopping at the first
+/// that fails. The error carries the tail of its output, which is captured
+/// rather than streamed so that a passing gate stays quiet.
+pub fn run_verify_commands(info: &WorktreeInfo, commands: &[String]) -> Result<()> {
+    if !info.path.is_dir() {
+        bail_with!(
+            ErrorCategory::NotFound,
+            "Directory of worktree '{}' is missing: {}",
+            info.name,
+            info.path.display()
+        );
+    }
+    for command in commands {
+        if dry_run::is_enabled() {
+            dry_run::announce(&format!("verify: {command}"));
+            continue;
+        }
+        println!("  {} {}", "▶".cyan(), command);
+        let (program, args) = split_command_line(command)
+            .with_category(ErrorCategory::Config, || {
+                format!("Invalid entry in verify_commands: {command}")
+            })?;
+        let started = Instant::now();
+        let output = Command::new(&program)
+            .args(&args)
+            .current_dir(&info.path)
+            .output()
+            .with_context(|| format!("Failed to run '{program}'"))?;
+        if !output.status.success() {
+            let mut captured = String::from_utf8_lossy(&output.stdout).into_owned();
+            captured.push_str(&String::from_utf8_lossy(&output.stderr));
+            let lines: Vec<&str> = captured.lines().collect();
+            let tail = lines[lines.len().saturating_sub(OUTPUT_LINES)..].join("\n");
+            bail_with!(
+                ErrorCategory::General,
+                "Verify command '{}' failed in '{}' ({}):\n{}",
+                command,
+                info.name,
+                output.status,
+                tail
+            );
+        }
+        println!(
+            "    {} {}",
+            "✓".green(),
+            format!("passed in {:.1}s", started.elapsed().as_secs_f64()).bright_black()
+        );
+    }
+    Ok(())
+}
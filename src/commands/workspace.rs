@@ -0,0 +1,353 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+use serde::Serialize;
+use std::process::Command;
+
+use crate::agent::{Launch, guard_permissions};
+use crate::bail_with;
+use crate::claude::get_claude_sessions;
+use crate::codex;
+use crate::commands::list::format_time_ago;
+use crate::dashboard::{GitStatusSummary, summarize_git};
+use crate::dry_run;
+use crate::error::{ErrorCategory, WithCategory};
+use crate::state::{Workspace, WorktreeInfo, XlaudeState};
+use crate::utils::prepare_agent_command;
+
+pub fn handle_workspace_create(name: String, members: Vec<String>) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    if state.workspaces.contains_key(&name) {
+        bail_with!(
+            ErrorCategory::Conflict,
+            "Workspace '{}' already exists",
+            name
+        );
+    }
+
+    let mut keys: Vec<String> = Vec::new();
+    for member in &members {
+        let (key, _) = state
+            .find_worktree(member)
+            .with_category(ErrorCategory::NotFound, || {
+                format!("Worktree '{member}' not found")
+            })?;
+        if !keys.contains(key) {
+            keys.push(key.clone());
+        }
+    }
+    if keys.is_empty() {
+        anyhow::bail!("A workspace needs at least one worktree");
+    }
+
+    println!(
+        "{} Created workspace '{}' with {} worktree(s):",
+        "🧩".green(),
+        name.cyan(),
+        keys.len()
+    );
+    for key in &keys {
+        println!("  {} {}", "•".green(), key);
+    }
+
+    state.workspaces.insert(
+        name,
+        Workspace {
+            members: keys,
+            created_at: Utc::now(),
+        },
+    );
+    state.save()?;
+
+    Ok(())
+}
+
+pub fn handle_workspace_list() -> Result<()> {
+    let state = XlaudeState::load()?;
+    if state.workspaces.is_empty() {
+        println!("{} No workspaces", "📭".yellow());
+        return Ok(());
+    }
+
+    let mut names: Vec<_> = state.workspaces.keys().collect();
+    names.sort();
+    for name in names {
+        let workspace = &state.workspaces[name];
+        println!("  {} {}", "🧩".blue(), name.bold());
+        for member in &workspace.members {
+            println!("    {} {}", "•".green(), member.cyan());
+        }
+    }
+
+    Ok(())
+}
+
+pub fn handle_workspace_delete(name: String) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    if state.workspaces.remove(&name).is_none() {
+        bail_with!(ErrorCategory::NotFound, "Workspace '{}' not found", name);
+    }
+    state.save()?;
+
+    println!(
+        "{} Deleted workspace '{}' (worktrees are left untouched)",
+        "🗑️".green(),
+        name.cyan()
+    );
+    Ok(())
+}
+
+pub fn handle_workspace_open(name: String) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let members = workspace_members(&state, &name)?;
+
+    let mut windows = Vec::new();
+    for (key, info) in members {
+        match info {
+            Some(info) if info.path.exists() => windows.push(info),
+            _ => println!("{} Skipping '{}': worktree is missing", "⚠️".yellow(), key),
+        }
+    }
+    if windows.is_empty() {
+        anyhow::bail!("Workspace '{}' has no worktrees to open", name);
+    }
+
+    let tmux_available = Command::new("tmux")
+        .arg("-V")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !tmux_available {
+        anyhow::bail!("'workspace open' requires tmux to be installed");
+    }
+
+    let inside_tmux = std::env::var_os("TMUX").is_some();
+    let session = format!("xlaude-{}", name.replace(['.', ':'], "-"));
+
+    if !inside_tmux && run_tmux(&["has-session", "-t", &format!("={session}")]).is_ok() {
+        println!(
+            "{} Attaching to existing tmux session '{}'",
+            "🔗".cyan(),
+            session
+        );
+        return attach_tmux(&session);
+    }
+
+    for (index, info) in windows.iter().enumerate() {
+        let (program, mut args) = prepare_agent_command(info)?;
+        guard_permissions(
+            &state,
+            &info.repo_name,
+            &program,
+            &mut args,
+            Launch::Interactive,
+        )?;
+        let mut parts = vec![program];
+        parts.extend(args);
+        let command = shell_words::join(&parts);
+        let path = info.path.to_string_lossy();
+
+        if !inside_tmux && index == 0 {
+            run_tmux(&[
+                "new-session",
+                "-d",
+                "-s",
+                &session,
+                "-n",
+                &info.name,
+                "-c",
+                &path,
+                &command,
+            ])?;
+        } else {
+            let mut args = vec!["new-window", "-n", &info.name, "-c", &path];
+            let target = format!("{session}:");
+            if !inside_tmux {
+                args.extend(["-t", target.as_str()]);
+            }
+            args.push(&command);
+            run_tmux(&args)?;
+        }
+        println!(
+            "{} Opened {}/{} in tmux",
+            "🚀".green(),
+            info.repo_name,
+            info.name.cyan()
+        );
+    }
+
+    if inside_tmux {
+        Ok(())
+    } else {
+        attach_tmux(&session)
+    }
+}
+
+#[derive(Serialize)]
+struct MemberStatus {
+    key: String,
+    branch: Option<String>,
+    path: Option<String>,
+    git: Option<GitStatusSummary>,
+    claude_sessions: usize,
+    codex_sessions: usize,
+}
+
+pub fn handle_workspace_status(name: String, json: bool) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let members = workspace_members(&state, &name)?;
+
+    let mut statuses = Vec::new();
+    for (key, info) in members {
+        let status = match info {
+            Some(info) => MemberStatus {
+                key: key.clone(),
+                branch: Some(info.branch.clone()),
+                path: Some(info.path.display().to_string()),
+                git: Some(summarize_git(
+                    &info.path,
+                    state.repo_config(&info.repo_name).base_remote(),
+                )),
+                claude_sessions: get_claude_sessions(&info.path).len(),
+                codex_sessions: codex::recent_sessions(&info.path, 0)?.1,
+            },
+            None => MemberStatus {
+                key: key.clone(),
+                branch: None,
+                path: None,
+                git: None,
+                claude_sessions: 0,
+                codex_sessions: 0,
+            },
+        };
+        statuses.push(status);
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&statuses)?);
+        return Ok(());
+    }
+
+    println!(
+        "{} Workspace '{}' ({} worktree(s))",
+        "🧩".cyan(),
+        name.bold(),
+        statuses.len()
+    );
+    println!();
+
+    for status in &statuses {
+        let Some(git) = &status.git else {
+            println!(
+                "  {} {} {}",
+                "•".red(),
+                status.key.cyan(),
+                "(no longer managed by xlaude)".bright_black()
+            );
+            continue;
+        };
+
+        println!(
+            "  {} {} [{}]",
+            "•".green(),
+            status.key.cyan(),
+            status.branch.as_deref().unwrap_or_default()
+        );
+        println!("      {} {}", "Git:".bright_black(), describe_git(git));
+        if let Some(message) = &git.last_commit_message {
+            println!(
+                "      {} {} ({})",
+                "Last commit:".bright_black(),
+                message,
+                format_time_ago(git.last_commit_time)
+            );
+        }
+        println!(
+            "      {} Claude {}, Codex {}",
+            "Sessions:".bright_black(),
+            status.claude_sessions,
+            status.codex_sessions
+        );
+    }
+
+    Ok(())
+}
+
+fn describe_git(git: &GitStatusSummary) -> String {
+    if let Some(error) = &git.error {
+        return format!("error: {error}");
+    }
+
+    let mut parts = Vec::new();
+    if git.clean {
+        parts.push("clean".to_string());
+    }
+    for (count, label) in [
+        (git.staged_files, "staged"),
+        (git.unstaged_files, "unstaged"),
+        (git.untracked_files, "untracked"),
+        (git.conflict_files, "conflicted"),
+    ] {
+        if count > 0 {
+            parts.push(format!("{count} {label}"));
+        }
+    }
+    if let (Some(base_ref), Some(ahead), Some(behind)) = (&git.base_ref, git.ahead, git.behind)
+        && (ahead > 0 || behind > 0)
+    {
+        parts.push(format!("{ahead} ahead, {behind} behind {base_ref}"));
+    }
+    parts.join(", ")
+}
+
+fn workspace_members<'a>(
+    state: &'a XlaudeState,
+    name: &str,
+) -> Result<Vec<(&'a String, Option<&'a WorktreeInfo>)>> {
+    let workspace = state
+        .workspaces
+        .get(name)
+        .with_category(ErrorCategory::NotFound, || {
+            format!("Workspace '{name}' not found")
+        })?;
+    Ok(workspace
+        .members
+        .iter()
+        .map(|key| (key, state.worktrees.get(key)))
+        .collect())
+}
+
+fn run_tmux(args: &[&str]) -> Result<()> {
+    if dry_run::is_enabled() {
+        dry_run::announce(&shell_words::join(
+            std::iter::once("tmux").chain(args.iter().copied()),
+        ));
+        return Ok(());
+    }
+    let output = Command::new("tmux")
+        .args(args)
+        .output()
+        .context("Failed to run tmux")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "tmux {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+fn attach_tmux(session: &str) -> Result<()> {
+    if dry_run::is_enabled() {
+        dry_run::announce(&format!("tmux attach-session -t {session}"));
+        return Ok(());
+    }
+    let status = Command::new("tmux")
+        .args(["attach-session", "-t", session])
+        .status()
+        .context("Failed to attach to tmux session")?;
+    if !status.success() {
+        anyhow::bail!("Failed to attach to tmux session '{}'", session);
+    }
+    Ok(())
+}
@@ -32,7 +32,7 @@ _xlaude() {{
     fi
 
     # Main commands
-    local commands="create open delete add rename list clean dir completions"
+    local commands="create open delete add rename list clean dir sessions completions"
 
     # Complete main commands
     if [[ $cword -eq 1 ]]; then
@@ -56,6 +56,19 @@ _xlaude() {{
                 COMPREPLY=($(compgen -W "$worktrees" -- "$cur"))
             fi
             ;;
+        sessions)
+            if [[ $cword -eq 2 ]]; then
+                COMPREPLY=($(compgen -W "search list show export resume" -- "$cur"))
+            elif [[ $cword -eq 3 ]]; then
+                case "${{words[2]}}" in
+                    show|export|resume)
+                        # Session ids of all managed worktrees, newest first
+                        local ids=$(xlaude sessions list --ids 2>/dev/null)
+                        COMPREPLY=($(compgen -W "$ids" -- "$cur"))
+                        ;;
+                esac
+            fi
+            ;;
         completions)
             if [[ $cword -eq 2 ]]; then
                 COMPREPLY=($(compgen -W "bash zsh fish" -- "$cur"))
@@ -84,6 +97,7 @@ _xlaude() {{
         'list:List all active Claude instances'
         'clean:Clean up invalid worktrees from state'
         'dir:Get the directory path of a worktree'
+        'sessions:Search, list, show and resume agent sessions'
         'completions:Generate shell completions'
     )
 
@@ -112,6 +126,24 @@ _xlaude() {{
                 _message "worktree name"
             fi
             ;;
+        sessions)
+            if (( CURRENT == 3 )); then
+                local -a actions
+                actions=(
+                    'search:Search the messages of every session'
+                    'list:List recorded sessions'
+                    'show:Print the messages of a session'
+                    'export:Print a session transcript as JSON'
+                    'resume:Continue a session with its agent'
+                )
+                _describe 'action' actions
+            elif (( CURRENT == 4 )) && [[ "${{words[3]}}" == (show|export|resume) ]]; then
+                local -a ids
+                ids=(${{(f)"$(xlaude sessions list --ids 2>/dev/null)"}})
+                # -V keeps the newest-first order
+                compadd -V sessions -a ids
+            fi
+            ;;
         completions)
             if (( CURRENT == 3 )); then
                 local -a shells
@@ -177,6 +209,7 @@ complete -c xlaude -n "__fish_use_subcommand" -a rename -d "Rename a worktree"
 complete -c xlaude -n "__fish_use_subcommand" -a list -d "List all active Claude instances"
 complete -c xlaude -n "__fish_use_subcommand" -a clean -d "Clean up invalid worktrees from state"
 complete -c xlaude -n "__fish_use_subcommand" -a dir -d "Get the directory path of a worktree"
+complete -c xlaude -n "__fish_use_subcommand" -a sessions -d "Search, list, show and resume agent sessions"
 complete -c xlaude -n "__fish_use_subcommand" -a completions -d "Generate shell completions"
 
 # Function to get worktree completions with repo markers
@@ -202,6 +235,10 @@ end
 complete -c xlaude -n "__fish_seen_subcommand_from open dir delete" -a "(__xlaude_worktrees)"
 complete -c xlaude -n "__fish_seen_subcommand_from rename" -n "not __fish_seen_argument_from (__xlaude_worktrees_simple)" -a "(__xlaude_worktrees)"
 
+# Session actions, and session ids for the ones that take one
+complete -c xlaude -n "__fish_seen_subcommand_from sessions; and not __fish_seen_subcommand_from search list show export resume" -a "search list show export resume"
+complete -c xlaude -n "__fish_seen_subcommand_from sessions; and __fish_seen_subcommand_from show export resume" -a "(xlaude sessions list --ids 2>/dev/null)" -k
+
 # Shell completions for completions command
 complete -c xlaude -n "__fish_seen_subcommand_from completions" -a "bash zsh fish"
 "#
@@ -0,0 +1,538 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use axum::Json;
+use axum::extract::Path as AxumPath;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::claude::{SessionInfo, get_claude_sessions_in};
+use crate::codex::{self, CodexSession};
+use crate::dashboard::{self, BindAddress, DashboardConfig};
+use crate::processes::{AgentProcess, ProcessRegistry};
+use crate::redact::Redactor;
+use crate::state::{WorktreeInfo, XlaudeState, get_config_dir};
+use crate::store;
+use crate::time_window::TimeWindow;
+use crate::transcript::{
+    self, Transcript, claude_project_dir, last_session_activity, recent_transcripts,
+};
+
+// How often session directories and agent PIDs are re-checked
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+// Set inside the daemon process only; other processes talk to it over its socket
+static INDEX: Lazy<RwLock<Option<SessionIndex>>> = Lazy::new(|| RwLock::new(None));
+
+/// Snapshot of a session directory: a change in either field means sessions
+/// were added or written to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Fingerprint {
+    files: usize,
+    modified: Option<SystemTime>,
+}
+
+impl Fingerprint {
+    fn of(files: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut fingerprint = Self::default();
+        for file in files {
+            fingerprint.files += 1;
+            let modified = fs::metadata(&file).and_then(|meta| meta.modified()).ok();
+            fingerprint.modified = fingerprint.modified.max(modified);
+        }
+        fingerprint
+    }
+}
+
+struct IndexEntry {
+    // Claude project directory and Codex archive state when last indexed
+    fingerprint: (Fingerprint, Fingerprint),
+    transcripts: Vec<Transcript>,
+    sessions: Sessions,
+}
+
+struct SessionIndex {
+    started_at: DateTime<Utc>,
+    refreshed_at: Option<DateTime<Utc>>,
+    entries: HashMap<String, IndexEntry>,
+    agents: Vec<AgentProcess>,
+    // Worktree paths as of this state revision, reloaded only when it changes
+    state_revision: Option<u64>,
+    worktrees: HashMap<String, PathBuf>,
+}
+
+/// A worktree's Claude and Codex sessions, newest first, summarized for
+/// `list`, `sessions`, and `recent`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sessions {
+    pub claude: Vec<SessionInfo>,
+    pub codex: Vec<CodexSession>,
+    // Latest write to a session file, including ones with no message yet
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+impl Sessions {
+    fn scan(worktree_path: &Path, window: &TimeWindow) -> Result<Self> {
+        Ok(Self {
+            claude: get_claude_sessions_in(worktree_path, window),
+            codex: codex::recent_sessions_in(worktree_path, usize::MAX, window)?.0,
+            last_activity: last_session_activity(worktree_path),
+        })
+    }
+
+    fn within(mut self, window: &TimeWindow) -> Self {
+        self.claude
+            .retain(|session| window.overlaps(session.first_timestamp, session.last_timestamp));
+        self.codex
+            .retain(|session| window.overlaps(session.started_at, session.last_timestamp));
+        self
+    }
+
+    fn redacted(mut self, redactor: &Redactor) -> Self {
+        for session in &mut self.claude {
+            session.last_user_message = redactor.redact(&session.last_user_message);
+        }
+        for session in &mut self.codex {
+            if let Some(message) = &mut session.last_user_message {
+                *message = redactor.redact(message);
+            }
+        }
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonStatus {
+    pub running: bool,
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    pub refreshed_at: Option<DateTime<Utc>>,
+    pub indexed_worktrees: usize,
+    pub indexed_sessions: usize,
+    pub agents: Vec<AgentProcess>,
+}
+
+pub fn socket_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("daemon.sock"))
+}
+
+pub fn run_daemon(address: Option<String>, readonly: bool) -> Result<()> {
+    let addr = match address {
+        Some(address) => BindAddress::parse(&address)?,
+        None => default_address()?,
+    };
+
+    *INDEX.write().expect("session index lock poisoned") = Some(SessionIndex {
+        started_at: Utc::now(),
+        refreshed_at: None,
+        entries: HashMap::new(),
+        agents: Vec::new(),
+        state_revision: None,
+        worktrees: HashMap::new(),
+    });
+
+    let app = dashboard::router(DashboardConfig::new(readonly))
+        .route("/api/daemon/status", get(api_status))
+        .route("/api/daemon/agents", get(api_agents))
+        .route("/api/daemon/transcripts/:repo/:name", get(api_transcripts))
+        .route("/api/daemon/sessions/:repo/:name", get(api_sessions));
+
+    let runtime = tokio::runtime::Runtime::new().context("Failed to start async runtime")?;
+    runtime.block_on(async move {
+        tokio::spawn(async {
+            loop {
+                match tokio::task::spawn_blocking(refresh_index).await {
+                    Ok(Err(err)) => eprintln!("[daemon] failed to refresh index: {err:#}"),
+                    Err(err) => eprintln!("[daemon] index refresh panicked: {err}"),
+                    Ok(Ok(())) => {}
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        });
+        println!(
+            "🛰️  xlaude daemon indexing sessions (pid {})",
+            std::process::id()
+        );
+        dashboard::serve(addr, app, readonly, false).await
+    })
+}
+
+#[cfg(unix)]
+fn default_address() -> Result<BindAddress> {
+    Ok(BindAddress::Unix(socket_path()?))
+}
+
+#[cfg(not(unix))]
+fn default_address() -> Result<BindAddress> {
+    BindAddress::parse("127.0.0.1:5711")
+}
+
+/// Status of the daemon listening on the default socket, if one is running.
+pub fn status() -> Option<DaemonStatus> {
+    client::get("/api/daemon/status")
+}
+
+/// Up to `limit` transcripts of a worktree, newest first. Served from the
+/// daemon's index when a daemon is running, read from disk otherwise.
+pub fn transcripts(info: &WorktreeInfo, limit: usize) -> Result<Vec<Transcript>> {
+    transcripts_in(info, limit, &TimeWindow::default())
+}
+
+/// Like [`transcripts`], keeping only messages sent inside `window`.
+pub fn transcripts_in(
+    info: &WorktreeInfo,
+    limit: usize,
+    window: &TimeWindow,
+) -> Result<Vec<Transcript>> {
+    match indexed(info, "transcripts", |entry| entry.transcripts.clone()) {
+        Some(transcripts) => Ok(windowed(transcripts, limit, window)),
+        None => transcript::transcripts_in(&info.path, limit, window),
+    }
+}
+
+/// Sessions of a worktree active inside `window`, from the daemon's index
+/// when a daemon is running and from disk otherwise.
+pub fn sessions_in(info: &WorktreeInfo, window: &TimeWindow) -> Result<Sessions> {
+    match indexed(info, "sessions", |entry| entry.sessions.clone()) {
+        Some(sessions) => Ok(sessions.within(window)),
+        None => Sessions::scan(&info.path, window),
+    }
+}
+
+/// When an agent last wrote to one of the worktree's sessions.
+pub fn last_activity(info: &WorktreeInfo) -> Option<DateTime<Utc>> {
+    match indexed(info, "sessions", |entry| entry.sessions.clone()) {
+        Some(sessions) => sessions.last_activity,
+        None => last_session_activity(&info.path),
+    }
+}
+
+/// What the daemon indexed for a worktree under `/api/daemon/<kind>`, read
+/// from the index inside the daemon and over its socket elsewhere. `None`
+/// when there is no daemon or it has not indexed the worktree yet.
+fn indexed<T: serde::de::DeserializeOwned>(
+    info: &WorktreeInfo,
+    kind: &str,
+    read: impl FnOnce(&IndexEntry) -> T,
+) -> Option<T> {
+    let key = XlaudeState::make_key(&info.repo_name, &info.name);
+    {
+        let index = INDEX.read().expect("session index lock poisoned");
+        if let Some(index) = index.as_ref() {
+            return index.entries.get(&key).map(read);
+        }
+    }
+    client::get(&format!(
+        "/api/daemon/{kind}/{}/{}",
+        client::encode_segment(&info.repo_name),
+        client::encode_segment(&info.name)
+    ))
+}
+
+fn windowed(transcripts: Vec<Transcript>, limit: usize, window: &TimeWindow) -> Vec<Transcript> {
+    transcripts
+        .into_iter()
+        .filter_map(|transcript| transcript.within(window))
+        .take(limit)
+        .collect()
+}
+
+fn refresh_index() -> Result<()> {
+    let state_revision = store::open()?.watch()?;
+    let cached = {
+        let index = INDEX.read().expect("session index lock poisoned");
+        index
+            .as_ref()
+            .filter(|index| state_revision.is_some() && index.state_revision == state_revision)
+            .map(|index| index.worktrees.clone())
+    };
+    let worktrees = match cached {
+        Some(worktrees) => worktrees,
+        None => XlaudeState::load()?
+            .worktrees
+            .into_iter()
+            .map(|(key, info)| (key, info.path))
+            .collect(),
+    };
+    let codex_fingerprint = Fingerprint::of(codex::iterate_session_files(true)?);
+
+    let known: HashMap<String, (Fingerprint, Fingerprint)> = {
+        let index = INDEX.read().expect("session index lock poisoned");
+        index
+            .as_ref()
+            .map(|index| {
+                index
+                    .entries
+                    .iter()
+                    .map(|(key, entry)| (key.clone(), entry.fingerprint))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut updates = Vec::new();
+    for (key, path) in &worktrees {
+        let fingerprint = (claude_fingerprint(path), codex_fingerprint);
+        if known.get(key) == Some(&fingerprint) {
+            continue;
+        }
+        let scanned = recent_transcripts(path, usize::MAX).and_then(|transcripts| {
+            Ok((transcripts, Sessions::scan(path, &TimeWindow::default())?))
+        });
+        match scanned {
+            Ok((transcripts, sessions)) => updates.push((
+                key.clone(),
+                IndexEntry {
+                    fingerprint,
+                    transcripts,
+                    sessions,
+                },
+            )),
+            Err(err) => eprintln!("[daemon] failed to index {key}: {err:#}"),
+        }
+    }
+
+    let agents = ProcessRegistry::load_live()
+        .map(|registry| registry.agents().to_vec())
+        .unwrap_or_default();
+
+    let mut index = INDEX.write().expect("session index lock poisoned");
+    if let Some(index) = index.as_mut() {
+        index.entries.retain(|key, _| worktrees.contains_key(key));
+        index.entries.extend(updates);
+        index.agents = agents;
+        index.state_revision = state_revision;
+        index.worktrees = worktrees;
+        index.refreshed_at = Some(Utc::now());
+    }
+    Ok(())
+}
+
+fn claude_fingerprint(worktree_path: &Path) -> Fingerprint {
+    let Some(entries) = claude_project_dir(worktree_path).and_then(|dir| fs::read_dir(dir).ok())
+    else {
+        return Fingerprint::default();
+    };
+    Fingerprint::of(entries.flatten().map(|entry| entry.path()))
+}
+
+async fn api_status() -> impl IntoResponse {
+    let index = INDEX.read().expect("session index lock poisoned");
+    let Some(index) = index.as_ref() else {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    };
+    Ok(Json(DaemonStatus {
+        running: true,
+        pid: std::process::id(),
+        started_at: index.started_at,
+        refreshed_at: index.refreshed_at,
+        indexed_worktrees: index.entries.len(),
+        indexed_sessions: index
+            .entries
+            .values()
+            .map(|entry| entry.transcripts.len())
+            .sum(),
+        agents: index.agents.clone(),
+    }))
+}
+
+async fn api_agents() -> impl IntoResponse {
+    let index = INDEX.read().expect("session index lock poisoned");
+    Json(
+        index
+            .as_ref()
+            .map(|index| index.agents.clone())
+            .unwrap_or_default(),
+    )
+}
+
+async fn api_transcripts(AxumPath((repo, name)): AxumPath<(String, String)>) -> impl IntoResponse {
+    indexed_entry(&repo, &name, |entry, redactor| {
+        let mut transcripts = entry.transcripts.clone();
+        for transcript in &mut transcripts {
+            redactor.redact_transcript(transcript);
+        }
+        transcripts
+    })
+}
+
+async fn api_sessions(AxumPath((repo, name)): AxumPath<(String, String)>) -> impl IntoResponse {
+    indexed_entry(&repo, &name, |entry, redactor| {
+        entry.sessions.clone().redacted(redactor)
+    })
+}
+
+/// What `read` takes from the entry of `repo/name`, with secrets in it cut by
+/// the same redactor the dashboard uses.
+fn indexed_entry<T: Serialize>(
+    repo: &str,
+    name: &str,
+    read: impl FnOnce(&IndexEntry, &Redactor) -> T,
+) -> Result<Json<T>, (StatusCode, String)> {
+    let redactor = XlaudeState::load()
+        .and_then(|state| Redactor::from_state(&state))
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, format!("{err:#}")))?;
+    let key = XlaudeState::make_key(repo, name);
+    let index = INDEX.read().expect("session index lock poisoned");
+    match index.as_ref().and_then(|index| index.entries.get(&key)) {
+        Some(entry) => Ok(Json(read(entry, &redactor))),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            format!("Worktree '{key}' is not indexed yet"),
+        )),
+    }
+}
+
+/// Minimal blocking HTTP client for the daemon socket. Every failure means
+/// "no daemon", so callers fall back to scanning on their own.
+mod client {
+    use serde::de::DeserializeOwned;
+
+    // Set to skip the daemon even when it is running
+    const DISABLE_ENV: &str = "XLAUDE_NO_DAEMON";
+
+    #[cfg(unix)]
+    pub fn get<T: DeserializeOwned>(path: &str) -> Option<T> {
+        use std::io::{Read, Write};
+        use std::os::unix::net::UnixStream;
+        use std::time::Duration;
+
+        const TIMEOUT: Duration = Duration::from_secs(2);
+
+        if std::env::var_os(DISABLE_ENV).is_some() {
+            return None;
+        }
+        let socket = super::socket_path().ok()?;
+        let mut stream = UnixStream::connect(socket).ok()?;
+        stream.set_read_timeout(Some(TIMEOUT)).ok()?;
+        stream.set_write_timeout(Some(TIMEOUT)).ok()?;
+        write!(
+            stream,
+            "GET {path} HTTP/1.1\r\nHost: xlaude\r\nConnection: close\r\n\r\n"
+        )
+        .ok()?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).ok()?;
+        let split = response
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")?;
+        let head = std::str::from_utf8(&response[..split]).ok()?;
+        if head.split_whitespace().nth(1) != Some("200") {
+            return None;
+        }
+        serde_json::from_slice(&response[split + 4..]).ok()
+    }
+
+    #[cfg(not(unix))]
+    pub fn get<T: DeserializeOwned>(_path: &str) -> Option<T> {
+        let _ = DISABLE_ENV;
+        None
+    }
+
+    /// Percent-encode a URL path segment.
+    pub fn encode_segment(segment: &str) -> String {
+        segment
+            .bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    (byte as char).to_string()
+                }
+                _ => format!("%{byte:02X}"),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn encodes_path_segments() {
+        assert_eq!(client::encode_segment("feat-x_1.2"), "feat-x_1.2");
+        assert_eq!(client::encode_segment("a b/c"), "a%20b%2Fc");
+    }
+
+    #[test]
+    fn fingerprint_tracks_count_and_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.jsonl");
+        fs::write(&file, "{}").unwrap();
+        let first = Fingerprint::of([file.clone()]);
+        assert_eq!(first.files, 1);
+        assert!(first.modified.is_some());
+        assert_eq!(Fingerprint::of([file.clone(), file]).files, 2);
+    }
+
+    #[test]
+    fn indexed_sessions_are_filtered_by_window() {
+        let at = |hour: u32| Some(Utc.with_ymd_and_hms(2025, 6, 1, hour, 0, 0).unwrap());
+        let claude = |id: &str, first, last| SessionInfo {
+            id: id.to_string(),
+            path: PathBuf::from(format!("{id}.jsonl")),
+            last_user_message: "hi".to_string(),
+            first_timestamp: first,
+            last_timestamp: last,
+            outcome: None,
+        };
+        let sessions = Sessions {
+            claude: vec![
+                claude("late", at(10), at(12)),
+                claude("early", at(1), at(2)),
+            ],
+            codex: Vec::new(),
+            last_activity: at(12),
+        };
+        let window = TimeWindow {
+            since: at(11),
+            until: None,
+        };
+        let ids: Vec<String> = sessions
+            .within(&window)
+            .claude
+            .into_iter()
+            .map(|session| session.id)
+            .collect();
+        assert_eq!(ids, ["late"]);
+    }
+
+    #[test]
+    fn served_sessions_are_redacted() {
+        let sessions = Sessions {
+            claude: vec![SessionInfo {
+                id: "a".to_string(),
+                path: PathBuf::from("a.jsonl"),
+                last_user_message: "use sk-ant-REDACTED".to_string(),
+                first_timestamp: None,
+                last_timestamp: None,
+                outcome: None,
+            }],
+            codex: vec![CodexSession {
+                id: "b".to_string(),
+                path: PathBuf::from("b.jsonl"),
+                cwd: PathBuf::from("/repo"),
+                started_at: None,
+                last_timestamp: None,
+                last_user_message: Some("token=hunter2hunter2".to_string()),
+                outcome: None,
+            }],
+            last_activity: None,
+        };
+        let sessions = sessions.redacted(&Redactor::new(&[]).unwrap());
+        assert!(!sessions.claude[0].last_user_message.contains("sk-ant"));
+        let codex = sessions.codex[0].last_user_message.as_deref().unwrap();
+        assert!(!codex.contains("hunter2"), "{codex}");
+    }
+}
@@ -5,15 +5,15 @@ use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::process::{Command as StdCommand, Stdio};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering as AtomicOrdering};
 use std::time::Duration;
 
 use anyhow::{Context, Result, anyhow};
 use axum::extract::{
-    Path as AxumPath, State,
+    Path as AxumPath, Query, State,
     ws::{Message, WebSocket, WebSocketUpgrade},
 };
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode, header};
 use axum::response::{Html, IntoResponse};
 use axum::routing::{get, post};
 use axum::{Json, Router};
@@ -29,11 +29,27 @@ use uuid::Uuid;
 
 use shell_words::split as shell_split;
 
+use crate::agent::{Launch, guard_permissions};
+use crate::agent_errors::{AgentError, last_run_error};
+use crate::budget::{self, BudgetStatus};
 use crate::claude;
 use crate::codex;
 use crate::codex::CodexSession;
+use crate::daemon;
+use crate::disk::{DiskUsageCache, budget_warning, dir_size, disk_budget};
+use crate::git;
+use crate::history;
+use crate::interrupt;
+use crate::outcome::SessionOutcome;
+use crate::overlap;
+use crate::processes::ProcessRegistry;
+use crate::redact::Redactor;
+use crate::search::{SessionHit, SessionQuery, WorktreeFilter, search_worktree};
 use crate::state::{WorktreeInfo, XlaudeState};
-use crate::utils::prepare_agent_command;
+use crate::time_window::TimeWindow;
+use crate::transcript::{self, Cursor, Transcript};
+use crate::utils::{parse_duration, prepare_agent_command, spawn_detached_self};
+use crate::webhook;
 
 const STATIC_INDEX: &str = include_str!("../dashboard/static/index.html");
 const DEFAULT_ADDR: &str = "127.0.0.1:5710";
@@ -41,40 +57,95 @@ const DEFAULT_SESSION_LIMIT: usize = 5;
 const SESSION_RETENTION_SECS: u64 = 300;
 const PTY_ROWS: u16 = 40;
 const PTY_COLS: u16 = 120;
+// Monthly usage changes slowly, so budgets are rescanned at most this often
+const BUDGET_REFRESH: Duration = Duration::from_secs(60);
 const CURSOR_POSITION_QUERY: &[u8] = b"\x1b[6n";
 
+type BudgetCache = Option<(std::time::Instant, Vec<BudgetStatus>)>;
+static BUDGET_CACHE: Lazy<std::sync::Mutex<BudgetCache>> =
+    Lazy::new(|| std::sync::Mutex::new(None));
+
+// Set while a background thread measures worktree sizes for the disk usage cache
+static SIZING: AtomicBool = AtomicBool::new(false);
+
 #[derive(Clone)]
 pub struct DashboardConfig {
     session_limit: usize,
+    // Reject every endpoint that launches processes or changes state
+    readonly: bool,
 }
 
 impl Default for DashboardConfig {
     fn default() -> Self {
         Self {
             session_limit: DEFAULT_SESSION_LIMIT,
+            readonly: false,
+        }
+    }
+}
+
+impl DashboardConfig {
+    pub fn new(readonly: bool) -> Self {
+        Self {
+            readonly,
+            ..Default::default()
+        }
+    }
+
+    fn ensure_writable(&self) -> Result<(), (StatusCode, String)> {
+        if self.readonly {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Dashboard is running in read-only mode".to_string(),
+            ));
         }
+        Ok(())
     }
 }
 
-pub fn run_dashboard(address: Option<String>, auto_open: bool) -> Result<()> {
-    let addr: SocketAddr = address
-        .unwrap_or_else(|| DEFAULT_ADDR.to_string())
-        .parse()
-        .context("Invalid bind address for dashboard")?;
+/// Where the dashboard listens: a TCP address or, with `unix:<path>`, a Unix socket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindAddress {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
 
-    let config = DashboardConfig::default();
+impl BindAddress {
+    pub fn parse(input: &str) -> Result<Self> {
+        if let Some(path) = input.strip_prefix("unix:") {
+            if path.is_empty() {
+                anyhow::bail!("Unix socket address needs a path, e.g. unix:/tmp/xlaude.sock");
+            }
+            return Ok(BindAddress::Unix(PathBuf::from(path)));
+        }
+        input
+            .parse()
+            .map(BindAddress::Tcp)
+            .with_context(|| format!("Invalid bind address for dashboard: {input}"))
+    }
+}
+
+pub fn run_dashboard(address: Option<String>, auto_open: bool, readonly: bool) -> Result<()> {
+    let addr = BindAddress::parse(address.as_deref().unwrap_or(DEFAULT_ADDR))?;
+
+    let app = router(DashboardConfig::new(readonly));
     let runtime = tokio::runtime::Runtime::new().context("Failed to start async runtime")?;
-    runtime.block_on(async move { start_server(addr, config, auto_open).await })
+    runtime.block_on(async move { serve(addr, app, readonly, auto_open).await })
 }
 
-async fn start_server(addr: SocketAddr, config: DashboardConfig, auto_open: bool) -> Result<()> {
-    let app = Router::new()
+/// Routes of the dashboard UI and its REST/WebSocket API.
+pub fn router(config: DashboardConfig) -> Router {
+    Router::new()
         .route("/", get(serve_index))
         .route("/api/worktrees", get(api_worktrees))
         .route(
             "/api/worktrees/:repo/:name/actions",
             post(api_worktree_action),
         )
+        .route(
+            "/api/worktrees/:repo/:name/transcripts",
+            get(api_worktree_transcripts),
+        )
         .route(
             "/api/worktrees/:repo/:name/live-session",
             post(api_resume_session),
@@ -86,8 +157,21 @@ async fn start_server(addr: SocketAddr, config: DashboardConfig, auto_open: bool
             "/api/settings",
             get(api_get_settings).post(api_update_settings),
         )
-        .with_state(config);
+        .route("/api/hooks/create", post(api_hook_create))
+        .with_state(config)
+}
 
+/// Serve `app` until Ctrl+C.
+pub async fn serve(addr: BindAddress, app: Router, readonly: bool, auto_open: bool) -> Result<()> {
+    // Ctrl+C stops the server gracefully instead of exiting at once
+    let _ctrl_c = interrupt::defer();
+    match addr {
+        BindAddress::Tcp(addr) => serve_tcp(addr, app, readonly, auto_open).await,
+        BindAddress::Unix(path) => serve_unix(&path, app, readonly).await,
+    }
+}
+
+async fn serve_tcp(addr: SocketAddr, app: Router, readonly: bool, auto_open: bool) -> Result<()> {
     let listener = tokio::net::TcpListener::bind(addr)
         .await
         .context("Failed to bind dashboard listener")?;
@@ -96,6 +180,7 @@ async fn start_server(addr: SocketAddr, config: DashboardConfig, auto_open: bool
         .context("Failed to read listener address")?;
 
     println!("🚀 xlaude dashboard available at http://{actual_addr} (press Ctrl+C to stop)");
+    print_readonly_notice(readonly);
 
     if auto_open {
         let url = format!("http://{actual_addr}");
@@ -112,6 +197,69 @@ async fn start_server(addr: SocketAddr, config: DashboardConfig, auto_open: bool
     Ok(())
 }
 
+#[cfg(unix)]
+async fn serve_unix(path: &Path, app: Router, readonly: bool) -> Result<()> {
+    use hyper::server::conn::http1;
+    use hyper_util::rt::TokioIo;
+    use hyper_util::service::TowerToHyperService;
+    use std::os::unix::fs::FileTypeExt;
+
+    // A socket left behind by a previous run would make bind fail
+    let is_socket = std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket());
+    if is_socket && std::os::unix::net::UnixStream::connect(path).is_ok() {
+        anyhow::bail!("Another server is already listening on {}", path.display());
+    }
+    if is_socket {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+    }
+    let listener = tokio::net::UnixListener::bind(path)
+        .with_context(|| format!("Failed to bind dashboard socket {}", path.display()))?;
+
+    println!(
+        "🚀 xlaude dashboard available at unix:{} (press Ctrl+C to stop)",
+        path.display()
+    );
+    print_readonly_notice(readonly);
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    eprintln!("⚠️  Failed to accept dashboard connection: {err}");
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+        let service = TowerToHyperService::new(app.clone());
+        tokio::spawn(async move {
+            // Upgrades are needed for the live-session WebSocket
+            let _ = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .with_upgrades()
+                .await;
+        });
+    }
+
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn serve_unix(_path: &Path, _app: Router, _readonly: bool) -> Result<()> {
+    anyhow::bail!("Unix socket addresses are only supported on Unix platforms")
+}
+
+fn print_readonly_notice(readonly: bool) {
+    if readonly {
+        println!("🔒 Read-only mode: launching agents, actions, and settings changes are disabled");
+    }
+}
+
 async fn shutdown_signal() {
     let _ = signal::ctrl_c().await;
     println!("👋 Stopping dashboard");
@@ -121,10 +269,45 @@ async fn serve_index() -> Html<&'static str> {
     Html(STATIC_INDEX)
 }
 
-async fn api_worktrees(State(config): State<DashboardConfig>) -> impl IntoResponse {
+async fn api_worktrees(
+    State(config): State<DashboardConfig>,
+    Query(params): Query<WorktreeListQuery>,
+) -> impl IntoResponse {
     let limit = config.session_limit;
-    match tokio::task::spawn_blocking(move || build_dashboard_payload(limit)).await {
-        Ok(Ok(payload)) => Json(payload).into_response(),
+    let active_since = match params.active.as_deref().filter(|value| !value.is_empty()) {
+        Some(value) => match parse_duration(value) {
+            Ok(window) => match Utc::now().checked_sub_signed(window) {
+                Some(since) => Some(since),
+                None => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        format!("Activity window '{value}' is too long"),
+                    )
+                        .into_response();
+                }
+            },
+            Err(err) => return (StatusCode::BAD_REQUEST, format!("{err:#}")).into_response(),
+        },
+        None => None,
+    };
+    let filter = WorktreeFilter {
+        repo: non_empty(params.repo),
+        tag: non_empty(params.tag),
+        window: TimeWindow {
+            since: active_since,
+            until: None,
+        },
+    };
+    let query = params.q.as_deref().and_then(SessionQuery::parse);
+    match tokio::task::spawn_blocking(move || {
+        build_dashboard_payload(limit, &filter, query.as_ref())
+    })
+    .await
+    {
+        Ok(Ok(mut payload)) => {
+            payload.readonly = config.readonly;
+            Json(payload).into_response()
+        }
         Ok(Err(err)) => {
             eprintln!("[dashboard] failed to gather worktree info: {err:?}");
             (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
@@ -140,19 +323,124 @@ async fn api_worktrees(State(config): State<DashboardConfig>) -> impl IntoRespon
     }
 }
 
+async fn api_worktree_transcripts(
+    State(config): State<DashboardConfig>,
+    AxumPath((repo, name)): AxumPath<(String, String)>,
+    Query(params): Query<TranscriptPageQuery>,
+) -> impl IntoResponse {
+    let limit = params.limit.unwrap_or(config.session_limit);
+    let cursor = match params.cursor.as_deref().filter(|token| !token.is_empty()) {
+        Some(token) => match Cursor::decode(token) {
+            Some(cursor) => Some(cursor),
+            None => return (StatusCode::BAD_REQUEST, "Invalid cursor".to_string()).into_response(),
+        },
+        None => None,
+    };
+    match tokio::task::spawn_blocking(move || load_transcripts(&repo, &name, usize::MAX)).await {
+        Ok(Ok(transcripts)) => {
+            let (transcripts, next) = transcript::page(transcripts, cursor.as_ref(), limit);
+            Json(json!({
+                "transcripts": transcripts,
+                "nextCursor": next.map(|cursor| cursor.encode()),
+            }))
+            .into_response()
+        }
+        Ok(Err((status, message))) => (status, message).into_response(),
+        Err(err) => {
+            eprintln!("[dashboard] worker thread panicked: {err:?}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "dashboard worker panicked".to_string(),
+            )
+                .into_response()
+        }
+    }
+}
+
+fn load_transcripts(
+    repo: &str,
+    name: &str,
+    limit: usize,
+) -> Result<Vec<Transcript>, (StatusCode, String)> {
+    let state = XlaudeState::load().map_err(|err| {
+        eprintln!("[dashboard] failed to load state: {err:?}");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to load state".to_string(),
+        )
+    })?;
+    let key = XlaudeState::make_key(repo, name);
+    let info = state.worktrees.get(&key).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("Worktree '{repo}/{name}' not found"),
+        )
+    })?;
+    let redactor = Redactor::from_state(&state)
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, format!("{err:#}")))?;
+    let mut transcripts = daemon::transcripts(info, limit).map_err(|err| {
+        eprintln!("[dashboard] failed to read transcripts: {err:?}");
+        (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+    })?;
+    for transcript in &mut transcripts {
+        redactor.redact_transcript(transcript);
+    }
+    Ok(transcripts)
+}
+
 async fn api_worktree_action(
+    State(config): State<DashboardConfig>,
     AxumPath((repo, name)): AxumPath<(String, String)>,
     Json(req): Json<ActionRequest>,
 ) -> impl IntoResponse {
+    if let Err(rejection) = config.ensure_writable() {
+        return rejection.into_response();
+    }
     match handle_worktree_action(&repo, &name, req.action.as_str()) {
         Ok(response) => Json(response).into_response(),
         Err((status, message)) => (status, message).into_response(),
     }
 }
 
+async fn api_hook_create(
+    State(config): State<DashboardConfig>,
+    headers: HeaderMap,
+    Json(req): Json<webhook::CreateRequest>,
+) -> impl IntoResponse {
+    if let Err(rejection) = config.ensure_writable() {
+        return rejection.into_response();
+    }
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    if let Err(rejection) = webhook::authorize(authorization) {
+        return rejection.into_response();
+    }
+
+    match tokio::task::spawn_blocking(move || webhook::create_worktree(req)).await {
+        Ok(Ok(response)) => {
+            let status = if response.created {
+                StatusCode::CREATED
+            } else {
+                StatusCode::OK
+            };
+            (status, Json(response)).into_response()
+        }
+        Ok(Err(err)) => {
+            eprintln!("[dashboard] webhook create failed: {err:?}");
+            (webhook::status_for(&err), format!("{err:#}")).into_response()
+        }
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
 async fn api_resume_session(
+    State(config): State<DashboardConfig>,
     AxumPath((repo, name)): AxumPath<(String, String)>,
 ) -> impl IntoResponse {
+    if let Err(rejection) = config.ensure_writable() {
+        return rejection.into_response();
+    }
     match start_live_session(&repo, &name).await {
         Ok(runtime) => {
             let events = runtime.snapshot().await;
@@ -177,9 +465,13 @@ async fn api_get_session_logs(AxumPath(id): AxumPath<String>) -> impl IntoRespon
 }
 
 async fn api_send_session_message(
+    State(config): State<DashboardConfig>,
     AxumPath(id): AxumPath<String>,
     Json(req): Json<SendMessageRequest>,
 ) -> impl IntoResponse {
+    if let Err(rejection) = config.ensure_writable() {
+        return rejection.into_response();
+    }
     let Some(runtime) = get_session_runtime(&id).await else {
         return (StatusCode::NOT_FOUND, "Session not found").into_response();
     };
@@ -232,7 +524,13 @@ async fn api_get_settings() -> impl IntoResponse {
     }
 }
 
-async fn api_update_settings(Json(req): Json<SettingsPayload>) -> impl IntoResponse {
+async fn api_update_settings(
+    State(config): State<DashboardConfig>,
+    Json(req): Json<SettingsPayload>,
+) -> impl IntoResponse {
+    if let Err(rejection) = config.ensure_writable() {
+        return rejection.into_response();
+    }
     match update_settings_state(req) {
         Ok(payload) => Json(payload).into_response(),
         Err(err) => {
@@ -351,8 +649,15 @@ fn spawn_session_blocking(
         pixel_height: 0,
     })?;
 
-    let (program, args) =
-        prepare_agent_command(&info.path).context("Failed to resolve agent command")?;
+    let (program, mut args) =
+        prepare_agent_command(&info).context("Failed to resolve agent command")?;
+    guard_permissions(
+        &XlaudeState::load()?,
+        &info.repo_name,
+        &program,
+        &mut args,
+        Launch::Headless,
+    )?;
     let mut builder = CommandBuilder::new(program);
     for arg in args {
         builder.arg(arg);
@@ -457,13 +762,34 @@ async fn get_session_runtime(id: &str) -> Option<Arc<SessionRuntime>> {
     SESSION_REGISTRY.read().await.get(id).cloned()
 }
 
-fn build_dashboard_payload(limit: usize) -> Result<DashboardPayload> {
+fn build_dashboard_payload(
+    limit: usize,
+    filter: &WorktreeFilter,
+    query: Option<&SessionQuery>,
+) -> Result<DashboardPayload> {
     let state = XlaudeState::load()?;
-    let worktree_paths: Vec<PathBuf> = state
+    let redactor = Redactor::from_state(&state)?;
+    let mut repos: Vec<String> = state
+        .worktrees
+        .values()
+        .map(|info| info.repo_name.clone())
+        .collect();
+    repos.sort();
+    repos.dedup();
+    let mut tags: Vec<String> = state
+        .worktrees
+        .values()
+        .flat_map(|info| info.tags.iter().cloned())
+        .collect();
+    tags.sort();
+    tags.dedup();
+
+    let selected: Vec<&WorktreeInfo> = state
         .worktrees
         .values()
-        .map(|info| info.path.clone())
+        .filter(|info| info.archived.is_none() && filter.matches(info))
         .collect();
+    let worktree_paths: Vec<PathBuf> = selected.iter().map(|info| info.path.clone()).collect();
 
     let (codex_sessions, codex_error) =
         match codex::collect_recent_sessions_for_paths(&worktree_paths, limit) {
@@ -479,21 +805,104 @@ fn build_dashboard_payload(limit: usize) -> Result<DashboardPayload> {
         error: codex_error,
     };
 
-    let mut worktrees: Vec<_> = state
-        .worktrees
-        .values()
-        .map(|info| summarize_worktree(info, limit, &codex_context))
-        .collect();
+    let du_cache = DiskUsageCache::load()?;
+    let mut to_measure = Vec::new();
+    let registry = ProcessRegistry::load_live().unwrap_or_default();
+    let mut worktrees = Vec::new();
+    for info in selected {
+        let key = XlaudeState::make_key(&info.repo_name, &info.name);
+        // Stale sizes are shown until the background measurement replaces them
+        let disk_usage = info.path.exists().then(|| du_cache.cached(&key)).flatten();
+        if info.path.exists() && du_cache.is_stale(&key) {
+            to_measure.push((key.clone(), info.path.clone()));
+        }
+        let repo_config = state.repo_config(&info.repo_name);
+        let mut summary = summarize_worktree(
+            info,
+            limit,
+            &codex_context,
+            disk_usage,
+            repo_config.base_remote(),
+            &redactor,
+        );
+        summary.running_agents = registry
+            .for_worktree(&key)
+            .into_iter()
+            .map(|agent| RunningAgent {
+                pid: agent.pid,
+                command: agent.command.clone(),
+                started_at: agent.started_at,
+            })
+            .collect();
+        if !filter.is_recent(Some(summary.last_activity)) {
+            continue;
+        }
+        if let Some(query) = query {
+            summary.search_hits = search_worktree(info, query, &redactor)?;
+            if summary.search_hits.is_empty() {
+                continue;
+            }
+            summary.search_hits.truncate(limit);
+        }
+        worktrees.push(summary);
+    }
+    measure_in_background(to_measure);
+
+    let disk_total_bytes = worktrees
+        .iter()
+        .filter_map(|tree| tree.disk_usage_bytes)
+        .sum();
+    let disk_budget_warning = match disk_budget(state.disk_budget.as_deref()) {
+        Ok(limit) => budget_warning(disk_total_bytes, limit),
+        Err(err) => Some(format!("{err:#}")),
+    };
+    let (usage_budgets, usage_budget_warnings) = match cached_budgets(&state) {
+        Ok(statuses) => (
+            statuses.iter().map(BudgetStatus::summary).collect(),
+            budget::warnings(&statuses),
+        ),
+        Err(err) => (Vec::new(), vec![format!("{err:#}")]),
+    };
 
+    // Most recently opened first, then the rest by repository and name
+    let opened = history::last_opened();
+    // Across all worktrees, whatever the filters show
+    let overlaps = overlap::file_overlaps(&state, state.worktrees.values());
+    for worktree in &mut worktrees {
+        worktree.last_opened_at = opened.get(&worktree.key).copied();
+        worktree.overlaps = overlaps
+            .iter()
+            .filter(|overlap| {
+                overlap.repo == worktree.repo_name && overlap.worktrees.contains(&worktree.name)
+            })
+            .map(|overlap| SharedFile {
+                path: overlap.path.clone(),
+                worktrees: overlap
+                    .worktrees
+                    .iter()
+                    .filter(|name| **name != worktree.name)
+                    .cloned()
+                    .collect(),
+            })
+            .collect();
+    }
     worktrees.sort_by(|a, b| {
-        a.repo_name
-            .cmp(&b.repo_name)
+        b.last_opened_at
+            .cmp(&a.last_opened_at)
+            .then_with(|| a.repo_name.cmp(&b.repo_name))
             .then_with(|| a.name.cmp(&b.name))
     });
 
     Ok(DashboardPayload {
         generated_at: Utc::now(),
         worktrees,
+        disk_total_bytes,
+        disk_budget_warning,
+        usage_budgets,
+        usage_budget_warnings,
+        readonly: false,
+        repos,
+        tags,
     })
 }
 
@@ -501,33 +910,46 @@ fn summarize_worktree(
     info: &WorktreeInfo,
     limit: usize,
     codex_ctx: &CodexContext,
+    disk_usage_bytes: Option<u64>,
+    base_remote: &str,
+    redactor: &Redactor,
 ) -> WorktreeSummary {
-    let git_status = summarize_git(&info.path);
+    let git_status = summarize_git(&info.path, base_remote);
     let claude_sessions = claude::get_claude_sessions(&info.path);
+    let codex_sessions = codex_ctx
+        .sessions
+        .get(&codex::normalized_worktree_path(&info.path))
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let last_run_error =
+        last_run_error(&claude_sessions, codex_sessions).map(|error| LastRunError {
+            summary: error.summary(),
+            error,
+        });
     let mut sessions = Vec::new();
 
     for session in claude_sessions.into_iter().take(limit) {
         sessions.push(SessionPreview {
             provider: "Claude".to_string(),
-            message: Some(session.last_user_message),
+            message: Some(redactor.redact(&session.last_user_message)),
             timestamp: session.last_timestamp,
+            outcome: session.outcome,
         });
     }
 
     let session_error = codex_ctx.error.clone();
-    if codex_ctx.error.is_none() {
-        let normalized = codex::normalized_worktree_path(&info.path);
-        if let Some(entries) = codex_ctx.sessions.get(&normalized) {
-            for session in entries.iter().take(limit) {
-                let fallback = format!("Session {}", short_session_id(session));
-                let message = session.last_user_message.clone().unwrap_or(fallback);
-                sessions.push(SessionPreview {
-                    provider: "Codex".to_string(),
-                    message: Some(message),
-                    timestamp: session.last_timestamp,
-                });
-            }
-        }
+    for session in codex_sessions.iter().take(limit) {
+        let fallback = format!("Session {}", short_session_id(session));
+        let message = session
+            .last_user_message
+            .as_deref()
+            .map_or(fallback, |message| redactor.redact(message));
+        sessions.push(SessionPreview {
+            provider: "Codex".to_string(),
+            message: Some(message),
+            timestamp: session.last_timestamp,
+            outcome: session.outcome,
+        });
     }
 
     sessions.sort_by(|a, b| compare_option_desc(a.timestamp, b.timestamp));
@@ -558,6 +980,13 @@ fn summarize_worktree(
         git_status,
         sessions,
         session_error,
+        last_run_error,
+        disk_usage_bytes,
+        tags: info.tags.clone(),
+        running_agents: Vec::new(),
+        last_opened_at: None,
+        search_hits: Vec::new(),
+        overlaps: Vec::new(),
     }
 }
 
@@ -580,6 +1009,10 @@ fn update_settings_state(req: SettingsPayload) -> Result<SettingsPayload> {
     })
 }
 
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|value| !value.trim().is_empty())
+}
+
 fn normalize_setting(value: Option<String>) -> Option<String> {
     value.and_then(|s| {
         let trimmed = s.trim();
@@ -615,6 +1048,47 @@ fn short_session_id(session: &CodexSession) -> String {
     }
 }
 
+/// Measure the worktrees whose cached size is missing or stale on another
+/// thread, so a refresh never waits on walking a large worktree. The sizes
+/// show up in the payload after the measurement finishes.
+fn measure_in_background(worktrees: Vec<(String, PathBuf)>) {
+    if worktrees.is_empty() || SIZING.swap(true, AtomicOrdering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(move || {
+        let sizes: Vec<(String, u64)> = worktrees
+            .into_iter()
+            .map(|(key, path)| {
+                let bytes = dir_size(&path);
+                (key, bytes)
+            })
+            .collect();
+        // Reloaded so sizes that `list --du` stored in the meantime are kept
+        let saved = DiskUsageCache::load().and_then(|mut cache| {
+            for (key, bytes) in &sizes {
+                cache.record(key, *bytes);
+            }
+            cache.save()
+        });
+        if let Err(err) = saved {
+            eprintln!("[dashboard] failed to save disk usage cache: {err:?}");
+        }
+        SIZING.store(false, AtomicOrdering::SeqCst);
+    });
+}
+
+fn cached_budgets(state: &XlaudeState) -> Result<Vec<BudgetStatus>> {
+    let mut cache = BUDGET_CACHE.lock().expect("budget cache lock poisoned");
+    if let Some((checked_at, statuses)) = cache.as_ref()
+        && checked_at.elapsed() < BUDGET_REFRESH
+    {
+        return Ok(statuses.clone());
+    }
+    let statuses = budget::check(state)?;
+    *cache = Some((std::time::Instant::now(), statuses.clone()));
+    Ok(statuses)
+}
+
 struct CodexContext {
     sessions: HashMap<PathBuf, Vec<CodexSession>>,
     error: Option<String>,
@@ -625,6 +1099,33 @@ struct CodexContext {
 struct DashboardPayload {
     generated_at: DateTime<Utc>,
     worktrees: Vec<WorktreeSummary>,
+    disk_total_bytes: u64,
+    disk_budget_warning: Option<String>,
+    // One line per configured usage budget, and warnings for those past their threshold
+    usage_budgets: Vec<String>,
+    usage_budget_warnings: Vec<String>,
+    readonly: bool,
+    // Every repository and tag in state, for the filter controls
+    repos: Vec<String>,
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct WorktreeListQuery {
+    repo: Option<String>,
+    tag: Option<String>,
+    // Only worktrees active within this window, e.g. "24h"
+    active: Option<String>,
+    // Session text search
+    q: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct TranscriptPageQuery {
+    // Transcripts per page (default: the dashboard's session limit)
+    limit: Option<usize>,
+    // `nextCursor` of the previous page
+    cursor: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -670,19 +1171,58 @@ struct WorktreeSummary {
     git_status: GitStatusSummary,
     sessions: Vec<SessionPreview>,
     session_error: Option<String>,
+    // API error (rate limit, overload, auth) the latest session ended on
+    last_run_error: Option<LastRunError>,
+    disk_usage_bytes: Option<u64>,
+    tags: Vec<String>,
+    running_agents: Vec<RunningAgent>,
+    // When an agent was last launched here, from the open log
+    last_opened_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    search_hits: Vec<SessionHit>,
+    // Files this worktree changes that other worktrees of the repository change too
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    overlaps: Vec<SharedFile>,
+}
+
+#[derive(Serialize)]
+struct SharedFile {
+    path: String,
+    // The other worktrees changing it
+    worktrees: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct LastRunError {
+    // "Last run hit rate limits"
+    summary: String,
+    #[serde(flatten)]
+    error: AgentError,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunningAgent {
+    pid: u32,
+    command: String,
+    started_at: DateTime<Utc>,
 }
 
 #[derive(Serialize, Default, Clone)]
 #[serde(rename_all = "camelCase")]
-struct GitStatusSummary {
-    clean: bool,
-    staged_files: usize,
-    unstaged_files: usize,
-    untracked_files: usize,
-    conflict_files: usize,
-    last_commit_message: Option<String>,
-    last_commit_time: Option<DateTime<Utc>>,
-    error: Option<String>,
+pub(crate) struct GitStatusSummary {
+    pub(crate) clean: bool,
+    pub(crate) staged_files: usize,
+    pub(crate) unstaged_files: usize,
+    pub(crate) untracked_files: usize,
+    pub(crate) conflict_files: usize,
+    pub(crate) last_commit_message: Option<String>,
+    pub(crate) last_commit_time: Option<DateTime<Utc>>,
+    // Position relative to the base remote's default branch, when it is known
+    pub(crate) base_ref: Option<String>,
+    pub(crate) ahead: Option<usize>,
+    pub(crate) behind: Option<usize>,
+    pub(crate) error: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -691,6 +1231,8 @@ struct SessionPreview {
     provider: String,
     message: Option<String>,
     timestamp: Option<DateTime<Utc>>,
+    // How the session ended: awaiting input, completed, errored, unfinished
+    outcome: Option<SessionOutcome>,
 }
 
 #[derive(Clone, Serialize)]
@@ -820,7 +1362,7 @@ static SESSION_REGISTRY: Lazy<RwLock<HashMap<String, Arc<SessionRuntime>>>> =
 static WORKTREE_SESSION_INDEX: Lazy<RwLock<HashMap<String, String>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
 
-fn summarize_git(path: &Path) -> GitStatusSummary {
+pub(crate) fn summarize_git(path: &Path, base_remote: &str) -> GitStatusSummary {
     if !path.exists() {
         return GitStatusSummary {
             error: Some("Worktree path missing".to_string()),
@@ -860,6 +1402,14 @@ fn summarize_git(path: &Path) -> GitStatusSummary {
         summary.last_commit_time = Some(commit.timestamp);
     }
 
+    if let Some(base_ref) = git::remote_default_ref(path, base_remote)
+        && let Some((ahead, behind)) = git::ahead_behind(path, &base_ref)
+    {
+        summary.base_ref = Some(base_ref);
+        summary.ahead = Some(ahead);
+        summary.behind = Some(behind);
+    }
+
     summary
 }
 
@@ -954,6 +1504,17 @@ fn handle_worktree_action(
         "open_editor" => launch_editor(&info.path, editor_override).map(|_| ActionResponse {
             message: format!("Opening editor for {}", info.path.display()),
         }),
+        "stop_agent" => spawn_detached_self(&["kill", &key])
+            .map(|_| ActionResponse {
+                message: format!("Stopping agent in {}/{}", info.repo_name, info.name),
+            })
+            .map_err(|err| {
+                eprintln!("[dashboard] failed to stop agent: {err:?}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to stop agent".to_string(),
+                )
+            }),
         other => Err((
             StatusCode::BAD_REQUEST,
             format!("Unsupported action '{other}'"),
@@ -978,29 +1539,13 @@ fn shell_command(override_cmd: Option<String>) -> String {
 }
 
 fn launch_agent(info: &WorktreeInfo) -> Result<(), (StatusCode, String)> {
-    let exe = std::env::current_exe().map_err(|err| {
-        eprintln!("[dashboard] failed to locate binary: {err:?}");
+    spawn_detached_self(&["open", &info.name]).map_err(|err| {
+        eprintln!("[dashboard] failed to launch agent: {err:?}");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to locate xlaude binary".to_string(),
+            "Failed to launch agent".to_string(),
         )
-    })?;
-
-    StdCommand::new(exe)
-        .arg("open")
-        .arg(&info.name)
-        .stdin(Stdio::null())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
-        .map(|_| ())
-        .map_err(|err| {
-            eprintln!("[dashboard] failed to launch agent: {err:?}");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to launch agent".to_string(),
-            )
-        })
+    })
 }
 
 fn launch_shell(
@@ -1097,3 +1642,22 @@ fn scrub_terminal_queries(chunk: &[u8]) -> (Vec<u8>, Vec<Vec<u8>>) {
 fn cursor_position_response() -> Vec<u8> {
     format!("\x1b[{};{}R", PTY_ROWS, PTY_COLS).into_bytes()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tcp_and_unix_bind_addresses() {
+        assert_eq!(
+            BindAddress::parse("127.0.0.1:5710").unwrap(),
+            BindAddress::Tcp("127.0.0.1:5710".parse().unwrap())
+        );
+        assert_eq!(
+            BindAddress::parse("unix:/run/user/1000/xlaude.sock").unwrap(),
+            BindAddress::Unix(PathBuf::from("/run/user/1000/xlaude.sock"))
+        );
+        assert!(BindAddress::parse("unix:").is_err());
+        assert!(BindAddress::parse("localhost").is_err());
+    }
+}
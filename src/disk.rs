@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{ErrorCategory, WithCategory};
+use crate::state::get_config_dir;
+
+// Sizes older than this are recomputed the next time they are requested
+const CACHE_TTL_MINUTES: i64 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSize {
+    bytes: u64,
+    computed_at: DateTime<Utc>,
+}
+
+/// Directory sizes keyed by worktree state key, persisted in `du-cache.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DiskUsageCache {
+    #[serde(default)]
+    entries: HashMap<String, CachedSize>,
+}
+
+impl DiskUsageCache {
+    pub fn load() -> Result<Self> {
+        let path = get_cache_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path).context("Failed to read disk usage cache")?;
+        // A corrupt cache is only a performance problem; start over
+        Ok(serde_json::from_str(&content).unwrap_or_default())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        // A cache, so a dry run simply keeps the old sizes on disk
+        if crate::dry_run::is_enabled() {
+            return Ok(());
+        }
+        let path = get_cache_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize disk usage cache")?;
+        fs::write(&path, content).context("Failed to write disk usage cache")?;
+        Ok(())
+    }
+
+    /// Size of a worktree directory, recomputed only when the cached value is stale.
+    pub fn size_of(&mut self, key: &str, path: &Path) -> Option<u64> {
+        if !path.exists() {
+            self.entries.remove(key);
+            return None;
+        }
+        if !self.is_stale(key) {
+            return self.cached(key);
+        }
+
+        let bytes = dir_size(path);
+        self.record(key, bytes);
+        Some(bytes)
+    }
+
+    /// The last size computed for a worktree, however old, without computing one.
+    pub fn cached(&self, key: &str) -> Option<u64> {
+        self.entries.get(key).map(|cached| cached.bytes)
+    }
+
+    /// Whether a worktree's size is missing or older than the cache TTL.
+    pub fn is_stale(&self, key: &str) -> bool {
+        self.entries.get(key).is_none_or(|cached| {
+            Utc::now() - cached.computed_at >= Duration::minutes(CACHE_TTL_MINUTES)
+        })
+    }
+
+    /// Store a size computed elsewhere, such as on a background thread.
+    pub fn record(&mut self, key: &str, bytes: u64) {
+        self.entries.insert(
+            key.to_string(),
+            CachedSize {
+                bytes,
+                computed_at: Utc::now(),
+            },
+        );
+    }
+
+    /// Size of a worktree directory, recomputed whatever the cached value.
+    pub fn refresh(&mut self, key: &str, path: &Path) -> Option<u64> {
+        self.entries.remove(key);
+        self.size_of(key, path)
+    }
+
+    /// Drop entries for worktrees that are no longer managed.
+    pub fn retain_keys<'a>(&mut self, keys: impl IntoIterator<Item = &'a String>) {
+        let keep: Vec<&String> = keys.into_iter().collect();
+        self.entries.retain(|key, _| keep.contains(&key));
+    }
+}
+
+/// The `disk_budget` from state.json in bytes, checked before anything is
+/// printed so that a typo does not fail a command halfway through its output.
+pub fn disk_budget(budget: Option<&str>) -> Result<Option<u64>> {
+    budget
+        .map(|budget| {
+            parse_size(budget).with_category(
+                ErrorCategory::Config,
+                || "Invalid disk_budget in state.json",
+            )
+        })
+        .transpose()
+}
+
+/// Warning shown when worktrees together use more than the budget `limit`.
+pub fn budget_warning(total: u64, limit: Option<u64>) -> Option<String> {
+    let limit = limit.filter(|limit| total > *limit)?;
+    Some(format!(
+        "Worktrees use {} which exceeds the disk budget of {}. Consider deleting finished worktrees",
+        format_bytes(total),
+        format_bytes(limit)
+    ))
+}
+
+pub fn get_cache_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("du-cache.json"))
+}
+
+/// Total size of regular files below `path`. Symlinks are not followed and
+/// unreadable entries are skipped.
+pub fn dir_size(path: &Path) -> u64 {
+    let mut total = 0;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file()
+                && let Ok(metadata) = entry.metadata()
+            {
+                total += metadata.len();
+            }
+        }
+    }
+    total
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Parse a human-readable size such as `500M`, `20GB`, or `1.5T` (binary units).
+pub fn parse_size(input: &str) -> Result<u64> {
+    let trimmed = input.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let value: f64 = number
+        .parse()
+        .with_context(|| format!("Invalid size: {input}"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        other => anyhow::bail!("Unknown size unit '{}' in '{}'", other, input),
+    };
+    Ok((value * multiplier as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes_with_units() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("2K").unwrap(), 2048);
+        assert_eq!(parse_size("1.5 GB").unwrap(), 3 << 29);
+        assert_eq!(parse_size("20gib").unwrap(), 20 << 30);
+        assert!(parse_size("ten GB").is_err());
+        assert!(parse_size("5 parsecs").is_err());
+    }
+
+    #[test]
+    fn formats_bytes_in_binary_units() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(5 << 30), "5.0 GB");
+    }
+}
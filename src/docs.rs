@@ -0,0 +1,326 @@
+use anyhow::{Context, Result};
+use clap::{Arg, Command};
+use colored::Colorize;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Write man pages and/or a markdown reference for `command` and all of its
+/// visible subcommands, straight from the clap definitions.
+pub fn handle_gen_docs(
+    mut command: Command,
+    man: Option<PathBuf>,
+    markdown: Option<PathBuf>,
+) -> Result<()> {
+    command.build();
+    let pages = collect(&command, Vec::new());
+    if let Some(dir) = man {
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        for page in &pages {
+            write(
+                &dir.join(format!("{}.1", page.path.join("-"))),
+                &man_page(page),
+            )?;
+        }
+        println!(
+            "{} Wrote {} man page(s) to {}",
+            "✅".green(),
+            pages.len(),
+            dir.display()
+        );
+    }
+    if let Some(dir) = markdown {
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let file = dir.join(format!("{}.md", command.get_name()));
+        write(&file, &markdown_reference(&pages))?;
+        println!(
+            "{} Wrote the command reference to {}",
+            "✅".green(),
+            file.display()
+        );
+    }
+    Ok(())
+}
+
+fn write(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// One documented command: `["xlaude", "sessions", "search"]` and its parts.
+struct Page {
+    path: Vec<String>,
+    about: String,
+    description: Option<String>,
+    usage: String,
+    arguments: Vec<Entry>,
+    options: Vec<Entry>,
+    examples: Option<String>,
+    subcommands: Vec<(String, String)>,
+}
+
+/// A flag or positional argument: how it is written and what it does.
+struct Entry {
+    syntax: String,
+    help: String,
+}
+
+fn collect(command: &Command, parents: Vec<String>) -> Vec<Page> {
+    let mut path = parents;
+    path.push(command.get_name().to_string());
+    let is_root = path.len() == 1;
+    let visible = || {
+        command
+            .get_subcommands()
+            .filter(|sub| !sub.is_hide_set() && sub.get_name() != "help")
+    };
+
+    let mut arguments = Vec::new();
+    let mut options = Vec::new();
+    for arg in command.get_arguments() {
+        // Global flags are documented once, on the top-level page
+        if arg.is_hide_set() || (arg.is_global_set() && !is_root) {
+            continue;
+        }
+        let entry = Entry {
+            syntax: syntax(arg),
+            help: help(arg),
+        };
+        if arg.is_positional() {
+            arguments.push(entry);
+        } else {
+            options.push(entry);
+        }
+    }
+
+    let text = |styled: Option<&clap::builder::StyledStr>| {
+        styled
+            .map(|text| text.to_string().trim().to_string())
+            .filter(|text| !text.is_empty())
+    };
+    let about = text(command.get_about()).unwrap_or_default();
+    let description = text(command.get_long_about()).filter(|long| *long != about);
+    let usage = command
+        .clone()
+        .render_usage()
+        .to_string()
+        .trim_start_matches("Usage:")
+        .trim()
+        .to_string();
+    let examples = text(command.get_after_long_help().or(command.get_after_help())).map(|text| {
+        text.strip_prefix("Examples:")
+            .unwrap_or(&text)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+
+    let mut pages = vec![Page {
+        path: path.clone(),
+        about,
+        description,
+        usage,
+        arguments,
+        options,
+        examples,
+        subcommands: visible()
+            .map(|sub| {
+                let about = sub.get_about().map(|about| about.to_string());
+                (sub.get_name().to_string(), about.unwrap_or_default())
+            })
+            .collect(),
+    }];
+    for sub in visible() {
+        pages.extend(collect(sub, path.clone()));
+    }
+    pages
+}
+
+/// `-p, --prompt <PROMPT>` for flags, `<NAME>` or `[NAME]` for positionals.
+fn syntax(arg: &Arg) -> String {
+    let value_name = || {
+        arg.get_value_names()
+            .and_then(|names| names.first())
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| arg.get_id().as_str().to_uppercase())
+    };
+    if arg.is_positional() {
+        let many = if arg
+            .get_num_args()
+            .is_some_and(|range| range.max_values() > 1)
+        {
+            "..."
+        } else {
+            ""
+        };
+        return if arg.is_required_set() {
+            format!("<{}>{many}", value_name())
+        } else {
+            format!("[{}]{many}", value_name())
+        };
+    }
+    let mut syntax = String::new();
+    if let Some(short) = arg.get_short() {
+        syntax.push_str(&format!("-{short}"));
+    }
+    if let Some(long) = arg.get_long() {
+        if !syntax.is_empty() {
+            syntax.push_str(", ");
+        }
+        syntax.push_str(&format!("--{long}"));
+    }
+    if arg.get_action().takes_values() {
+        syntax.push_str(&format!(" <{}>", value_name()));
+    }
+    syntax
+}
+
+/// The help text with the notes `--help` appends: choices, default, and variable.
+fn help(arg: &Arg) -> String {
+    let mut help = arg
+        .get_long_help()
+        .or(arg.get_help())
+        .map(|help| help.to_string().trim().to_string())
+        .unwrap_or_default();
+    let mut note = |text: String| {
+        if !help.is_empty() {
+            help.push(' ');
+        }
+        help.push_str(&format!("[{text}]"));
+    };
+    let choices: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    if !choices.is_empty() && arg.get_action().takes_values() {
+        note(format!("possible values: {}", choices.join(", ")));
+    }
+    let defaults: Vec<String> = arg
+        .get_default_values()
+        .iter()
+        .map(|value| value.to_string_lossy().into_owned())
+        .collect();
+    if !defaults.is_empty() && arg.get_action().takes_values() {
+        note(format!("default: {}", defaults.join(",")));
+    }
+    if let Some(env) = arg.get_env() {
+        note(format!("env: {}", env.to_string_lossy()));
+    }
+    help
+}
+
+fn man_page(page: &Page) -> String {
+    let name = page.path.join("-");
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        ".TH {} 1 \"\" \"{} {}\" \"User Commands\"",
+        name.to_uppercase(),
+        page.path[0],
+        env!("CARGO_PKG_VERSION")
+    );
+    let _ = writeln!(out, ".SH NAME\n{} \\- {}", roff(&name), roff(&page.about));
+    let _ = writeln!(out, ".SH SYNOPSIS\n\\fB{}\\fR", roff(&page.usage));
+    let _ = writeln!(
+        out,
+        ".SH DESCRIPTION\n{}",
+        roff(page.description.as_deref().unwrap_or(&page.about))
+    );
+    for (title, entries) in [("ARGUMENTS", &page.arguments), ("OPTIONS", &page.options)] {
+        if entries.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, ".SH {title}");
+        for entry in entries {
+            let _ = writeln!(
+                out,
+                ".TP\n\\fB{}\\fR\n{}",
+                roff(&entry.syntax),
+                roff(&entry.help)
+            );
+        }
+    }
+    if !page.subcommands.is_empty() {
+        let _ = writeln!(out, ".SH COMMANDS");
+        for (sub, about) in &page.subcommands {
+            let _ = writeln!(
+                out,
+                ".TP\n\\fB{}\\-{}\\fR(1)\n{}",
+                roff(&name),
+                roff(sub),
+                roff(about)
+            );
+        }
+    }
+    if let Some(examples) = &page.examples {
+        let _ = writeln!(out, ".SH EXAMPLES\n.nf\n{}\n.fi", roff(examples));
+    }
+    if page.path.len() > 1 {
+        let parent = page.path[..page.path.len() - 1].join("-");
+        let _ = writeln!(out, ".SH SEE ALSO\n\\fB{}\\fR(1)", roff(&parent));
+    }
+    out
+}
+
+/// Escape text for roff: backslashes and hyphens, and lines that would
+/// otherwise start a request.
+fn roff(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{line}")
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn markdown_reference(pages: &[Page]) -> String {
+    let mut out = String::new();
+    for page in pages {
+        let title = page.path.join(" ");
+        let level = if page.path.len() == 1 { "#" } else { "##" };
+        let _ = writeln!(out, "{level} {title}\n");
+        let _ = writeln!(
+            out,
+            "{}\n",
+            page.description.as_deref().unwrap_or(&page.about)
+        );
+        let _ = writeln!(out, "```\n{}\n```\n", page.usage);
+        for (title, entries) in [("Arguments", &page.arguments), ("Options", &page.options)] {
+            if entries.is_empty() {
+                continue;
+            }
+            let _ = writeln!(out, "**{title}:**\n");
+            for entry in entries {
+                let _ = writeln!(
+                    out,
+                    "- `{}`: {}",
+                    entry.syntax,
+                    entry.help.replace('\n', " ")
+                );
+            }
+            out.push('\n');
+        }
+        if !page.subcommands.is_empty() {
+            let _ = writeln!(out, "**Commands:**\n");
+            for (sub, about) in &page.subcommands {
+                let anchor = format!("{title} {sub}").replace(' ', "-");
+                let _ = writeln!(out, "- [`{sub}`](#{anchor}): {about}");
+            }
+            out.push('\n');
+        }
+        if let Some(examples) = &page.examples {
+            let _ = writeln!(out, "**Examples:**\n\n```\n{examples}\n```\n");
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
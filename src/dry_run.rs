@@ -0,0 +1,144 @@
+use colored::Colorize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn disable() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Print an action that would have been performed.
+pub fn announce(action: &str) {
+    println!("{} {}", "[dry-run]".yellow(), action);
+}
+
+/// Whether a git invocation only reads repository state. Anything not known to be
+/// read-only is treated as a mutation and skipped in dry-run mode.
+pub fn is_read_only_git(args: &[&str]) -> bool {
+    let mut rest = args;
+    while let [flag, _, tail @ ..] = rest
+        && (*flag == "-C" || *flag == "-c")
+    {
+        rest = tail;
+    }
+    let Some((subcommand, rest)) = rest.split_first() else {
+        return true;
+    };
+
+    match *subcommand {
+        "rev-parse" | "show-ref" | "status" | "log" | "diff" | "ls-files" | "rev-list"
+        | "merge-base" | "for-each-ref" | "cat-file" | "describe" | "show" | "version"
+        | "count-objects" => true,
+        // `stash create` only writes an unreferenced commit object
+        "stash" => matches!(
+            rest.first(),
+            Some(&"create") | Some(&"list") | Some(&"show")
+        ),
+        "branch" => rest.iter().all(|arg| arg.starts_with('-')),
+        "worktree" | "sparse-checkout" => rest.first() == Some(&"list"),
+        "remote" => matches!(rest.first(), Some(&"get-url") | Some(&"-v") | None),
+        "symbolic-ref" => rest.iter().filter(|arg| !arg.starts_with('-')).count() <= 1,
+        "config" => rest
+            .iter()
+            .any(|arg| arg.starts_with("--get") || *arg == "--list"),
+        "lfs" => rest.first() == Some(&"version"),
+        _ => false,
+    }
+}
+
+/// Human-readable summary of the differences between two serialized states.
+pub fn describe_state_changes(old: &Value, new: &Value) -> Vec<String> {
+    let empty = serde_json::Map::new();
+    let old_map = old.as_object().unwrap_or(&empty);
+    let new_map = new.as_object().unwrap_or(&empty);
+    let mut changes = Vec::new();
+
+    let old_worktrees = old_map.get("worktrees").and_then(Value::as_object);
+    let new_worktrees = new_map.get("worktrees").and_then(Value::as_object);
+    let old_worktrees = old_worktrees.unwrap_or(&empty);
+    let new_worktrees = new_worktrees.unwrap_or(&empty);
+
+    let mut keys: Vec<&String> = old_worktrees.keys().chain(new_worktrees.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        match (old_worktrees.get(key), new_worktrees.get(key)) {
+            (None, Some(entry)) => changes.push(format!(
+                "add worktree '{}' ({})",
+                key,
+                entry["path"].as_str().unwrap_or_default()
+            )),
+            (Some(_), None) => changes.push(format!("remove worktree '{key}'")),
+            (Some(before), Some(after)) if before != after => {
+                changes.push(format!("update worktree '{key}'"))
+            }
+            _ => {}
+        }
+    }
+
+    let mut fields: Vec<&String> = old_map.keys().chain(new_map.keys()).collect();
+    fields.sort();
+    fields.dedup();
+    for field in fields {
+        if field != "worktrees" && old_map.get(field) != new_map.get(field) {
+            changes.push(format!("update '{field}'"));
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn classifies_git_commands() {
+        assert!(is_read_only_git(&["rev-parse", "--show-toplevel"]));
+        assert!(is_read_only_git(&["-C", "/repo", "worktree", "list"]));
+        assert!(is_read_only_git(&["branch", "--merged"]));
+        assert!(is_read_only_git(&[
+            "symbolic-ref",
+            "refs/remotes/origin/HEAD"
+        ]));
+        assert!(!is_read_only_git(&[
+            "-C", "/repo", "worktree", "add", "../x", "x"
+        ]));
+        assert!(!is_read_only_git(&["branch", "-d", "feature"]));
+        assert!(!is_read_only_git(&["branch", "feature"]));
+        assert!(!is_read_only_git(&["fetch", "origin"]));
+        assert!(!is_read_only_git(&["worktree", "prune"]));
+    }
+
+    #[test]
+    fn describes_worktree_and_setting_changes() {
+        let old = json!({
+            "worktrees": { "repo/a": { "path": "/a" }, "repo/b": { "path": "/b" } },
+            "editor": "vim"
+        });
+        let new = json!({
+            "worktrees": { "repo/b": { "path": "/b2" }, "repo/c": { "path": "/c" } },
+            "editor": "vim",
+            "agent": "codex"
+        });
+        assert_eq!(
+            describe_state_changes(&old, &new),
+            vec![
+                "remove worktree 'repo/a'",
+                "update worktree 'repo/b'",
+                "add worktree 'repo/c' (/c)",
+                "update 'agent'",
+            ]
+        );
+    }
+}
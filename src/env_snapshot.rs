@@ -0,0 +1,219 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::agent::agent_version;
+use crate::error::{ErrorCategory, WithCategory};
+use crate::git;
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::utils::resolve_agent_command;
+
+// Variables whose name contains one of these are recorded without their value
+const SECRET_MARKERS: &[&str] = &[
+    "KEY",
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "CREDENTIAL",
+    "AUTH",
+    "COOKIE",
+];
+pub const REDACTED: &str = "<redacted>";
+// Shell bookkeeping that says nothing about the run
+const SKIPPED: &[&str] = &["_", "PWD", "OLDPWD", "SHLVL"];
+
+/// What an agent ran with: its command, the environment, and the code it saw,
+/// enough to run it again the same way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvSnapshot {
+    pub captured_at: DateTime<Utc>,
+    // State key of the worktree: "{repo_name}/{worktree_name}"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree: Option<String>,
+    // Directory the agent ran in
+    pub dir: PathBuf,
+    // Agent command line, program first
+    pub command: Vec<String>,
+    // Input the agent was given on stdin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdin: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    // Whether the worktree had uncommitted changes
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dirty: bool,
+    // Secrets keep their name with the value REDACTED
+    pub env: BTreeMap<String, String>,
+}
+
+impl EnvSnapshot {
+    /// Snapshot `command` about to run in `dir` with this process's
+    /// environment plus `extra_env`.
+    pub fn capture(
+        worktree: Option<&WorktreeInfo>,
+        dir: &Path,
+        command: Vec<String>,
+        extra_env: &[(String, String)],
+    ) -> Self {
+        let env = std::env::vars()
+            .chain(extra_env.iter().cloned())
+            .filter(|(name, _)| !SKIPPED.contains(&name.as_str()))
+            .map(|(name, value)| {
+                let value = if is_secret(&name) {
+                    REDACTED.to_string()
+                } else {
+                    value
+                };
+                (name, value)
+            })
+            .collect();
+        EnvSnapshot {
+            captured_at: Utc::now(),
+            worktree: worktree.map(|info| XlaudeState::make_key(&info.repo_name, &info.name)),
+            dir: dir.to_path_buf(),
+            agent_version: command
+                .first()
+                .and_then(|program| agent_version(program).flatten()),
+            git_sha: git::head_sha(dir),
+            branch: git::branch_in(dir),
+            dirty: git::prompt_status(dir).is_some_and(|status| status.dirty),
+            command,
+            stdin: None,
+            env,
+        }
+    }
+
+    /// Snapshot the configured agent for the worktree containing the current
+    /// directory, or for the directory itself outside managed worktrees.
+    pub fn capture_here() -> Result<Self> {
+        let state = XlaudeState::load()?;
+        let dir = std::env::current_dir().context("Failed to read the current directory")?;
+        let worktree = state.worktree_containing(&dir);
+        Self::for_worktree(&state, worktree, &dir)
+    }
+
+    /// Snapshot the agent `worktree` is configured to launch.
+    pub fn for_worktree(
+        state: &XlaudeState,
+        worktree: Option<&WorktreeInfo>,
+        dir: &Path,
+    ) -> Result<Self> {
+        let (program, mut args) =
+            resolve_agent_command(worktree.map(|info| info.repo_name.as_str()))?;
+        if let Some(info) = worktree {
+            args.extend(state.agent_args_for(info)?);
+        }
+        let command = std::iter::once(program).chain(args).collect();
+        Ok(Self::capture(worktree, dir, command, &[]))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).with_category(ErrorCategory::NotFound, || {
+            format!("Failed to read {}", path.display())
+        })?;
+        serde_json::from_str(&content).with_category(ErrorCategory::Usage, || {
+            format!("{} is not an environment snapshot", path.display())
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if crate::dry_run::is_enabled() {
+            crate::dry_run::announce(&format!("write {}", path.display()));
+            return Ok(());
+        }
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize the snapshot")?;
+        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// The recorded variables whose values are known.
+    pub fn variables(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.env.iter().filter(|(_, value)| *value != REDACTED)
+    }
+
+    pub fn redacted(&self) -> usize {
+        self.env.len() - self.variables().count()
+    }
+
+    /// How the agent and the checkout differ from when the snapshot was taken.
+    pub fn drift(&self) -> Vec<String> {
+        let mut drift = Vec::new();
+        if let Some(program) = self.command.first() {
+            let now = agent_version(program).flatten();
+            if self.agent_version.is_some() && now != self.agent_version {
+                drift.push(format!(
+                    "{program} is now {}, was {}",
+                    now.as_deref().unwrap_or("unavailable"),
+                    self.agent_version.as_deref().unwrap_or_default()
+                ));
+            }
+        }
+        if let Some(sha) = &self.git_sha {
+            match git::head_sha(&self.dir) {
+                Some(now) if now == *sha => {}
+                now => drift.push(format!(
+                    "HEAD is at {}, was {} (git checkout {sha} to match)",
+                    now.as_deref().map_or("nothing", short_sha),
+                    short_sha(sha)
+                )),
+            }
+        }
+        if self.dirty {
+            drift.push("the worktree had uncommitted changes".to_string());
+        }
+        drift
+    }
+}
+
+/// Where `run` keeps the snapshot of a task: next to its log.
+pub fn path_for_log(log_path: &Path) -> PathBuf {
+    log_path.with_extension("env.json")
+}
+
+fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    SECRET_MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(12)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_recorded_without_their_values() {
+        let snapshot = EnvSnapshot::capture(
+            None,
+            Path::new("/nonexistent"),
+            vec!["agent".to_string()],
+            &[
+                ("ANTHROPIC_API_KEY".to_string(), "sk-ant-123".to_string()),
+                ("github_token".to_string(), "ghp_456".to_string()),
+                ("XLAUDE_MODEL".to_string(), "opus".to_string()),
+            ],
+        );
+        assert_eq!(snapshot.env["ANTHROPIC_API_KEY"], REDACTED);
+        assert_eq!(snapshot.env["github_token"], REDACTED);
+        assert_eq!(snapshot.env["XLAUDE_MODEL"], "opus");
+        assert!(
+            snapshot
+                .variables()
+                .all(|(name, _)| name != "ANTHROPIC_API_KEY")
+        );
+        assert_eq!(
+            path_for_log(Path::new("/logs/task-7.log")),
+            Path::new("/logs/task-7.env.json")
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Broad failure classes, each mapped to its own process exit code so wrapper
+/// scripts can tell a missing worktree from a crashed agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    General,
+    NotFound,
+    DirtyWorktree,
+    AgentFailure,
+    Conflict,
+    Git,
+    Config,
+    Usage,
+}
+
+impl ErrorCategory {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorCategory::General => 1,
+            ErrorCategory::NotFound => 2,
+            ErrorCategory::DirtyWorktree => 3,
+            ErrorCategory::AgentFailure => 4,
+            ErrorCategory::Conflict => 5,
+            ErrorCategory::Git => 6,
+            ErrorCategory::Config => 7,
+            ErrorCategory::Usage => 8,
+        }
+    }
+
+    /// Category of an error, taken from the outermost categorized layer of its chain.
+    pub fn of(err: &anyhow::Error) -> Self {
+        err.downcast_ref::<CategorizedError>()
+            .map(|categorized| categorized.category)
+            .unwrap_or(ErrorCategory::General)
+    }
+}
+
+#[derive(Debug)]
+pub struct CategorizedError {
+    pub category: ErrorCategory,
+    message: String,
+    // Replaces the category's exit code, e.g. with the code an agent exited with
+    exit_code: Option<i32>,
+}
+
+impl fmt::Display for CategorizedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CategorizedError {}
+
+pub fn categorized(category: ErrorCategory, message: impl fmt::Display) -> anyhow::Error {
+    anyhow::Error::new(CategorizedError {
+        category,
+        message: message.to_string(),
+        exit_code: None,
+    })
+}
+
+/// A categorized error that makes xlaude exit with `exit_code` instead of the
+/// category's code.
+pub fn with_exit_code(
+    category: ErrorCategory,
+    exit_code: i32,
+    message: impl fmt::Display,
+) -> anyhow::Error {
+    anyhow::Error::new(CategorizedError {
+        category,
+        message: message.to_string(),
+        exit_code: Some(exit_code),
+    })
+}
+
+/// The failed exit status of a program xlaude ran in its place, such as a
+/// plugin, which has already reported the failure itself.
+#[derive(Debug)]
+struct ExitStatus(i32);
+
+impl fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "exited with code {}", self.0)
+    }
+}
+
+impl std::error::Error for ExitStatus {}
+
+/// Make xlaude exit with `code` without printing an error.
+pub fn exit_status(code: i32) -> anyhow::Error {
+    anyhow::Error::new(ExitStatus(code))
+}
+
+/// Process exit code for an error: an explicit code anywhere in the chain wins
+/// over the category's code.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    if let Some(ExitStatus(code)) = err.downcast_ref::<ExitStatus>() {
+        return *code;
+    }
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<CategorizedError>())
+        .find_map(|categorized| categorized.exit_code)
+        .unwrap_or_else(|| ErrorCategory::of(err).exit_code())
+}
+
+/// Bail out of the current function with a categorized error.
+#[macro_export]
+macro_rules! bail_with {
+    ($category:expr, $($arg:tt)*) => {
+        return Err($crate::error::categorized($category, format!($($arg)*)))
+    };
+}
+
+/// Attach a category (and message) to a failure, like `anyhow::Context::with_context`.
+pub trait WithCategory<T> {
+    fn with_category<M, F>(self, category: ErrorCategory, message: F) -> anyhow::Result<T>
+    where
+        M: fmt::Display,
+        F: FnOnce() -> M;
+}
+
+impl<T, E> WithCategory<T> for Result<T, E>
+where
+    E: Into<anyhow::Error>,
+{
+    fn with_category<M, F>(self, category: ErrorCategory, message: F) -> anyhow::Result<T>
+    where
+        M: fmt::Display,
+        F: FnOnce() -> M,
+    {
+        self.map_err(|err| {
+            err.into().context(CategorizedError {
+                category,
+                message: message().to_string(),
+                exit_code: None,
+            })
+        })
+    }
+}
+
+impl<T> WithCategory<T> for Option<T> {
+    fn with_category<M, F>(self, category: ErrorCategory, message: F) -> anyhow::Result<T>
+    where
+        M: fmt::Display,
+        F: FnOnce() -> M,
+    {
+        self.ok_or_else(|| categorized(category, message()))
+    }
+}
+
+#[derive(Serialize)]
+struct JsonError<'a> {
+    code: i32,
+    category: ErrorCategory,
+    message: &'a str,
+}
+
+/// Print an error to stderr, as JSON when `json` is set, and return its exit code.
+pub fn report(err: &anyhow::Error, json: bool) -> i32 {
+    let category = ErrorCategory::of(err);
+    let code = exit_code(err);
+    if err.is::<ExitStatus>() {
+        return code;
+    }
+    if json {
+        let message = format!("{err:#}");
+        let payload = JsonError {
+            code,
+            category,
+            message: &message,
+        };
+        match serde_json::to_string(&payload) {
+            Ok(line) => eprintln!("{line}"),
+            Err(_) => eprintln!("{} {err:?}", crate::tr!("Error:")),
+        }
+    } else {
+        eprintln!("{} {err:?}", crate::tr!("Error:"));
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    fn missing() -> anyhow::Result<()> {
+        bail_with!(ErrorCategory::NotFound, "Worktree '{}' not found", "x");
+    }
+
+    #[test]
+    fn category_survives_extra_context() {
+        let err = missing().context("while opening").unwrap_err();
+        assert_eq!(ErrorCategory::of(&err), ErrorCategory::NotFound);
+        assert_eq!(format!("{err:#}"), "while opening: Worktree 'x' not found");
+    }
+
+    #[test]
+    fn with_category_wraps_results_and_options() {
+        let io: Result<(), std::io::Error> = Err(std::io::Error::other("boom"));
+        let err = io
+            .with_category(ErrorCategory::AgentFailure, || "Failed to launch agent")
+            .unwrap_err();
+        assert_eq!(ErrorCategory::of(&err), ErrorCategory::AgentFailure);
+        assert_eq!(format!("{err:#}"), "Failed to launch agent: boom");
+
+        let err = None::<u8>
+            .with_category(ErrorCategory::Conflict, || "taken")
+            .unwrap_err();
+        assert_eq!(ErrorCategory::of(&err).exit_code(), 5);
+
+        let plain = anyhow::anyhow!("plain");
+        assert_eq!(ErrorCategory::of(&plain), ErrorCategory::General);
+    }
+
+    #[test]
+    fn explicit_exit_code_survives_wrapping() {
+        let inner: anyhow::Result<()> = Err(with_exit_code(
+            ErrorCategory::AgentFailure,
+            130,
+            "Agent was terminated by SIGINT",
+        ));
+        let err = inner
+            .with_category(ErrorCategory::General, || "while opening")
+            .unwrap_err();
+        assert_eq!(ErrorCategory::of(&err), ErrorCategory::General);
+        assert_eq!(exit_code(&err), 130);
+        assert_eq!(exit_code(&missing().unwrap_err()), 2);
+    }
+}
@@ -1,8 +1,26 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use crate::bail_with;
+use crate::dry_run;
+use crate::error::ErrorCategory;
+
 pub fn execute_git(args: &[&str]) -> Result<String> {
+    if dry_run::is_enabled() && !dry_run::is_read_only_git(args) {
+        let mut command = vec!["git".to_string()];
+        if args.first() != Some(&"-C") {
+            // Commands without -C run in the current directory; make that explicit
+            let cwd = std::env::current_dir().context("Failed to get current directory")?;
+            command.extend(["-C".to_string(), cwd.display().to_string()]);
+        }
+        command.extend(args.iter().map(|arg| arg.to_string()));
+        dry_run::announce(&shell_words::join(&command));
+        return Ok(String::new());
+    }
+
     let output = Command::new("git")
         .args(args)
         .output()
@@ -12,7 +30,7 @@ pub fn execute_git(args: &[&str]) -> Result<String> {
         Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Git command failed: {}", stderr);
+        bail_with!(ErrorCategory::Git, "Git command failed: {}", stderr);
     }
 }
 
@@ -62,43 +80,73 @@ pub fn extract_repo_name_from_url(url: &str) -> Option<String> {
 }
 
 fn get_repo_name_from_directory() -> Result<String> {
-    // For worktrees, we need to get the main repository path
-    // Try to get the common git directory first (which points to main repo for worktrees)
-    let git_common_dir = execute_git(&["rev-parse", "--git-common-dir"])?;
-    let git_dir = execute_git(&["rev-parse", "--git-dir"])?;
-
-    let repo_path = if git_common_dir != git_dir {
-        // We're in a worktree - git-common-dir points to main repo's .git
-        let path = Path::new(&git_common_dir);
-        if path.file_name().is_some_and(|n| n == ".git") {
-            // Get the parent directory which is the main repo
-            path.parent()
-                .and_then(|p| p.to_str())
-                .map(|s| s.to_string())
-                .context("Failed to get main repository path")?
-        } else {
-            // git-common-dir doesn't end with .git, use it directly
-            git_common_dir
-        }
-    } else {
-        // Not in a worktree, use toplevel
-        execute_git(&["rev-parse", "--show-toplevel"])?
-    };
+    repo_name_from_dir(&main_repo_dir()?).context("Failed to get repository name")
+}
 
-    let path = Path::new(&repo_path);
-    path.file_name()
-        .and_then(|n| n.to_str())
-        .map(std::string::ToString::to_string)
-        .context("Failed to get repository name")
+/// Repository name for a repository directory; a bare `repo.git` is `repo`.
+pub fn repo_name_from_dir(dir: &Path) -> Option<String> {
+    let name = dir.file_name()?.to_str()?;
+    Some(name.strip_suffix(".git").unwrap_or(name).to_string())
+}
+
+/// Directory of the repository the current directory belongs to: its main
+/// checkout, or the repository itself when it is bare. Linked worktrees
+/// resolve to it as well.
+pub fn main_repo_dir() -> Result<PathBuf> {
+    // Relative at the top of the main checkout or bare repository, absolute elsewhere
+    let common_dir = execute_git(&["rev-parse", "--git-common-dir"])?;
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let common_dir: PathBuf = cwd.join(common_dir).components().collect();
+    match repo_dir_for_common_dir(common_dir) {
+        Some(dir) => Ok(dir),
+        // The git directory lives elsewhere (--separate-git-dir)
+        None => Ok(PathBuf::from(execute_git(&[
+            "rev-parse",
+            "--show-toplevel",
+        ])?)),
+    }
+}
+
+fn repo_dir_for_common_dir(common_dir: PathBuf) -> Option<PathBuf> {
+    if common_dir.file_name().is_some_and(|name| name == ".git") {
+        return common_dir.parent().map(Path::to_path_buf);
+    }
+    is_bare_repository(&common_dir).then_some(common_dir)
+}
+
+pub fn is_bare_repository(repo_dir: &Path) -> bool {
+    Command::new("git")
+        .args(["rev-parse", "--is-bare-repository"])
+        .current_dir(repo_dir)
+        .output()
+        .is_ok_and(|output| output.status.success() && output.stdout.starts_with(b"true"))
 }
 
 pub fn get_current_branch() -> Result<String> {
     execute_git(&["symbolic-ref", "--short", "HEAD"])
 }
 
+/// Branch checked out in the worktree at `path`, or `None` when its HEAD is
+/// detached or cannot be read.
+pub fn branch_in(path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .current_dir(path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!branch.is_empty()).then_some(branch)
+}
+
 pub fn get_default_branch() -> Result<String> {
+    get_default_branch_of("origin")
+}
+
+/// Default branch of `remote`, falling back to "main" when it cannot be told.
+pub fn get_default_branch_of(remote: &str) -> Result<String> {
     // Try to get the default branch from remote HEAD
-    if let Ok(output) = execute_git(&["remote", "show", "origin"]) {
+    if let Ok(output) = execute_git(&["remote", "show", remote]) {
         for line in output.lines() {
             if let Some(branch) = line.strip_prefix("  HEAD branch: ") {
                 return Ok(branch.trim().to_string());
@@ -107,8 +155,8 @@ pub fn get_default_branch() -> Result<String> {
     }
 
     // Fallback: try to get HEAD from symbolic-ref
-    if let Ok(output) = execute_git(&["symbolic-ref", "refs/remotes/origin/HEAD"])
-        && let Some(branch) = output.strip_prefix("refs/remotes/origin/")
+    if let Ok(output) = execute_git(&["symbolic-ref", &format!("refs/remotes/{remote}/HEAD")])
+        && let Some(branch) = output.strip_prefix(&format!("refs/remotes/{remote}/"))
     {
         return Ok(branch.to_string());
     }
@@ -171,6 +219,21 @@ pub fn has_unpushed_commits() -> bool {
     execute_git(&["log", "@{u}.."]).is_ok_and(|output| !output.is_empty())
 }
 
+/// Whether HEAD has commits that no branch, tag, or remote-tracking ref
+/// contains, as happens after committing on a detached HEAD.
+pub fn has_commits_outside_refs() -> bool {
+    execute_git(&[
+        "rev-list",
+        "-1",
+        "HEAD",
+        "--not",
+        "--branches",
+        "--tags",
+        "--remotes",
+    ])
+    .is_ok_and(|output| !output.is_empty())
+}
+
 pub fn is_in_worktree() -> Result<bool> {
     // Check if we're in a worktree by looking for .git file (not directory)
     let git_path = Path::new(".git");
@@ -210,6 +273,211 @@ pub fn list_worktrees() -> Result<Vec<PathBuf>> {
     Ok(worktrees)
 }
 
+/// Remote-tracking ref of `remote`'s default branch (e.g. `upstream/main`) as
+/// known locally, without contacting the remote.
+pub fn remote_default_ref(worktree_path: &Path, remote: &str) -> Option<String> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(worktree_path)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    git(&[
+        "symbolic-ref",
+        "--short",
+        &format!("refs/remotes/{remote}/HEAD"),
+    ])
+    .or_else(|| {
+        ["main", "master"].into_iter().find_map(|branch| {
+            git(&[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("refs/remotes/{remote}/{branch}"),
+            ])
+            .map(|_| format!("{remote}/{branch}"))
+        })
+    })
+}
+
+/// The branch a worktree's work is compared with: the base remote's default
+/// branch, or a local main/master (other than `branch`, the worktree's own)
+/// when the repository has no such remote.
+pub fn base_ref(worktree_path: &Path, branch: &str, remote: &str) -> Option<String> {
+    remote_default_ref(worktree_path, remote).or_else(|| {
+        ["main", "master"]
+            .into_iter()
+            .filter(|candidate| *candidate != branch)
+            .find(|candidate| {
+                Command::new("git")
+                    .args([
+                        "rev-parse",
+                        "--verify",
+                        "--quiet",
+                        &format!("refs/heads/{candidate}"),
+                    ])
+                    .current_dir(worktree_path)
+                    .output()
+                    .is_ok_and(|output| output.status.success())
+            })
+            .map(str::to_string)
+    })
+}
+
+/// The commit where HEAD forked from `base`, so that a diff against it shows
+/// only the worktree's own work, committed or not.
+pub fn merge_base(worktree_path: &Path, base: &str) -> Option<String> {
+    Command::new("git")
+        .args(["merge-base", base, "HEAD"])
+        .current_dir(worktree_path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Commits of HEAD missing from `base`, and commits of `base` missing from HEAD.
+pub fn ahead_behind(worktree_path: &Path, base: &str) -> Option<(usize, usize)> {
+    let output = Command::new("git")
+        .args([
+            "rev-list",
+            "--left-right",
+            "--count",
+            &format!("{base}...HEAD"),
+        ])
+        .current_dir(worktree_path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let counts = String::from_utf8_lossy(&output.stdout);
+    let (behind, ahead) = counts.trim().split_once('\t')?;
+    Some((ahead.parse().ok()?, behind.parse().ok()?))
+}
+
+/// Files a worktree changed since `since`, committed or not (untracked files
+/// excluded).
+pub fn changed_paths(worktree_path: &Path, since: &str) -> Option<Vec<String>> {
+    let output = Command::new("git")
+        .args(["-c", "core.quotePath=false", "diff", "--name-only", since])
+        .current_dir(worktree_path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Files that would conflict if `left` and `right` were merged. The merge is
+/// done in memory (`git merge-tree --write-tree`, git 2.38 or later), so
+/// neither the index nor any working tree is touched.
+pub fn merge_conflicts(repo_path: &Path, left: &str, right: &str) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args([
+            "merge-tree",
+            "--write-tree",
+            "--name-only",
+            "--no-messages",
+            left,
+            right,
+        ])
+        .current_dir(repo_path)
+        .output()
+        .context("Failed to execute git command")?;
+    match output.status.code() {
+        Some(0) => Ok(Vec::new()),
+        // The first line is the tree with conflict markers; the files follow
+        Some(1) => {
+            let mut files: Vec<String> = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .skip(1)
+                .take_while(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect();
+            files.dedup();
+            Ok(files)
+        }
+        _ => bail_with!(
+            ErrorCategory::Git,
+            "git merge-tree failed for {left} and {right} (git 2.38 or later is needed): {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+    }
+}
+
+/// What a shell prompt shows about a worktree, from a single `git status`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PromptStatus {
+    pub dirty: bool,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+/// Uncommitted changes and the distance to the upstream branch, or `None`
+/// when git cannot tell.
+pub fn prompt_status(worktree_path: &Path) -> Option<PromptStatus> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain=v2", "--branch"])
+        .current_dir(worktree_path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(parse_prompt_status(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+fn parse_prompt_status(porcelain: &str) -> PromptStatus {
+    let mut status = PromptStatus::default();
+    for line in porcelain.lines() {
+        match line.strip_prefix("# branch.ab ") {
+            Some(counts) => {
+                for count in counts.split_whitespace() {
+                    if let Some(ahead) = count.strip_prefix('+') {
+                        status.ahead = ahead.parse().unwrap_or_default();
+                    } else if let Some(behind) = count.strip_prefix('-') {
+                        status.behind = behind.parse().unwrap_or_default();
+                    }
+                }
+            }
+            None if !line.starts_with('#') && !line.is_empty() => status.dirty = true,
+            None => {}
+        }
+    }
+    status
+}
+
+/// The commit checked out in the worktree at `path`.
+pub fn head_sha(path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let sha = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!sha.is_empty()).then_some(sha)
+}
+
+/// Commit time of the worktree's HEAD, or `None` when git cannot tell.
+pub fn last_commit_time(worktree_path: &Path) -> Option<DateTime<Utc>> {
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%cI"])
+        .current_dir(worktree_path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    DateTime::parse_from_rfc3339(String::from_utf8_lossy(&output.stdout).trim())
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
 pub fn update_submodules(worktree_path: &Path) -> Result<()> {
     // Check if submodules exist
     let gitmodules = worktree_path.join(".gitmodules");
@@ -231,6 +499,191 @@ pub fn update_submodules(worktree_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Whether the checkout tracks files through Git LFS.
+pub fn uses_lfs(worktree_path: &Path) -> bool {
+    std::fs::read_to_string(worktree_path.join(".gitattributes"))
+        .is_ok_and(|content| content.contains("filter=lfs"))
+}
+
+pub fn lfs_pull(worktree_path: &Path) -> Result<()> {
+    let path = worktree_path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+    execute_git(&["-C", path, "lfs", "version"]).context("git-lfs is not installed")?;
+    execute_git(&["-C", path, "lfs", "pull"]).context("Failed to pull LFS objects")?;
+    Ok(())
+}
+
+/// Stash the uncommitted changes of a worktree, untracked files included, or
+/// only those of `pathspec` when given. Returns the stash commit, which
+/// `pop_stash` takes to put them back.
+pub fn stash_changes(
+    worktree_path: &Path,
+    message: &str,
+    pathspec: Option<&str>,
+) -> Result<String> {
+    let path = worktree_path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+    let mut args = vec![
+        "-C",
+        path,
+        "stash",
+        "push",
+        "--include-untracked",
+        "-m",
+        message,
+    ];
+    if let Some(pathspec) = pathspec {
+        args.extend(["--", pathspec]);
+    }
+    execute_git(&args).context("Failed to stash changes")?;
+    if dry_run::is_enabled() {
+        return Ok(String::new());
+    }
+    execute_git(&["-C", path, "rev-parse", "refs/stash"])
+}
+
+/// Apply a stash made by `stash_changes` to a worktree and drop it from the
+/// stash list. A stash that was dropped by hand is applied from its commit.
+pub fn pop_stash(worktree_path: &Path, stash: &str) -> Result<()> {
+    let path = worktree_path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+    let entries = execute_git(&["-C", path, "stash", "list", "--format=%H"])?;
+    match entries.lines().position(|sha| sha == stash) {
+        Some(index) => execute_git(&["-C", path, "stash", "pop", &format!("stash@{{{index}}}")]),
+        None => execute_git(&["-C", path, "stash", "apply", stash]),
+    }
+    .context("Failed to apply stashed changes")?;
+    Ok(())
+}
+
+/// Restrict a worktree added with `--no-checkout` to the given cone-mode
+/// directories, then populate it.
+pub fn apply_sparse_checkout(worktree_path: &Path, directories: &[String]) -> Result<()> {
+    let path = worktree_path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+    let mut args = vec!["-C", path, "sparse-checkout", "set", "--cone", "--"];
+    args.extend(directories.iter().map(String::as_str));
+    execute_git(&args).context("Failed to configure sparse checkout")?;
+    execute_git(&["-C", path, "checkout"]).context("Failed to check out sparse worktree")?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorktreeHealth {
+    Healthy,
+    MissingDirectory,
+    // The `.git` link no longer resolves (e.g. the main repository moved)
+    BrokenLink(String),
+}
+
+pub fn check_worktree_health(worktree_path: &Path) -> WorktreeHealth {
+    if !worktree_path.exists() {
+        return WorktreeHealth::MissingDirectory;
+    }
+
+    let Some(path) = worktree_path.to_str() else {
+        return WorktreeHealth::BrokenLink("Worktree path contains invalid UTF-8".to_string());
+    };
+    match execute_git(&["-C", path, "rev-parse", "--git-dir"]) {
+        Ok(_) => WorktreeHealth::Healthy,
+        Err(err) => WorktreeHealth::BrokenLink(
+            err.to_string()
+                .trim_start_matches("Git command failed: ")
+                .trim()
+                .to_string(),
+        ),
+    }
+}
+
+/// Locate the main repository of a worktree: through git's common dir when the
+/// link still works, otherwise the `<parent>/<repo_name>` sibling xlaude uses
+/// (or `<parent>/<repo_name>.git` for a bare repository).
+pub fn find_main_repo(worktree_path: &Path, repo_name: &str) -> Option<PathBuf> {
+    if let Some(path) = worktree_path.to_str()
+        && let Ok(common_dir) = execute_git(&[
+            "-C",
+            path,
+            "rev-parse",
+            "--path-format=absolute",
+            "--git-common-dir",
+        ])
+        && let Some(repo_dir) = repo_dir_for_common_dir(PathBuf::from(common_dir))
+    {
+        return Some(repo_dir);
+    }
+
+    let parent = worktree_path.parent()?;
+    let sibling = parent.join(repo_name);
+    if sibling.join(".git").exists() {
+        return Some(sibling);
+    }
+    let bare = parent.join(format!("{repo_name}.git"));
+    is_bare_repository(&bare).then_some(bare)
+}
+
+/// Worktree of the repository at `repo_path`, the main checkout included, that
+/// has `branch` checked out. Git refuses to check a branch out twice.
+pub fn worktree_with_branch(repo_path: &Path, branch: &str) -> Option<PathBuf> {
+    let output =
+        execute_git(&["-C", repo_path.to_str()?, "worktree", "list", "--porcelain"]).ok()?;
+    let checked_out = format!("branch refs/heads/{branch}");
+    let mut current: Option<PathBuf> = None;
+    for line in output.lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            current = Some(PathBuf::from(path));
+        } else if line == checked_out {
+            return current;
+        }
+    }
+    None
+}
+
+/// Locked worktrees of the repository at `repo_path`, mapped to their lock reason
+/// (empty when none was given).
+pub fn locked_worktrees(repo_path: &Path) -> Result<HashMap<PathBuf, String>> {
+    let repo = repo_path
+        .to_str()
+        .context("Repository path contains invalid UTF-8")?;
+    let output = execute_git(&["-C", repo, "worktree", "list", "--porcelain"])?;
+
+    let mut locked = HashMap::new();
+    let mut current: Option<PathBuf> = None;
+    for line in output.lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            current = Some(PathBuf::from(path));
+        } else if let Some(rest) = line.strip_prefix("locked")
+            && let Some(path) = &current
+        {
+            locked.insert(path.clone(), rest.trim().to_string());
+        }
+    }
+
+    Ok(locked)
+}
+
+/// Lock reason of a worktree if git has it locked.
+pub fn worktree_lock_reason(worktree_path: &Path, repo_name: &str) -> Option<String> {
+    let main_repo = find_main_repo(worktree_path, repo_name)?;
+    locked_worktrees(&main_repo).ok()?.remove(worktree_path)
+}
+
+/// Run `git worktree repair` from the main repository for the given worktree.
+pub fn repair_worktree(main_repo: &Path, worktree_path: &Path) -> Result<()> {
+    let main = main_repo
+        .to_str()
+        .context("Repository path contains invalid UTF-8")?;
+    let worktree = worktree_path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+    execute_git(&["-C", main, "worktree", "repair", worktree])
+        .context("Failed to repair worktree")?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -274,6 +727,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn prompt_status_reads_porcelain_v2() {
+        let clean = "# branch.oid abc\n# branch.head feature\n# branch.upstream origin/feature\n# branch.ab +2 -1\n";
+        assert_eq!(
+            parse_prompt_status(clean),
+            PromptStatus {
+                dirty: false,
+                ahead: 2,
+                behind: 1
+            }
+        );
+        let dirty = "# branch.head feature\n? notes.txt\n";
+        assert_eq!(
+            parse_prompt_status(dirty),
+            PromptStatus {
+                dirty: true,
+                ahead: 0,
+                behind: 0
+            }
+        );
+    }
+
     #[test]
     fn test_get_default_branch() {
         // This test will work based on the actual git repository it's run in
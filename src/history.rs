@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::state::{WorktreeInfo, XlaudeState, get_config_dir};
+use crate::time_window::TimeWindow;
+
+/// A worktree removed by `xlaude delete`. Deleted worktrees leave the state
+/// file, so this record is what reports know about them afterwards.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedWorktree {
+    pub deleted_at: DateTime<Utc>,
+    pub repo_name: String,
+    pub name: String,
+    pub branch: String,
+    // Merged into the main repository, directly or through a pull request
+    pub merged: bool,
+    pub branch_deleted: bool,
+}
+
+impl DeletedWorktree {
+    pub fn new(info: &WorktreeInfo, merged: bool, branch_deleted: bool) -> Self {
+        Self {
+            deleted_at: Utc::now(),
+            repo_name: info.repo_name.clone(),
+            name: info.name.clone(),
+            branch: info.branch.clone(),
+            merged,
+            branch_deleted,
+        }
+    }
+}
+
+pub fn record_deletion(record: &DeletedWorktree) -> Result<()> {
+    let path = get_deletions_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create log directory")?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .context("Failed to open deletion log")?;
+    let line = serde_json::to_string(record).context("Failed to serialize deletion record")?;
+    writeln!(file, "{line}").context("Failed to write deletion log")?;
+    Ok(())
+}
+
+/// Worktrees deleted inside `window`, oldest first. Unreadable lines are skipped.
+pub fn deletions_in(window: &TimeWindow) -> Result<Vec<DeletedWorktree>> {
+    let path = get_deletions_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).context("Failed to read deletion log")?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<DeletedWorktree>(line).ok())
+        .filter(|record| window.contains(Some(record.deleted_at)))
+        .collect())
+}
+
+pub fn get_deletions_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("logs").join("deletions.jsonl"))
+}
+
+// Launches kept in the open log; older ones are dropped once it holds twice as many
+const MAX_OPEN_RECORDS: usize = 1000;
+
+/// An agent launched in a worktree, by `open` or anything built on it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenRecord {
+    pub opened_at: DateTime<Utc>,
+    // State key of the worktree, "{repo_name}/{worktree_name}"
+    pub key: String,
+    // Command line of the agent
+    pub agent: String,
+}
+
+pub fn record_open(key: &str, agent: &str) -> Result<()> {
+    if crate::dry_run::is_enabled() {
+        return Ok(());
+    }
+    let path = get_opens_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create log directory")?;
+    }
+    let record = OpenRecord {
+        opened_at: Utc::now(),
+        key: key.to_string(),
+        agent: agent.to_string(),
+    };
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .context("Failed to open the open log")?;
+    let line = serde_json::to_string(&record).context("Failed to serialize open record")?;
+    writeln!(file, "{line}").context("Failed to write the open log")?;
+
+    let content = fs::read_to_string(&path).context("Failed to read the open log")?;
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.len() > 2 * MAX_OPEN_RECORDS {
+        let kept = lines[lines.len() - MAX_OPEN_RECORDS..].join("\n");
+        fs::write(&path, kept + "\n").context("Failed to trim the open log")?;
+    }
+    Ok(())
+}
+
+/// When an agent was last launched in each worktree, by state key. A missing
+/// or unreadable log counts as no launches.
+pub fn last_opened() -> HashMap<String, DateTime<Utc>> {
+    let mut last = HashMap::new();
+    let Ok(content) = get_opens_path().and_then(|path| Ok(fs::read_to_string(path)?)) else {
+        return last;
+    };
+    for record in content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<OpenRecord>(line).ok())
+    {
+        let at = last.entry(record.key).or_insert(record.opened_at);
+        *at = (*at).max(record.opened_at);
+    }
+    last
+}
+
+/// The state's worktrees that are checked out, most recently opened first,
+/// then the ones never opened by repository and name.
+pub fn by_recent_use(state: &XlaudeState) -> Vec<(String, WorktreeInfo)> {
+    let last = last_opened();
+    let mut worktrees: Vec<(String, WorktreeInfo)> = state
+        .worktrees
+        .iter()
+        .filter(|(_, info)| info.archived.is_none())
+        .map(|(key, info)| (key.clone(), info.clone()))
+        .collect();
+    worktrees.sort_by(|(a, _), (b, _)| last.get(b).cmp(&last.get(a)).then_with(|| a.cmp(b)));
+    worktrees
+}
+
+pub fn get_opens_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("logs").join("opens.jsonl"))
+}
@@ -0,0 +1,209 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::dry_run;
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::utils::split_command_line;
+
+// How long a command waits on the event handler before stopping it
+const HANDLER_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    WorktreeCreated,
+    WorktreeAdded,
+    WorktreeRenamed,
+    WorktreeDeleted,
+    // `maintain` will archive the worktree on a later run unless it is used
+    WorktreeArchivePending,
+    WorktreeArchived,
+    AgentStarted,
+    AgentExited,
+    TaskStarted,
+    TaskFinished,
+}
+
+impl EventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventKind::WorktreeCreated => "worktree_created",
+            EventKind::WorktreeAdded => "worktree_added",
+            EventKind::WorktreeRenamed => "worktree_renamed",
+            EventKind::WorktreeDeleted => "worktree_deleted",
+            EventKind::WorktreeArchivePending => "worktree_archive_pending",
+            EventKind::WorktreeArchived => "worktree_archived",
+            EventKind::AgentStarted => "agent_started",
+            EventKind::AgentExited => "agent_exited",
+            EventKind::TaskStarted => "task_started",
+            EventKind::TaskFinished => "task_finished",
+        }
+    }
+}
+
+/// Document written to the `event_handler` command's stdin.
+#[derive(Debug, Serialize)]
+pub struct Event<'a> {
+    #[serde(rename = "type")]
+    pub kind: EventKind,
+    pub timestamp: DateTime<Utc>,
+    pub worktree: &'a WorktreeInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent: Option<&'a str>,
+    // Event-specific fields, e.g. `exit_code` for agent_exited
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub details: Value,
+}
+
+impl<'a> Event<'a> {
+    pub fn new(kind: EventKind, worktree: &'a WorktreeInfo) -> Self {
+        Self {
+            kind,
+            timestamp: Utc::now(),
+            worktree,
+            agent: None,
+            details: Value::Null,
+        }
+    }
+
+    pub fn agent(mut self, agent: &'a str) -> Self {
+        self.agent = Some(agent);
+        self
+    }
+
+    pub fn details(mut self, details: Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Send an event to the `event_handler` configured in `state`. Handler
+/// failures, including a handler still running after `HANDLER_TIMEOUT`, are
+/// reported as warnings and never fail the command that triggered the event.
+pub fn emit(state: &XlaudeState, event: Event<'_>) {
+    let Some(handler) = state.event_handler.as_deref() else {
+        return;
+    };
+    if dry_run::is_enabled() {
+        dry_run::announce(&format!(
+            "event_handler <- {} ({})",
+            event.kind.as_str(),
+            event.worktree.name
+        ));
+        return;
+    }
+    if let Err(err) = run_handler(handler, &event, HANDLER_TIMEOUT) {
+        eprintln!(
+            "{} event_handler failed for {}: {:#}",
+            "⚠️".yellow(),
+            event.kind.as_str(),
+            err
+        );
+    }
+}
+
+fn run_handler(handler: &str, event: &Event<'_>, timeout: Duration) -> Result<()> {
+    let payload = serde_json::to_string(event).context("Failed to serialize event")?;
+    let (program, args) = split_command_line(handler)?;
+
+    let mut cmd = Command::new(&program);
+    cmd.args(&args)
+        .env("XLAUDE_EVENT", event.kind.as_str())
+        .stdin(Stdio::piped())
+        // Keep stdout clean for commands with machine-readable output
+        .stdout(std::io::stderr());
+    if event.worktree.path.is_dir() {
+        cmd.current_dir(&event.worktree.path);
+    }
+
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to run '{program}'"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Written aside so a handler that never reads a large payload is still
+        // stopped at the deadline; exiting without reading it is not our error.
+        // The thread ends once the pipe closes, even if the handler left
+        // children holding it.
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(payload.as_bytes());
+        });
+    }
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child
+            .try_wait()
+            .context("Failed to wait for event handler")?
+        {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!("still running after {}s; stopped it", timeout.as_secs());
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    if !status.success() {
+        anyhow::bail!("exited with {}", status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    #[test]
+    fn serializes_event_payload() {
+        let info = WorktreeInfo::new(
+            "feat".into(),
+            "feat".into(),
+            PathBuf::from("/repo-feat"),
+            "repo".into(),
+        );
+        let event = Event::new(EventKind::AgentExited, &info)
+            .agent("claude")
+            .details(json!({ "exit_code": 0 }));
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["type"], "agent_exited");
+        assert_eq!(value["worktree"]["name"], "feat");
+        assert_eq!(value["agent"], "claude");
+        assert_eq!(value["details"]["exit_code"], 0);
+
+        let value = serde_json::to_value(Event::new(EventKind::WorktreeDeleted, &info)).unwrap();
+        assert!(value.get("agent").is_none());
+        assert!(value.get("details").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stops_a_handler_that_hangs() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = WorktreeInfo::new(
+            "feat".into(),
+            "feat".into(),
+            dir.path().to_path_buf(),
+            "repo".into(),
+        );
+        let event = Event::new(EventKind::WorktreeCreated, &info);
+        let started = Instant::now();
+        let err = run_handler("sleep 30", &event, Duration::from_millis(200)).unwrap_err();
+        assert!(err.to_string().contains("still running"), "{err:#}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        // A payload larger than the pipe holds, never read
+        let event = event.details(json!({ "log": "x".repeat(1 << 20) }));
+        let started = Instant::now();
+        let err = run_handler("sleep 30", &event, Duration::from_millis(200)).unwrap_err();
+        assert!(err.to_string().contains("still running"), "{err:#}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,519 @@
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// Overrides the `language` setting and the locale variables, e.g. "zh" or "en"
+pub const LANG_ENV: &str = "XLAUDE_LANG";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Language {
+    English,
+    Chinese,
+}
+
+static LANGUAGE: AtomicU8 = AtomicU8::new(Language::English as u8);
+
+impl Language {
+    /// The language of a locale or setting value such as "zh_CN.UTF-8" or "en".
+    /// Anything xlaude has no translations for is English.
+    pub fn from_locale(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+        Some(if value.to_ascii_lowercase().starts_with("zh") {
+            Language::Chinese
+        } else {
+            Language::English
+        })
+    }
+}
+
+/// Pick the language from `XLAUDE_LANG`, then the `language` setting, then
+/// `LC_ALL`, `LC_MESSAGES`, and `LANG`, like gettext.
+pub fn detect(configured: Option<&str>) -> Language {
+    let from_env = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .and_then(|value| Language::from_locale(&value))
+    };
+    from_env(LANG_ENV)
+        .or_else(|| configured.and_then(Language::from_locale))
+        .or_else(|| from_env("LC_ALL"))
+        .or_else(|| from_env("LC_MESSAGES"))
+        .or_else(|| from_env("LANG"))
+        .unwrap_or(Language::English)
+}
+
+pub fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    match LANGUAGE.load(Ordering::Relaxed) {
+        1 => Language::Chinese,
+        _ => Language::English,
+    }
+}
+
+/// Translate `message`, the English text, and fill in its `{}` placeholders
+/// with `args` in order. Translations may number them (`{0}`, `{1}`) to change
+/// the order. Messages without a translation are shown in English.
+pub fn translate(message: &str, args: &[&dyn Display]) -> String {
+    let template = match language() {
+        Language::English => message,
+        Language::Chinese => CHINESE
+            .iter()
+            .find(|(english, _)| *english == message)
+            .map_or(message, |(_, chinese)| chinese),
+    };
+    fill(template, args)
+}
+
+fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let index = match &after[..end] {
+            "" => {
+                next += 1;
+                Some(next - 1)
+            }
+            number => number.parse::<usize>().ok(),
+        };
+        match index.and_then(|index| args.get(index)) {
+            Some(arg) => out.push_str(&arg.to_string()),
+            None => out.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `format!` for user-facing messages: `tr!("{} Worktree '{}' deleted", icon, name)`
+/// looks the English text up in the catalog of the current language.
+#[macro_export]
+macro_rules! tr {
+    ($message:literal) => {
+        $crate::i18n::translate($message, &[])
+    };
+    ($message:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::translate($message, &[$(&$arg as &dyn ::std::fmt::Display),+])
+    };
+}
+
+// English message and its Chinese translation. The leading `{}` is the
+// message's icon.
+const CHINESE: &[(&str, &str)] = &[
+    // create
+    (
+        "{} Fetching branch '{}' from {}...",
+        "{} 正在从 {2} 获取分支 '{1}'...",
+    ),
+    (
+        "{} '{}' is already taken, using '{}' instead",
+        "{} '{}' 已被占用，改用 '{}'",
+    ),
+    (
+        "{} Creating detached worktree '{}' at '{}'...",
+        "{} 正在于 '{2}' 创建分离的 worktree '{1}'...",
+    ),
+    (
+        "{} Creating worktree '{}' from existing branch '{}'...",
+        "{} 正在基于已有分支 '{2}' 创建 worktree '{1}'...",
+    ),
+    (
+        "{} Creating worktree '{}' with new branch '{}'...",
+        "{} 正在创建 worktree '{}' 及新分支 '{}'...",
+    ),
+    ("{} Worktree created at: {}", "{} Worktree 已创建于：{}"),
+    (
+        "  {} To open it, run: {} {}",
+        "  {} 运行以下命令打开：{} {}",
+    ),
+    (
+        "  {} To open it later, run: {} {}",
+        "  {} 稍后打开请运行：{} {}",
+    ),
+    (
+        "{} Creating worktree {}/{}...",
+        "{} 正在创建第 {}/{} 个 worktree...",
+    ),
+    ("{} Fetching issue #{}...", "{} 正在获取 issue #{}..."),
+    ("{} Fetching ticket {}...", "{} 正在获取工单 {}..."),
+    (
+        "{} Applied sparse profile '{}' ({})",
+        "{} 已应用稀疏检出配置 '{}'（{}）",
+    ),
+    (
+        "Choose an agent for the new worktree:",
+        "为新 worktree 选择一个智能体：",
+    ),
+    (
+        "Would you like to open the worktree now?",
+        "现在打开这个 worktree 吗？",
+    ),
+    ("{} Updated submodules", "{} 已更新子模块"),
+    (
+        "{} Warning: Failed to update submodules: {}",
+        "{} 警告：更新子模块失败：{}",
+    ),
+    ("{} Pulled LFS objects", "{} 已拉取 LFS 对象"),
+    (
+        "{} Warning: Failed to pull LFS objects: {}",
+        "{} 警告：拉取 LFS 对象失败：{}",
+    ),
+    (
+        "{} Copied CLAUDE.local.md to worktree",
+        "{} 已将 CLAUDE.local.md 复制到 worktree",
+    ),
+    // open
+    (
+        "{} Current directory is a worktree but not managed by xlaude",
+        "{} 当前目录是一个 worktree，但不受 xlaude 管理",
+    ),
+    ("Worktree:", "Worktree："),
+    ("Path:", "路径："),
+    (
+        "Would you like to add this worktree to xlaude and open it?",
+        "将这个 worktree 加入 xlaude 并打开吗？",
+    ),
+    (
+        "{} Adding worktree '{}' to xlaude management...",
+        "{} 正在将 worktree '{}' 加入 xlaude 管理...",
+    ),
+    ("{} Worktree added successfully", "{} Worktree 已添加"),
+    (
+        "Select an agent to open the current worktree with:",
+        "选择用于打开当前 worktree 的智能体：",
+    ),
+    ("Select a worktree to open", "选择要打开的 worktree"),
+    ("Choose another agent:", "选择另一个智能体："),
+    ("{} Agent '{}' is not installed", "{} 智能体 '{}' 未安装"),
+    ("{} Falling back to '{}'", "{} 改用 '{}'"),
+    ("{} Failed to record the launch: {}", "{} 记录启动失败：{}"),
+    (
+        "{} xlaude cannot resume sessions of '{}'; starting a new one",
+        "{} xlaude 无法恢复 '{}' 的会话，将开始新会话",
+    ),
+    (
+        "{} No earlier sessions in '{}'; starting a new one",
+        "{} '{}' 中没有以往的会话，将开始新会话",
+    ),
+    ("Select a session to resume", "选择要恢复的会话"),
+    // agent menu
+    ("Open with codex", "用 codex 打开"),
+    (
+        "Open the worktree in the codex CLI.",
+        "在 codex CLI 中打开该 worktree。",
+    ),
+    ("Launching with `codex`", "正在用 `codex` 启动"),
+    ("Open with Claude", "用 Claude 打开"),
+    (
+        "Launch using the configured Claude command.",
+        "使用配置的 Claude 命令启动。",
+    ),
+    (
+        "Launching with `claude --dangerously-skip-permissions`",
+        "正在用 `claude --dangerously-skip-permissions` 启动",
+    ),
+    ("Open with Gemini", "用 Gemini 打开"),
+    (
+        "Open the worktree in the Gemini CLI.",
+        "在 Gemini CLI 中打开该 worktree。",
+    ),
+    ("Launching with `gemini -y`", "正在用 `gemini -y` 启动"),
+    ("Skip launch", "跳过启动"),
+    (
+        "Keep the worktree open without launching an agent.",
+        "保留 worktree，不启动智能体。",
+    ),
+    ("Skipping launch", "已跳过启动"),
+    (
+        "{} Ignoring menu entry '{}': its key '{}' is taken or not usable",
+        "{} 忽略菜单项 '{}'：按键 '{}' 已被占用或不可用",
+    ),
+    ("Running `{}`", "正在运行 `{}`"),
+    ("Launching `{}`", "正在启动 `{}`"),
+    (" (default)", "（默认）"),
+    ("Command:", "命令："),
+    (
+        "  Press {} or {}; Enter accepts the default.",
+        "  按 {} 或 {}；回车选择默认项。",
+    ),
+    (
+        "↑/↓ move between options and ? shows this list again.",
+        "↑/↓ 在选项间移动，? 重新显示此列表。",
+    ),
+    // delete
+    (
+        "{} Checking worktree '{}'...",
+        "{} 正在检查 worktree '{}'...",
+    ),
+    ("{} Checking branch '{}'...", "{} 正在检查分支 '{}'..."),
+    ("{} Cancelled", "{} 已取消"),
+    (
+        "{} Worktree '{}' deleted successfully",
+        "{} Worktree '{}' 已删除",
+    ),
+    (
+        "{} Worktree directory not found at {}",
+        "{} 未在 {} 找到 worktree 目录",
+    ),
+    (
+        "  {} The worktree may have been manually deleted",
+        "  {} 该 worktree 可能已被手动删除",
+    ),
+    ("  {} Last agent activity {}", "  {} 智能体最近活动于 {}"),
+    ("  {} Branch was merged via PR", "  {} 分支已通过 PR 合并"),
+    ("{} You have uncommitted changes", "{} 有未提交的更改"),
+    ("{} You have unpushed commits", "{} 有未推送的提交"),
+    (
+        "{} Branch '{}' is not fully merged",
+        "{} 分支 '{}' 尚未完全合并",
+    ),
+    (
+        "  {} No merged PR found for this branch",
+        "  {} 未找到该分支已合并的 PR",
+    ),
+    ("{} Removing worktree...", "{} 正在移除 worktree..."),
+    (
+        "{} Standard removal failed, trying force removal...",
+        "{} 常规移除失败，正在尝试强制移除...",
+    ),
+    (
+        "{} Pruning non-existent worktree...",
+        "{} 正在清理不存在的 worktree...",
+    ),
+    (
+        "{} Branch '{}' is protected; kept",
+        "{} 分支 '{}' 受保护，已保留",
+    ),
+    ("{} Deleting branch '{}'...", "{} 正在删除分支 '{}'..."),
+    ("{} Branch deleted", "{} 分支已删除"),
+    (
+        "{} Branch kept (not fully merged)",
+        "{} 分支已保留（尚未完全合并）",
+    ),
+    ("{} Branch force deleted", "{} 分支已强制删除"),
+    ("{} Branch kept", "{} 分支已保留"),
+    ("{} Failed to record deletion: {}", "{} 记录删除失败：{}"),
+    (
+        "Remove this worktree from xlaude management?",
+        "将这个 worktree 移出 xlaude 管理吗？",
+    ),
+    (
+        "{} An agent is still running in this worktree: {} (pid {})",
+        "{} 该 worktree 中仍有智能体在运行：{}（pid {}）",
+    ),
+    (
+        "{} An agent wrote to this worktree's session {}; it may still be working here",
+        "{} 智能体于 {} 写入了该 worktree 的会话，可能仍在工作",
+    ),
+    ("Delete it anyway?", "仍要删除吗？"),
+    (
+        "Are you sure you want to delete this worktree?",
+        "确定要删除这个 worktree 吗？",
+    ),
+    (
+        "  {} Detached at '{}'; there is no branch to delete",
+        "  {} 分离于 '{}'，没有可删除的分支",
+    ),
+    ("Delete worktree '{}'?", "删除 worktree '{}' 吗？"),
+    (
+        "Branch is not fully merged. Force delete?",
+        "分支尚未完全合并。强制删除吗？",
+    ),
+    // clean
+    ("{} No worktrees in state", "{} 状态中没有 worktree"),
+    (
+        "{} Checking for invalid worktrees...",
+        "{} 正在检查无效的 worktree...",
+    ),
+    (
+        "  {} Skipping locked worktree: {}{}",
+        "  {} 跳过已锁定的 worktree：{}{}",
+    ),
+    (
+        "  {} Skipping kept worktree: {}",
+        "  {} 跳过保留的 worktree：{}",
+    ),
+    (
+        "  {} Skipping archived worktree: {}",
+        "  {} 跳过已归档的 worktree：{}",
+    ),
+    (
+        "  {} Found invalid worktree: {} ({})",
+        "  {} 发现无效的 worktree：{}（{}）",
+    ),
+    ("{} All worktrees are valid", "{} 所有 worktree 均有效"),
+    (
+        "{} Removed {} invalid worktree",
+        "{} 已移除 {} 个无效的 worktree",
+    ),
+    (
+        "{} Removed {} invalid worktrees",
+        "{} 已移除 {} 个无效的 worktree",
+    ),
+    (
+        "{} Stale detection is turned off (stale_after in state.json)",
+        "{} 闲置检测已关闭（state.json 中的 stale_after）",
+    ),
+    (
+        "{} Checking for stale worktrees...",
+        "{} 正在检查闲置的 worktree...",
+    ),
+    ("locked{}", "已锁定{}"),
+    ("an agent is running", "有智能体正在运行"),
+    (
+        "uncommitted changes or unpushed commits",
+        "有未提交的更改或未推送的提交",
+    ),
+    ("  {} {} (last activity {})", "  {} {}（最近活动于 {}）"),
+    ("      {} Skipped: {}", "      {} 已跳过：{}"),
+    (
+        "{} No stale worktrees to delete",
+        "{} 没有需要删除的闲置 worktree",
+    ),
+    (
+        "Delete {} stale worktree(s)?",
+        "删除 {} 个闲置的 worktree 吗？",
+    ),
+    // list
+    ("{} No active worktrees", "{} 没有活跃的 worktree"),
+    (
+        "Run 'xlaude import' to adopt the checkouts of your Claude Code sessions",
+        "运行 'xlaude import' 以接管 Claude Code 会话所在的检出目录",
+    ),
+    (
+        "{} No worktrees with sessions in that time range",
+        "{} 该时间范围内没有包含会话的 worktree",
+    ),
+    ("Recent activity:", "最近活动："),
+    ("{} ... and {} more", "{} ……还有 {} 个"),
+    ("unknown", "未知"),
+    ("{}m ago", "{} 分钟前"),
+    ("{}h ago", "{} 小时前"),
+    ("{}d ago", "{} 天前"),
+    ("{}w ago", "{} 周前"),
+    ("{}mo ago", "{} 个月前"),
+    ("{}y ago", "{} 年前"),
+    ("{} session(s):", "{} 个会话："),
+    ("(no user message)", "（无用户消息）"),
+    ("{} Active worktrees {}:", "{} 活跃的 worktree {}："),
+    ("(profile {})", "（配置 {}）"),
+    ("{} Active worktrees:", "{} 活跃的 worktree："),
+    ("(detached at {})", "（分离于 {}）"),
+    ("(stale)", "（闲置）"),
+    ("Disk:", "磁盘："),
+    ("Locked", "已锁定"),
+    ("Stale: last activity {}", "闲置：最近活动于 {}"),
+    (", attempt {}", "，第 {} 次尝试"),
+    ("Running: {}", "运行中：{}"),
+    ("(pid {}, started {}{})", "（pid {}，启动于 {}{}）"),
+    (
+        "Branch renamed to '{}' outside xlaude (run 'xlaude doctor' to update)",
+        "分支已在 xlaude 之外重命名为 '{}'（运行 'xlaude doctor' 更新）",
+    ),
+    (
+        "Directory missing (run 'xlaude clean')",
+        "目录缺失（运行 'xlaude clean'）",
+    ),
+    ("Broken git link: {}", "git 链接已损坏：{}"),
+    ("Created:", "创建于："),
+    ("Issue:", "Issue："),
+    ("Ticket:", "工单："),
+    ("Note:", "备注："),
+    ("Sparse:", "稀疏检出："),
+    ("Tags:", "标签："),
+    ("Active:", "活跃时长："),
+    ("{} Archived worktrees:", "{} 已归档的 worktree："),
+    ("detached at {}", "分离于 {}"),
+    ("({}, archived {})", "（{}，归档于 {}）"),
+    (
+        "Run 'xlaude restore <name>' to check one out again",
+        "运行 'xlaude restore <name>' 重新检出",
+    ),
+    ("{} Total disk usage: {}", "{} 磁盘总用量：{}"),
+    (
+        "{} {} stale worktree(s); run 'xlaude clean --stale' to review them",
+        "{} {} 个闲置的 worktree；运行 'xlaude clean --stale' 查看",
+    ),
+    ("{} Agent usage this month:", "{} 本月智能体用量："),
+    (
+        "Repair {} broken worktree(s) with 'git worktree repair'?",
+        "用 'git worktree repair' 修复 {} 个损坏的 worktree 吗？",
+    ),
+    // errors
+    ("Error:", "错误："),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_placeholders_in_order_or_by_number() {
+        assert_eq!(
+            fill("{} deleted '{}'", &[&"✅", &"auth"]),
+            "✅ deleted 'auth'"
+        );
+        assert_eq!(fill("{1} then {0}", &[&"a", &"b"]), "b then a");
+        assert_eq!(fill("{} and {}", &[&1]), "1 and {}");
+        assert_eq!(fill("no args {", &[]), "no args {");
+    }
+
+    #[test]
+    fn every_message_has_a_translation() {
+        let message = regex::Regex::new(r#"\btr!\(\s*"((?:[^"\\]|\\.)*)""#).unwrap();
+        let mut missing = Vec::new();
+        let mut dirs = vec![std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src")];
+        while let Some(dir) = dirs.pop() {
+            for path in std::fs::read_dir(dir).unwrap().flatten().map(|e| e.path()) {
+                if path.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                // The macro's own documentation shows it with a made-up message
+                if path.ends_with("i18n.rs") {
+                    continue;
+                }
+                let Ok(source) = std::fs::read_to_string(&path) else {
+                    continue;
+                };
+                for capture in message.captures_iter(&source) {
+                    let english = capture[1].replace("\\\"", "\"").replace("\\\\", "\\");
+                    if !CHINESE.iter().any(|(key, _)| *key == english) {
+                        missing.push(english);
+                    }
+                }
+            }
+        }
+        assert!(
+            missing.is_empty(),
+            "messages without a translation: {missing:#?}"
+        );
+    }
+
+    #[test]
+    fn translations_keep_their_placeholders() {
+        let count = |text: &str| text.matches('{').count();
+        for (english, chinese) in CHINESE {
+            assert_eq!(count(english), count(chinese), "{english}");
+        }
+        assert_eq!(
+            Language::from_locale("zh_CN.UTF-8"),
+            Some(Language::Chinese)
+        );
+        assert_eq!(Language::from_locale("C"), Some(Language::English));
+        assert_eq!(Language::from_locale(""), None);
+    }
+}
@@ -1,92 +1,43 @@
 use anyhow::Result;
-use atty::Stream;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
-use dialoguer::{Confirm, Select};
-use std::io::{self, BufRead, BufReader, Write};
-use std::sync::Mutex;
+use serde_json::Value;
+
+use crate::interaction::{Choice, ChoiceDisplay, Prompt, with_current};
+use crate::replay;
 
 /// Check if stdin is piped (not a terminal)
 pub fn is_piped_input() -> bool {
-    !atty::is(Stream::Stdin)
+    !with_current(|interaction| interaction.is_terminal())
 }
 
-/// Piped input reader that supports reading multiple lines
-pub struct PipedInputReader {
-    reader: BufReader<io::Stdin>,
-    buffer: Vec<String>,
+/// Read a single line from piped input. Nothing is read from an answer script.
+pub fn read_piped_line() -> Result<Option<String>> {
+    with_current(|interaction| interaction.read_line())
 }
 
-impl PipedInputReader {
-    pub fn new() -> Self {
-        Self {
-            reader: BufReader::new(io::stdin()),
-            buffer: Vec::new(),
-        }
-    }
-
-    /// Read the next line of input
-    pub fn read_line(&mut self) -> Result<Option<String>> {
-        // Use buffered input first if available
-        if !self.buffer.is_empty() {
-            return Ok(Some(self.buffer.remove(0)));
-        }
-
-        let mut line = String::new();
-        match self.reader.read_line(&mut line)? {
-            0 => Ok(None), // EOF
-            _ => Ok(Some(line.trim().to_string())),
-        }
-    }
+/// Smart confirmation that supports piped input (yes/no). `id` names the
+/// prompt in answer scripts.
+pub fn smart_confirm(id: &str, prompt: &str, default: bool) -> Result<bool> {
+    confirm_with_policy(id, prompt, default, false)
 }
 
-/// Global piped input reader (singleton)
-static PIPED_INPUT: std::sync::LazyLock<Mutex<Option<PipedInputReader>>> =
-    std::sync::LazyLock::new(|| {
-        if is_piped_input() {
-            Mutex::new(Some(PipedInputReader::new()))
-        } else {
-            Mutex::new(None)
-        }
-    });
-
-/// Read a single line from piped input
-pub fn read_piped_line() -> Result<Option<String>> {
-    let mut reader = PIPED_INPUT.lock().unwrap();
-    match reader.as_mut() {
-        Some(r) => r.read_line(),
-        None => Ok(None),
-    }
+/// Like `smart_confirm`, for prompts guarding data loss (deleting worktrees or branches).
+/// These keep prompting under `confirm = "destructive"`.
+pub fn smart_confirm_destructive(id: &str, prompt: &str, default: bool) -> Result<bool> {
+    confirm_with_policy(id, prompt, default, true)
 }
 
-/// Smart confirmation that supports piped input (yes/no)
-pub fn smart_confirm(prompt: &str, default: bool) -> Result<bool> {
-    // 1. Check for force-yes environment variable
-    if std::env::var("XLAUDE_YES").is_ok() {
-        return Ok(true);
-    }
-
-    // 2. Check for piped input
-    if let Some(input) = read_piped_line()? {
-        let input = input.to_lowercase();
-        return Ok(input == "y" || input == "yes");
-    }
-
-    // 3. Non-interactive mode uses default value
-    if std::env::var("XLAUDE_NON_INTERACTIVE").is_ok() {
-        return Ok(default);
-    }
-
-    // 4. Interactive confirmation
-    Confirm::new()
-        .with_prompt(prompt)
-        .default(default)
-        .interact()
-        .map_err(Into::into)
+fn confirm_with_policy(id: &str, prompt: &str, default: bool, destructive: bool) -> Result<bool> {
+    let answer = with_current(|interaction| {
+        interaction.confirm(Prompt { id, text: prompt }, default, destructive)
+    })?;
+    replay::record(id, "confirm", prompt, Value::Bool(answer));
+    Ok(answer)
 }
 
-/// Smart selection that supports piped input
+/// Smart selection that supports piped input. `id` names the prompt in
+/// answer scripts, which pick an item by index or by its displayed text.
 pub fn smart_select<T>(
+    id: &str,
     prompt: &str,
     items: &[T],
     display_fn: impl Fn(&T) -> String,
@@ -94,38 +45,14 @@ pub fn smart_select<T>(
 where
     T: Clone,
 {
-    // 1. Check for piped input
-    if let Some(input) = read_piped_line()? {
-        // Try to parse as index
-        if let Ok(index) = input.parse::<usize>()
-            && index < items.len()
-        {
-            return Ok(Some(index));
-        }
-
-        // Try to match display text
-        for (i, item) in items.iter().enumerate() {
-            if display_fn(item) == input {
-                return Ok(Some(i));
-            }
-        }
-
-        anyhow::bail!("Invalid selection: {}", input);
-    }
-
-    // 2. Non-interactive mode returns None
-    if std::env::var("XLAUDE_NON_INTERACTIVE").is_ok() {
-        return Ok(None);
-    }
-
-    // 3. Interactive selection
     let display_items: Vec<String> = items.iter().map(display_fn).collect();
-    let selection = Select::new()
-        .with_prompt(prompt)
-        .items(&display_items)
-        .interact()?;
-
-    Ok(Some(selection))
+    let selection = with_current(|interaction| {
+        interaction.select(Prompt { id, text: prompt }, &display_items)
+    })?;
+    if let Some(index) = selection {
+        replay::record(id, "select", prompt, Value::from(index));
+    }
+    Ok(selection)
 }
 
 /// Get command argument with pipe input support
@@ -154,11 +81,17 @@ pub fn get_command_arg(arg: Option<String>) -> Result<Option<String>> {
 /// Read a single-choice input with support for piped input and defaults.
 /// Returns the canonical key from `valid_keys` that matches the user's selection.
 #[allow(dead_code)]
-pub fn smart_choice(prompt: &str, valid_keys: &[&str], default_key: &str) -> Result<String> {
-    smart_choice_with_formatter(prompt, valid_keys, default_key, |key| key.to_string())
+pub fn smart_choice(
+    id: &str,
+    prompt: &str,
+    valid_keys: &[&str],
+    default_key: &str,
+) -> Result<String> {
+    smart_choice_with_formatter(id, prompt, valid_keys, default_key, |key| key.to_string())
 }
 
 pub fn smart_choice_with_formatter<F>(
+    id: &str,
     prompt: &str,
     valid_keys: &[&str],
     default_key: &str,
@@ -167,188 +100,51 @@ pub fn smart_choice_with_formatter<F>(
 where
     F: Fn(&str) -> String,
 {
-    if !valid_keys
-        .iter()
-        .any(|key| key.eq_ignore_ascii_case(default_key))
-    {
-        anyhow::bail!(
-            "Default choice '{}' is not present in the list of valid options",
-            default_key
-        );
-    }
-
-    let normalized_keys: Vec<String> = valid_keys.iter().map(|key| key.to_lowercase()).collect();
-    let default_index = normalized_keys
-        .iter()
-        .position(|key| key.eq_ignore_ascii_case(&default_key.to_lowercase()))
-        .unwrap();
-    let default_key_canonical = valid_keys[default_index];
-
-    if let Some(input) = read_piped_line()? {
-        let trimmed = input.trim();
-
-        if trimmed.is_empty() {
-            return Ok(default_key_canonical.to_string());
-        }
-
-        let normalized = trimmed.to_lowercase();
-
-        let alias_key = match normalized.as_str() {
-            "y" | "yes" => Some(default_key.to_lowercase()),
-            "n" | "no" => Some("n".to_string()),
-            _ => None,
-        }
-        .and_then(|alias| {
-            normalized_keys
-                .iter()
-                .position(|key| key == &alias)
-                .map(|index| valid_keys[index].to_string())
-        });
-
-        if let Some(mapped) = alias_key {
-            return Ok(mapped);
-        }
-
-        if let Some(index) = normalized_keys.iter().position(|key| key == &normalized) {
-            return Ok(valid_keys[index].to_string());
-        }
-
-        anyhow::bail!("Invalid selection: {}", trimmed);
-    }
-
-    if std::env::var("XLAUDE_NON_INTERACTIVE").is_ok() {
-        return Ok(default_key_canonical.to_string());
-    }
-
-    struct RawModeGuard;
-    impl Drop for RawModeGuard {
-        fn drop(&mut self) {
-            let _ = disable_raw_mode();
-        }
-    }
-
-    let raw_mode_enabled = enable_raw_mode().is_ok();
-    let _guard = raw_mode_enabled.then_some(RawModeGuard);
-
-    if !raw_mode_enabled {
-        return read_line_choice(
-            prompt,
-            &normalized_keys,
-            valid_keys,
-            default_index,
-            &format_selected,
-        );
-    }
-
-    if !prompt.is_empty() {
-        print!("{}", prompt);
-        io::stdout().flush()?;
-    }
-
-    loop {
-        match event::read()? {
-            Event::Key(key_event)
-                if matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat) =>
-            {
-                match key_event.code {
-                    KeyCode::Char(c) => {
-                        if key_event.modifiers.contains(KeyModifiers::CONTROL)
-                            && (c == 'c' || c == 'C')
-                        {
-                            println!();
-                            return Err(anyhow::anyhow!("Operation cancelled by Ctrl+C"));
-                        }
-
-                        let normalized = c.to_ascii_lowercase().to_string();
-                        if let Some(index) =
-                            normalized_keys.iter().position(|key| key == &normalized)
-                        {
-                            let selection = valid_keys[index];
-                            let rendered = format_selected(selection);
-                            if prompt.is_empty() {
-                                println!("{}", rendered);
-                            } else {
-                                print!("\r{}{}\n", prompt, rendered);
-                            }
-                            io::stdout().flush()?;
-                            return Ok(selection.to_string());
-                        }
-                    }
-                    KeyCode::Enter | KeyCode::Esc => {
-                        let selection = valid_keys[default_index];
-                        let rendered = format_selected(selection);
-                        if prompt.is_empty() {
-                            println!("{}", rendered);
-                        } else {
-                            print!("\r{}{}\n", prompt, rendered);
-                        }
-                        io::stdout().flush()?;
-                        return Ok(selection.to_string());
-                    }
-                    _ => {}
-                }
-
-                println!(
-                    "Invalid selection. Please choose from: {}",
-                    valid_keys.join(", ")
-                );
-                if !prompt.is_empty() {
-                    print!("{}", prompt);
-                    io::stdout().flush()?;
-                }
-            }
-            _ => {}
-        }
-    }
+    smart_choice_with_display(id, prompt, valid_keys, default_key, None, format_selected)
 }
 
-fn read_line_choice<F>(
+/// Like `smart_choice_with_formatter`, for options listed by the caller. In a
+/// terminal, keys longer than one character are typed out (Enter confirms a
+/// key that starts a longer one), the arrow keys move a cursor over the
+/// options, and `?` prints them again. Answer scripts give a key, or a word
+/// from a single option's label such as "codex".
+pub fn smart_choice_with_display<F>(
+    id: &str,
     prompt: &str,
-    normalized_keys: &[String],
     valid_keys: &[&str],
-    default_index: usize,
-    format_selected: &F,
+    default_key: &str,
+    display: Option<&ChoiceDisplay<'_>>,
+    format_selected: F,
 ) -> Result<String>
 where
     F: Fn(&str) -> String,
 {
-    loop {
-        if !prompt.is_empty() {
-            print!("{}", prompt);
-            io::stdout().flush()?;
-        }
-
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        let trimmed = input.trim();
-
-        let selected = if trimmed.is_empty() {
-            normalized_keys[default_index].clone()
-        } else {
-            trimmed.to_lowercase()
-        };
-
-        if let Some(index) = normalized_keys.iter().position(|key| key == &selected) {
-            let selection = valid_keys[index];
-            let rendered = format_selected(selection);
-            if prompt.is_empty() {
-                println!("{}", rendered);
-            } else {
-                println!("{}{}", prompt, rendered);
-            }
-            return Ok(selection.to_string());
-        }
-
-        println!(
-            "Invalid selection. Please choose from: {}",
-            valid_keys.join(", ")
+    let Some(default) = valid_keys
+        .iter()
+        .position(|key| key.eq_ignore_ascii_case(default_key))
+    else {
+        anyhow::bail!(
+            "Default choice '{}' is not present in the list of valid options",
+            default_key
         );
-    }
+    };
+    let choice = Choice {
+        keys: valid_keys,
+        default,
+        display,
+        format_selected: &format_selected,
+    };
+    let index =
+        with_current(|interaction| interaction.choose(Prompt { id, text: prompt }, &choice))?;
+    let key = valid_keys[index].to_string();
+    replay::record(id, "choice", prompt, Value::from(key.as_str()));
+    Ok(key)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::interaction::{MockInteraction, with_interaction};
 
     #[test]
     fn smart_choice_uses_default_when_non_interactive() {
@@ -356,11 +152,11 @@ mod tests {
             std::env::set_var("XLAUDE_NON_INTERACTIVE", "1");
         }
 
-        let default_result = smart_choice("> ", &["1", "2"], "2")
+        let default_result = smart_choice("test", "> ", &["1", "2"], "2")
             .expect("smart_choice should succeed when non-interactive");
         assert_eq!(default_result, "2");
 
-        let result = smart_choice_with_formatter("> ", &["1", "2"], "2", |_| {
+        let result = smart_choice_with_formatter("test", "> ", &["1", "2"], "2", |_| {
             unreachable!("formatter should not be invoked")
         })
         .expect("smart_choice_with_formatter should succeed");
@@ -371,4 +167,54 @@ mod tests {
             std::env::remove_var("XLAUDE_NON_INTERACTIVE");
         }
     }
+
+    #[test]
+    fn prompts_are_answered_by_the_thread_interaction() {
+        let mock = MockInteraction::new()
+            .line("feature")
+            .answer("confirm_delete", true)
+            .answer("worktree", "beta")
+            .answer("agent", "codex");
+        with_interaction(Box::new(mock), || {
+            assert!(is_piped_input());
+            assert_eq!(get_command_arg(None).unwrap().as_deref(), Some("feature"));
+            assert!(smart_confirm_destructive("confirm_delete", "Delete?", false).unwrap());
+            let items = ["alpha", "beta"];
+            assert_eq!(
+                smart_select("worktree", "Pick", &items, |item| item.to_string()).unwrap(),
+                Some(1)
+            );
+            let label = |key: &str| match key {
+                "1" => "[1] Claude".to_string(),
+                "2" => "[2] codex".to_string(),
+                _ => "[N] Skip".to_string(),
+            };
+            let display = ChoiceDisplay {
+                label: &label,
+                print: &|| {},
+            };
+            let key = smart_choice_with_display(
+                "agent",
+                "> ",
+                &["1", "2", "n"],
+                "1",
+                Some(&display),
+                |key| key.to_string(),
+            );
+            assert_eq!(key.unwrap(), "2");
+            // The mock has no answer left, and never falls back to a default
+            assert!(smart_confirm("confirm_delete", "Delete?", true).is_err());
+        });
+    }
+
+    #[test]
+    fn confirm_policy_only_keeps_destructive_prompts_when_asked() {
+        use crate::state::ConfirmPolicy;
+
+        assert!(ConfirmPolicy::Always.should_prompt(false));
+        assert!(ConfirmPolicy::Always.should_prompt(true));
+        assert!(!ConfirmPolicy::Destructive.should_prompt(false));
+        assert!(ConfirmPolicy::Destructive.should_prompt(true));
+        assert!(!ConfirmPolicy::Never.should_prompt(true));
+    }
 }
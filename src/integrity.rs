@@ -0,0 +1,354 @@
+use anyhow::Result;
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use crate::git::{branch_in, find_main_repo, repo_name_from_dir};
+use crate::state::XlaudeState;
+use crate::utils::with_numeric_suffix;
+
+/// A problem with a state entry that `xlaude doctor --fix` can repair.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Issue {
+    /// Stored under a key other than `repo/name`
+    MismatchedKey { key: String },
+    /// Points at the same directory as the entry stored under `kept`
+    Duplicate { key: String, kept: String },
+    /// The directory is a worktree of another repository
+    ForeignRepo { key: String, repo_name: String },
+    /// The recorded branch is gone; `head` is the commit the worktree is
+    /// detached at, if its directory still exists
+    MissingBranch { key: String, head: Option<String> },
+    /// The path is relative or contains `.`/`..` components
+    NonCanonicalPath { key: String, canonical: PathBuf },
+    /// Created after the current time, which breaks sorting and staleness
+    FutureTimestamp { key: String },
+}
+
+impl Issue {
+    pub fn key(&self) -> &str {
+        match self {
+            Issue::MismatchedKey { key }
+            | Issue::Duplicate { key, .. }
+            | Issue::ForeignRepo { key, .. }
+            | Issue::MissingBranch { key, .. }
+            | Issue::NonCanonicalPath { key, .. }
+            | Issue::FutureTimestamp { key } => key,
+        }
+    }
+
+    pub fn describe(&self, state: &XlaudeState) -> String {
+        let info = &state.worktrees[self.key()];
+        match self {
+            Issue::MismatchedKey { key } => format!(
+                "{key}: stored under the wrong key (expected '{}')",
+                XlaudeState::make_key(&info.repo_name, &info.name)
+            ),
+            Issue::Duplicate { key, kept } => {
+                format!("{key}: same directory as '{kept}'")
+            }
+            Issue::ForeignRepo { key, repo_name } => format!(
+                "{key}: {} belongs to repository '{repo_name}'",
+                info.path.display()
+            ),
+            Issue::MissingBranch { key, .. } => {
+                format!("{key}: branch '{}' no longer exists", info.branch)
+            }
+            Issue::NonCanonicalPath { key, canonical } => format!(
+                "{key}: path {} is not canonical ({})",
+                info.path.display(),
+                canonical.display()
+            ),
+            Issue::FutureTimestamp { key } => format!(
+                "{key}: created in the future ({})",
+                info.created_at.to_rfc3339()
+            ),
+        }
+    }
+
+    /// The question asked before applying the fix.
+    pub fn fix_prompt(&self) -> String {
+        match self {
+            Issue::MismatchedKey { key } => format!("Move '{key}' to its proper key?"),
+            Issue::Duplicate { key, .. } => format!("Remove the duplicate entry '{key}'?"),
+            Issue::ForeignRepo { key, repo_name } => {
+                format!("Move '{key}' to repository '{repo_name}'?")
+            }
+            Issue::MissingBranch {
+                key,
+                head: Some(head),
+            } => format!("Record '{key}' as detached at {head}?"),
+            Issue::MissingBranch { key, head: None } => {
+                format!("Remove '{key}' from state?")
+            }
+            Issue::NonCanonicalPath { key, canonical } => {
+                format!("Store {} for '{key}'?", canonical.display())
+            }
+            Issue::FutureTimestamp { key } => {
+                format!("Reset the creation time of '{key}' to now?")
+            }
+        }
+    }
+
+    /// Repair the entry in `state` without saving it.
+    pub fn apply(&self, state: &mut XlaudeState) {
+        let key = self.key();
+        match self {
+            Issue::MismatchedKey { .. } => {
+                rekey(state, key);
+            }
+            Issue::Duplicate { .. } | Issue::MissingBranch { head: None, .. } => {
+                state.remove_worktree(key);
+            }
+            Issue::ForeignRepo { repo_name, .. } => {
+                if let Some(info) = state.worktrees.get_mut(key) {
+                    info.repo_name = repo_name.clone();
+                }
+                rekey(state, key);
+            }
+            Issue::MissingBranch {
+                head: Some(head), ..
+            } => {
+                if let Some(info) = state.worktrees.get_mut(key) {
+                    info.branch = head.clone();
+                    info.detached = true;
+                }
+            }
+            Issue::NonCanonicalPath { canonical, .. } => {
+                if let Some(info) = state.worktrees.get_mut(key) {
+                    info.path = canonical.clone();
+                }
+            }
+            Issue::FutureTimestamp { .. } => {
+                if let Some(info) = state.worktrees.get_mut(key) {
+                    info.created_at = Utc::now();
+                }
+            }
+        }
+    }
+}
+
+/// Offer each issue in `state` to `confirm` and apply the accepted fixes,
+/// checking again after every fix since it may move entries to other keys.
+/// Returns the issues that were declined.
+pub fn repair(
+    state: &mut XlaudeState,
+    mut confirm: impl FnMut(&Issue, &XlaudeState) -> Result<bool>,
+) -> Result<Vec<Issue>> {
+    let mut declined: Vec<Issue> = Vec::new();
+    let mut seen = HashSet::new();
+    while let Some(issue) = check_state(state)
+        .into_iter()
+        .find(|issue| !seen.contains(issue))
+    {
+        seen.insert(issue.clone());
+        if confirm(&issue, state)? {
+            issue.apply(state);
+        } else {
+            declined.push(issue);
+        }
+    }
+    Ok(declined)
+}
+
+/// Move the entry at `key` to `repo/name`, adding a numeric suffix to the name
+/// when another worktree already holds that key.
+fn rekey(state: &mut XlaudeState, key: &str) {
+    let Some(info) = state.worktrees.get(key) else {
+        return;
+    };
+    let name = with_numeric_suffix(&info.name, |candidate| {
+        state
+            .worktrees
+            .contains_key(&XlaudeState::make_key(&info.repo_name, candidate))
+    });
+    state.rename_worktree(key, &name);
+}
+
+/// Check every state entry for problems, sorted by key. Duplicates are
+/// reported for all but one entry per directory, preferring the entry whose
+/// key is correct and then the oldest.
+pub fn check_state(state: &XlaudeState) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut keys: Vec<&String> = state.worktrees.keys().collect();
+    keys.sort();
+
+    let mut by_path: HashMap<PathBuf, Vec<&String>> = HashMap::new();
+    for key in &keys {
+        by_path
+            .entry(normalize(&state.worktrees[*key].path))
+            .or_default()
+            .push(key);
+    }
+    for mut group in by_path.into_values().filter(|group| group.len() > 1) {
+        group.sort_by_key(|key| {
+            let info = &state.worktrees[*key];
+            (
+                **key != XlaudeState::make_key(&info.repo_name, &info.name),
+                info.created_at,
+            )
+        });
+        for key in &group[1..] {
+            issues.push(Issue::Duplicate {
+                key: key.to_string(),
+                kept: group[0].clone(),
+            });
+        }
+    }
+
+    let now = Utc::now();
+    for key in keys {
+        if issues.iter().any(|issue| issue.key() == key) {
+            continue;
+        }
+        let info = &state.worktrees[key];
+        if *key != XlaudeState::make_key(&info.repo_name, &info.name) {
+            issues.push(Issue::MismatchedKey { key: key.clone() });
+        }
+        if let Some(canonical) = canonical_path(&info.path) {
+            issues.push(Issue::NonCanonicalPath {
+                key: key.clone(),
+                canonical,
+            });
+        }
+        if info.created_at > now {
+            issues.push(Issue::FutureTimestamp { key: key.clone() });
+        }
+
+        let Some(repo_dir) = find_main_repo(&info.path, &info.repo_name) else {
+            continue;
+        };
+        if info.path.exists()
+            && let Some(repo_name) = repo_name_from_dir(&repo_dir)
+            && repo_name != info.repo_name
+        {
+            issues.push(Issue::ForeignRepo {
+                key: key.clone(),
+                repo_name,
+            });
+        }
+        if !info.detached && !branch_exists(&repo_dir, &info.branch) {
+            let head = if info.path.exists() {
+                // A worktree on another branch was renamed, not lost
+                if branch_in(&info.path).is_some() {
+                    continue;
+                }
+                short_head(&info.path)
+            } else {
+                None
+            };
+            issues.push(Issue::MissingBranch {
+                key: key.clone(),
+                head,
+            });
+        }
+    }
+    issues.sort_by(|a, b| a.key().cmp(b.key()));
+    issues
+}
+
+/// The absolute, `.`/`..`-free form of `path` when it differs from `path`.
+/// Symlinks are left alone, since worktrees are routinely reached through them.
+fn canonical_path(path: &Path) -> Option<PathBuf> {
+    let canonical = if path.is_absolute() {
+        normalize(path)
+    } else {
+        path.canonicalize().ok()?
+    };
+    (canonical.as_os_str() != path.as_os_str()).then_some(canonical)
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+fn branch_exists(repo_dir: &Path, branch: &str) -> bool {
+    Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("refs/heads/{branch}"))
+        .current_dir(repo_dir)
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+fn short_head(path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .current_dir(path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::WorktreeInfo;
+
+    fn entry(name: &str, path: &str) -> WorktreeInfo {
+        WorktreeInfo::new(name.into(), name.into(), PathBuf::from(path), "repo".into())
+    }
+
+    #[test]
+    fn finds_and_fixes_state_problems() {
+        let mut state = XlaudeState::default();
+        state
+            .worktrees
+            .insert("repo/a".into(), entry("a", "/nonexistent/repo-a"));
+        let mut copy = entry("a", "/nonexistent/./repo-a");
+        copy.created_at -= chrono::Duration::days(1);
+        state.worktrees.insert("repo/a-copy".into(), copy);
+        state
+            .worktrees
+            .insert("repo/old".into(), entry("b", "/nonexistent/x/../repo-b"));
+        let mut future = entry("c", "/nonexistent/repo-c");
+        future.created_at += chrono::Duration::days(1);
+        state.worktrees.insert("repo/c".into(), future);
+
+        let issues = check_state(&state);
+        assert_eq!(
+            issues,
+            vec![
+                Issue::Duplicate {
+                    key: "repo/a-copy".into(),
+                    kept: "repo/a".into()
+                },
+                Issue::FutureTimestamp {
+                    key: "repo/c".into()
+                },
+                Issue::MismatchedKey {
+                    key: "repo/old".into()
+                },
+                Issue::NonCanonicalPath {
+                    key: "repo/old".into(),
+                    canonical: PathBuf::from("/nonexistent/repo-b")
+                },
+            ]
+        );
+
+        let mut offered = 0;
+        let declined = repair(&mut state, |_, _| {
+            offered += 1;
+            Ok(true)
+        })
+        .unwrap();
+        assert!(declined.is_empty());
+        assert_eq!(offered, 4);
+        assert!(check_state(&state).is_empty());
+        let mut keys: Vec<_> = state.worktrees.keys().cloned().collect();
+        keys.sort();
+        assert_eq!(keys, ["repo/a", "repo/b", "repo/c"]);
+    }
+}
@@ -0,0 +1,963 @@
+use anyhow::Result;
+use atty::Stream;
+use colored::Colorize;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use dialoguer::{Confirm, Select};
+use serde::Deserialize;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use crate::error::{ErrorCategory, WithCategory, categorized};
+use crate::interrupt;
+use crate::state::XlaudeState;
+
+/// A question put to the user. `id` names it in answer scripts and recordings.
+#[derive(Debug, Clone, Copy)]
+pub struct Prompt<'a> {
+    pub id: &'a str,
+    pub text: &'a str,
+}
+
+/// How a list of options is shown while one is being chosen.
+pub struct ChoiceDisplay<'a> {
+    /// One line describing an option, shown when the arrow keys move to it
+    pub label: &'a dyn Fn(&str) -> String,
+    /// Print all options again, when `?` is pressed
+    pub print: &'a dyn Fn(),
+}
+
+/// Options picked by key, such as the launch menu's.
+pub struct Choice<'a> {
+    pub keys: &'a [&'a str],
+    pub default: usize,
+    pub display: Option<&'a ChoiceDisplay<'a>>,
+    /// The selection as echoed after the prompt
+    pub format_selected: &'a dyn Fn(&str) -> String,
+}
+
+impl Choice<'_> {
+    fn normalized_keys(&self) -> Vec<String> {
+        self.keys.iter().map(|key| key.to_lowercase()).collect()
+    }
+
+    fn label(&self, index: usize) -> String {
+        match self.display {
+            Some(display) => (display.label)(self.keys[index]),
+            None => self.keys[index].to_string(),
+        }
+    }
+}
+
+/// Where prompts get their answers: a terminal, piped stdin, an answer
+/// script, or a test. Commands ask through `input`, which uses the
+/// interaction installed on the current thread with [`with_interaction`].
+/// Threads without one, such as the dashboard's workers, take the defaults.
+pub trait Interaction: Send {
+    /// Whether a person answers at a terminal. Commands that would otherwise
+    /// read arguments from stdin only do so when this is false.
+    fn is_terminal(&self) -> bool {
+        false
+    }
+
+    /// The next line of piped input, if there is one.
+    fn read_line(&mut self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Answer a yes/no question. `destructive` marks prompts guarding data loss.
+    fn confirm(&mut self, prompt: Prompt<'_>, default: bool, destructive: bool) -> Result<bool>;
+
+    /// Pick one of `items`, or `None` when nobody can pick.
+    fn select(&mut self, prompt: Prompt<'_>, items: &[String]) -> Result<Option<usize>>;
+
+    /// The index of the chosen key.
+    fn choose(&mut self, prompt: Prompt<'_>, choice: &Choice<'_>) -> Result<usize>;
+}
+
+thread_local! {
+    static THREAD_INTERACTION: RefCell<Option<Box<dyn Interaction>>> = const { RefCell::new(None) };
+}
+
+/// Asks through the process's stdin and stdout, as the binary does.
+pub(crate) fn from_stdio() -> Box<dyn Interaction> {
+    match Terminal::detect() {
+        Terminal::NoInput => Box::new(PipedInteraction::stdin()),
+        Terminal::Full | Terminal::InputOnly => Box::new(TerminalInteraction),
+    }
+}
+
+/// Answer the prompts of `f` on this thread with `interaction`. Other
+/// threads keep their own.
+pub fn with_interaction<R>(interaction: Box<dyn Interaction>, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Box<dyn Interaction>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            THREAD_INTERACTION.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(THREAD_INTERACTION.with(|current| current.replace(Some(interaction))));
+    f()
+}
+
+/// Answer the following prompts on this thread with `interaction`, until
+/// the enclosing [`with_interaction`] returns.
+pub fn set_interaction(interaction: Box<dyn Interaction>) {
+    THREAD_INTERACTION.with(|current| *current.borrow_mut() = Some(interaction));
+}
+
+/// Run `f` with the interaction prompts on this thread currently use.
+pub fn with_current<R>(f: impl FnOnce(&mut dyn Interaction) -> R) -> R {
+    THREAD_INTERACTION.with(|current| match current.borrow_mut().as_mut() {
+        Some(interaction) => f(interaction.as_mut()),
+        None => f(&mut ScriptedInteraction::new(Script {
+            answers: HashMap::new(),
+        })),
+    })
+}
+
+/// How prompts reach the user, given which of stdin and stdout are terminals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Terminal {
+    /// Both are: raw-mode keypresses, cursors, and colors
+    Full,
+    /// Only stdin is, e.g. `xlaude open | tee log`: plain numbered prompts on
+    /// stderr, answered a line at a time
+    InputOnly,
+    /// stdin is not: answers come from the pipe, and defaults once it runs dry
+    NoInput,
+}
+
+impl Terminal {
+    fn detect() -> Self {
+        Self::from_ttys(atty::is(Stream::Stdin), atty::is(Stream::Stdout))
+    }
+
+    fn from_ttys(stdin: bool, stdout: bool) -> Self {
+        match (stdin, stdout) {
+            (true, true) => Terminal::Full,
+            (true, false) => Terminal::InputOnly,
+            (false, _) => Terminal::NoInput,
+        }
+    }
+}
+
+// Answers every confirmation with yes
+fn forced_yes() -> bool {
+    std::env::var("XLAUDE_YES").is_ok()
+}
+
+// Takes defaults instead of asking
+fn non_interactive() -> bool {
+    std::env::var("XLAUDE_NON_INTERACTIVE").is_ok()
+}
+
+/// Asks the person at the terminal, honoring `XLAUDE_YES`,
+/// `XLAUDE_NON_INTERACTIVE`, and the configured confirmation policy.
+pub struct TerminalInteraction;
+
+impl Interaction for TerminalInteraction {
+    fn is_terminal(&self) -> bool {
+        true
+    }
+
+    fn confirm(&mut self, prompt: Prompt<'_>, default: bool, destructive: bool) -> Result<bool> {
+        if forced_yes() {
+            return Ok(true);
+        }
+        // The configured policy may answer with the default without asking
+        let policy = XlaudeState::load()
+            .ok()
+            .and_then(|state| state.confirm)
+            .unwrap_or_default();
+        if !policy.should_prompt(destructive) || non_interactive() {
+            return Ok(default);
+        }
+        match Terminal::detect() {
+            Terminal::Full => Confirm::new()
+                .with_prompt(prompt.text)
+                .default(default)
+                .interact()
+                .map_err(Into::into),
+            Terminal::InputOnly => read_plain_confirm(
+                &mut io::stdin().lock(),
+                &mut io::stderr(),
+                prompt.text,
+                default,
+            ),
+            Terminal::NoInput => Ok(default),
+        }
+    }
+
+    fn select(&mut self, prompt: Prompt<'_>, items: &[String]) -> Result<Option<usize>> {
+        if non_interactive() {
+            return Ok(None);
+        }
+        match Terminal::detect() {
+            Terminal::Full => Ok(Some(
+                Select::new()
+                    .with_prompt(prompt.text)
+                    .items(items)
+                    .interact()?,
+            )),
+            Terminal::InputOnly => read_plain_select(
+                &mut io::stdin().lock(),
+                &mut io::stderr(),
+                prompt.text,
+                items,
+            )
+            .map(Some),
+            Terminal::NoInput => Ok(None),
+        }
+    }
+
+    fn choose(&mut self, prompt: Prompt<'_>, choice: &Choice<'_>) -> Result<usize> {
+        if non_interactive() {
+            return Ok(choice.default);
+        }
+        match Terminal::detect() {
+            Terminal::Full => choose_with_keys(prompt.text, choice),
+            Terminal::InputOnly => {
+                let labels: Vec<String> = (0..choice.keys.len())
+                    .map(|index| choice.label(index))
+                    .collect();
+                read_plain_choice(
+                    &mut io::stdin().lock(),
+                    &mut io::stderr(),
+                    &choice.normalized_keys(),
+                    &labels,
+                    choice.default,
+                )
+            }
+            Terminal::NoInput => Ok(choice.default),
+        }
+    }
+}
+
+/// Answers to prompts, given as `{"answers": {"<prompt id>": ...}}` instead
+/// of lines in prompt order.
+#[derive(Debug, Deserialize)]
+pub struct Script {
+    pub answers: HashMap<String, Value>,
+}
+
+impl Script {
+    /// The answer for prompt `id`, or `None` when it has none (left). Each
+    /// answer is used once; an array answers the same prompt several times.
+    fn take(&mut self, id: &str) -> Option<Value> {
+        match self.answers.get_mut(id) {
+            Some(Value::Array(queue)) if !queue.is_empty() => Some(queue.remove(0)),
+            Some(Value::Array(_)) | None => None,
+            Some(_) => self.answers.remove(id),
+        }
+    }
+}
+
+/// Answers prompts from a [`Script`]; prompts it does not answer take their
+/// defaults.
+pub struct ScriptedInteraction {
+    script: Script,
+}
+
+impl ScriptedInteraction {
+    pub fn new(script: Script) -> Self {
+        Self { script }
+    }
+}
+
+impl Interaction for ScriptedInteraction {
+    fn confirm(&mut self, prompt: Prompt<'_>, default: bool, _destructive: bool) -> Result<bool> {
+        match self.script.take(prompt.id) {
+            Some(answer) => scripted_confirm(prompt.id, answer),
+            None => Ok(default),
+        }
+    }
+
+    fn select(&mut self, prompt: Prompt<'_>, items: &[String]) -> Result<Option<usize>> {
+        self.script
+            .take(prompt.id)
+            .map(|answer| scripted_select(prompt.id, answer, items))
+            .transpose()
+    }
+
+    fn choose(&mut self, prompt: Prompt<'_>, choice: &Choice<'_>) -> Result<usize> {
+        match self.script.take(prompt.id) {
+            Some(answer) => scripted_choice(prompt.id, answer, choice),
+            None => Ok(choice.default),
+        }
+    }
+}
+
+/// Answers from stdin, or any reader: a line per prompt, or an answer script
+/// when the first line starts with `{`. Prompts take their defaults once the
+/// input runs dry. `XLAUDE_YES` answers every confirmation with yes.
+pub struct PipedInteraction {
+    reader: BufReader<Box<dyn Read + Send>>,
+    buffer: VecDeque<String>,
+    // Set once the input turned out to be a JSON script
+    script: Option<ScriptedInteraction>,
+    started: bool,
+}
+
+impl PipedInteraction {
+    pub fn stdin() -> Self {
+        Self::from_reader(Box::new(io::stdin()))
+    }
+
+    pub fn from_reader(reader: Box<dyn Read + Send>) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            buffer: VecDeque::new(),
+            script: None,
+            started: false,
+        }
+    }
+
+    /// Look at the first line to tell a JSON script from line-based answers.
+    /// Returns the script, if it is one.
+    fn start(&mut self) -> Result<Option<&mut ScriptedInteraction>> {
+        if !self.started {
+            self.started = true;
+            let mut first = String::new();
+            if self.reader.read_line(&mut first)? > 0 {
+                if first.trim_start().starts_with('{') {
+                    let mut document = first;
+                    self.reader.read_to_string(&mut document)?;
+                    let script = serde_json::from_str(&document).with_category(
+                        ErrorCategory::Usage,
+                        || "Failed to parse the answer script on stdin; expected {\"answers\": {...}}",
+                    )?;
+                    self.script = Some(ScriptedInteraction::new(script));
+                } else {
+                    self.buffer.push_back(first.trim().to_string());
+                }
+            }
+        }
+        Ok(self.script.as_mut())
+    }
+}
+
+impl Interaction for PipedInteraction {
+    /// Nothing is read from an answer script.
+    fn read_line(&mut self) -> Result<Option<String>> {
+        if self.start()?.is_some() {
+            return Ok(None);
+        }
+
+        // Use buffered input first if available
+        if let Some(line) = self.buffer.pop_front() {
+            return Ok(Some(line));
+        }
+
+        let mut line = String::new();
+        match self.reader.read_line(&mut line)? {
+            0 => Ok(None), // EOF
+            _ => Ok(Some(line.trim().to_string())),
+        }
+    }
+
+    fn confirm(&mut self, prompt: Prompt<'_>, default: bool, destructive: bool) -> Result<bool> {
+        if forced_yes() {
+            return Ok(true);
+        }
+        if let Some(script) = self.start()? {
+            return script.confirm(prompt, default, destructive);
+        }
+        match self.read_line()? {
+            Some(input) => {
+                let input = input.to_lowercase();
+                Ok(input == "y" || input == "yes")
+            }
+            None => Ok(default),
+        }
+    }
+
+    fn select(&mut self, prompt: Prompt<'_>, items: &[String]) -> Result<Option<usize>> {
+        if let Some(script) = self.start()? {
+            return script.select(prompt, items);
+        }
+        let Some(input) = self.read_line()? else {
+            return Ok(None);
+        };
+
+        // Try to parse as index
+        if let Ok(index) = input.parse::<usize>()
+            && index < items.len()
+        {
+            return Ok(Some(index));
+        }
+
+        // Try to match display text
+        if let Some(index) = items.iter().position(|item| *item == input) {
+            return Ok(Some(index));
+        }
+
+        anyhow::bail!("Invalid selection: {}", input);
+    }
+
+    fn choose(&mut self, prompt: Prompt<'_>, choice: &Choice<'_>) -> Result<usize> {
+        if let Some(script) = self.start()? {
+            return script.choose(prompt, choice);
+        }
+        let Some(input) = self.read_line()? else {
+            return Ok(choice.default);
+        };
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            return Ok(choice.default);
+        }
+
+        let normalized_keys = choice.normalized_keys();
+        let normalized = trimmed.to_lowercase();
+        let alias = match normalized.as_str() {
+            "y" | "yes" => Some(normalized_keys[choice.default].as_str()),
+            "n" | "no" => Some("n"),
+            _ => None,
+        };
+        if let Some(index) =
+            alias.and_then(|alias| normalized_keys.iter().position(|key| key == alias))
+        {
+            return Ok(index);
+        }
+        if let Some(index) = normalized_keys.iter().position(|key| *key == normalized) {
+            return Ok(index);
+        }
+
+        anyhow::bail!("Invalid selection: {}", trimmed);
+    }
+}
+
+/// For tests: answers prompts by id and hands out lines of input, and fails
+/// on any prompt it was not given an answer for.
+#[derive(Debug, Default)]
+pub struct MockInteraction {
+    answers: HashMap<String, VecDeque<Value>>,
+    lines: VecDeque<String>,
+}
+
+impl MockInteraction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer the next prompt `id` with `answer`, in the form answer scripts use.
+    pub fn answer(mut self, id: &str, answer: impl Into<Value>) -> Self {
+        self.answers
+            .entry(id.to_string())
+            .or_default()
+            .push_back(answer.into());
+        self
+    }
+
+    /// Add a line of piped input.
+    pub fn line(mut self, line: &str) -> Self {
+        self.lines.push_back(line.to_string());
+        self
+    }
+
+    fn take(&mut self, id: &str) -> Result<Value> {
+        self.answers
+            .get_mut(id)
+            .and_then(VecDeque::pop_front)
+            .ok_or_else(|| {
+                categorized(ErrorCategory::Usage, format!("No answer for prompt '{id}'"))
+            })
+    }
+}
+
+impl Interaction for MockInteraction {
+    fn read_line(&mut self) -> Result<Option<String>> {
+        Ok(self.lines.pop_front())
+    }
+
+    fn confirm(&mut self, prompt: Prompt<'_>, _default: bool, _destructive: bool) -> Result<bool> {
+        let answer = self.take(prompt.id)?;
+        scripted_confirm(prompt.id, answer)
+    }
+
+    fn select(&mut self, prompt: Prompt<'_>, items: &[String]) -> Result<Option<usize>> {
+        let answer = self.take(prompt.id)?;
+        scripted_select(prompt.id, answer, items).map(Some)
+    }
+
+    fn choose(&mut self, prompt: Prompt<'_>, choice: &Choice<'_>) -> Result<usize> {
+        let answer = self.take(prompt.id)?;
+        scripted_choice(prompt.id, answer, choice)
+    }
+}
+
+fn scripted_confirm(id: &str, answer: Value) -> Result<bool> {
+    match &answer {
+        Value::Bool(answer) => Ok(*answer),
+        Value::String(text) => match text.to_lowercase().as_str() {
+            "y" | "yes" => Ok(true),
+            "n" | "no" => Ok(false),
+            _ => Err(invalid_answer(id, &answer)),
+        },
+        _ => Err(invalid_answer(id, &answer)),
+    }
+}
+
+/// An item picked by index or by its displayed text.
+fn scripted_select(id: &str, answer: Value, items: &[String]) -> Result<usize> {
+    let index = match &answer {
+        Value::Number(index) => index
+            .as_u64()
+            .map(|index| index as usize)
+            .filter(|index| *index < items.len()),
+        Value::String(text) => items.iter().position(|item| item == text),
+        _ => None,
+    };
+    index.ok_or_else(|| invalid_answer(id, &answer))
+}
+
+/// A key, or a word from a single option's label such as "codex".
+fn scripted_choice(id: &str, answer: Value, choice: &Choice<'_>) -> Result<usize> {
+    let Value::String(text) = &answer else {
+        return Err(invalid_answer(id, &answer));
+    };
+    let wanted = text.to_lowercase();
+    if let Some(index) = choice
+        .normalized_keys()
+        .iter()
+        .position(|key| *key == wanted)
+    {
+        return Ok(index);
+    }
+    let mut matches = (0..choice.keys.len()).filter(|index| {
+        choice
+            .label(*index)
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| word == wanted)
+    });
+    match (matches.next(), matches.next()) {
+        (Some(index), None) => Ok(index),
+        _ => Err(invalid_answer(id, &answer)),
+    }
+}
+
+fn invalid_answer(id: &str, answer: &Value) -> anyhow::Error {
+    categorized(
+        ErrorCategory::Usage,
+        format!("Invalid scripted answer for '{id}': {answer}"),
+    )
+}
+
+/// Read keypresses in raw mode: keys longer than one character are typed out
+/// (Enter confirms a key that starts a longer one), the arrow keys move a
+/// cursor over the options, and `?` prints them again.
+fn choose_with_keys(prompt: &str, choice: &Choice<'_>) -> Result<usize> {
+    struct RawModeGuard;
+    impl Drop for RawModeGuard {
+        fn drop(&mut self) {
+            let _ = disable_raw_mode();
+        }
+    }
+
+    let valid_keys = choice.keys;
+    let normalized_keys = choice.normalized_keys();
+    let default_index = choice.default;
+    let raw_mode_enabled = enable_raw_mode().is_ok();
+    let _guard = raw_mode_enabled.then_some(RawModeGuard);
+
+    let print_options = || match choice.display {
+        Some(display) => (display.print)(),
+        None => println!("Choose from: {}", valid_keys.join(", ")),
+    };
+
+    if !raw_mode_enabled {
+        return read_line_choice(
+            prompt,
+            &normalized_keys,
+            valid_keys,
+            default_index,
+            choice.format_selected,
+            &print_options,
+        );
+    }
+
+    let select = |index: usize| -> Result<usize> {
+        let rendered = (choice.format_selected)(valid_keys[index]);
+        print!("\r\x1b[2K{}{}\r\n", prompt, rendered);
+        io::stdout().flush()?;
+        Ok(index)
+    };
+    // In raw mode a newline does not return the cursor to the start of the line
+    let print_in_raw_mode = |print: &dyn Fn()| {
+        let _ = disable_raw_mode();
+        println!();
+        print();
+        let _ = enable_raw_mode();
+    };
+
+    let mut cursor = default_index;
+    let mut typed = String::new();
+    loop {
+        // The prompt line shows what was typed so far, or the option under the cursor
+        if typed.is_empty() && cursor != default_index {
+            print!("\r\x1b[2K{}{}", prompt, choice.label(cursor).reversed());
+        } else {
+            print!("\r\x1b[2K{}{}", prompt, typed);
+        }
+        io::stdout().flush()?;
+
+        let Event::Key(key_event) = event::read()? else {
+            continue;
+        };
+        if !matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat) {
+            continue;
+        }
+        match key_event.code {
+            KeyCode::Char('c' | 'C') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                print!("\r\n");
+                return Err(interrupt::interrupted());
+            }
+            KeyCode::Up | KeyCode::Left => {
+                typed.clear();
+                cursor = (cursor + valid_keys.len() - 1) % valid_keys.len();
+            }
+            KeyCode::Down | KeyCode::Right | KeyCode::Tab => {
+                typed.clear();
+                cursor = (cursor + 1) % valid_keys.len();
+            }
+            KeyCode::Backspace => {
+                typed.pop();
+            }
+            KeyCode::Enter if typed.is_empty() => return select(cursor),
+            KeyCode::Enter => match normalized_keys.iter().position(|key| *key == typed) {
+                Some(index) => return select(index),
+                None => {
+                    typed.clear();
+                    print_in_raw_mode(&|| {
+                        println!(
+                            "Invalid selection. Please choose from: {}",
+                            valid_keys.join(", ")
+                        )
+                    });
+                }
+            },
+            KeyCode::Esc => return select(default_index),
+            KeyCode::Char(c) => {
+                typed.extend(c.to_lowercase());
+                match match_typed(&normalized_keys, &typed) {
+                    TypedKey::Complete(index) => return select(index),
+                    TypedKey::Partial => {}
+                    TypedKey::Unknown if typed == "?" => {
+                        typed.clear();
+                        print_in_raw_mode(&print_options);
+                    }
+                    TypedKey::Unknown => {
+                        typed.clear();
+                        print_in_raw_mode(&|| {
+                            println!(
+                                "Invalid selection. Please choose from: {}",
+                                valid_keys.join(", ")
+                            )
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum TypedKey {
+    /// Exactly one key, and not the start of a longer one
+    Complete(usize),
+    /// The start of one or more keys; Enter picks an exact match
+    Partial,
+    Unknown,
+}
+
+fn match_typed(keys: &[String], typed: &str) -> TypedKey {
+    let mut candidates = keys
+        .iter()
+        .enumerate()
+        .filter(|(_, key)| key.starts_with(typed));
+    match (candidates.next(), candidates.next()) {
+        (Some((index, key)), None) if key == typed => TypedKey::Complete(index),
+        (Some(_), _) => TypedKey::Partial,
+        (None, _) => TypedKey::Unknown,
+    }
+}
+
+/// Ask for one of `labels` by its key, for terminals that cannot show the menu.
+fn read_plain_choice(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    keys: &[String],
+    labels: &[String],
+    default_index: usize,
+) -> Result<usize> {
+    let print_list = |output: &mut dyn Write| -> io::Result<()> {
+        for (index, label) in labels.iter().enumerate() {
+            let marker = if index == default_index {
+                " (default)"
+            } else {
+                ""
+            };
+            writeln!(output, "  {label}{marker}")?;
+        }
+        Ok(())
+    };
+    print_list(output)?;
+    loop {
+        write!(output, "Choose [{}]: ", keys[default_index])?;
+        output.flush()?;
+        let answer = read_plain_line(input)?.to_lowercase();
+        if answer.is_empty() {
+            return Ok(default_index);
+        }
+        if let Some(index) = keys.iter().position(|key| *key == answer) {
+            return Ok(index);
+        }
+        if answer == "?" {
+            print_list(output)?;
+            continue;
+        }
+        writeln!(
+            output,
+            "Invalid selection. Please choose from: {}",
+            keys.join(", ")
+        )?;
+    }
+}
+
+/// Ask for one of `items` by its number (1-based) or its text.
+fn read_plain_select(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    prompt: &str,
+    items: &[String],
+) -> Result<usize> {
+    writeln!(output, "{prompt}")?;
+    for (index, item) in items.iter().enumerate() {
+        writeln!(output, "  {}) {item}", index + 1)?;
+    }
+    loop {
+        write!(output, "Enter a number (1-{}): ", items.len())?;
+        output.flush()?;
+        let answer = read_plain_line(input)?;
+        if let Ok(number) = answer.parse::<usize>()
+            && (1..=items.len()).contains(&number)
+        {
+            return Ok(number - 1);
+        }
+        if let Some(index) = items.iter().position(|item| *item == answer) {
+            return Ok(index);
+        }
+        writeln!(output, "Invalid selection: {answer}")?;
+    }
+}
+
+fn read_plain_confirm(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    prompt: &str,
+    default: bool,
+) -> Result<bool> {
+    loop {
+        write!(
+            output,
+            "{prompt} {} ",
+            if default { "[Y/n]" } else { "[y/N]" }
+        )?;
+        output.flush()?;
+        match read_plain_line(input)?.to_lowercase().as_str() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => writeln!(output, "Please answer y or n")?,
+        }
+    }
+}
+
+/// The next line of an interactive answer; end of input cancels the prompt.
+fn read_plain_line(input: &mut impl BufRead) -> Result<String> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        anyhow::bail!("Operation cancelled: no more input");
+    }
+    Ok(line.trim().to_string())
+}
+
+fn read_line_choice(
+    prompt: &str,
+    normalized_keys: &[String],
+    valid_keys: &[&str],
+    default_index: usize,
+    format_selected: &dyn Fn(&str) -> String,
+    print_options: &dyn Fn(),
+) -> Result<usize> {
+    loop {
+        if !prompt.is_empty() {
+            print!("{}", prompt);
+            io::stdout().flush()?;
+        }
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let trimmed = input.trim();
+
+        let selected = if trimmed.is_empty() {
+            normalized_keys[default_index].clone()
+        } else {
+            trimmed.to_lowercase()
+        };
+
+        if let Some(index) = normalized_keys.iter().position(|key| key == &selected) {
+            let rendered = format_selected(valid_keys[index]);
+            if prompt.is_empty() {
+                println!("{}", rendered);
+            } else {
+                println!("{}{}", prompt, rendered);
+            }
+            return Ok(index);
+        }
+
+        if selected == "?" {
+            print_options();
+            continue;
+        }
+
+        println!(
+            "Invalid selection. Please choose from: {}",
+            valid_keys.join(", ")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer_scripts_are_used_once_per_prompt() {
+        let script = r#"{"answers": {"worktree": "beta", "fix_state": [true, false]}}"#;
+        let mut piped = PipedInteraction::from_reader(Box::new(script.as_bytes()));
+        let prompt = |id| Prompt { id, text: "" };
+        let items: Vec<String> = ["alpha", "beta"].map(String::from).to_vec();
+        assert_eq!(piped.read_line().unwrap(), None);
+        assert_eq!(piped.select(prompt("worktree"), &items).unwrap(), Some(1));
+        assert_eq!(piped.select(prompt("worktree"), &items).unwrap(), None);
+        assert!(piped.confirm(prompt("fix_state"), false, false).unwrap());
+        assert!(!piped.confirm(prompt("fix_state"), true, false).unwrap());
+        assert!(piped.confirm(prompt("fix_state"), true, false).unwrap());
+
+        let mut piped = PipedInteraction::from_reader(Box::new("y\nfeature\n".as_bytes()));
+        assert!(
+            piped
+                .confirm(prompt("confirm_delete"), false, true)
+                .unwrap()
+        );
+        assert_eq!(piped.read_line().unwrap().as_deref(), Some("feature"));
+        assert_eq!(piped.read_line().unwrap(), None);
+    }
+
+    #[test]
+    fn prompts_fall_back_when_stdin_or_stdout_is_not_a_terminal() {
+        assert_eq!(Terminal::from_ttys(true, true), Terminal::Full);
+        assert_eq!(Terminal::from_ttys(true, false), Terminal::InputOnly);
+        assert_eq!(Terminal::from_ttys(false, true), Terminal::NoInput);
+        assert_eq!(Terminal::from_ttys(false, false), Terminal::NoInput);
+    }
+
+    #[test]
+    fn plain_prompts_read_answers_a_line_at_a_time() {
+        let keys: Vec<String> = ["1", "2", "10", "n"].map(String::from).to_vec();
+        let labels: Vec<String> = ["[1] codex", "[2] Claude", "[10] lazygit", "[N] Skip"]
+            .map(String::from)
+            .to_vec();
+        let mut output = Vec::new();
+        let index = read_plain_choice(&mut "x\n?\n10\n".as_bytes(), &mut output, &keys, &labels, 1)
+            .unwrap();
+        assert_eq!(index, 2);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("  [2] Claude (default)\n"));
+        assert!(output.contains("Invalid selection. Please choose from: 1, 2, 10, n"));
+        assert_eq!(output.matches("  [N] Skip").count(), 2);
+        assert!(!output.contains('\x1b'));
+
+        let index =
+            read_plain_choice(&mut "\n".as_bytes(), &mut Vec::new(), &keys, &labels, 1).unwrap();
+        assert_eq!(index, 1);
+
+        let items: Vec<String> = ["alpha", "beta"].map(String::from).to_vec();
+        let mut output = Vec::new();
+        let index =
+            read_plain_select(&mut "0\nbeta\n".as_bytes(), &mut output, "Pick", &items).unwrap();
+        assert_eq!(index, 1);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("Pick\n  1) alpha\n  2) beta\n"));
+        assert!(output.contains("Invalid selection: 0"));
+        let index = read_plain_select(&mut "1\n".as_bytes(), &mut Vec::new(), "Pick", &items);
+        assert_eq!(index.unwrap(), 0);
+
+        let confirm = |answers: &str, default| {
+            read_plain_confirm(&mut answers.as_bytes(), &mut Vec::new(), "Go?", default)
+        };
+        assert!(confirm("\n", true).unwrap());
+        assert!(!confirm("maybe\nno\n", true).unwrap());
+        assert!(confirm("Y\n", false).unwrap());
+        assert!(confirm("", true).is_err());
+    }
+
+    #[test]
+    fn typed_keys_wait_while_a_longer_key_could_follow() {
+        let keys: Vec<String> = ["1", "2", "10", "11", "vs", "n"]
+            .iter()
+            .map(|key| key.to_string())
+            .collect();
+        assert_eq!(match_typed(&keys, "2"), TypedKey::Complete(1));
+        assert_eq!(match_typed(&keys, "1"), TypedKey::Partial);
+        assert_eq!(match_typed(&keys, "11"), TypedKey::Complete(3));
+        assert_eq!(match_typed(&keys, "v"), TypedKey::Partial);
+        assert_eq!(match_typed(&keys, "vs"), TypedKey::Complete(4));
+        assert_eq!(match_typed(&keys, "x"), TypedKey::Unknown);
+    }
+
+    #[test]
+    fn threads_prompt_independently() {
+        let (asked, answer) = std::sync::mpsc::channel::<()>();
+        let waiting = std::thread::spawn(move || {
+            // Blocks in a prompt until the main thread has answered its own
+            struct Waiting(std::sync::mpsc::Receiver<()>);
+            impl Interaction for Waiting {
+                fn confirm(&mut self, _: Prompt<'_>, _: bool, _: bool) -> Result<bool> {
+                    self.0.recv()?;
+                    Ok(true)
+                }
+                fn select(&mut self, _: Prompt<'_>, _: &[String]) -> Result<Option<usize>> {
+                    Ok(None)
+                }
+                fn choose(&mut self, _: Prompt<'_>, choice: &Choice<'_>) -> Result<usize> {
+                    Ok(choice.default)
+                }
+            }
+            with_interaction(Box::new(Waiting(answer)), || {
+                with_current(|interaction| {
+                    interaction.confirm(Prompt { id: "a", text: "" }, false, false)
+                })
+            })
+        });
+
+        let prompt = Prompt { id: "b", text: "" };
+        let mock = MockInteraction::new().answer("b", false);
+        let confirmed = with_interaction(Box::new(mock), || {
+            with_current(|interaction| interaction.confirm(prompt, true, false))
+        });
+        assert!(!confirmed.unwrap());
+        // Without an interaction of its own, a thread takes the defaults
+        assert!(with_current(|interaction| interaction.confirm(prompt, true, false)).unwrap());
+
+        asked.send(()).unwrap();
+        assert!(waiting.join().unwrap().unwrap());
+    }
+}
@@ -0,0 +1,213 @@
+use std::fs;
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(unix)]
+use std::{ffi::CString, os::unix::ffi::OsStrExt, ptr, sync::atomic::AtomicPtr};
+
+use crate::error::{ErrorCategory, with_exit_code};
+
+// Exit code after Ctrl+C, 128 + SIGINT like a shell
+pub const EXIT_CODE: i32 = 130;
+
+// Set once `install` took over SIGINT; embedders of `run_cli` keep their own handler
+static HANDLER_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+// Guards of code that handles Ctrl+C itself, such as the dashboard's graceful shutdown
+static DEFERRED: AtomicUsize = AtomicUsize::new(0);
+
+// Terminal settings of stdin when the handler was installed
+#[cfg(unix)]
+static SAVED_TERMIOS: OnceLock<libc::termios> = OnceLock::new();
+
+// Paths of live `TempFile`s, as C strings the signal handler can unlink
+#[cfg(unix)]
+static TEMP_FILES: [AtomicPtr<libc::c_char>; 8] = [const { AtomicPtr::new(ptr::null_mut()) }; 8];
+
+/// Handle Ctrl+C (SIGINT) for the whole process: restore the terminal from
+/// raw mode and a hidden cursor, delete live `TempFile`s, and exit with 130.
+/// Programs xlaude is running in the foreground, such as git, get the same
+/// Ctrl+C and stop with it. Supervised agents get the signal forwarded instead
+/// (see `processes::track`).
+#[cfg(unix)]
+pub fn install() {
+    static INSTALLED: std::sync::Once = std::sync::Once::new();
+    INSTALLED.call_once(|| {
+        let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+        // SAFETY: tcgetattr fills `termios` when it succeeds
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, termios.as_mut_ptr()) } == 0 {
+            let _ = SAVED_TERMIOS.set(unsafe { termios.assume_init() });
+        }
+        let handler = on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        // SAFETY: the handler only makes async-signal-safe calls
+        unsafe { libc::signal(libc::SIGINT, handler) };
+        HANDLER_INSTALLED.store(true, Ordering::SeqCst);
+    });
+}
+
+#[cfg(not(unix))]
+pub fn install() {}
+
+#[cfg(unix)]
+extern "C" fn on_interrupt(_signal: libc::c_int) {
+    if DEFERRED.load(Ordering::SeqCst) > 0 {
+        return;
+    }
+    // SAFETY: tcsetattr, isatty, write, unlink, and _exit are async-signal-safe
+    unsafe {
+        if let Some(termios) = SAVED_TERMIOS.get() {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, termios);
+        }
+        if libc::isatty(libc::STDERR_FILENO) == 1 {
+            // Show the cursor that menus hide, and end the prompt's line
+            let reset = b"\x1b[?25h\n";
+            libc::write(libc::STDERR_FILENO, reset.as_ptr().cast(), reset.len());
+        }
+        for slot in &TEMP_FILES {
+            let path = slot.load(Ordering::SeqCst);
+            if !path.is_null() {
+                libc::unlink(path);
+            }
+        }
+        libc::_exit(EXIT_CODE);
+    }
+}
+
+/// Act on Ctrl+C read as a keypress, as in raw mode, like on the signal.
+/// Returns the error to leave with when the signal is handled elsewhere or,
+/// inside another program, not taken over by xlaude at all.
+pub fn interrupted() -> anyhow::Error {
+    #[cfg(unix)]
+    if HANDLER_INSTALLED.load(Ordering::SeqCst) {
+        // SAFETY: raise only delivers SIGINT to this thread
+        unsafe {
+            libc::raise(libc::SIGINT);
+        }
+    }
+    with_exit_code(
+        ErrorCategory::General,
+        EXIT_CODE,
+        "Operation cancelled by Ctrl+C",
+    )
+}
+
+/// Leaves Ctrl+C to the code holding it, which must stop on its own.
+pub struct Deferred;
+
+pub fn defer() -> Deferred {
+    DEFERRED.fetch_add(1, Ordering::SeqCst);
+    Deferred
+}
+
+impl Drop for Deferred {
+    fn drop(&mut self) {
+        DEFERRED.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A temporary file, deleted when dropped or when xlaude is interrupted.
+pub struct TempFile {
+    path: PathBuf,
+    keep: bool,
+    #[cfg(unix)]
+    slot: Option<usize>,
+}
+
+impl TempFile {
+    /// Create a file with `contents` under a fresh name in the temp directory,
+    /// readable only by the user. The name is never reused, so a file or link
+    /// planted there by someone else is not followed.
+    pub fn create(prefix: &str, extension: &str, contents: &str) -> io::Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "{prefix}-{}.{extension}",
+            uuid::Uuid::new_v4().simple()
+        ));
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(&path)?;
+        let temp = TempFile::new(path);
+        file.write_all(contents.as_bytes())?;
+        Ok(temp)
+    }
+
+    /// Take charge of `path`; the file is created by the caller.
+    pub fn new(path: PathBuf) -> Self {
+        #[cfg(unix)]
+        let slot = CString::new(path.as_os_str().as_bytes())
+            .ok()
+            .and_then(|name| {
+                let name = name.into_raw();
+                let slot = TEMP_FILES.iter().position(|slot| {
+                    slot.compare_exchange(ptr::null_mut(), name, Ordering::SeqCst, Ordering::SeqCst)
+                        .is_ok()
+                });
+                if slot.is_none() {
+                    // SAFETY: `name` came from `into_raw` and was not stored
+                    drop(unsafe { CString::from_raw(name) });
+                }
+                slot
+            });
+        TempFile {
+            path,
+            keep: false,
+            #[cfg(unix)]
+            slot,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Leave the file in place, e.g. for the user to pick up.
+    pub fn keep(mut self) {
+        self.keep = true;
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = fs::remove_file(&self.path);
+        }
+        #[cfg(unix)]
+        if let Some(slot) = self.slot {
+            let name = TEMP_FILES[slot].swap(ptr::null_mut(), Ordering::SeqCst);
+            // SAFETY: the slot held the pointer `new` got from `into_raw`
+            drop(unsafe { CString::from_raw(name) });
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temp_files_are_tracked_until_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("draft.txt");
+        fs::write(&path, "draft").unwrap();
+        let file = TempFile::new(path.clone());
+        let tracked = || {
+            TEMP_FILES.iter().any(|slot| {
+                let name = slot.load(Ordering::SeqCst);
+                // SAFETY: live slots hold valid C strings
+                !name.is_null()
+                    && unsafe { std::ffi::CStr::from_ptr(name) }.to_bytes()
+                        == path.as_os_str().as_bytes()
+            })
+        };
+        assert!(tracked());
+        assert_eq!(file.path(), path);
+        drop(file);
+        assert!(!tracked());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::{Context, Result};
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::interaction::{PipedInteraction, with_interaction};
+use crate::{dry_run, replay, state};
+
+/// What a command run with [`run_cli`] printed, and the exit code the binary
+/// would have exited with.
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub code: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    pub fn success(&self) -> bool {
+        self.code == 0
+    }
+}
+
+// Commands share the process's environment, working directory, and stdio
+static RUN_LOCK: Mutex<()> = Mutex::new(());
+
+/// Run `xlaude <args>` in `dir` inside this process instead of spawning the
+/// binary. `env` is set on top of the current environment and `stdin` is
+/// read as piped input, including answer scripts.
+///
+/// Calls are serialized, and the environment, working directory, `--state-dir`,
+/// `--profile`, and `--dry-run` are restored afterwards. Output is captured
+/// from file descriptors 1 and 2, so it is empty while the Rust test harness
+/// captures output (run tests with `--nocapture`, or without the harness as
+/// `tests/run_cli.rs` does, to assert on it); the exit
+/// code and the state are always available. Commands that start xlaude in
+/// the background run `XLAUDE_BIN`, which should point at the binary.
+pub fn run_cli<I, S>(
+    dir: &Path,
+    args: I,
+    env: &[(&str, &str)],
+    stdin: &str,
+) -> Result<CommandOutput>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<OsString>,
+{
+    let _guard = RUN_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let saved_env: Vec<(OsString, OsString)> = std::env::vars_os().collect();
+    let saved_dir = std::env::current_dir().context("Failed to read the working directory")?;
+
+    for (key, value) in env {
+        // SAFETY: `RUN_LOCK` keeps other commands out; callers must not touch
+        // the environment from other threads while a command runs
+        unsafe { std::env::set_var(key, value) };
+    }
+    std::env::set_current_dir(dir)
+        .with_context(|| format!("Failed to change directory to {}", dir.display()))?;
+    // Captured output is not a terminal
+    colored::control::set_override(false);
+
+    let args: Vec<OsString> = std::iter::once(OsString::from("xlaude"))
+        .chain(args.into_iter().map(Into::into))
+        .collect();
+    let stdin = io::Cursor::new(stdin.as_bytes().to_vec());
+    let result = with_interaction(
+        Box::new(PipedInteraction::from_reader(Box::new(stdin))),
+        || capture(|| crate::execute(args)),
+    );
+
+    colored::control::unset_override();
+    replay::stop_recording();
+    state::clear_overrides();
+    dry_run::disable();
+    let _ = std::env::set_current_dir(&saved_dir);
+    restore_env(&saved_env);
+
+    let (code, stdout, stderr) = result?;
+    Ok(CommandOutput {
+        code,
+        stdout,
+        stderr,
+    })
+}
+
+fn restore_env(saved: &[(OsString, OsString)]) {
+    for (key, _) in std::env::vars_os() {
+        if !saved.iter().any(|(saved_key, _)| *saved_key == key) {
+            // SAFETY: see `run_cli`
+            unsafe { std::env::remove_var(&key) };
+        }
+    }
+    for (key, value) in saved {
+        if std::env::var_os(key).as_ref() != Some(value) {
+            // SAFETY: see `run_cli`
+            unsafe { std::env::set_var(key, value) };
+        }
+    }
+}
+
+/// Run `f` with stdout and stderr redirected to files, returning what it printed.
+fn capture(f: impl FnOnce() -> i32) -> Result<(i32, String, String)> {
+    let stdout = Redirect::start(io::stdout().as_raw_fd())?;
+    let stderr = match Redirect::start(io::stderr().as_raw_fd()) {
+        Ok(stderr) => stderr,
+        Err(err) => {
+            let _ = stdout.finish();
+            return Err(err);
+        }
+    };
+    let code = f();
+    let stderr = stderr.finish();
+    let stdout = stdout.finish();
+    Ok((code, stdout?, stderr?))
+}
+
+/// A file descriptor pointed at a temporary file until `finish`.
+struct Redirect {
+    fd: i32,
+    saved: i32,
+    file: File,
+}
+
+impl Redirect {
+    fn start(fd: i32) -> Result<Self> {
+        let path =
+            std::env::temp_dir().join(format!("xlaude-output-{}", uuid::Uuid::new_v4().simple()));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+            .context("Failed to create a file for command output")?;
+        // The open file outlives its name
+        let _ = fs::remove_file(&path);
+
+        flush_stdio();
+        // SAFETY: plain descriptor calls; `saved` is closed in `finish`
+        let saved = unsafe { libc::dup(fd) };
+        if saved < 0 || unsafe { libc::dup2(file.as_raw_fd(), fd) } < 0 {
+            return Err(io::Error::last_os_error()).context("Failed to redirect command output");
+        }
+        Ok(Redirect { fd, saved, file })
+    }
+
+    fn finish(mut self) -> Result<String> {
+        flush_stdio();
+        // SAFETY: restores the descriptor saved in `start`
+        unsafe {
+            libc::dup2(self.saved, self.fd);
+            libc::close(self.saved);
+        }
+        let mut output = Vec::new();
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.read_to_end(&mut output)?;
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
+}
+
+fn flush_stdio() {
+    let _ = io::stdout().flush();
+    let _ = io::stderr().flush();
+}
@@ -0,0 +1,1056 @@
+//! xlaude manages AI coding agents in git worktrees. The binary is a thin
+//! wrapper around [`main`]; [`run_cli`] runs commands inside the calling
+//! process for tests and embedders, whose prompts are answered by an
+//! [`Interaction`] installed with [`with_interaction`].
+
+use anyhow::{Context, Result};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use colored::Colorize;
+use error::ErrorCategory;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use tabular::OutputFormat;
+
+pub use interaction::{
+    Choice, ChoiceDisplay, Interaction, MockInteraction, PipedInteraction, Prompt, Script,
+    ScriptedInteraction, TerminalInteraction, set_interaction, with_interaction,
+};
+#[cfg(unix)]
+pub use invoke::{CommandOutput, run_cli};
+
+mod activity;
+mod agent;
+mod agent_auth;
+mod agent_errors;
+mod backup;
+mod budget;
+mod claude;
+mod codex;
+mod commands;
+mod completions;
+mod daemon;
+mod dashboard;
+mod disk;
+mod docs;
+mod dry_run;
+mod env_snapshot;
+mod error;
+mod git;
+mod history;
+mod hooks;
+mod i18n;
+mod input;
+mod integrity;
+mod interaction;
+mod interrupt;
+#[cfg(unix)]
+mod invoke;
+mod lock;
+mod outcome;
+mod overlap;
+mod processes;
+mod redact;
+mod remote;
+mod replay;
+mod search;
+mod stale;
+mod state;
+mod store;
+mod tabular;
+mod tasks;
+mod time_window;
+mod tracker;
+mod transcript;
+mod update_check;
+mod usage;
+mod utils;
+mod webhook;
+
+use commands::agent_launcher::{LaunchOptions, Resume};
+use commands::list::SessionPage;
+use commands::{
+    CreateOptions, MaintainFormat, PlainField, ReportFormat, handle_add, handle_archive,
+    handle_bundle, handle_checkout, handle_clean, handle_commit, handle_config, handle_conflicts,
+    handle_create, handle_create_many, handle_daemon, handle_daemon_status, handle_dashboard,
+    handle_delete, handle_dir, handle_doctor, handle_duplicate, handle_env_apply,
+    handle_env_capture, handle_external, handle_gc, handle_import, handle_kill, handle_list,
+    handle_lock, handle_maintain, handle_open, handle_prompt_info, handle_recent, handle_rename,
+    handle_report, handle_restore, handle_review, handle_run, handle_sessions_export,
+    handle_sessions_list, handle_sessions_resume, handle_sessions_search, handle_sessions_show,
+    handle_state_backups, handle_state_migrate, handle_state_restore, handle_tag, handle_task_add,
+    handle_task_list, handle_task_remove, handle_trust, handle_unlock, handle_untrust,
+    handle_verify, handle_workspace_create, handle_workspace_delete, handle_workspace_list,
+    handle_workspace_open, handle_workspace_status,
+};
+
+#[derive(Parser)]
+#[command(name = "xlaude")]
+#[command(about = "Manage Claude instances with git worktrees", long_about = None)]
+struct Cli {
+    /// Print the git commands and state changes instead of performing them
+    #[arg(long, global = true)]
+    dry_run: bool,
+    /// Print errors to stderr as JSON: {"code", "category", "message"}
+    #[arg(long, global = true)]
+    json_errors: bool,
+    /// Show times as local timestamps instead of "3h ago"
+    #[arg(long, global = true)]
+    absolute: bool,
+    /// Keep state and caches in DIR instead of the platform default
+    #[arg(long, global = true, value_name = "DIR", env = state::DATA_DIR_ENV)]
+    state_dir: Option<PathBuf>,
+    /// Use a separate set of worktrees, settings, and agent environment
+    #[arg(long, global = true, value_name = "NAME", env = state::PROFILE_ENV)]
+    profile: Option<String>,
+    /// Write every prompt and its answer to FILE, e.g. for a bug report
+    #[arg(long, global = true, hide = true, value_name = "FILE")]
+    record: Option<PathBuf>,
+    /// Answer prompts from a file written by --record
+    #[arg(
+        long,
+        global = true,
+        hide = true,
+        value_name = "FILE",
+        conflicts_with = "record"
+    )]
+    replay: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Create a new git worktree
+    #[command(after_help = "Examples:
+  xlaude create                 # random name, new branch from the current one
+  xlaude create auth-fix
+  xlaude create --issue 42
+  xlaude create --from origin/feature-x
+  xlaude create auth-fix --open --agent codex -p \"Fix the login redirect\"")]
+    Create {
+        /// Name for the worktree (random BIP39 word if not provided)
+        name: Option<String>,
+        /// GitHub issue number to create the worktree from (name derived from its title)
+        #[arg(long, value_name = "NUMBER", conflicts_with = "ticket")]
+        issue: Option<String>,
+        /// Jira/Linear ticket key to create the worktree from (e.g. PROJ-42)
+        #[arg(long, value_name = "KEY")]
+        ticket: Option<String>,
+        /// Sparse-checkout profile from the repository config
+        #[arg(long, value_name = "PROFILE")]
+        sparse: Option<String>,
+        /// Create N worktrees with generated names and print them as JSON
+        #[arg(long, value_name = "N", conflicts_with_all = ["name", "issue", "ticket"])]
+        count: Option<usize>,
+        /// Commit or tag to start from instead of the current branch
+        #[arg(long, value_name = "REV", conflicts_with_all = ["from", "count"])]
+        at: Option<String>,
+        /// Check out --at without creating a branch, for read-only investigation
+        #[arg(long, requires = "at")]
+        detach: bool,
+        /// Remote branch to fetch and track, e.g. origin/feature-x (named after it by default)
+        #[arg(long, value_name = "REMOTE/BRANCH", conflicts_with_all = ["issue", "ticket", "count"])]
+        from: Option<String>,
+        /// Launch an agent in the new worktree right away
+        #[arg(long, conflicts_with = "count")]
+        open: bool,
+        /// Agent to launch with --open instead of showing the menu: claude, codex,
+        /// gemini, or a menu entry
+        #[arg(long, value_name = "AGENT", requires = "open")]
+        agent: Option<String>,
+        /// Initial instruction for the agent launched with --open
+        #[arg(long, short = 'p', requires = "open")]
+        prompt: Option<String>,
+    },
+    /// Checkout a branch or pull request into a worktree
+    Checkout {
+        /// Branch name or pull request number
+        target: Option<String>,
+    },
+    /// Fork a worktree into a new branch starting from its HEAD
+    Duplicate {
+        /// Worktree to duplicate
+        name: String,
+        /// Name for the new worktree and branch
+        new_name: String,
+        /// Carry over uncommitted and untracked changes
+        #[arg(long)]
+        with_changes: bool,
+    },
+    /// Open an existing worktree and launch Claude
+    #[command(after_help = "Examples:
+  xlaude open auth-fix
+  xlaude open auth-fix -p \"Fix the failing login test\"
+  xlaude open auth-fix --continue")]
+    Open {
+        /// Name of the worktree to open (interactive selection if not provided)
+        name: Option<String>,
+        /// Initial instruction to send to the launched agent
+        #[arg(long, short = 'p')]
+        prompt: Option<String>,
+        /// Open the worktree on a remote from the `remotes` config, over SSH
+        #[arg(long, value_name = "NAME")]
+        remote: Option<String>,
+        /// Resume the agent's most recent session in the worktree
+        #[arg(long = "continue", conflicts_with = "select_session")]
+        resume_latest: bool,
+        /// Choose which of the agent's recent sessions to resume
+        #[arg(long)]
+        select_session: bool,
+        /// Model for the agent to use, passed with its model flag (e.g. opus, gpt-5)
+        #[arg(long, short = 'm')]
+        model: Option<String>,
+        /// Print the command, directory, and environment the agent would get instead of launching it
+        #[arg(long)]
+        print_cmd: bool,
+    },
+    /// Pick one of the most recently active worktrees with a single key and open it
+    Recent {
+        /// Number of worktrees to offer (at most 35, one key each)
+        #[arg(long, short = 'n', value_name = "N", default_value_t = 9)]
+        limit: usize,
+        /// Print the worktrees, most recent first, without opening one
+        #[arg(long)]
+        list: bool,
+    },
+    /// Delete a worktree and clean up
+    Delete {
+        /// Name of the worktree to delete (current if not provided)
+        name: Option<String>,
+    },
+    /// Delete a worktree's checkout to free disk space, keeping its branch
+    /// and its entry for `xlaude restore`
+    Archive {
+        /// Name of the worktree
+        name: String,
+    },
+    /// Check an archived worktree out again at its recorded path
+    Restore {
+        /// Name of the worktree
+        name: String,
+    },
+    /// Lock a worktree so git and xlaude will not remove it
+    Lock {
+        /// Name of the worktree
+        name: String,
+        /// Why the worktree is locked (e.g. lives on removable storage)
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Unlock a previously locked worktree
+    Unlock {
+        /// Name of the worktree
+        name: String,
+    },
+    /// Let agents launch in a repository without permission prompts, as configured
+    Trust {
+        /// Repository name (the current repository if not provided)
+        repo: Option<String>,
+    },
+    /// Ask before agents launch in a repository without permission prompts
+    Untrust {
+        /// Repository name (the current repository if not provided)
+        repo: Option<String>,
+    },
+    /// Stop the agent running in a worktree (SIGINT, then SIGTERM/SIGKILL)
+    Kill {
+        /// Name of the worktree
+        name: String,
+        /// Time to wait after each signal, e.g. 10s (default 5s)
+        #[arg(long, value_name = "DURATION")]
+        grace: Option<String>,
+    },
+    /// Show, add, or remove the tags of a worktree
+    Tag {
+        /// Name of the worktree
+        name: String,
+        /// Tags to add (or remove with --remove); shows the current tags when empty
+        tags: Vec<String>,
+        /// Remove the given tags instead of adding them
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Add current worktree to xlaude management
+    Add {
+        /// Name for the worktree (defaults to current branch name)
+        name: Option<String>,
+    },
+    /// Adopt the git checkouts existing Claude Code sessions were recorded in
+    Import,
+    /// Rename a worktree
+    Rename {
+        /// Current name of the worktree
+        old_name: String,
+        /// New name for the worktree
+        new_name: String,
+    },
+    /// List all active Claude instances
+    #[command(after_help = "Examples:
+  xlaude list
+  xlaude list --since 2d --full
+  xlaude list --format tsv")]
+    List {
+        /// Output as JSON (same as --format json)
+        #[arg(long, group = "output")]
+        json: bool,
+        /// Output format; `help` lists the TSV/CSV columns
+        #[arg(long, value_enum, group = "output")]
+        format: Option<OutputFormat>,
+        /// Show disk usage per worktree (cached for an hour)
+        #[arg(long, conflicts_with_all = ["names", "keys", "paths"])]
+        du: bool,
+        /// Print only worktree names, one per line
+        #[arg(long, group = "output")]
+        names: bool,
+        /// Print only `repo/name` keys, one per line
+        #[arg(long, group = "output")]
+        keys: bool,
+        /// Print only worktree paths, one per line
+        #[arg(long, group = "output")]
+        paths: bool,
+        /// Only show worktrees and sessions active after this: a duration like 2h or 7d, or a date
+        #[arg(long, value_name = "WHEN")]
+        since: Option<String>,
+        /// Only show worktrees and sessions active before this: a duration like 2h or 7d, or a date
+        #[arg(long, value_name = "WHEN")]
+        until: Option<String>,
+        /// Show Claude and Codex sessions in separate blocks instead of one
+        /// combined "Recent activity" list
+        #[arg(long)]
+        full: bool,
+        /// Show at most this many sessions per worktree (text output shows 3 by default)
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+        /// Skip this many of each worktree's newest sessions
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        offset: usize,
+        /// List the worktrees of a remote from the `remotes` config instead
+        #[arg(long, value_name = "NAME")]
+        remote: Option<String>,
+    },
+    /// Stage all changes in a worktree and commit them with a drafted message
+    Commit {
+        /// Name of the worktree
+        name: String,
+        /// Skip the pre-commit and commit-msg hooks
+        #[arg(long)]
+        no_verify: bool,
+        /// Amend the previous commit instead of creating a new one
+        #[arg(long)]
+        amend: bool,
+    },
+    /// Step through a worktree's diff against its base branch file by file,
+    /// accepting or reverting each change
+    Review {
+        /// Name of the worktree
+        name: String,
+    },
+    /// Run the repository's verify_commands (formatters, linters, tests) in a
+    /// worktree, failing with their output if one does not pass
+    Verify {
+        /// Name of the worktree
+        name: String,
+    },
+    /// Trial-merge worktree branches with their base branch and with each
+    /// other, and report the pairs that would conflict
+    Conflicts {
+        /// Only check this repository's worktrees
+        #[arg(long, value_name = "NAME")]
+        repo: Option<String>,
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+    /// Clean up invalid worktrees from state
+    Clean {
+        /// Offer worktrees without recent session or commit activity for deletion instead
+        #[arg(long)]
+        stale: bool,
+    },
+    /// Run the housekeeping selected by maintain_tasks (state backup, worktree
+    /// pruning, auto-archiving, stale detection, task cleanup, disk usage
+    /// refresh) and report on it; meant to run from cron
+    Maintain {
+        /// Report format
+        #[arg(long, value_enum, default_value = "text")]
+        format: MaintainFormat,
+    },
+    /// Prune git's records of removed worktrees and reconcile the state with them
+    Gc {
+        /// Also run `git gc` in each repository
+        #[arg(long)]
+        objects: bool,
+    },
+    /// Get the directory path of a worktree
+    Dir {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+        /// Print the worktree root instead of the subdirectory last worked in
+        #[arg(long)]
+        root: bool,
+    },
+    /// Print `repo:name*↑2` for the worktree of the current directory, for shell prompts
+    PromptInfo {
+        /// Leave out the git status, skipping the `git status` call
+        #[arg(long)]
+        no_git: bool,
+    },
+    /// Generate shell completions
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Write man pages or a markdown command reference, e.g. for packaging
+    #[command(
+        hide = true,
+        after_help = "Examples:\n  xlaude gen-docs --man target/man\n  xlaude gen-docs --markdown docs"
+    )]
+    GenDocs {
+        /// Write xlaude.1 and one page per subcommand into DIR
+        #[arg(long, value_name = "DIR", required_unless_present = "markdown")]
+        man: Option<PathBuf>,
+        /// Write the reference of every command to DIR/xlaude.md
+        #[arg(long, value_name = "DIR")]
+        markdown: Option<PathBuf>,
+    },
+    /// Output worktree info for shell completions (hidden)
+    #[command(hide = true)]
+    CompleteWorktrees {
+        /// Output format: simple or detailed
+        #[arg(long, default_value = "simple")]
+        format: String,
+    },
+    /// Open the xlaude state file in $EDITOR
+    Config,
+    /// Diagnose the environment and managed worktrees, offering repairs
+    Doctor {
+        /// Also repair problems in the state file: mismatched keys, duplicate
+        /// entries, foreign repositories, missing branches, odd paths and times
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Launch the embedded dashboard
+    Dashboard {
+        /// Bind address: host:port (default 127.0.0.1:5710) or unix:<socket path>
+        #[arg(long)]
+        addr: Option<String>,
+        /// Do not open the browser automatically
+        #[arg(long)]
+        no_browser: bool,
+        /// Disable launching agents, actions, and settings changes (monitoring only)
+        #[arg(long)]
+        readonly: bool,
+    },
+    /// Run the background session indexer and API server
+    Daemon {
+        #[command(subcommand)]
+        action: Option<DaemonCommands>,
+        /// Bind address: unix:<socket path> (default <config dir>/daemon.sock) or host:port
+        #[arg(long)]
+        addr: Option<String>,
+        /// Disable launching agents, actions, and settings changes through the API
+        #[arg(long)]
+        readonly: bool,
+    },
+    /// Inspect recorded Claude and Codex sessions
+    Sessions {
+        #[command(subcommand)]
+        action: SessionsCommands,
+    },
+    /// Summarize activity per repository, e.g. for a weekly update
+    Report {
+        /// Start of the period: a duration like 7d, or a date
+        #[arg(long, value_name = "WHEN", default_value = "7d")]
+        since: String,
+        /// End of the period (defaults to now): a duration like 1d, or a date
+        #[arg(long, value_name = "WHEN")]
+        until: Option<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value = "md")]
+        format: ReportFormat,
+    },
+    /// Zip a worktree's recent sessions, diff, state, and logs, with secrets
+    /// redacted, to attach to a bug report or share with a teammate
+    Bundle {
+        /// Worktree to bundle (defaults to the current one)
+        name: Option<String>,
+        /// Zip file to write (defaults to xlaude-bundle-<repo>-<name>-<time>.zip)
+        #[arg(long, short = 'o', value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// How many of the most recent sessions to include
+        #[arg(long, value_name = "N", default_value_t = commands::bundle::DEFAULT_SESSIONS)]
+        sessions: usize,
+    },
+    /// Capture the environment an agent runs with, or bring a captured one back
+    Env {
+        #[command(subcommand)]
+        action: EnvCommands,
+    },
+    /// Inspect and restore the automatic backups of the state file
+    State {
+        #[command(subcommand)]
+        action: StateCommands,
+    },
+    /// Manage the per-worktree task queue
+    Task {
+        #[command(subcommand)]
+        action: TaskCommands,
+    },
+    /// Manage named groups of worktrees spanning repositories
+    Workspace {
+        #[command(subcommand)]
+        action: WorkspaceCommands,
+    },
+    /// Run queued tasks through the agent in headless mode
+    #[command(after_help = "Examples:
+  xlaude run
+  xlaude run auth-fix --next
+  xlaude run -j 4 --restart on-failure:2")]
+    Run {
+        /// Only run tasks queued for this worktree
+        worktree: Option<String>,
+        /// Run just the next pending task instead of draining the queue
+        #[arg(long, conflicts_with = "parallel")]
+        next: bool,
+        /// Number of worktrees to run concurrently (one task per worktree at a time)
+        #[arg(long, short = 'j', default_value_t = 1, value_name = "N")]
+        parallel: usize,
+        /// Where to write the JSON run report (defaults to logs/run-<timestamp>.json)
+        #[arg(long, value_name = "PATH")]
+        report: Option<PathBuf>,
+        /// Relaunch agents that exit with an error: `no` (default), `on-failure`, or `on-failure:N`
+        #[arg(long, value_name = "POLICY")]
+        restart: Option<String>,
+        /// Model for the agents to use, passed with their model flag (e.g. opus, gpt-5)
+        #[arg(long, short = 'm')]
+        model: Option<String>,
+    },
+    /// Any other command runs the `xlaude-<name>` executable found on PATH
+    #[command(external_subcommand)]
+    External(Vec<String>),
+}
+
+#[derive(Subcommand)]
+enum DaemonCommands {
+    /// Show whether a daemon is running and what it has indexed
+    Status {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SessionsCommands {
+    /// Search the messages of every session across managed worktrees
+    #[command(after_help = "Examples:
+  xlaude sessions search \"rate limit\"
+  xlaude sessions search migration --repo api --since 7d")]
+    Search {
+        /// Words that must all appear in a message (case-insensitive)
+        query: String,
+        /// Only search worktrees of this repository
+        #[arg(long)]
+        repo: Option<String>,
+        /// Only search worktrees with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only search messages sent after this: a duration like 2h or 7d, or a date
+        #[arg(long, alias = "active", value_name = "WHEN")]
+        since: Option<String>,
+        /// Only search messages sent before this: a duration like 2h or 7d, or a date
+        #[arg(long, value_name = "WHEN")]
+        until: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List the Claude and Codex sessions of managed worktrees, newest first
+    List {
+        /// Only list sessions of this repository's worktrees
+        #[arg(long)]
+        repo: Option<String>,
+        /// Only list sessions of worktrees with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Print only full session ids, one per line
+        #[arg(long, conflicts_with = "json")]
+        ids: bool,
+        /// List at most this many sessions
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+        /// Skip this many of the newest sessions
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        offset: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the messages of a session
+    Show {
+        /// Session id, or a unique prefix of it
+        id: String,
+        /// Show secrets such as API keys instead of redacting them
+        #[arg(long)]
+        no_redact: bool,
+    },
+    /// Print a session's transcript as JSON
+    Export {
+        /// Session id, or a unique prefix of it
+        id: String,
+        /// Keep secrets such as API keys instead of redacting them
+        #[arg(long)]
+        no_redact: bool,
+    },
+    /// Continue a session with its agent in the worktree it was run in
+    Resume {
+        /// Session id, or a unique prefix of it
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum WorkspaceCommands {
+    /// Group worktrees under a workspace name
+    Create {
+        /// Name of the workspace
+        name: String,
+        /// Worktrees to include (name or repo/name)
+        #[arg(required = true)]
+        worktrees: Vec<String>,
+    },
+    /// List workspaces and their members
+    List,
+    /// Open every member in its own tmux window with the configured agent
+    Open {
+        /// Name of the workspace
+        name: String,
+    },
+    /// Show git and session state for every member
+    Status {
+        /// Name of the workspace
+        name: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Delete a workspace (member worktrees are kept)
+    Delete {
+        /// Name of the workspace
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum EnvCommands {
+    /// Save the variables, agent command and version, and git commit of a worktree
+    Capture {
+        /// Name of the worktree (current if not provided)
+        name: Option<String>,
+        /// File to write the snapshot to instead of stdout
+        #[arg(long, short = 'o', value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Print shell commands that restore a snapshot, or run its agent command again
+    Apply {
+        /// Snapshot file, or the id of a task run by `xlaude run`
+        snapshot: String,
+        /// Run the recorded agent command with the recorded variables
+        #[arg(long)]
+        run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum StateCommands {
+    /// List the state snapshots taken before each change
+    Backups,
+    /// Replace the state file with a snapshot (prompts when no id is given)
+    Restore {
+        /// Snapshot id as shown by `xlaude state backups`
+        snapshot: Option<String>,
+    },
+    /// Move the state to another storage backend
+    Migrate {
+        /// Backend to move the state to
+        #[arg(long, value_enum)]
+        to: store::Backend,
+    },
+}
+
+#[derive(Subcommand)]
+enum TaskCommands {
+    /// Queue an instruction for a worktree
+    Add {
+        /// Name of the worktree
+        worktree: String,
+        /// Instruction to hand to the agent
+        instruction: String,
+    },
+    /// List queued and finished tasks
+    List {
+        /// Only show tasks for this worktree
+        worktree: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Remove a task from the queue
+    Remove {
+        /// Task id
+        id: u64,
+    },
+}
+
+/// Run xlaude with `args` (including the program name) and return its exit code.
+/// Ctrl+C is handled by xlaude for the rest of the process's life, and prompts
+/// on the calling thread are answered through stdin and stdout.
+pub fn main(args: impl IntoIterator<Item = impl Into<OsString>>) -> i32 {
+    interrupt::install();
+    with_interaction(interaction::from_stdio(), || execute(args))
+}
+
+/// [`main`] without taking over Ctrl+C, which stays with the program
+/// [`run_cli`] runs inside.
+fn execute(args: impl IntoIterator<Item = impl Into<OsString>>) -> i32 {
+    let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    let cli = match Cli::try_parse_from(&args) {
+        Ok(cli) => cli,
+        Err(err) if err.use_stderr() => {
+            // Usage errors get their own exit code instead of clap's default 2 (not found)
+            if args.iter().any(|arg| arg == "--json-errors") {
+                error::report(
+                    &error::categorized(ErrorCategory::Usage, err.to_string().trim()),
+                    true,
+                );
+            } else {
+                let _ = err.print();
+            }
+            return ErrorCategory::Usage.exit_code();
+        }
+        // --help and --version
+        Err(err) => {
+            let _ = err.print();
+            return err.exit_code();
+        }
+    };
+    let json_errors = cli.json_errors;
+
+    match run(cli, &args) {
+        Ok(()) => 0,
+        Err(err) => error::report(&err, json_errors),
+    }
+}
+
+/// Commands whose work is done by agents, servers, or editors xlaude cannot
+/// stop from changing things, so `--dry-run` refuses them instead of claiming
+/// nothing changed.
+fn without_dry_run(command: &Commands) -> Option<&'static str> {
+    match command {
+        Commands::Run { .. } => Some("run"),
+        Commands::Dashboard { .. } => Some("dashboard"),
+        Commands::Daemon { action: None, .. } => Some("daemon"),
+        Commands::Config => Some("config"),
+        Commands::Env {
+            action: EnvCommands::Apply { run: true, .. },
+        } => Some("env apply --run"),
+        _ => None,
+    }
+}
+
+/// Pick the language of messages, look for a newer release, and export the
+/// `env` setting so that agents, hooks, and plugins see it. A state that cannot
+/// be loaded is reported by the command itself.
+fn apply_configured_settings(check_updates: bool) {
+    let state = state::XlaudeState::load();
+    let language = state
+        .as_ref()
+        .ok()
+        .and_then(|state| state.language.as_deref());
+    i18n::set_language(i18n::detect(language));
+    if check_updates {
+        update_check::check(state.as_ref().is_ok_and(|state| state.no_update_check));
+    }
+    let Ok(state) = state else {
+        return;
+    };
+    for (key, value) in &state.env {
+        // SAFETY: runs before xlaude starts any other thread
+        unsafe { std::env::set_var(key, value) };
+    }
+}
+
+fn run(cli: Cli, args: &[OsString]) -> Result<()> {
+    if cli.dry_run {
+        dry_run::enable();
+    }
+    commands::list::set_absolute_times(cli.absolute);
+    if let Some(dir) = cli.state_dir {
+        // Child processes may run elsewhere
+        let dir = std::path::absolute(&dir)
+            .with_context(|| format!("Invalid state directory {}", dir.display()))?;
+        state::set_data_dir(dir);
+    }
+    if let Some(profile) = &cli.profile {
+        state::set_profile(profile)?;
+    }
+    if cli.dry_run
+        && let Some(command) = without_dry_run(&cli.command)
+    {
+        bail_with!(
+            ErrorCategory::Usage,
+            "'xlaude {}' cannot be previewed with --dry-run",
+            command
+        );
+    }
+    // Output read by shells and scripts stays free of the update hint, and a
+    // dry run leaves the update check's cache alone
+    apply_configured_settings(
+        !cli.dry_run
+            && !matches!(
+                cli.command,
+                Commands::PromptInfo { .. }
+                    | Commands::CompleteWorktrees { .. }
+                    | Commands::Completions { .. }
+            ),
+    );
+    if let Some(path) = &cli.record {
+        let command = args
+            .iter()
+            .skip(1)
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        replay::start_recording(path, command)?;
+    }
+    if let Some(path) = &cli.replay {
+        replay::start_replay(path)?;
+    }
+
+    let is_plugin = matches!(cli.command, Commands::External(_));
+    let result = match cli.command {
+        Commands::Create {
+            sparse,
+            count: Some(count),
+            ..
+        } => handle_create_many(count, sparse),
+        Commands::Create {
+            name,
+            issue,
+            ticket,
+            sparse,
+            count: None,
+            at,
+            detach,
+            from,
+            open,
+            agent,
+            prompt,
+        } => handle_create(CreateOptions {
+            name,
+            issue,
+            ticket,
+            sparse,
+            base: at,
+            from,
+            detach,
+            open,
+            agent,
+            prompt,
+            ..Default::default()
+        }),
+        Commands::Checkout { target } => handle_checkout(target),
+        Commands::Duplicate {
+            name,
+            new_name,
+            with_changes,
+        } => handle_duplicate(name, new_name, with_changes),
+        Commands::Open {
+            remote: Some(remote),
+            ..
+        } => remote::proxy(&remote, true, args),
+        Commands::Open {
+            name,
+            prompt,
+            resume_latest,
+            select_session,
+            model,
+            print_cmd,
+            ..
+        } => {
+            let resume = if select_session {
+                Some(Resume::Select)
+            } else {
+                resume_latest.then_some(Resume::Latest)
+            };
+            handle_open(
+                name,
+                LaunchOptions {
+                    prompt: prompt.as_deref(),
+                    resume,
+                    model: model.as_deref(),
+                    print_cmd,
+                },
+            )
+        }
+        Commands::Recent { limit, list } => handle_recent(limit, list),
+        Commands::Delete { name } => handle_delete(name),
+        Commands::Archive { name } => handle_archive(name),
+        Commands::Restore { name } => handle_restore(name),
+        Commands::Lock { name, reason } => handle_lock(name, reason),
+        Commands::Unlock { name } => handle_unlock(name),
+        Commands::Trust { repo } => handle_trust(repo),
+        Commands::Untrust { repo } => handle_untrust(repo),
+        Commands::Kill { name, grace } => handle_kill(name, grace),
+        Commands::Tag { name, tags, remove } => handle_tag(name, tags, remove),
+        Commands::Add { name } => handle_add(name),
+        Commands::Import => handle_import(),
+        Commands::Rename { old_name, new_name } => handle_rename(old_name, new_name),
+        Commands::List {
+            remote: Some(remote),
+            ..
+        } => remote::proxy(&remote, false, args),
+        Commands::List {
+            json,
+            format,
+            du,
+            names,
+            keys,
+            paths,
+            since,
+            until,
+            full,
+            limit,
+            offset,
+            remote: None,
+        } => {
+            let plain = if names {
+                Some(PlainField::Name)
+            } else if keys {
+                Some(PlainField::Key)
+            } else if paths {
+                Some(PlainField::Path)
+            } else {
+                None
+            };
+            let format = if json {
+                OutputFormat::Json
+            } else {
+                format.unwrap_or(OutputFormat::Text)
+            };
+            handle_list(
+                format,
+                du,
+                plain,
+                since,
+                until,
+                full,
+                SessionPage { offset, limit },
+            )
+        }
+        Commands::Commit {
+            name,
+            no_verify,
+            amend,
+        } => handle_commit(name, no_verify, amend),
+        Commands::Review { name } => handle_review(name),
+        Commands::Verify { name } => handle_verify(name),
+        Commands::Conflicts { repo, json } => handle_conflicts(repo, json),
+        Commands::Clean { stale } => handle_clean(stale),
+        Commands::Maintain { format } => handle_maintain(format),
+        Commands::Gc { objects } => handle_gc(objects),
+        Commands::Dir { name, root } => handle_dir(name, root),
+        Commands::PromptInfo { no_git } => handle_prompt_info(!no_git),
+        Commands::Completions { shell } => completions::handle_completions(shell),
+        Commands::GenDocs { man, markdown } => docs::handle_gen_docs(Cli::command(), man, markdown),
+        Commands::CompleteWorktrees { format } => commands::handle_complete_worktrees(&format),
+        Commands::Config => handle_config(),
+        Commands::Doctor { fix } => handle_doctor(fix),
+        Commands::Dashboard {
+            addr,
+            no_browser,
+            readonly,
+        } => handle_dashboard(addr, no_browser, readonly),
+        Commands::Daemon {
+            action,
+            addr,
+            readonly,
+        } => match action {
+            None => handle_daemon(addr, readonly),
+            Some(DaemonCommands::Status { json }) => handle_daemon_status(json),
+        },
+        Commands::Sessions { action } => match action {
+            SessionsCommands::Search {
+                query,
+                repo,
+                tag,
+                since,
+                until,
+                json,
+            } => handle_sessions_search(query, repo, tag, since, until, json),
+            SessionsCommands::List {
+                repo,
+                tag,
+                ids,
+                limit,
+                offset,
+                json,
+            } => handle_sessions_list(repo, tag, ids, SessionPage { offset, limit }, json),
+            SessionsCommands::Show { id, no_redact } => handle_sessions_show(id, !no_redact),
+            SessionsCommands::Export { id, no_redact } => handle_sessions_export(id, !no_redact),
+            SessionsCommands::Resume { id } => handle_sessions_resume(id),
+        },
+        Commands::Report {
+            since,
+            until,
+            format,
+        } => handle_report(since, until, format),
+        Commands::Bundle {
+            name,
+            output,
+            sessions,
+        } => handle_bundle(name, output, sessions),
+        Commands::Env { action } => match action {
+            EnvCommands::Capture { name, output } => handle_env_capture(name, output),
+            EnvCommands::Apply { snapshot, run } => handle_env_apply(snapshot, run),
+        },
+        Commands::State { action } => match action {
+            StateCommands::Backups => handle_state_backups(),
+            StateCommands::Restore { snapshot } => handle_state_restore(snapshot),
+            StateCommands::Migrate { to } => handle_state_migrate(to),
+        },
+        Commands::Task { action } => match action {
+            TaskCommands::Add {
+                worktree,
+                instruction,
+            } => handle_task_add(worktree, instruction),
+            TaskCommands::List { worktree, json } => handle_task_list(worktree, json),
+            TaskCommands::Remove { id } => handle_task_remove(id),
+        },
+        Commands::Workspace { action } => match action {
+            WorkspaceCommands::Create { name, worktrees } => {
+                handle_workspace_create(name, worktrees)
+            }
+            WorkspaceCommands::List => handle_workspace_list(),
+            WorkspaceCommands::Open { name } => handle_workspace_open(name),
+            WorkspaceCommands::Status { name, json } => handle_workspace_status(name, json),
+            WorkspaceCommands::Delete { name } => handle_workspace_delete(name),
+        },
+        Commands::Run {
+            worktree,
+            next,
+            parallel,
+            report,
+            restart,
+            model,
+        } => handle_run(worktree, next, parallel, report, restart, model),
+        Commands::External(args) => handle_external(args),
+    };
+
+    // Plugins receive XLAUDE_DRY_RUN and report on their own
+    if cli.dry_run && result.is_ok() && !is_plugin {
+        println!("{} Dry run: no changes were made", "ℹ️".blue());
+    }
+    result
+}
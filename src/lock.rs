@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+// One mutex per locked file, so threads of this process queue up like processes do
+static THREAD_LOCKS: Lazy<Mutex<HashMap<PathBuf, &'static Mutex<()>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Exclusive access to a file shared by xlaude's threads and processes, such
+/// as agents.json or tasks.json, until dropped. Other processes are kept out
+/// by an advisory lock on `<file>.lock` next to it.
+pub struct FileLock {
+    _guard: MutexGuard<'static, ()>,
+    #[cfg(unix)]
+    _file: fs::File,
+}
+
+impl FileLock {
+    /// Wait for the lock on `path`. `what` names the file in errors, e.g.
+    /// "the task queue".
+    pub fn acquire(path: &Path, what: &str) -> Result<Self> {
+        let mutex = *THREAD_LOCKS
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .entry(path.to_path_buf())
+            .or_insert_with(|| Box::leak(Box::new(Mutex::new(()))));
+        let guard = mutex.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        #[cfg(unix)]
+        let file = {
+            use std::os::unix::io::AsRawFd;
+            let file = fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(path.with_extension("lock"))
+                .with_context(|| format!("Failed to open the lock of {what}"))?;
+            // SAFETY: flock on a descriptor owned by `file`; released when it closes
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+                return Err(std::io::Error::last_os_error())
+                    .with_context(|| format!("Failed to lock {what}"));
+            }
+            file
+        };
+        Ok(FileLock {
+            _guard: guard,
+            #[cfg(unix)]
+            _file: file,
+        })
+    }
+}
@@ -0,0 +1,216 @@
+use colored::{ColoredString, Colorize};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::agent_errors::{self, Event};
+use crate::claude::{conversation_message, is_conversation_line};
+use crate::transcript::{is_system_message, message_text};
+
+// Tools with which Claude hands the turn back to the user
+const QUESTION_TOOLS: &[&str] = &["AskUserQuestion", "ExitPlanMode"];
+
+/// How a session ended, judged from its trailing events: whether the agent
+/// is waiting for the user, done, stuck on an error, or stopped mid-turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionOutcome {
+    // The agent asked a question, or the user interrupted it
+    AwaitingInput,
+    // The agent finished its turn with a reply
+    Completed,
+    // The last request failed, see `agent_errors`
+    Errored,
+    // The log ends on a prompt or tool call: still running, or killed
+    Unfinished,
+}
+
+impl SessionOutcome {
+    pub fn glyph(self) -> ColoredString {
+        match self {
+            SessionOutcome::AwaitingInput => "?".yellow().bold(),
+            SessionOutcome::Completed => "✓".green(),
+            SessionOutcome::Errored => "✗".red().bold(),
+            SessionOutcome::Unfinished => "…".bright_black(),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SessionOutcome::AwaitingInput => "awaiting input",
+            SessionOutcome::Completed => "completed",
+            SessionOutcome::Errored => "errored",
+            SessionOutcome::Unfinished => "unfinished",
+        }
+    }
+
+    /// Whether a human should look at the session next.
+    pub fn needs_attention(self) -> bool {
+        matches!(
+            self,
+            SessionOutcome::AwaitingInput | SessionOutcome::Errored
+        )
+    }
+
+    /// A reply that ends on a question hands the turn back to the user.
+    fn of_reply(text: &str) -> Self {
+        if text.trim_end().ends_with('?') {
+            SessionOutcome::AwaitingInput
+        } else {
+            SessionOutcome::Completed
+        }
+    }
+}
+
+/// The outcome of a Claude session if it ended on this line, or `None` for
+/// lines that do not change it (summaries, metadata, slash command output).
+pub fn claude_outcome(value: &Value) -> Option<SessionOutcome> {
+    // Subagents and lines Claude Code adds itself do not end the session
+    if !is_conversation_line(value) {
+        return None;
+    }
+    if let Some(Event::Error(_)) = agent_errors::claude_event(value) {
+        return Some(SessionOutcome::Errored);
+    }
+    let content = value.get("message")?.get("content")?;
+    match value.get("type")?.as_str()? {
+        "assistant" => {
+            let blocks = content.as_array().map(Vec::as_slice).unwrap_or_default();
+            let tools: Vec<&str> = blocks
+                .iter()
+                .filter(|block| block.get("type").and_then(Value::as_str) == Some("tool_use"))
+                .filter_map(|block| block.get("name").and_then(Value::as_str))
+                .collect();
+            if tools.iter().any(|tool| QUESTION_TOOLS.contains(tool)) {
+                Some(SessionOutcome::AwaitingInput)
+            } else if !tools.is_empty() {
+                Some(SessionOutcome::Unfinished)
+            } else {
+                message_text(content).map(|text| SessionOutcome::of_reply(&text))
+            }
+        }
+        "user" => match message_text(content) {
+            Some(text) if text.contains("[Request interrupted") => {
+                Some(SessionOutcome::AwaitingInput)
+            }
+            // Tool results the agent has yet to act on
+            None => Some(SessionOutcome::Unfinished),
+            // A prompt, unless Claude Code wrote the whole message itself
+            Some(_) => conversation_message(value).map(|_| SessionOutcome::Unfinished),
+        },
+        _ => None,
+    }
+}
+
+/// The outcome of a Codex session if it ended on this line.
+pub fn codex_outcome(value: &Value) -> Option<SessionOutcome> {
+    if let Some(Event::Error(_)) = agent_errors::codex_event(value) {
+        return Some(SessionOutcome::Errored);
+    }
+    let item = match value.get("type")?.as_str()? {
+        "event_msg" | "response_item" => value.get("payload")?,
+        _ => value,
+    };
+    match item.get("type")?.as_str()? {
+        "task_complete" => Some(
+            item.get("last_agent_message")
+                .and_then(Value::as_str)
+                .map(SessionOutcome::of_reply)
+                .unwrap_or(SessionOutcome::Completed),
+        ),
+        "agent_message" => item
+            .get("message")
+            .and_then(Value::as_str)
+            .map(SessionOutcome::of_reply),
+        "turn_aborted" | "exec_approval_request" | "apply_patch_approval_request" => {
+            Some(SessionOutcome::AwaitingInput)
+        }
+        "user_message"
+        | "function_call"
+        | "function_call_output"
+        | "custom_tool_call"
+        | "custom_tool_call_output"
+        | "local_shell_call" => Some(SessionOutcome::Unfinished),
+        "message" => match item.get("role").and_then(Value::as_str)? {
+            "assistant" => {
+                message_text(item.get("content")?).map(|text| SessionOutcome::of_reply(&text))
+            }
+            "user" => match message_text(item.get("content")?) {
+                Some(text) if is_system_message(&text) => None,
+                _ => Some(SessionOutcome::Unfinished),
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn last(
+        lines: &[Value],
+        outcome: fn(&Value) -> Option<SessionOutcome>,
+    ) -> Option<SessionOutcome> {
+        lines.iter().filter_map(outcome).next_back()
+    }
+
+    #[test]
+    fn sessions_are_classified_by_their_trailing_events() {
+        let prompt = json!({"type": "user", "message": {"content": "Fix the login bug"}});
+        let tool_call = json!({"type": "assistant", "message": {"content": [
+            {"type": "tool_use", "name": "Edit", "input": {}}
+        ]}});
+        let done = json!({"type": "assistant", "message": {"content": [
+            {"type": "text", "text": "Fixed and tested."}
+        ]}});
+        let question =
+            json!({"type": "assistant", "message": {"content": "Should I also update the docs?"}});
+        let summary = json!({"type": "summary", "summary": "Login fix"});
+        let interrupted =
+            json!({"type": "user", "message": {"content": "[Request interrupted by user]"}});
+        let failed = json!({"type": "assistant", "isApiErrorMessage": true, "message": {"content": "API Error: 529 Overloaded"}});
+
+        assert_eq!(
+            last(std::slice::from_ref(&prompt), claude_outcome),
+            Some(SessionOutcome::Unfinished)
+        );
+        assert_eq!(
+            last(&[prompt.clone(), tool_call.clone()], claude_outcome),
+            Some(SessionOutcome::Unfinished)
+        );
+        assert_eq!(
+            last(
+                &[prompt.clone(), tool_call.clone(), done, summary],
+                claude_outcome
+            ),
+            Some(SessionOutcome::Completed)
+        );
+        assert_eq!(
+            last(&[prompt.clone(), question], claude_outcome),
+            Some(SessionOutcome::AwaitingInput)
+        );
+        assert_eq!(
+            last(&[prompt.clone(), tool_call, interrupted], claude_outcome),
+            Some(SessionOutcome::AwaitingInput)
+        );
+        assert_eq!(
+            last(&[prompt, failed], claude_outcome),
+            Some(SessionOutcome::Errored)
+        );
+
+        let codex = [
+            json!({"type": "event_msg", "payload": {"type": "user_message", "message": "Add tests"}}),
+            json!({"type": "response_item", "payload": {"type": "function_call", "name": "shell"}}),
+            json!({"type": "event_msg", "payload": {"type": "task_complete", "last_agent_message": "Added three tests."}}),
+            json!({"type": "event_msg", "payload": {"type": "token_count"}}),
+        ];
+        assert_eq!(last(&codex, codex_outcome), Some(SessionOutcome::Completed));
+        assert_eq!(
+            last(&codex[..2], codex_outcome),
+            Some(SessionOutcome::Unfinished)
+        );
+        assert_eq!(last(&[], codex_outcome), None);
+    }
+}
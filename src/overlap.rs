@@ -0,0 +1,73 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::git;
+use crate::state::{WorktreeInfo, XlaudeState};
+
+/// A file changed by several in-flight worktrees of the same repository.
+#[derive(Debug, Clone, Serialize)]
+pub struct Overlap {
+    pub repo: String,
+    pub path: String,
+    // Names of the worktrees changing it, sorted
+    pub worktrees: Vec<String>,
+}
+
+/// Files changed by more than one of `worktrees` within a repository, each
+/// worktree's changes being its diff (committed or not) against the point
+/// where it forked from the repository's base branch. The files changed by the
+/// most worktrees come first.
+pub fn file_overlaps<'a>(
+    state: &XlaudeState,
+    worktrees: impl IntoIterator<Item = &'a WorktreeInfo>,
+) -> Vec<Overlap> {
+    let mut repos: BTreeMap<&str, Vec<&WorktreeInfo>> = BTreeMap::new();
+    for info in worktrees {
+        if info.path.is_dir() {
+            repos.entry(&info.repo_name).or_default().push(info);
+        }
+    }
+
+    let mut overlaps = Vec::new();
+    for (repo, worktrees) in repos {
+        if worktrees.len() < 2 {
+            continue;
+        }
+        let remote = state.repo_config(repo).base_remote().to_string();
+        let Some(base) = git::base_ref(&worktrees[0].path, &worktrees[0].branch, &remote) else {
+            continue;
+        };
+        let mut changed_by: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for info in worktrees {
+            let Some(paths) = git::merge_base(&info.path, &base)
+                .and_then(|since| git::changed_paths(&info.path, &since))
+            else {
+                continue;
+            };
+            for path in paths {
+                changed_by.entry(path).or_default().push(info.name.clone());
+            }
+        }
+        overlaps.extend(
+            changed_by
+                .into_iter()
+                .filter(|(_, names)| names.len() > 1)
+                .map(|(path, mut names)| {
+                    names.sort();
+                    Overlap {
+                        repo: repo.to_string(),
+                        path,
+                        worktrees: names,
+                    }
+                }),
+        );
+    }
+    overlaps.sort_by(|a, b| {
+        b.worktrees
+            .len()
+            .cmp(&a.worktrees.len())
+            .then_with(|| a.repo.cmp(&b.repo))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    overlaps
+}
@@ -0,0 +1,708 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Child, ExitStatus};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::time::Duration;
+
+use crate::bail_with;
+use crate::error::{ErrorCategory, WithCategory, categorized, with_exit_code};
+use crate::lock::FileLock;
+use crate::state::get_config_dir;
+
+// Termination signal xlaude received while supervising agents, 0 if none
+static RECEIVED_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+// Whether that signal came from the terminal (Ctrl+C, a hangup) rather than
+// from another process, in which case a foreground agent got it as well
+static SIGNAL_FROM_TERMINAL: AtomicBool = AtomicBool::new(false);
+
+// Number of agents being tracked; the signal handlers are installed while non-zero
+static TRACKING: Mutex<usize> = Mutex::new(0);
+
+// Handlers the forwarding ones replaced, such as the Ctrl+C handler
+#[cfg(unix)]
+static PREVIOUS_HANDLERS: Mutex<Vec<libc::sigaction>> = Mutex::new(Vec::new());
+
+// How long a forwarded signal gets to stop a headless agent before it is killed
+const FORWARD_GRACE: Duration = Duration::from_secs(5);
+
+/// An agent launched by xlaude that has not been seen exiting yet.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AgentProcess {
+    pub pid: u32,
+    // State key (`repo/name`) of the worktree the agent runs in
+    pub worktree: String,
+    pub command: String,
+    pub started_at: DateTime<Utc>,
+    // 1 for the first run, incremented each time a restart policy relaunches it
+    #[serde(default = "first_attempt")]
+    pub attempt: u32,
+    // Boot id and kernel start time of the process where /proc has them, so a
+    // process that later reuses the PID, e.g. after a reboot, is not taken for it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_start: Option<String>,
+}
+
+impl AgentProcess {
+    /// Whether the agent is still running: its PID is alive and, where the
+    /// start time was recorded, belongs to the same process.
+    pub fn is_running(&self) -> bool {
+        is_alive(self.pid)
+            && match (&self.process_start, process_start(self.pid)) {
+                (Some(recorded), Some(current)) => *recorded == current,
+                _ => true,
+            }
+    }
+}
+
+fn first_attempt() -> u32 {
+    1
+}
+
+/// Running agents, persisted in `agents.json` so other xlaude processes and the
+/// daemon can see them.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProcessRegistry {
+    #[serde(default)]
+    agents: Vec<AgentProcess>,
+}
+
+impl ProcessRegistry {
+    fn load() -> Result<Self> {
+        let path = get_registry_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path).context("Failed to read process registry")?;
+        // Entries are re-validated against live PIDs, so a corrupt file can be dropped
+        Ok(serde_json::from_str(&content).unwrap_or_default())
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = get_registry_path()?;
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize process registry")?;
+        // Readers that do not take the lock never see a half-written file
+        let partial = path.with_extension("json.tmp");
+        fs::write(&partial, content).context("Failed to write process registry")?;
+        fs::rename(&partial, &path).context("Failed to write process registry")?;
+        Ok(())
+    }
+
+    pub fn agents(&self) -> &[AgentProcess] {
+        &self.agents
+    }
+
+    pub fn for_worktree(&self, key: &str) -> Vec<&AgentProcess> {
+        self.agents
+            .iter()
+            .filter(|agent| agent.worktree == key)
+            .collect()
+    }
+
+    /// Forget agents whose process is gone or whose PID now belongs to another
+    /// process. Returns whether anything was removed.
+    pub fn prune(&mut self) -> bool {
+        let before = self.agents.len();
+        self.agents.retain(AgentProcess::is_running);
+        self.agents.len() != before
+    }
+
+    /// Load the registry, drop stale entries, and persist the result if it changed.
+    pub fn load_live() -> Result<Self> {
+        let _lock = RegistryLock::acquire()?;
+        let mut registry = Self::load()?;
+        if registry.prune() && !crate::dry_run::is_enabled() {
+            registry.save()?;
+        }
+        Ok(registry)
+    }
+}
+
+/// Exclusive access to agents.json, for this thread and against other
+/// processes, until dropped.
+struct RegistryLock {
+    _lock: FileLock,
+}
+
+impl RegistryLock {
+    fn acquire() -> Result<Self> {
+        Ok(RegistryLock {
+            _lock: FileLock::acquire(&get_registry_path()?, "the process registry")?,
+        })
+    }
+}
+
+/// Record a freshly spawned agent.
+pub fn register(agent: AgentProcess) -> Result<()> {
+    let _lock = RegistryLock::acquire()?;
+    let mut registry = ProcessRegistry::load()?;
+    registry.prune();
+    registry.agents.retain(|existing| existing.pid != agent.pid);
+    registry.agents.push(agent);
+    registry.save()
+}
+
+/// Remove an agent from the registry, returning its entry if it was still listed.
+pub fn unregister(pid: u32) -> Result<Option<AgentProcess>> {
+    let _lock = RegistryLock::acquire()?;
+    let mut registry = ProcessRegistry::load()?;
+    let removed = registry
+        .agents
+        .iter()
+        .position(|agent| agent.pid == pid)
+        .map(|index| registry.agents.remove(index));
+    registry.prune();
+    registry.save()?;
+    Ok(removed)
+}
+
+/// How a tracked agent ended.
+#[derive(Debug, Clone, Copy)]
+pub struct AgentExit {
+    pub status: ExitStatus,
+    // Removed from the registry by `xlaude kill` while it was running
+    pub stopped: bool,
+    // Signal xlaude received and forwarded to the agent
+    pub interrupted: Option<i32>,
+}
+
+/// Wait for a spawned agent while it is listed in the registry, then record how
+/// it ended in the job log. A SIGINT, SIGTERM, or SIGHUP sent to xlaude in the
+/// meantime is forwarded to the agent; see `wait_forwarding_signals` for how
+/// headless and interactive agents differ.
+pub fn track(
+    mut child: Child,
+    worktree: String,
+    command: &str,
+    attempt: u32,
+) -> std::io::Result<AgentExit> {
+    let agent = AgentProcess {
+        pid: child.id(),
+        worktree,
+        command: command.to_string(),
+        started_at: Utc::now(),
+        attempt,
+        process_start: process_start(child.id()),
+    };
+    let registered = register(agent.clone());
+    if let Err(err) = &registered {
+        eprintln!("⚠️  Failed to record agent process: {err:#}");
+    }
+
+    let (status, interrupted) = {
+        let _signals = SignalGuard::install();
+        wait_forwarding_signals(&mut child)?
+    };
+
+    // `xlaude kill` removes the entry itself and logs the termination
+    let mut stopped = false;
+    if registered.is_ok() {
+        let outcome = match interrupted {
+            Some(_) => JobOutcome::Interrupted,
+            None => JobOutcome::Exited,
+        };
+        match unregister(agent.pid) {
+            Ok(Some(agent)) => log_exit(agent, &status, outcome),
+            Ok(None) => stopped = true,
+            Err(_) => {}
+        }
+    }
+    Ok(AgentExit {
+        status,
+        stopped,
+        interrupted,
+    })
+}
+
+/// Signal xlaude received while an agent was running, if any.
+pub fn received_signal() -> Option<i32> {
+    match RECEIVED_SIGNAL.load(Ordering::SeqCst) {
+        0 => None,
+        signal => Some(signal),
+    }
+}
+
+/// Error to leave xlaude with after forwarding `signal` to its agents, exiting
+/// with 128 + the signal number like a shell.
+pub fn interrupted_error(signal: i32) -> anyhow::Error {
+    with_exit_code(
+        ErrorCategory::AgentFailure,
+        128 + signal,
+        format!("Interrupted by {}", describe_signal(signal)),
+    )
+}
+
+#[cfg(unix)]
+fn describe_signal(signal: i32) -> String {
+    signal_name(signal)
+}
+
+#[cfg(not(unix))]
+fn describe_signal(signal: i32) -> String {
+    format!("signal {signal}")
+}
+
+/// Keeps the forwarding signal handlers installed while at least one guard lives.
+struct SignalGuard;
+
+#[cfg(unix)]
+const FORWARDED_SIGNALS: [i32; 3] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP];
+
+#[cfg(unix)]
+extern "C" fn record_signal(
+    signal: libc::c_int,
+    info: *mut libc::siginfo_t,
+    _context: *mut libc::c_void,
+) {
+    // The kernel sends the signals of keys typed at the terminal; kill(2) and
+    // friends leave a code of zero or below
+    // SAFETY: with SA_SIGINFO the kernel passes a valid siginfo
+    let from_process = !info.is_null() && unsafe { (*info).si_code } <= 0;
+    SIGNAL_FROM_TERMINAL.store(!from_process, Ordering::SeqCst);
+    RECEIVED_SIGNAL.store(signal, Ordering::SeqCst);
+}
+
+impl SignalGuard {
+    fn install() -> Self {
+        let mut tracking = TRACKING.lock().unwrap();
+        #[cfg(unix)]
+        if *tracking == 0 {
+            // SAFETY: an all-zero sigaction is a valid value to fill in
+            let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+            action.sa_sigaction = record_signal
+                as extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void)
+                as libc::sighandler_t;
+            action.sa_flags = libc::SA_SIGINFO;
+            let mut previous = PREVIOUS_HANDLERS.lock().unwrap();
+            previous.clear();
+            for signal in FORWARDED_SIGNALS {
+                // SAFETY: as above
+                let mut replaced: libc::sigaction = unsafe { std::mem::zeroed() };
+                // SAFETY: the handler only stores into atomics
+                unsafe { libc::sigaction(signal, &action, &mut replaced) };
+                previous.push(replaced);
+            }
+        }
+        *tracking += 1;
+        SignalGuard
+    }
+}
+
+impl Drop for SignalGuard {
+    fn drop(&mut self) {
+        let mut tracking = TRACKING.lock().unwrap();
+        *tracking -= 1;
+        #[cfg(unix)]
+        if *tracking == 0 {
+            let previous = PREVIOUS_HANDLERS.lock().unwrap();
+            for (signal, previous) in FORWARDED_SIGNALS.into_iter().zip(previous.iter()) {
+                // SAFETY: restores the disposition `install` replaced
+                unsafe { libc::sigaction(signal, previous, std::ptr::null_mut()) };
+            }
+        }
+    }
+}
+
+/// Wait for `child`, forwarding a received termination signal to it. Returns its
+/// exit status and the forwarded signal.
+///
+/// Headless agents run in their own process group, which gets the signal and
+/// is killed if it outlives the grace period. An interactive agent shares
+/// xlaude's group, so a signal from the terminal reaches it directly: it is
+/// left to decide, as Claude does when Ctrl+C only cancels a turn. Signals
+/// from other processes are passed on to it, but never escalated.
+#[cfg(unix)]
+fn wait_forwarding_signals(child: &mut Child) -> std::io::Result<(ExitStatus, Option<i32>)> {
+    let pid = child.id() as libc::pid_t;
+    // SAFETY: getpgid only reads process attributes
+    let leads_group = unsafe { libc::getpgid(pid) } == pid;
+    let target = if leads_group { -pid } else { pid };
+
+    let mut forwarded: Option<(i32, std::time::Instant)> = None;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok((status, forwarded.map(|(signal, _)| signal)));
+        }
+        match forwarded {
+            None => {
+                if let Some(signal) = received_signal() {
+                    if leads_group {
+                        // SAFETY: plain kill(2) on our own child
+                        unsafe { libc::kill(target, signal) };
+                        forwarded = Some((signal, std::time::Instant::now()));
+                    } else {
+                        if !SIGNAL_FROM_TERMINAL.load(Ordering::SeqCst) {
+                            // SAFETY: plain kill(2) on our own child
+                            unsafe { libc::kill(target, signal) };
+                        }
+                        // Whether the agent stops is up to it; its exit
+                        // status tells
+                        let _ = RECEIVED_SIGNAL.compare_exchange(
+                            signal,
+                            0,
+                            Ordering::SeqCst,
+                            Ordering::SeqCst,
+                        );
+                    }
+                }
+            }
+            Some((_, since)) if leads_group && since.elapsed() >= FORWARD_GRACE => {
+                // SAFETY: plain kill(2) on our own child
+                unsafe { libc::kill(target, libc::SIGKILL) };
+            }
+            Some(_) => {}
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[cfg(not(unix))]
+fn wait_forwarding_signals(child: &mut Child) -> std::io::Result<(ExitStatus, Option<i32>)> {
+    Ok((child.wait()?, None))
+}
+
+fn log_exit(agent: AgentProcess, status: &ExitStatus, outcome: JobOutcome) {
+    let record = JobRecord {
+        finished_at: Utc::now(),
+        outcome,
+        exit_code: status.code(),
+        signal: exit_signal(status),
+        agent,
+    };
+    if let Err(err) = log_job(&record) {
+        eprintln!("⚠️  Failed to write job log: {err:#}");
+    }
+}
+
+/// Whether a headless agent that exited unsuccessfully is launched again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    OnFailure {
+        max_restarts: u32,
+    },
+}
+
+impl RestartPolicy {
+    // Restarts allowed by a bare `on-failure`
+    const DEFAULT_MAX_RESTARTS: u32 = 3;
+    const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+    /// Parse `no`, `on-failure`, or `on-failure:N`.
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        match value.split_once(':') {
+            None if value == "no" || value == "never" => Ok(Self::Never),
+            None if value == "on-failure" => Ok(Self::OnFailure {
+                max_restarts: Self::DEFAULT_MAX_RESTARTS,
+            }),
+            Some(("on-failure", count)) => count
+                .trim()
+                .parse()
+                .map(|max_restarts| Self::OnFailure { max_restarts })
+                .with_category(ErrorCategory::Usage, || {
+                    format!("Invalid restart count '{count}'")
+                }),
+            _ => bail_with!(
+                ErrorCategory::Usage,
+                "Invalid restart policy '{}'. Use 'no', 'on-failure', or 'on-failure:N'",
+                value
+            ),
+        }
+    }
+
+    /// Whether a failed run may be retried as attempt `next_attempt`.
+    pub fn allows(&self, next_attempt: u32) -> bool {
+        match self {
+            Self::Never => false,
+            Self::OnFailure { max_restarts } => next_attempt <= max_restarts.saturating_add(1),
+        }
+    }
+
+    /// Delay before launching attempt `next_attempt`: 1s, 2s, 4s, ... up to a minute.
+    pub fn backoff(next_attempt: u32) -> Duration {
+        let exponent = next_attempt.saturating_sub(2).min(6);
+        (Duration::from_secs(1) * 2u32.pow(exponent)).min(Self::MAX_BACKOFF)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobOutcome {
+    Exited,
+    Killed,
+    // xlaude itself was asked to stop and took the agent down with it
+    Interrupted,
+}
+
+/// One line of the job log: an agent run and how it ended.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRecord {
+    #[serde(flatten)]
+    pub agent: AgentProcess,
+    pub finished_at: DateTime<Utc>,
+    pub outcome: JobOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    // Signal that ended the process, e.g. "SIGTERM"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<String>,
+}
+
+pub fn log_job(record: &JobRecord) -> Result<()> {
+    let path = get_job_log_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create log directory")?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .context("Failed to open job log")?;
+    let line = serde_json::to_string(record).context("Failed to serialize job record")?;
+    writeln!(file, "{line}").context("Failed to write job log")?;
+    Ok(())
+}
+
+pub fn get_job_log_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("logs").join("jobs.jsonl"))
+}
+
+pub fn get_registry_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("agents.json"))
+}
+
+#[cfg(unix)]
+pub fn is_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks that the process exists and may be signalled
+    // SAFETY: kill with signal 0 has no side effects
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// `<boot id>:<start time>` of a process, the start time in clock ticks since
+/// boot (field 22 of /proc/<pid>/stat). `None` where /proc is not available.
+pub fn process_start(pid: u32) -> Option<String> {
+    let boot_id = fs::read_to_string("/proc/sys/kernel/random/boot_id").ok()?;
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The command name in parentheses may contain spaces; fields follow it
+    let fields = &stat[stat.rfind(')')? + 1..];
+    let start_ticks = fields.split_whitespace().nth(19)?;
+    Some(format!("{}:{start_ticks}", boot_id.trim()))
+}
+
+#[cfg(not(unix))]
+pub fn is_alive(_pid: u32) -> bool {
+    // Without a portable liveness check, trust the registry until unregister
+    true
+}
+
+/// Stop a process politely: SIGINT, then SIGTERM, then SIGKILL, waiting up to
+/// `grace` after each. Returns the signal that ended it, or `None` when it was
+/// already gone.
+#[cfg(unix)]
+pub fn terminate(pid: u32, grace: Duration) -> Result<Option<&'static str>> {
+    let target = libc::pid_t::try_from(pid).context("Invalid process id")?;
+    for (signal, name) in [
+        (libc::SIGINT, "SIGINT"),
+        (libc::SIGTERM, "SIGTERM"),
+        (libc::SIGKILL, "SIGKILL"),
+    ] {
+        if !is_alive(pid) {
+            return Ok(None);
+        }
+        // SAFETY: plain kill(2) on a pid taken from the registry
+        if unsafe { libc::kill(target, signal) } != 0 {
+            let err = std::io::Error::last_os_error();
+            if err.raw_os_error() == Some(libc::ESRCH) {
+                return Ok(None);
+            }
+            return Err(err).with_context(|| format!("Failed to send {name} to pid {pid}"));
+        }
+        if wait_for_exit(pid, grace) {
+            return Ok(Some(name));
+        }
+    }
+    anyhow::bail!("Process {pid} is still running after SIGKILL")
+}
+
+#[cfg(not(unix))]
+pub fn terminate(_pid: u32, _grace: Duration) -> Result<Option<&'static str>> {
+    anyhow::bail!("Stopping agents is only supported on Unix platforms")
+}
+
+#[cfg(unix)]
+fn wait_for_exit(pid: u32, timeout: Duration) -> bool {
+    let deadline = std::time::Instant::now() + timeout;
+    while std::time::Instant::now() < deadline {
+        if !is_alive(pid) {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    !is_alive(pid)
+}
+
+/// Error for an agent that exited unsuccessfully. xlaude then exits with the
+/// agent's own code, or 128 + the signal number when a signal ended it.
+pub fn exit_error(status: &ExitStatus) -> anyhow::Error {
+    if let Some(code) = status.code() {
+        return with_exit_code(
+            ErrorCategory::AgentFailure,
+            code,
+            format!("Agent exited with code {code}"),
+        );
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return with_exit_code(
+                ErrorCategory::AgentFailure,
+                128 + signal,
+                format!("Agent was terminated by {}", signal_name(signal)),
+            );
+        }
+    }
+    categorized(ErrorCategory::AgentFailure, "Agent exited with error")
+}
+
+/// Shell-style exit code for a signal recorded by `exit_signal`.
+#[cfg(unix)]
+pub fn signal_exit_code(name: &str) -> Option<i32> {
+    let signal = match name {
+        "SIGINT" => libc::SIGINT,
+        "SIGTERM" => libc::SIGTERM,
+        "SIGKILL" => libc::SIGKILL,
+        "SIGHUP" => libc::SIGHUP,
+        other => other.strip_prefix("signal ")?.parse().ok()?,
+    };
+    Some(128 + signal)
+}
+
+#[cfg(not(unix))]
+pub fn signal_exit_code(_name: &str) -> Option<i32> {
+    None
+}
+
+#[cfg(unix)]
+pub fn exit_signal(status: &ExitStatus) -> Option<String> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal().map(signal_name)
+}
+
+#[cfg(not(unix))]
+pub fn exit_signal(_status: &ExitStatus) -> Option<String> {
+    None
+}
+
+#[cfg(unix)]
+fn signal_name(signal: i32) -> String {
+    match signal {
+        libc::SIGINT => "SIGINT".to_string(),
+        libc::SIGTERM => "SIGTERM".to_string(),
+        libc::SIGKILL => "SIGKILL".to_string(),
+        libc::SIGHUP => "SIGHUP".to_string(),
+        other => format!("signal {other}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn prune_drops_exited_processes() {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let dead_pid = child.id();
+        child.wait().unwrap();
+
+        let agent = |pid| AgentProcess {
+            pid,
+            worktree: "repo/feat".into(),
+            command: "claude".into(),
+            started_at: Utc::now(),
+            attempt: 1,
+            process_start: None,
+        };
+        let mut registry = ProcessRegistry {
+            agents: vec![agent(std::process::id()), agent(dead_pid)],
+        };
+        assert!(registry.prune());
+        assert_eq!(registry.agents().len(), 1);
+        assert_eq!(registry.agents()[0].pid, std::process::id());
+        assert!(!registry.prune());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn prune_drops_entries_whose_pid_was_reused() {
+        let pid = std::process::id();
+        let agent = |process_start: Option<String>| AgentProcess {
+            pid,
+            worktree: "repo/feat".into(),
+            command: "claude".into(),
+            started_at: Utc::now(),
+            attempt: 1,
+            process_start,
+        };
+        let current = process_start(pid).expect("/proc describes this process");
+        assert_eq!(process_start(pid).as_deref(), Some(current.as_str()));
+        let mut registry = ProcessRegistry {
+            agents: vec![
+                agent(Some(current.clone())),
+                agent(Some("a-previous-boot:12345".into())),
+            ],
+        };
+        assert!(registry.prune());
+        assert_eq!(registry.agents().len(), 1);
+        assert_eq!(registry.agents()[0].process_start, Some(current));
+    }
+
+    #[test]
+    fn parses_restart_policies() {
+        assert_eq!(RestartPolicy::parse("no").unwrap(), RestartPolicy::Never);
+        assert_eq!(
+            RestartPolicy::parse("on-failure").unwrap(),
+            RestartPolicy::OnFailure { max_restarts: 3 }
+        );
+        let policy = RestartPolicy::parse("on-failure:1").unwrap();
+        assert!(policy.allows(2));
+        assert!(!policy.allows(3));
+        assert!(RestartPolicy::parse("always").is_err());
+        assert!(RestartPolicy::parse("on-failure:x").is_err());
+
+        assert_eq!(RestartPolicy::backoff(2), Duration::from_secs(1));
+        assert_eq!(RestartPolicy::backoff(4), Duration::from_secs(4));
+        assert_eq!(RestartPolicy::backoff(20), Duration::from_secs(60));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn agent_exit_codes_are_propagated() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let exited = ExitStatus::from_raw(3 << 8);
+        let err = exit_error(&exited);
+        assert_eq!(crate::error::exit_code(&err), 3);
+        assert_eq!(err.to_string(), "Agent exited with code 3");
+
+        let interrupted = ExitStatus::from_raw(libc::SIGINT);
+        let err = exit_error(&interrupted);
+        assert_eq!(crate::error::exit_code(&err), 130);
+        assert_eq!(err.to_string(), "Agent was terminated by SIGINT");
+        assert_eq!(signal_exit_code("SIGTERM"), Some(143));
+        assert_eq!(signal_exit_code("signal 10"), Some(138));
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use std::ffi::OsString;
+use std::process::Command;
+
+use crate::error::{self, ErrorCategory, WithCategory};
+use crate::state::XlaudeState;
+
+/// Run this invocation of xlaude, given by its `args`, on the configured remote
+/// `name` over SSH without its host-local flags, and exit with the remote
+/// command's status. `tty` allocates a terminal for commands that launch an agent.
+pub fn proxy(name: &str, tty: bool, args: &[OsString]) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let remote = state
+        .remotes
+        .get(name)
+        .with_category(ErrorCategory::NotFound, || {
+            format!("Remote '{name}' is not configured (see remotes in state.json)")
+        })?;
+    let args = forwarded_args(
+        args.iter()
+            .skip(1)
+            .map(|arg| arg.to_string_lossy().into_owned()),
+    );
+    let command = format!("{} {}", remote.command(), shell_words::join(&args));
+
+    let status = Command::new("ssh")
+        .arg(if tty { "-t" } else { "-T" })
+        .arg(&remote.host)
+        .arg(&command)
+        .status()
+        .context("Failed to run ssh; is it installed?")?;
+    if !status.success() {
+        // ssh exits with the remote command's status, or 255 when it cannot connect
+        return Err(error::exit_status(status.code().unwrap_or(1)));
+    }
+    Ok(())
+}
+
+// Global flags naming paths or hosts on this machine, each with a value
+const LOCAL_FLAGS: &[&str] = &["--remote", "--state-dir", "--record", "--replay"];
+
+/// The command-line arguments minus the `LOCAL_FLAGS`, given either as
+/// `--flag <value>` or `--flag=<value>`.
+fn forwarded_args(args: impl Iterator<Item = String>) -> Vec<String> {
+    let mut forwarded = Vec::new();
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            forwarded.push(arg);
+            forwarded.extend(args);
+            break;
+        }
+        if LOCAL_FLAGS.contains(&arg.as_str()) {
+            args.next();
+        } else if !LOCAL_FLAGS.iter().any(|flag| {
+            arg.strip_prefix(flag)
+                .is_some_and(|rest| rest.starts_with('='))
+        }) {
+            forwarded.push(arg);
+        }
+    }
+    forwarded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_only_host_local_flags() {
+        let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            forwarded_args(args(&["list", "--remote", "dev", "--json"]).into_iter()),
+            args(&["list", "--json"])
+        );
+        assert_eq!(
+            forwarded_args(args(&["open", "--remote=dev", "feat", "-p", "--remote x"]).into_iter()),
+            args(&["open", "feat", "-p", "--remote x"])
+        );
+        assert_eq!(
+            forwarded_args(
+                args(&[
+                    "--state-dir",
+                    "/tmp/x",
+                    "--record=a.json",
+                    "list",
+                    "--replay",
+                    "b.json",
+                    "--state-directory",
+                    "--",
+                    "--record",
+                ])
+                .into_iter()
+            ),
+            args(&["list", "--state-directory", "--", "--record"])
+        );
+    }
+}
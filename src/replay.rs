@@ -0,0 +1,107 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::env_snapshot::EnvSnapshot;
+use crate::error::{ErrorCategory, WithCategory};
+use crate::interaction::{self, Script, ScriptedInteraction};
+
+/// A prompt and the answer it got, however it was answered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Exchange {
+    id: String,
+    // confirm, select, or choice
+    kind: String,
+    prompt: String,
+    answer: Value,
+}
+
+/// A recording: the command and its exchanges for people reading a bug report,
+/// and the same answers as an answer script for `--replay`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Recording {
+    command: Vec<String>,
+    // What the command ran with, for reproducing it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    environment: Option<EnvSnapshot>,
+    exchanges: Vec<Exchange>,
+    answers: Map<String, Value>,
+}
+
+struct Recorder {
+    path: PathBuf,
+    recording: Recording,
+}
+
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+/// Write every prompt and its answer to `path` from now on. The file is
+/// rewritten after each answer, so it survives a command that fails later.
+pub fn start_recording(path: &Path, command: Vec<String>) -> Result<()> {
+    let recorder = Recorder {
+        path: path.to_path_buf(),
+        recording: Recording {
+            command,
+            environment: EnvSnapshot::capture_here().ok(),
+            ..Default::default()
+        },
+    };
+    recorder.write()?;
+    *RECORDER.lock().unwrap() = Some(recorder);
+    Ok(())
+}
+
+pub fn stop_recording() {
+    *RECORDER.lock().unwrap() = None;
+}
+
+/// Note an answered prompt when recording.
+pub fn record(id: &str, kind: &str, prompt: &str, answer: Value) {
+    let mut recorder = RECORDER.lock().unwrap();
+    let Some(recorder) = recorder.as_mut() else {
+        return;
+    };
+    let answers = recorder
+        .recording
+        .answers
+        .entry(id)
+        .or_insert_with(|| Value::Array(Vec::new()));
+    if let Value::Array(answers) = answers {
+        answers.push(answer.clone());
+    }
+    recorder.recording.exchanges.push(Exchange {
+        id: id.to_string(),
+        kind: kind.to_string(),
+        prompt: prompt.trim().to_string(),
+        answer,
+    });
+    if let Err(err) = recorder.write() {
+        eprintln!("Warning: failed to update the recording: {err:#}");
+    }
+}
+
+impl Recorder {
+    fn write(&self) -> Result<()> {
+        let content = serde_json::to_string_pretty(&self.recording)
+            .context("Failed to serialize the recording")?;
+        fs::write(&self.path, content)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// Answer this run's prompts from a file written by `--record`, or any
+/// answer script, in place of stdin.
+pub fn start_replay(path: &Path) -> Result<()> {
+    let content = fs::read_to_string(path).with_category(ErrorCategory::NotFound, || {
+        format!("Failed to read {}", path.display())
+    })?;
+    let script: Script = serde_json::from_str(&content)
+        .with_category(ErrorCategory::Usage, || {
+            format!("{} is not a recording or answer script", path.display())
+        })?;
+    interaction::set_interaction(Box::new(ScriptedInteraction::new(script)));
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::daemon;
+use crate::redact::Redactor;
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::time_window::TimeWindow;
+use crate::transcript::{Role, Transcript};
+
+// Characters of context kept on each side of a match in snippets
+const SNIPPET_RADIUS: usize = 60;
+
+/// Worktree filters shared by `xlaude sessions search` and the dashboard.
+#[derive(Debug, Default, Clone)]
+pub struct WorktreeFilter {
+    pub repo: Option<String>,
+    pub tag: Option<String>,
+    // Only sessions active inside this window are considered
+    pub window: TimeWindow,
+}
+
+impl WorktreeFilter {
+    /// Whether a worktree passes the repo and tag filters.
+    pub fn matches(&self, info: &WorktreeInfo) -> bool {
+        self.repo
+            .as_ref()
+            .is_none_or(|repo| info.repo_name == *repo)
+            && self
+                .tag
+                .as_ref()
+                .is_none_or(|tag| info.tags.iter().any(|existing| existing == tag))
+    }
+
+    /// Whether a worktree was active recently enough.
+    pub fn is_recent(&self, last_activity: Option<DateTime<Utc>>) -> bool {
+        match (self.window.since, last_activity) {
+            (None, _) => true,
+            (Some(since), Some(activity)) => activity >= since,
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Case-insensitive search terms; a message matches when it contains all of them.
+#[derive(Debug, Clone)]
+pub struct SessionQuery {
+    terms: Vec<String>,
+}
+
+impl SessionQuery {
+    /// `None` for a blank query.
+    pub fn parse(query: &str) -> Option<Self> {
+        let terms: Vec<String> = query
+            .split_whitespace()
+            .map(|term| term.to_lowercase())
+            .collect();
+        (!terms.is_empty()).then_some(Self { terms })
+    }
+
+    pub fn matches(&self, text: &str) -> bool {
+        let haystack = text.to_lowercase();
+        self.terms.iter().all(|term| haystack.contains(term))
+    }
+
+    fn first_term(&self) -> &str {
+        &self.terms[0]
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionHit {
+    pub worktree: String,
+    pub provider: String,
+    pub session_id: String,
+    pub role: Role,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub snippet: String,
+}
+
+/// Messages in `transcripts` that match `query`, newest first. Secrets are
+/// redacted first, so they can neither be found nor shown.
+pub fn search_transcripts(
+    worktree_key: &str,
+    transcripts: &[Transcript],
+    query: &SessionQuery,
+    redactor: &Redactor,
+) -> Vec<SessionHit> {
+    let mut hits: Vec<SessionHit> = transcripts
+        .iter()
+        .flat_map(|transcript| {
+            transcript
+                .turns
+                .iter()
+                .filter(|turn| query.matches(&turn.text))
+                .filter_map(|turn| {
+                    let text = redactor.redact(&turn.text);
+                    query.matches(&text).then(|| SessionHit {
+                        worktree: worktree_key.to_string(),
+                        provider: transcript.provider.clone(),
+                        session_id: transcript.session_id.clone(),
+                        role: turn.role,
+                        timestamp: turn.timestamp,
+                        snippet: snippet(&text, query.first_term()),
+                    })
+                })
+        })
+        .collect();
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.timestamp));
+    hits
+}
+
+/// Search every recorded session of one worktree.
+pub fn search_worktree(
+    info: &WorktreeInfo,
+    query: &SessionQuery,
+    redactor: &Redactor,
+) -> Result<Vec<SessionHit>> {
+    search_worktree_in(info, query, &TimeWindow::default(), redactor)
+}
+
+/// Search the messages of one worktree sent inside `window`.
+pub fn search_worktree_in(
+    info: &WorktreeInfo,
+    query: &SessionQuery,
+    window: &TimeWindow,
+    redactor: &Redactor,
+) -> Result<Vec<SessionHit>> {
+    let transcripts = daemon::transcripts_in(info, usize::MAX, window)?;
+    let key = XlaudeState::make_key(&info.repo_name, &info.name);
+    Ok(search_transcripts(&key, &transcripts, query, redactor))
+}
+
+/// Search the sessions of every managed worktree that passes `filter`.
+pub fn search_sessions(
+    state: &XlaudeState,
+    query: &SessionQuery,
+    filter: &WorktreeFilter,
+) -> Result<Vec<SessionHit>> {
+    let redactor = Redactor::from_state(state)?;
+    let mut hits = Vec::new();
+    for info in state.worktrees.values().filter(|info| filter.matches(info)) {
+        hits.extend(search_worktree_in(info, query, &filter.window, &redactor)?);
+    }
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.timestamp));
+    Ok(hits)
+}
+
+/// One-line excerpt of `text` around the first occurrence of `term`.
+fn snippet(text: &str, term: &str) -> String {
+    let flat: String = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let chars: Vec<char> = flat.chars().collect();
+    let lowered: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let needle: Vec<char> = term.chars().collect();
+    let position = lowered
+        .windows(needle.len().max(1))
+        .position(|window| window == needle.as_slice())
+        .unwrap_or(0);
+
+    let start = position.saturating_sub(SNIPPET_RADIUS);
+    let end = (position + needle.len() + SNIPPET_RADIUS).min(chars.len());
+    let mut excerpt: String = chars[start..end].iter().collect();
+    if start > 0 {
+        excerpt.insert(0, '…');
+    }
+    if end < chars.len() {
+        excerpt.push('…');
+    }
+    excerpt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::Turn;
+
+    fn transcript(texts: &[(&str, &str)]) -> Transcript {
+        Transcript {
+            provider: "Claude".into(),
+            session_id: "abc".into(),
+            started_at: None,
+            updated_at: None,
+            turns: texts
+                .iter()
+                .enumerate()
+                .map(|(index, (role, text))| Turn {
+                    role: if *role == "user" {
+                        Role::User
+                    } else {
+                        Role::Assistant
+                    },
+                    text: text.to_string(),
+                    timestamp: DateTime::from_timestamp(index as i64 * 60, 0),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn matches_all_terms_case_insensitively() {
+        let query = SessionQuery::parse("Login  BUG").unwrap();
+        let transcripts = [transcript(&[
+            (
+                "user",
+                "please fix the login bug, key sk-ant-api03-abcdefgh",
+            ),
+            ("user", "login with sk-ant-bug-abcdefgh"),
+            ("assistant", "The bug is unrelated"),
+            ("assistant", "Fixed the LOGIN bug in auth.rs"),
+        ])];
+        let redactor = Redactor::new(&[]).unwrap();
+        let hits = search_transcripts("repo/feat", &transcripts, &query, &redactor);
+        // The second message matches only through its secret
+        assert_eq!(hits.len(), 2);
+        // Newest first
+        assert_eq!(hits[0].role, Role::Assistant);
+        assert_eq!(hits[1].snippet, "please fix the login bug, key <redacted>");
+        assert!(SessionQuery::parse("   ").is_none());
+    }
+
+    #[test]
+    fn snippets_are_trimmed_around_the_match() {
+        let text = format!("{} needle {}", "a ".repeat(100), "b ".repeat(100));
+        let excerpt = snippet(&text, "needle");
+        assert!(excerpt.starts_with('…') && excerpt.ends_with('…'));
+        assert!(excerpt.contains("needle"));
+        assert!(excerpt.chars().count() <= 2 * SNIPPET_RADIUS + "needle".len() + 2);
+    }
+
+    #[test]
+    fn filters_by_repo_and_tag() {
+        let mut info = WorktreeInfo::new("feat".into(), "feat".into(), "/x".into(), "api".into());
+        info.tags = vec!["urgent".into()];
+        let filter = WorktreeFilter {
+            repo: Some("api".into()),
+            tag: Some("urgent".into()),
+            window: TimeWindow::default(),
+        };
+        assert!(filter.matches(&info));
+        info.tags.clear();
+        assert!(!filter.matches(&info));
+    }
+}
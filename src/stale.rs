@@ -0,0 +1,52 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::error::{ErrorCategory, WithCategory};
+use crate::git::last_commit_time;
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::transcript::last_session_activity;
+use crate::utils::parse_duration;
+
+// Idle time after which a worktree counts as stale unless configured
+const DEFAULT_STALE_AFTER: chrono::Duration = chrono::Duration::days(14);
+
+/// The configured `stale_after` threshold, or `None` when it is turned off.
+pub fn stale_after(state: &XlaudeState) -> Result<Option<chrono::Duration>> {
+    let threshold = match &state.stale_after {
+        Some(value) => parse_duration(value).with_category(ErrorCategory::Config, || {
+            format!("Invalid stale_after '{value}'")
+        })?,
+        None => DEFAULT_STALE_AFTER,
+    };
+    Ok((threshold > chrono::Duration::zero()).then_some(threshold))
+}
+
+/// The configured `auto_archive_after` threshold, or `None` when automatic
+/// archiving is off, as it is by default.
+pub fn auto_archive_after(state: &XlaudeState) -> Result<Option<chrono::Duration>> {
+    let Some(value) = &state.auto_archive_after else {
+        return Ok(None);
+    };
+    let threshold = parse_duration(value).with_category(ErrorCategory::Config, || {
+        format!("Invalid auto_archive_after '{value}'")
+    })?;
+    Ok((threshold > chrono::Duration::zero()).then_some(threshold))
+}
+
+/// Latest sign of work in a worktree: its creation, an agent session, or
+/// the commit checked out there.
+pub fn last_activity(info: &WorktreeInfo) -> DateTime<Utc> {
+    [
+        last_session_activity(&info.path),
+        last_commit_time(&info.path),
+    ]
+    .into_iter()
+    .flatten()
+    .fold(info.created_at, DateTime::max)
+}
+
+/// When the worktree was last active, if that is at least `threshold` ago.
+pub fn stale_since(info: &WorktreeInfo, threshold: chrono::Duration) -> Option<DateTime<Utc>> {
+    let last = last_activity(info);
+    (Utc::now() - last >= threshold).then_some(last)
+}
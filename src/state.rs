@@ -3,16 +3,371 @@ use chrono::{DateTime, Utc};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::{ErrorCategory, WithCategory};
+use crate::utils::wildcard_match;
+
+const DEFAULT_REMOTE: &str = "origin";
+
+pub const DATA_DIR_ENV: &str = "XLAUDE_DATA_DIR";
+pub const PROFILE_ENV: &str = "XLAUDE_PROFILE";
+// Set by `--state-dir` and `--profile`, which take precedence over the environment
+static DATA_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+static PROFILE: Mutex<Option<String>> = Mutex::new(None);
+
+// Branches guarded when `protected_branches` is not configured
+pub const DEFAULT_PROTECTED_BRANCHES: &[&str] = &["main", "master", "develop", "release/*"];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorktreeInfo {
     pub name: String,
+    // Branch checked out in the worktree; the commit or tag when `detached`
     pub branch: String,
     pub path: PathBuf,
     pub repo_name: String,
     pub created_at: DateTime<Utc>,
+    // GitHub issue the worktree was created from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue: Option<IssueLink>,
+    // Jira/Linear ticket the worktree was created from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket: Option<TicketLink>,
+    // Free-form note shown in `list`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    // Sparse-checkout profile the worktree was created with, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sparse: Option<String>,
+    // Free-form labels set with `xlaude tag`, used for filtering
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    // Checked out without a branch, e.g. to bisect or reproduce an old bug
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub detached: bool,
+    // Subdirectory `open` or `dir` was last run from, relative to `path`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_subdir: Option<PathBuf>,
+    // Arguments added to every agent launched here, after the repository's,
+    // e.g. "--model opus"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_args: Option<String>,
+    // Set while the checkout is removed with `xlaude archive`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived: Option<Archive>,
+    // When `xlaude maintain` announced it would archive the idle worktree
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_notice_at: Option<DateTime<Utc>>,
+}
+
+impl WorktreeInfo {
+    pub fn new(name: String, branch: String, path: PathBuf, repo_name: String) -> Self {
+        Self {
+            name,
+            branch,
+            path,
+            repo_name,
+            created_at: Utc::now(),
+            issue: None,
+            ticket: None,
+            note: None,
+            sparse: None,
+            tags: Vec::new(),
+            detached: false,
+            last_subdir: None,
+            agent_args: None,
+            archived: None,
+            archive_notice_at: None,
+        }
+    }
+
+    /// Where `open` and `dir` take the user: the subdirectory they last worked
+    /// in, while it still exists, otherwise the worktree root.
+    pub fn working_dir(&self) -> PathBuf {
+        self.last_subdir
+            .as_ref()
+            .map(|subdir| self.path.join(subdir))
+            .filter(|dir| dir.is_dir())
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    /// `dir` relative to the worktree root, `Some("")` for the root itself, or
+    /// `None` when it lies outside the worktree.
+    fn subdir_of(&self, dir: &Path) -> Option<PathBuf> {
+        let root = self.path.canonicalize().ok()?;
+        dir.strip_prefix(&root).ok().map(Path::to_path_buf)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Archive {
+    pub archived_at: DateTime<Utc>,
+    // Main checkout the worktree is added back to; its own directory is gone
+    pub repo_path: PathBuf,
+    // Commit checked out when archived, which a detached worktree returns to
+    pub head: String,
+    // Stash holding the uncommitted changes, applied again on restore
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteHost {
+    // SSH destination, e.g. "me@dev-box" or a Host from ~/.ssh/config
+    pub host: String,
+    // How to run xlaude there (default "xlaude"), e.g. "~/.cargo/bin/xlaude"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+impl RemoteHost {
+    pub fn command(&self) -> &str {
+        self.command.as_deref().unwrap_or("xlaude")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueLink {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketLink {
+    pub key: String,
+    pub summary: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketProviderKind {
+    Jira,
+    Linear,
+    Command,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketProviderConfig {
+    pub kind: TicketProviderKind,
+    // Project keys handled by this provider (e.g. "PROJ"); empty matches every key
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+    // Jira site URL, e.g. https://acme.atlassian.net
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    // Environment variable holding the Jira account email
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_env: Option<String>,
+    // Environment variable holding the API token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+    // Command for the `command` kind; `{key}` is replaced with the ticket key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MenuEntryKind {
+    // Launched and tracked like the built-in agents
+    #[default]
+    Agent,
+    // Started in the background with the worktree path appended; the menu returns at once
+    Editor,
+    // Run in the worktree until it exits, e.g. lazygit or a shell
+    Command,
+}
+
+/// An extra entry in the menu shown when a worktree is opened.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuEntry {
+    // What to type to pick the entry; 1-3 and n are taken by the built-ins.
+    // Entries without a key are numbered after the built-in agents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub kind: MenuEntryKind,
+    pub title: String,
+    // Full command line, split with shell-style rules
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    // Shown once the entry is picked (default: "Running `<command>`")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmation: Option<String>,
+}
+
+/// Built-in word lists for generated worktree names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NameTheme {
+    #[default]
+    Bip39,
+    Animals,
+    Space,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NameGeneratorConfig {
+    // Number of words joined with '-' (default: 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub words: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<NameTheme>,
+    // File with one word per line ('#' comments allowed); takes precedence over theme
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wordlist: Option<PathBuf>,
+}
+
+/// Which confirmation prompts are shown; skipped prompts take their default answer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmPolicy {
+    #[default]
+    Always,
+    // Only prompts that guard deletions
+    Destructive,
+    Never,
+}
+
+impl ConfirmPolicy {
+    pub fn should_prompt(self, destructive: bool) -> bool {
+        match self {
+            ConfirmPolicy::Always => true,
+            ConfirmPolicy::Destructive => destructive,
+            ConfirmPolicy::Never => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NameCollisionPolicy {
+    // Fail and ask for another name
+    Error,
+    // Append -2, -3, ... to the name
+    Suffix,
+    // Pick another random word; names that were not generated get a suffix instead
+    NewWord,
+}
+
+/// What commands that need a clean worktree do with uncommitted changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DirtyWorktreePolicy {
+    // Refuse, or ask before discarding changes
+    #[default]
+    Abort,
+    // Stash the changes and record the stash to put them back
+    Stash,
+}
+
+/// A housekeeping step of `xlaude maintain`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintainTask {
+    // Snapshot the state file into the backups directory
+    Backup,
+    // `git worktree prune` and drop state entries git no longer knows
+    Prune,
+    // Apply `auto_archive_after`
+    Archive,
+    // Report worktrees idle for `stale_after`
+    Stale,
+    // Drop finished tasks and their logs after `task_retention`
+    Tasks,
+    // Recompute the disk usage cache behind `list --du` and the dashboard
+    DiskUsage,
+}
+
+impl MaintainTask {
+    pub const ALL: [MaintainTask; 6] = [
+        MaintainTask::Backup,
+        MaintainTask::Prune,
+        MaintainTask::Archive,
+        MaintainTask::Stale,
+        MaintainTask::Tasks,
+        MaintainTask::DiskUsage,
+    ];
+}
+
+/// Per-repository settings, keyed by repository name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepoConfig {
+    // Run `git submodule update --init --recursive` on new worktrees (default: when .gitmodules exists)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submodules: Option<bool>,
+    // Run `git lfs pull` on new worktrees (default: when .gitattributes uses the lfs filter)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lfs: Option<bool>,
+    // Named cone-mode sparse-checkout profiles: profile name -> directories to check out
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sparse_profiles: HashMap<String, Vec<String>>,
+    // Monthly agent usage allowed for this repository's worktrees
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage_budget: Option<UsageBudget>,
+    // Branch patterns (`*` wildcards allowed) or worktree names that `clean`
+    // never removes or offers as stale, e.g. long-lived integration worktrees
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keep: Vec<String>,
+    // Remote that worktree branches are pushed to (default "origin")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub push_remote: Option<String>,
+    // Remote whose default branch new work starts from and pull requests
+    // target, e.g. "upstream" for forks (default "origin")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_remote: Option<String>,
+    // Agent command for this repository's worktrees, ahead of `repo_agents`
+    // and the global `agent`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_agent: Option<String>,
+    // Arguments added to every agent launched in this repository's worktrees,
+    // e.g. "--sandbox read-only"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_args: Option<String>,
+    // Agents may launch here with their permission prompts turned off, e.g.
+    // `claude --dangerously-skip-permissions`; set with `xlaude trust`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub trusted: bool,
+    // Checks a worktree's branch must pass before it leaves xlaude, run in
+    // order inside the worktree, e.g. ["cargo fmt --check", "cargo test"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verify_commands: Vec<String>,
+}
+
+impl RepoConfig {
+    pub fn push_remote(&self) -> &str {
+        self.push_remote.as_deref().unwrap_or(DEFAULT_REMOTE)
+    }
+
+    pub fn base_remote(&self) -> &str {
+        self.base_remote.as_deref().unwrap_or(DEFAULT_REMOTE)
+    }
+}
+
+/// Monthly allowance of agent usage, counted from the first of the month.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageBudget {
+    // Tokens per month, e.g. "20M"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<String>,
+    // Estimated spend per month in US dollars
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dollars: Option<f64>,
+    // Share of the budget at which warnings start, between 0 and 1 (default 0.8)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warn_at: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    // Member worktree keys: "{repo_name}/{worktree_name}"
+    pub members: Vec<String>,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -22,25 +377,268 @@ pub struct XlaudeState {
     // Global agent command to launch sessions (full command line string)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub agent: Option<String>,
+    // Agent commands tried in order when the chosen agent is not installed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agent_fallbacks: Vec<String>,
+    // Agent command per repository name, overriding the global `agent`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub repo_agents: HashMap<String, String>,
+    // Extra entries for the launch menu, after the built-in agents
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub menu: Vec<MenuEntry>,
     // Preferred editor command (full command line string)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub editor: Option<String>,
     // Preferred interactive shell command
     #[serde(skip_serializing_if = "Option::is_none")]
     pub shell: Option<String>,
+    // Which confirmation prompts to show (default: always)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm: Option<ConfirmPolicy>,
+    // Word source for names generated by `create` without an explicit name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_generator: Option<NameGeneratorConfig>,
+    // How `create` resolves a name that is already taken (default: new-word for
+    // generated names, suffix for names given on the command line)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_collision: Option<NameCollisionPolicy>,
+    // Branch names (`*` wildcards allowed) that `create` refuses to use and
+    // `delete` never removes; replaces DEFAULT_PROTECTED_BRANCHES when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protected_branches: Option<Vec<String>>,
+    // Command that receives a JSON document on stdin for every lifecycle event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_handler: Option<String>,
+    // Bearer token for `POST /api/hooks/create`; the endpoint is disabled without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_token: Option<String>,
+    // `delete` asks for extra confirmation when an agent was active more recently
+    // than this, e.g. "30m" (default); "0m" turns the check off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recent_activity_window: Option<String>,
+    // Worktrees without session or commit activity for this long are flagged
+    // as stale, e.g. "14d" (default); "0d" turns the flag off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale_after: Option<String>,
+    // Worktrees idle this long are archived by `xlaude maintain`, a day after
+    // it first warns about them, e.g. "30d"; off unless set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_archive_after: Option<String>,
+    // What `xlaude maintain` runs, in its fixed order; every task when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintain_tasks: Option<Vec<MaintainTask>>,
+    // Finished `xlaude run` tasks older than this are dropped with their logs
+    // by `xlaude maintain`, e.g. "30d" (default); "0d" keeps them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_retention: Option<String>,
+    // Snapshots of this file kept in <config dir>/backups, one taken before
+    // every save that changes more than the last directory used (default:
+    // backup::DEFAULT_KEEP); 0 turns them off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_backups: Option<usize>,
+    // Language of xlaude's messages, "en" or "zh"; defaults to the locale (LANG)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    // Display width of session message previews in `list` and menus (default: 60)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_width: Option<usize>,
+    // Preview only the first line of multi-line messages instead of joining them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub preview_first_line: bool,
+    // Pager for `xlaude review` diffs, e.g. "delta"; defaults to git's own pager
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_pager: Option<String>,
+    // What `archive` and `review` reverts do with uncommitted changes:
+    // "abort" (default) or "stash"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dirty_worktree: Option<DirtyWorktreePolicy>,
+    // Skip the daily check for a newer xlaude release
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_update_check: bool,
+    // Total disk usage across worktrees before warning, e.g. "50G"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk_budget: Option<String>,
+    // Monthly agent usage allowed across all repositories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage_budget: Option<UsageBudget>,
+    // Providers used to resolve `create --ticket` keys
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ticket_providers: Vec<TicketProviderConfig>,
+    // Named groups of worktrees, possibly spanning repositories
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub workspaces: HashMap<String, Workspace>,
+    // Per-repository settings keyed by repository name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub repos: HashMap<String, RepoConfig>,
+    // Machines where another xlaude manages worktrees, by name; `list --remote`
+    // and `open --remote` run there over SSH
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub remotes: HashMap<String, RemoteHost>,
+    // Environment variables for every process xlaude starts (agents, hooks,
+    // plugins), e.g. the API key that belongs to this profile
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    // Regular expressions for secrets hidden from shown, searched, and bundled
+    // transcripts, on top of the built-in ones (API keys, tokens, private keys);
+    // a `secret` group limits what is cut
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redact_patterns: Vec<String>,
+    // The document this state was loaded from, so stores can save only what changed
+    #[serde(skip)]
+    loaded: Mutex<Option<serde_json::Value>>,
 }
 
 impl XlaudeState {
+    /// Remember the current directory as the last one used in the worktree
+    /// containing it. Returns whether anything changed.
+    pub fn remember_current_dir(&mut self) -> bool {
+        if crate::dry_run::is_enabled() {
+            return false;
+        }
+        let Some(cwd) = std::env::current_dir()
+            .ok()
+            .and_then(|dir| dir.canonicalize().ok())
+        else {
+            return false;
+        };
+        for info in self.worktrees.values_mut() {
+            if let Some(subdir) = info.subdir_of(&cwd) {
+                let subdir = Some(subdir).filter(|subdir| !subdir.as_os_str().is_empty());
+                if info.last_subdir == subdir {
+                    return false;
+                }
+                info.last_subdir = subdir;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// The managed worktree `dir` lies in, if any.
+    pub fn worktree_containing(&self, dir: &Path) -> Option<&WorktreeInfo> {
+        let dir = dir.canonicalize().ok()?;
+        self.worktrees
+            .values()
+            .find(|info| info.subdir_of(&dir).is_some())
+    }
+
     pub fn make_key(repo_name: &str, worktree_name: &str) -> String {
         format!("{repo_name}/{worktree_name}")
     }
 
+    /// Find a worktree by state key (`repo/name`) or by bare worktree name.
+    pub fn find_worktree(&self, name: &str) -> Option<(&String, &WorktreeInfo)> {
+        self.worktrees
+            .get_key_value(name)
+            .or_else(|| self.worktrees.iter().find(|(_, w)| w.name == name))
+    }
+
+    /// Directory-safe worktree name for `branch`. When a different branch already
+    /// sanitizes to the same name, a numeric suffix keeps the two apart.
+    pub fn worktree_name_for_branch(&self, repo_name: &str, branch: &str) -> String {
+        let base = crate::utils::sanitize_branch_name(branch);
+        crate::utils::with_numeric_suffix(&base, |candidate| {
+            self.worktrees
+                .get(&Self::make_key(repo_name, candidate))
+                .is_some_and(|info| info.branch != branch)
+        })
+    }
+
+    pub fn repo_config(&self, repo_name: &str) -> RepoConfig {
+        self.repos.get(repo_name).cloned().unwrap_or_default()
+    }
+
+    /// The agent command configured for a repository's worktrees: the repo's
+    /// `default_agent`, then its `repo_agents` entry, then the global `agent`.
+    /// `None` (no repository) skips straight to the global setting.
+    pub fn agent_for(&self, repo_name: Option<&str>) -> Option<&str> {
+        let repo_specific = repo_name.into_iter().flat_map(|repo| {
+            [
+                self.repos
+                    .get(repo)
+                    .and_then(|config| config.default_agent.as_deref()),
+                self.repo_agents.get(repo).map(String::as_str),
+            ]
+        });
+        repo_specific
+            .chain([self.agent.as_deref()])
+            .flatten()
+            .map(str::trim)
+            .find(|agent| !agent.is_empty())
+    }
+
+    /// The extra agent arguments for `worktree`: its repository's
+    /// `agent_args`, then its own, split with shell-style rules.
+    pub fn agent_args_for(&self, worktree: &WorktreeInfo) -> Result<Vec<String>> {
+        let repo_args = self
+            .repos
+            .get(&worktree.repo_name)
+            .and_then(|config| config.agent_args.as_deref());
+        let mut args = Vec::new();
+        for line in [repo_args, worktree.agent_args.as_deref()]
+            .into_iter()
+            .flatten()
+        {
+            args.extend(
+                shell_words::split(line).with_category(ErrorCategory::Config, || {
+                    format!("Invalid agent_args for '{}': {line}", worktree.name)
+                })?,
+            );
+        }
+        Ok(args)
+    }
+
+    /// Whether `branch` matches `protected_branches` (or the defaults).
+    pub fn is_protected_branch(&self, branch: &str) -> bool {
+        match &self.protected_branches {
+            Some(patterns) => patterns
+                .iter()
+                .any(|pattern| wildcard_match(pattern, branch)),
+            None => DEFAULT_PROTECTED_BRANCHES
+                .iter()
+                .any(|pattern| wildcard_match(pattern, branch)),
+        }
+    }
+
+    /// Whether the worktree matches its repository's `keep` list by worktree
+    /// name or branch.
+    pub fn is_kept(&self, info: &WorktreeInfo) -> bool {
+        self.repos.get(&info.repo_name).is_some_and(|config| {
+            config
+                .keep
+                .iter()
+                .any(|pattern| pattern == &info.name || wildcard_match(pattern, &info.branch))
+        })
+    }
+
+    /// Remove a worktree entry along with its workspace memberships.
+    /// Give a worktree a new name within its repository, keeping its workspace
+    /// memberships. Returns the new key, or `None` when `key` is unknown.
+    pub fn rename_worktree(&mut self, key: &str, new_name: &str) -> Option<String> {
+        let mut info = self.worktrees.remove(key)?;
+        info.name = new_name.to_string();
+        let new_key = Self::make_key(&info.repo_name, new_name);
+        self.worktrees.insert(new_key.clone(), info);
+        for workspace in self.workspaces.values_mut() {
+            for member in workspace.members.iter_mut().filter(|m| *m == key) {
+                *member = new_key.clone();
+            }
+        }
+        Some(new_key)
+    }
+
+    pub fn remove_worktree(&mut self, key: &str) -> Option<WorktreeInfo> {
+        for workspace in self.workspaces.values_mut() {
+            workspace.members.retain(|member| member != key);
+        }
+        self.worktrees.remove(key)
+    }
+
     pub fn load() -> Result<Self> {
-        let config_path = get_config_path()?;
-        if config_path.exists() {
-            let content = fs::read_to_string(&config_path).context("Failed to read config file")?;
-            let mut state: Self =
-                serde_json::from_str(&content).context("Failed to parse config file")?;
+        if let Some(document) = crate::store::open()?.load()? {
+            let mut state: Self = serde_json::from_value(document.clone())
+                .with_category(ErrorCategory::Config, || "Failed to parse config file")?;
+            state.loaded = Mutex::new(Some(document));
 
             // ============================================================================
             // MIGRATION LOGIC: Upgrade from v0.2 to v0.3 format
@@ -79,25 +677,125 @@ impl XlaudeState {
 
             Ok(state)
         } else {
-            Ok(Self::default())
+            Ok(Self {
+                loaded: Mutex::new(Some(serde_json::Value::Object(Default::default()))),
+                ..Self::default()
+            })
         }
     }
 
     pub fn save(&self) -> Result<()> {
-        let config_path = get_config_path()?;
-        if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        self.write(true)
+    }
+
+    /// Save bookkeeping that is not worth a backup snapshot, such as the
+    /// directory last worked in, so it never pushes real backups out.
+    pub fn save_without_backup(&self) -> Result<()> {
+        self.write(false)
+    }
+
+    fn write(&self, backup: bool) -> Result<()> {
+        let store = crate::store::open()?;
+        let document = serde_json::to_value(self).context("Failed to serialize state")?;
+        // A stored state that cannot be read is neither diffed nor backed up
+        let previous = store.load().ok().flatten();
+        if crate::dry_run::is_enabled() {
+            let before = previous.unwrap_or_default();
+            for change in crate::dry_run::describe_state_changes(&before, &document) {
+                crate::dry_run::announce(&format!("state: {change}"));
+            }
+            return Ok(());
+        }
+        if backup
+            && let Some(previous) = previous
+            && previous != document
+        {
+            let keep = self.state_backups.unwrap_or(crate::backup::DEFAULT_KEEP);
+            if let Err(err) = serde_json::to_string_pretty(&previous)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| crate::backup::snapshot(&content, keep))
+            {
+                eprintln!("⚠️ Could not back up the state file: {err:#}");
+            }
         }
-        let content = serde_json::to_string_pretty(self).context("Failed to serialize state")?;
-        fs::write(&config_path, content).context("Failed to write config file")?;
+        let mut loaded = self.loaded.lock().expect("state baseline lock poisoned");
+        store.save(&document, loaded.as_ref())?;
+        *loaded = Some(document);
         Ok(())
     }
 }
 
+/// Keep state and caches in `dir` instead of the platform default; set from
+/// `--state-dir` before anything reads them.
+pub fn set_data_dir(dir: PathBuf) {
+    *DATA_DIR.lock().unwrap() = Some(dir);
+}
+
+/// Use the isolated profile `name`; set from `--profile` before anything
+/// reads the state.
+pub fn set_profile(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        crate::bail_with!(
+            ErrorCategory::Usage,
+            "Invalid profile name '{}': use letters, digits, '-', '_' and '.'",
+            name
+        );
+    }
+    *PROFILE.lock().unwrap() = Some(name.to_string());
+    Ok(())
+}
+
+/// Forget `--state-dir` and `--profile`, before running another command in
+/// the same process.
+pub fn clear_overrides() {
+    *DATA_DIR.lock().unwrap() = None;
+    *PROFILE.lock().unwrap() = None;
+}
+
+/// The profile in use: `--profile`, then `XLAUDE_PROFILE`.
+pub fn current_profile() -> Option<String> {
+    PROFILE.lock().unwrap().clone().or_else(|| {
+        std::env::var(PROFILE_ENV)
+            .ok()
+            .filter(|name| !name.is_empty())
+    })
+}
+
+/// Directory holding the state, caches, and logs of the current profile.
+/// Profiles live in `profiles/<name>` under the data directory.
 pub fn get_config_dir() -> Result<PathBuf> {
-    // Allow overriding config directory for testing
-    if let Ok(config_dir) = std::env::var("XLAUDE_CONFIG_DIR") {
-        return Ok(PathBuf::from(config_dir));
+    let root = data_root()?;
+    Ok(match current_profile() {
+        Some(profile) => root.join("profiles").join(profile),
+        None => root,
+    })
+}
+
+/// Environment that makes another xlaude process use the same data directory
+/// and profile as this one.
+pub fn data_dir_env() -> Result<Vec<(&'static str, String)>> {
+    let mut env = vec![(DATA_DIR_ENV, data_root()?.display().to_string())];
+    if let Some(profile) = current_profile() {
+        env.push((PROFILE_ENV, profile));
+    }
+    Ok(env)
+}
+
+/// `--state-dir`, then `XLAUDE_DATA_DIR` (or its older name
+/// `XLAUDE_CONFIG_DIR`), then the platform's config directory.
+fn data_root() -> Result<PathBuf> {
+    if let Some(dir) = DATA_DIR.lock().unwrap().clone() {
+        return Ok(dir);
+    }
+    for var in [DATA_DIR_ENV, "XLAUDE_CONFIG_DIR"] {
+        if let Some(dir) = std::env::var_os(var).filter(|dir| !dir.is_empty()) {
+            return Ok(PathBuf::from(dir));
+        }
     }
 
     let proj_dirs = ProjectDirs::from("com", "xuanwo", "xlaude")
@@ -105,12 +803,9 @@ pub fn get_config_dir() -> Result<PathBuf> {
     Ok(proj_dirs.config_dir().to_path_buf())
 }
 
+/// Where the state of the current backend is kept.
 pub fn get_state_path() -> Result<PathBuf> {
-    get_config_path()
-}
-
-fn get_config_path() -> Result<PathBuf> {
-    Ok(get_config_dir()?.join("state.json"))
+    Ok(crate::store::open()?.path().to_path_buf())
 }
 
 /// Resolve the agent command from state with a sensible default.
@@ -118,3 +813,38 @@ fn get_config_path() -> Result<PathBuf> {
 pub fn get_default_agent() -> String {
     "claude --dangerously-skip-permissions".to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repo_agents_override_the_global_agent() {
+        let mut state = XlaudeState {
+            agent: Some("codex".to_string()),
+            ..Default::default()
+        };
+        state.repos.insert(
+            "web".to_string(),
+            RepoConfig {
+                default_agent: Some("gemini".to_string()),
+                ..Default::default()
+            },
+        );
+        state
+            .repo_agents
+            .insert("web".to_string(), "claude".to_string());
+        state
+            .repo_agents
+            .insert("ml".to_string(), "claude".to_string());
+        state
+            .repo_agents
+            .insert("blank".to_string(), "  ".to_string());
+
+        assert_eq!(state.agent_for(Some("web")), Some("gemini"));
+        assert_eq!(state.agent_for(Some("ml")), Some("claude"));
+        assert_eq!(state.agent_for(Some("blank")), Some("codex"));
+        assert_eq!(state.agent_for(Some("other")), Some("codex"));
+        assert_eq!(state.agent_for(None), Some("codex"));
+    }
+}
@@ -0,0 +1,311 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::error::{ErrorCategory, WithCategory};
+use crate::state::get_config_dir;
+
+// Forces a backend; without it the SQLite database is used once it exists
+pub const BACKEND_ENV: &str = "XLAUDE_STATE_BACKEND";
+// A save waits this long for another process to finish its own
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS worktrees (key TEXT PRIMARY KEY, info TEXT NOT NULL);
+CREATE TABLE IF NOT EXISTS settings (name TEXT PRIMARY KEY, value TEXT NOT NULL);
+CREATE TABLE IF NOT EXISTS meta (id INTEGER PRIMARY KEY CHECK (id = 1), revision INTEGER NOT NULL);
+";
+
+/// Persistence for the state document. `XlaudeState` takes care of
+/// (de)serializing and migrating it; stores only keep JSON documents.
+pub trait StateStore {
+    /// Where the state is kept.
+    fn path(&self) -> &Path;
+    /// The stored document, or `None` before the first save.
+    fn load(&self) -> Result<Option<Value>>;
+    /// Store `state`. `baseline` is the document the caller loaded: stores that
+    /// can update parts of the state write only what changed since then, so
+    /// other processes' changes to the rest survive. Without a baseline the
+    /// stored state is replaced as a whole.
+    fn save(&self, state: &Value, baseline: Option<&Value>) -> Result<()>;
+    /// A revision that changes with every save, cheap enough to poll for
+    /// changes. `None` before the first save.
+    fn watch(&self) -> Result<Option<u64>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// A single state.json, easy to read and edit by hand
+    Json,
+    /// state.db, saved row by row so concurrent commands do not overwrite each other
+    Sqlite,
+}
+
+impl Backend {
+    /// `XLAUDE_STATE_BACKEND` when set, otherwise SQLite once its database
+    /// exists and JSON until then.
+    pub fn current() -> Result<Self> {
+        match std::env::var(BACKEND_ENV) {
+            Ok(value) => Self::from_str(&value, true)
+                .map_err(anyhow::Error::msg)
+                .with_category(ErrorCategory::Config, || {
+                    format!("Invalid {BACKEND_ENV} '{value}', expected 'json' or 'sqlite'")
+                }),
+            Err(_) if get_config_dir()?.join(Backend::Sqlite.file_name()).exists() => {
+                Ok(Backend::Sqlite)
+            }
+            Err(_) => Ok(Backend::Json),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Json => "json",
+            Backend::Sqlite => "sqlite",
+        }
+    }
+
+    pub fn open(self) -> Result<Box<dyn StateStore>> {
+        let path = get_config_dir()?.join(self.file_name());
+        Ok(match self {
+            Backend::Json => Box::new(JsonStore { path }),
+            Backend::Sqlite => Box::new(SqliteStore { path }),
+        })
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Backend::Json => "state.json",
+            Backend::Sqlite => "state.db",
+        }
+    }
+}
+
+/// The store of the current backend.
+pub fn open() -> Result<Box<dyn StateStore>> {
+    Backend::current()?.open()
+}
+
+struct JsonStore {
+    path: PathBuf,
+}
+
+impl StateStore for JsonStore {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&self) -> Result<Option<Value>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&self.path).context("Failed to read config file")?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .with_category(ErrorCategory::Config, || "Failed to parse config file")
+    }
+
+    fn save(&self, state: &Value, _baseline: Option<&Value>) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        let content = serde_json::to_string_pretty(state).context("Failed to serialize state")?;
+        fs::write(&self.path, content).context("Failed to write config file")
+    }
+
+    fn watch(&self) -> Result<Option<u64>> {
+        let Ok(meta) = fs::metadata(&self.path) else {
+            return Ok(None);
+        };
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_nanos() as u64);
+        // Two saves within the clock's resolution usually differ in size
+        Ok(Some(modified.wrapping_add(meta.len())))
+    }
+}
+
+/// Worktrees are rows of their own and every other top-level setting is one
+/// row, each holding its JSON. A revision counter backs `watch`.
+struct SqliteStore {
+    path: PathBuf,
+}
+
+impl SqliteStore {
+    fn connect(&self) -> Result<Connection> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        let conn = Connection::open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // Readers do not block the writer and the other way round
+        conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+        conn.execute_batch(SCHEMA)
+            .context("Failed to create the state tables")?;
+        Ok(conn)
+    }
+}
+
+impl StateStore for SqliteStore {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&self) -> Result<Option<Value>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let conn = self.connect()?;
+        if revision(&conn)?.is_none() {
+            return Ok(None);
+        }
+        let mut state = read_rows(&conn, "SELECT name, value FROM settings")?;
+        let worktrees = read_rows(&conn, "SELECT key, info FROM worktrees")?;
+        state.insert("worktrees".into(), Value::Object(worktrees));
+        Ok(Some(Value::Object(state)))
+    }
+
+    fn save(&self, state: &Value, baseline: Option<&Value>) -> Result<()> {
+        let (worktrees, settings) = split(state);
+        let mut conn = self.connect()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        match baseline {
+            Some(baseline) => {
+                let (old_worktrees, old_settings) = split(baseline);
+                write_changes(&tx, "worktrees", "key", "info", &old_worktrees, &worktrees)?;
+                write_changes(&tx, "settings", "name", "value", &old_settings, &settings)?;
+            }
+            None => {
+                tx.execute_batch("DELETE FROM worktrees; DELETE FROM settings;")?;
+                write_changes(&tx, "worktrees", "key", "info", &Map::new(), &worktrees)?;
+                write_changes(&tx, "settings", "name", "value", &Map::new(), &settings)?;
+            }
+        }
+        tx.execute(
+            "INSERT INTO meta (id, revision) VALUES (1, 1)
+             ON CONFLICT (id) DO UPDATE SET revision = revision + 1",
+            [],
+        )?;
+        tx.commit().context("Failed to save state")
+    }
+
+    fn watch(&self) -> Result<Option<u64>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        revision(&self.connect()?)
+    }
+}
+
+fn revision(conn: &Connection) -> Result<Option<u64>> {
+    Ok(conn
+        .query_row("SELECT revision FROM meta WHERE id = 1", [], |row| {
+            row.get::<_, i64>(0)
+        })
+        .optional()?
+        .map(|revision| revision as u64))
+}
+
+fn read_rows(conn: &Connection, query: &str) -> Result<Map<String, Value>> {
+    let mut statement = conn.prepare(query)?;
+    let rows = statement.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    let mut map = Map::new();
+    for row in rows {
+        let (key, json) = row?;
+        let value = serde_json::from_str(&json).with_category(ErrorCategory::Config, || {
+            format!("Failed to parse state entry '{key}'")
+        })?;
+        map.insert(key, value);
+    }
+    Ok(map)
+}
+
+/// The worktrees of a state document, and its other top-level settings.
+fn split(state: &Value) -> (Map<String, Value>, Map<String, Value>) {
+    let mut settings = state.as_object().cloned().unwrap_or_default();
+    let worktrees = match settings.remove("worktrees") {
+        Some(Value::Object(worktrees)) => worktrees,
+        _ => Map::new(),
+    };
+    (worktrees, settings)
+}
+
+/// Write the rows that differ between `old` and `new` and delete the ones `new` dropped.
+fn write_changes(
+    conn: &Connection,
+    table: &str,
+    key_column: &str,
+    value_column: &str,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+) -> Result<()> {
+    let mut upsert = conn.prepare(&format!(
+        "INSERT INTO {table} ({key_column}, {value_column}) VALUES (?1, ?2)
+         ON CONFLICT ({key_column}) DO UPDATE SET {value_column} = excluded.{value_column}"
+    ))?;
+    for (key, value) in new {
+        if old.get(key) != Some(value) {
+            upsert.execute(params![key, value.to_string()])?;
+        }
+    }
+    let mut delete = conn.prepare(&format!("DELETE FROM {table} WHERE {key_column} = ?1"))?;
+    for key in old.keys().filter(|key| !new.contains_key(*key)) {
+        delete.execute(params![key])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn sqlite_saves_only_what_changed_since_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SqliteStore {
+            path: dir.path().join("state.db"),
+        };
+        assert!(store.load().unwrap().is_none());
+        assert!(store.watch().unwrap().is_none());
+
+        let initial = json!({
+            "worktrees": { "r/a": { "name": "a" }, "r/b": { "name": "b" } },
+            "editor": "vim"
+        });
+        store.save(&initial, None).unwrap();
+        assert_eq!(store.load().unwrap().unwrap(), initial);
+        let revision = store.watch().unwrap();
+
+        // Two commands load the same state; one deletes a, the other adds c
+        let mut first = initial.clone();
+        first["worktrees"].as_object_mut().unwrap().remove("r/a");
+        let mut second = initial.clone();
+        second["worktrees"]["r/c"] = json!({ "name": "c" });
+        second["editor"] = json!("nvim");
+        store.save(&first, Some(&initial)).unwrap();
+        store.save(&second, Some(&initial)).unwrap();
+
+        assert_eq!(
+            store.load().unwrap().unwrap(),
+            json!({
+                "worktrees": { "r/b": { "name": "b" }, "r/c": { "name": "c" } },
+                "editor": "nvim"
+            })
+        );
+        assert_ne!(store.watch().unwrap(), revision);
+
+        // Without a baseline the state is replaced
+        store.save(&json!({ "worktrees": {} }), None).unwrap();
+        assert_eq!(store.load().unwrap().unwrap(), json!({ "worktrees": {} }));
+    }
+}
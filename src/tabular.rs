@@ -0,0 +1,89 @@
+use clap::ValueEnum;
+
+/// Output formats shared by commands that print tables of records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Tsv,
+    Csv,
+    /// Describe the TSV/CSV columns
+    Help,
+}
+
+/// A column of TSV/CSV output and what it contains.
+pub struct Column {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// Print `columns` as the answer to `--format help`.
+pub fn print_columns(columns: &[Column]) {
+    let width = columns
+        .iter()
+        .map(|column| column.name.len())
+        .max()
+        .unwrap_or(0);
+    println!("Columns of --format tsv and --format csv, in order:");
+    for column in columns {
+        println!("  {:width$}  {}", column.name, column.description);
+    }
+}
+
+/// Print a header row followed by `rows`, delimited as `format` requires.
+pub fn print_rows(format: OutputFormat, columns: &[Column], rows: &[Vec<String>]) {
+    let header: Vec<String> = columns
+        .iter()
+        .map(|column| column.name.to_string())
+        .collect();
+    for row in std::iter::once(&header).chain(rows) {
+        println!("{}", format_row(format, row));
+    }
+}
+
+fn format_row(format: OutputFormat, fields: &[String]) -> String {
+    match format {
+        OutputFormat::Csv => fields
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<_>>()
+            .join(","),
+        // TSV has no quoting, so tabs and line breaks inside fields become spaces
+        _ => fields
+            .iter()
+            .map(|field| field.replace(['\t', '\n', '\r'], " "))
+            .collect::<Vec<_>>()
+            .join("\t"),
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_fields_per_format() {
+        let fields = vec![
+            "plain".to_string(),
+            "a,b".to_string(),
+            "say \"hi\"".to_string(),
+            "tab\there".to_string(),
+        ];
+        assert_eq!(
+            format_row(OutputFormat::Csv, &fields),
+            "plain,\"a,b\",\"say \"\"hi\"\"\",tab\there"
+        );
+        assert_eq!(
+            format_row(OutputFormat::Tsv, &fields),
+            "plain\ta,b\tsay \"hi\"\ttab here"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::lock::FileLock;
+use crate::state::get_config_dir;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl TaskStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            TaskStatus::Pending => "pending",
+            TaskStatus::Running => "running",
+            TaskStatus::Succeeded => "succeeded",
+            TaskStatus::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Task {
+    pub id: u64,
+    // State key of the worktree: "{repo_name}/{worktree_name}"
+    pub worktree: String,
+    pub instruction: String,
+    pub status: TaskStatus,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    // Signal that ended the agent, e.g. "SIGTERM"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<String>,
+    // Last non-empty line the agent printed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_line: Option<String>,
+    // Times the agent was launched, restarts included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TaskQueue {
+    #[serde(default)]
+    pub next_id: u64,
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+}
+
+impl TaskQueue {
+    pub fn load() -> Result<Self> {
+        let path = get_tasks_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path).context("Failed to read task queue")?;
+        serde_json::from_str(&content).context("Failed to parse task queue")
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if crate::dry_run::is_enabled() {
+            crate::dry_run::announce("update the task queue");
+            return Ok(());
+        }
+        let path = get_tasks_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize tasks")?;
+        // Readers that do not take the lock never see a half-written file
+        let partial = path.with_extension("json.tmp");
+        fs::write(&partial, content).context("Failed to write task queue")?;
+        fs::rename(&partial, &path).context("Failed to write task queue")?;
+        Ok(())
+    }
+
+    pub fn push(&mut self, worktree: String, instruction: String) -> &Task {
+        self.next_id += 1;
+        self.tasks.push(Task {
+            id: self.next_id,
+            worktree,
+            instruction,
+            status: TaskStatus::Pending,
+            created_at: Utc::now(),
+            started_at: None,
+            finished_at: None,
+            exit_code: None,
+            signal: None,
+            last_line: None,
+            attempts: None,
+            log_path: None,
+        });
+        self.tasks.last().expect("task was just pushed")
+    }
+
+    /// Oldest pending task, optionally restricted to one worktree key.
+    pub fn next_pending(&self, worktree: Option<&str>) -> Option<&Task> {
+        self.tasks.iter().find(|task| {
+            task.status == TaskStatus::Pending && worktree.is_none_or(|key| task.worktree == key)
+        })
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Task> {
+        self.tasks.iter_mut().find(|task| task.id == id)
+    }
+}
+
+/// Exclusive access to tasks.json, for this thread and against other
+/// processes, until dropped. Held from `TaskQueue::load` to `save` by
+/// everything that changes the queue.
+pub struct QueueLock {
+    _lock: FileLock,
+}
+
+impl QueueLock {
+    pub fn acquire() -> Result<Self> {
+        Ok(QueueLock {
+            _lock: FileLock::acquire(&get_tasks_path()?, "the task queue")?,
+        })
+    }
+}
+
+pub fn get_tasks_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("tasks.json"))
+}
+
+pub fn get_task_log_path(id: u64) -> Result<PathBuf> {
+    Ok(get_config_dir()?
+        .join("logs")
+        .join(format!("task-{id}.log")))
+}
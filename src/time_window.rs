@@ -0,0 +1,210 @@
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::fs;
+use std::path::Path;
+
+use crate::bail_with;
+use crate::error::ErrorCategory;
+use crate::utils::parse_duration;
+
+/// Range of session activity selected by `--since` / `--until`. Either end
+/// may be open; the default window admits everything.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TimeWindow {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl TimeWindow {
+    /// Parse both bounds against the same notion of "now".
+    pub fn parse(since: Option<&str>, until: Option<&str>) -> Result<Self> {
+        let now = Utc::now();
+        let window = Self {
+            since: since
+                .map(|value| parse_time_bound(value, now))
+                .transpose()?,
+            until: until
+                .map(|value| parse_time_bound(value, now))
+                .transpose()?,
+        };
+        if let (Some(since), Some(until)) = (window.since, window.until)
+            && since > until
+        {
+            bail_with!(
+                ErrorCategory::Usage,
+                "--since ({}) is later than --until ({})",
+                since.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                until.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+            );
+        }
+        Ok(window)
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    /// Whether a single message falls inside the window. Messages without a
+    /// timestamp only pass an unbounded window.
+    pub fn contains(&self, timestamp: Option<DateTime<Utc>>) -> bool {
+        match timestamp {
+            Some(at) => {
+                self.since.is_none_or(|since| at >= since)
+                    && self.until.is_none_or(|until| at <= until)
+            }
+            None => !self.is_bounded(),
+        }
+    }
+
+    /// Whether a session that ran from `first` to `last` was active at some
+    /// point inside the window.
+    pub fn overlaps(&self, first: Option<DateTime<Utc>>, last: Option<DateTime<Utc>>) -> bool {
+        let first = first.or(last);
+        let last = last.or(first);
+        self.since
+            .is_none_or(|since| last.is_some_and(|last| last >= since))
+            && self
+                .until
+                .is_none_or(|until| first.is_some_and(|first| first <= until))
+    }
+
+    /// Cheap pre-check before parsing a session file: nothing written to it
+    /// after its modification time, so older files cannot reach `since`.
+    pub fn admits_file(&self, path: &Path) -> bool {
+        let Some(since) = self.since else {
+            return true;
+        };
+        fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .map(|modified| DateTime::<Utc>::from(modified) >= since)
+            .unwrap_or(true)
+    }
+
+    /// Latest calendar day a session could have started on and still be
+    /// active before `until`. Both the local and the UTC date are considered
+    /// since agents differ in which one they name their directories after.
+    pub fn last_day(&self) -> Option<NaiveDate> {
+        self.until.map(|until| {
+            until
+                .with_timezone(&Local)
+                .date_naive()
+                .max(until.date_naive())
+        })
+    }
+}
+
+/// Parse `--since` / `--until` values: a duration before `now` (`2h`, `7d`),
+/// a local date (`2024-05-01`, meaning its midnight), a local date and time
+/// (`2024-05-01 14:30`), or an RFC 3339 timestamp.
+pub fn parse_time_bound(input: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let trimmed = input.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(trimmed) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    let naive = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(trimmed, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(trimmed, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    });
+    if let Some(naive) = naive {
+        return match Local.from_local_datetime(&naive).earliest() {
+            Some(at) => Ok(at.with_timezone(&Utc)),
+            None => bail_with!(
+                ErrorCategory::Usage,
+                "'{}' does not exist in the local time zone",
+                input
+            ),
+        };
+    }
+    if let Ok(duration) = parse_duration(trimmed) {
+        return match now.checked_sub_signed(duration) {
+            Some(at) => Ok(at),
+            None => bail_with!(
+                ErrorCategory::Usage,
+                "'{}' reaches back before the earliest supported date",
+                input
+            ),
+        };
+    }
+    bail_with!(
+        ErrorCategory::Usage,
+        "Invalid time '{}' (use a duration like 2h or 7d, or a date like 2024-05-01)",
+        input
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_durations_and_dates() {
+        let now = at("2025-06-10T12:00:00Z");
+        assert_eq!(
+            parse_time_bound("2h", now).unwrap(),
+            at("2025-06-10T10:00:00Z")
+        );
+        assert_eq!(
+            parse_time_bound("7d", now).unwrap(),
+            at("2025-06-03T12:00:00Z")
+        );
+        assert_eq!(
+            parse_time_bound("2025-01-02T03:04:05Z", now).unwrap(),
+            at("2025-01-02T03:04:05Z")
+        );
+        let midnight = Local
+            .from_local_datetime(
+                &NaiveDate::from_ymd_opt(2025, 5, 1)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap(),
+            )
+            .earliest()
+            .unwrap();
+        assert_eq!(parse_time_bound("2025-05-01", now).unwrap(), midnight);
+        assert!(parse_time_bound("yesterday", now).is_err());
+        assert!(parse_time_bound("10000000000d", now).is_err());
+        assert!(TimeWindow::parse(Some("1h"), Some("2h")).is_err());
+    }
+
+    #[test]
+    fn windows_match_messages_and_sessions() {
+        let window = TimeWindow {
+            since: Some(at("2025-01-10T00:00:00Z")),
+            until: Some(at("2025-01-20T00:00:00Z")),
+        };
+        assert!(window.contains(Some(at("2025-01-15T00:00:00Z"))));
+        assert!(!window.contains(Some(at("2025-01-21T00:00:00Z"))));
+        assert!(!window.contains(None));
+        assert!(TimeWindow::default().contains(None));
+
+        // Sessions straddling either bound still count
+        assert!(window.overlaps(
+            Some(at("2025-01-01T00:00:00Z")),
+            Some(at("2025-01-11T00:00:00Z"))
+        ));
+        assert!(window.overlaps(
+            Some(at("2025-01-19T00:00:00Z")),
+            Some(at("2025-02-01T00:00:00Z"))
+        ));
+        assert!(!window.overlaps(
+            Some(at("2025-01-01T00:00:00Z")),
+            Some(at("2025-01-09T00:00:00Z"))
+        ));
+        assert!(!window.overlaps(None, None));
+    }
+}
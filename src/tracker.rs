@@ -0,0 +1,369 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::state::{IssueLink, TicketLink, TicketProviderConfig, TicketProviderKind};
+use crate::utils::split_command_line;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GithubIssue {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub body: String,
+}
+
+impl GithubIssue {
+    pub fn link(&self) -> IssueLink {
+        IssueLink {
+            number: self.number,
+            title: self.title.clone(),
+            url: self.url.clone(),
+        }
+    }
+
+    /// Branch name derived from the issue, e.g. `123-fix-login-timeout`
+    pub fn branch_name(&self) -> String {
+        let slug = slugify_title(&self.title);
+        if slug.is_empty() {
+            self.number.to_string()
+        } else {
+            format!("{}-{}", self.number, slug)
+        }
+    }
+
+    /// Initial instruction for an agent working on the issue: its title and body.
+    pub fn prompt(&self) -> String {
+        let body = self.body.trim();
+        if body.is_empty() {
+            format!("#{} {}", self.number, self.title)
+        } else {
+            format!("#{} {}\n\n{}", self.number, self.title, body)
+        }
+    }
+}
+
+pub fn parse_issue_number(input: &str) -> Result<u64> {
+    input
+        .trim()
+        .trim_start_matches('#')
+        .parse::<u64>()
+        .with_context(|| format!("Invalid issue number: {input}"))
+}
+
+pub fn fetch_github_issue(number: u64, repo_dir: Option<&Path>) -> Result<GithubIssue> {
+    let mut cmd = Command::new("gh");
+    cmd.args([
+        "issue",
+        "view",
+        &number.to_string(),
+        "--json",
+        "number,title,url,body",
+    ]);
+    if let Some(dir) = repo_dir {
+        cmd.current_dir(dir);
+    }
+
+    let output = cmd
+        .output()
+        .context("Failed to run 'gh'. Install the GitHub CLI to use --issue")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to fetch issue #{}: {}",
+            number,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    serde_json::from_slice(&output.stdout)
+        .with_context(|| format!("Failed to parse issue #{number} from gh output"))
+}
+
+impl TicketLink {
+    /// Branch name derived from the ticket, e.g. `PROJ-42-fix-login-timeout`
+    pub fn branch_name(&self) -> String {
+        let slug = slugify_title(&self.summary);
+        if slug.is_empty() {
+            self.key.clone()
+        } else {
+            format!("{}-{}", self.key, slug)
+        }
+    }
+}
+
+pub fn parse_ticket_key(input: &str) -> Result<String> {
+    let key = input.trim().to_uppercase();
+    let valid = key.split_once('-').is_some_and(|(project, number)| {
+        !project.is_empty()
+            && project.chars().all(|c| c.is_ascii_alphanumeric())
+            && !number.is_empty()
+            && number.chars().all(|c| c.is_ascii_digit())
+    });
+    if !valid {
+        anyhow::bail!("Invalid ticket key: {input} (expected something like PROJ-42)");
+    }
+    Ok(key)
+}
+
+pub fn fetch_ticket(key: &str, providers: &[TicketProviderConfig]) -> Result<TicketLink> {
+    let project = key.split('-').next().unwrap_or_default();
+    let provider = providers
+        .iter()
+        .find(|p| {
+            p.projects.is_empty() || p.projects.iter().any(|k| k.eq_ignore_ascii_case(project))
+        })
+        .with_context(|| {
+            format!(
+                "No ticket provider configured for '{key}'. Add one under \"ticket_providers\" with 'xlaude config'"
+            )
+        })?;
+
+    match provider.kind {
+        TicketProviderKind::Jira => fetch_jira_ticket(key, provider),
+        TicketProviderKind::Linear => fetch_linear_ticket(key, provider),
+        TicketProviderKind::Command => fetch_command_ticket(key, provider),
+    }
+}
+
+fn fetch_jira_ticket(key: &str, provider: &TicketProviderConfig) -> Result<TicketLink> {
+    let base_url = provider
+        .base_url
+        .as_deref()
+        .context("Jira ticket provider requires \"base_url\"")?
+        .trim_end_matches('/');
+    let user = read_env(provider.user_env.as_deref().unwrap_or("JIRA_USER"))?;
+    let token = read_env(provider.token_env.as_deref().unwrap_or("JIRA_API_TOKEN"))?;
+
+    let response = run_curl(
+        &[
+            "-H",
+            "Accept: application/json",
+            &format!("{base_url}/rest/api/2/issue/{key}?fields=summary"),
+        ],
+        &[("user", format!("{user}:{token}"))],
+    )?;
+
+    let summary = response
+        .pointer("/fields/summary")
+        .and_then(Value::as_str)
+        .with_context(|| format!("Jira did not return a summary for {key}"))?;
+
+    Ok(TicketLink {
+        key: key.to_string(),
+        summary: summary.to_string(),
+        url: Some(format!("{base_url}/browse/{key}")),
+    })
+}
+
+fn fetch_linear_ticket(key: &str, provider: &TicketProviderConfig) -> Result<TicketLink> {
+    let token = read_env(provider.token_env.as_deref().unwrap_or("LINEAR_API_KEY"))?;
+    let query = serde_json::json!({
+        "query": "query($id: String!) { issue(id: $id) { title url } }",
+        "variables": { "id": key },
+    });
+
+    let response = run_curl(
+        &[
+            "-X",
+            "POST",
+            "-H",
+            "Content-Type: application/json",
+            "--data",
+            &query.to_string(),
+            "https://api.linear.app/graphql",
+        ],
+        &[("header", format!("Authorization: {token}"))],
+    )?;
+
+    let issue = response
+        .pointer("/data/issue")
+        .filter(|v| !v.is_null())
+        .with_context(|| format!("Linear did not return an issue for {key}"))?;
+    let summary = issue
+        .get("title")
+        .and_then(Value::as_str)
+        .with_context(|| format!("Linear did not return a title for {key}"))?;
+
+    Ok(TicketLink {
+        key: key.to_string(),
+        summary: summary.to_string(),
+        url: issue.get("url").and_then(Value::as_str).map(String::from),
+    })
+}
+
+// The command prints JSON with at least a "summary" field and optionally a "url"
+fn fetch_command_ticket(key: &str, provider: &TicketProviderConfig) -> Result<TicketLink> {
+    let command = provider
+        .command
+        .as_deref()
+        .context("Command ticket provider requires \"command\"")?
+        .replace("{key}", key);
+    let (program, args) = split_command_line(&command)?;
+
+    let output = Command::new(&program)
+        .args(&args)
+        .output()
+        .with_context(|| format!("Failed to run ticket command '{program}'"))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Ticket command failed for {}: {}",
+            key,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let value: Value = serde_json::from_slice(&output.stdout)
+        .context("Ticket command did not print valid JSON")?;
+    let summary = value
+        .get("summary")
+        .and_then(Value::as_str)
+        .context("Ticket command output is missing \"summary\"")?;
+
+    Ok(TicketLink {
+        key: key.to_string(),
+        summary: summary.to_string(),
+        url: value.get("url").and_then(Value::as_str).map(String::from),
+    })
+}
+
+fn read_env(name: &str) -> Result<String> {
+    std::env::var(name).with_context(|| format!("Environment variable {name} is not set"))
+}
+
+/// Run curl with `args` on its command line and `secrets` (option name and
+/// value) in a config read from stdin, so credentials never show up in `ps`.
+fn run_curl(args: &[&str], secrets: &[(&str, String)]) -> Result<Value> {
+    let mut child = Command::new("curl")
+        .args(["-sS", "--fail", "-K", "-"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run 'curl'. It is required to fetch tickets")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(curl_config(secrets).as_bytes())
+            .context("Failed to pass credentials to curl")?;
+    }
+    let output = child
+        .wait_with_output()
+        .context("Failed to run 'curl'. It is required to fetch tickets")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Ticket request failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    serde_json::from_slice(&output.stdout).context("Ticket provider returned invalid JSON")
+}
+
+// A curl config file setting each option to its quoted value
+fn curl_config(options: &[(&str, String)]) -> String {
+    options
+        .iter()
+        .map(|(name, value)| {
+            let mut quoted = String::with_capacity(value.len());
+            for c in value.chars() {
+                match c {
+                    '\\' => quoted.push_str("\\\\"),
+                    '"' => quoted.push_str("\\\""),
+                    '\n' => quoted.push_str("\\n"),
+                    '\r' => quoted.push_str("\\r"),
+                    '\t' => quoted.push_str("\\t"),
+                    c => quoted.push(c),
+                }
+            }
+            format!("{name} = \"{quoted}\"\n")
+        })
+        .collect()
+}
+
+const MAX_SLUG_WORDS: usize = 6;
+const MAX_SLUG_LEN: usize = 40;
+
+fn slugify_title(title: &str) -> String {
+    let lowered = title.to_lowercase();
+    let words: Vec<&str> = lowered
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .take(MAX_SLUG_WORDS)
+        .collect();
+
+    let mut slug = String::new();
+    for word in words {
+        let extra = if slug.is_empty() { 0 } else { 1 };
+        if !slug.is_empty() && slug.len() + extra + word.len() > MAX_SLUG_LEN {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(word);
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    slug
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(number: u64, title: &str) -> GithubIssue {
+        GithubIssue {
+            number,
+            title: title.to_string(),
+            url: String::new(),
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_curl_config_quotes_secrets() {
+        assert_eq!(
+            curl_config(&[
+                ("user", "me@example.com:t\"o\\k".to_string()),
+                ("header", "Authorization: lin_api".to_string()),
+            ]),
+            "user = \"me@example.com:t\\\"o\\\\k\"\nheader = \"Authorization: lin_api\"\n"
+        );
+    }
+
+    #[test]
+    fn test_branch_name_from_issue() {
+        assert_eq!(
+            issue(123, "Fix login timeout").branch_name(),
+            "123-fix-login-timeout"
+        );
+        assert_eq!(
+            issue(7, "[Bug] Crash on `xlaude open` (macOS)!").branch_name(),
+            "7-bug-crash-on-xlaude-open-macos"
+        );
+        assert_eq!(issue(42, "???").branch_name(), "42");
+    }
+
+    #[test]
+    fn test_ticket_key_and_branch_name() {
+        assert_eq!(parse_ticket_key("proj-42").unwrap(), "PROJ-42");
+        assert!(parse_ticket_key("PROJ").is_err());
+        assert!(parse_ticket_key("PROJ-x1").is_err());
+
+        let ticket = TicketLink {
+            key: "PROJ-42".to_string(),
+            summary: "Fix login timeout".to_string(),
+            url: None,
+        };
+        assert_eq!(ticket.branch_name(), "PROJ-42-fix-login-timeout");
+    }
+
+    #[test]
+    fn test_parse_issue_number() {
+        assert_eq!(parse_issue_number("#12").unwrap(), 12);
+        assert_eq!(parse_issue_number("12").unwrap(), 12);
+        assert!(parse_issue_number("abc").is_err());
+    }
+}
@@ -0,0 +1,462 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use crate::claude;
+use crate::codex;
+use crate::time_window::TimeWindow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Turn {
+    pub role: Role,
+    pub text: String,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// One agent session, reduced to the user and assistant messages it contains.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transcript {
+    pub provider: String,
+    pub session_id: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub turns: Vec<Turn>,
+}
+
+impl Transcript {
+    /// The transcript reduced to the messages sent inside `window`, or `None`
+    /// when none were.
+    pub fn within(mut self, window: &TimeWindow) -> Option<Self> {
+        if window.is_bounded() {
+            self.turns.retain(|turn| window.contains(turn.timestamp));
+        }
+        (!self.turns.is_empty()).then_some(self)
+    }
+}
+
+/// Where a page of transcripts ended, handed to API clients as an opaque
+/// token. Sessions recorded in the meantime do not shift the pages after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    updated_at: Option<DateTime<Utc>>,
+    session_id: String,
+}
+
+impl Cursor {
+    fn after(transcript: &Transcript) -> Self {
+        Self {
+            updated_at: transcript.updated_at,
+            session_id: transcript.session_id.clone(),
+        }
+    }
+
+    /// The token: `<updated_at>|<session id>`, hex-encoded.
+    pub fn encode(&self) -> String {
+        let updated_at = self
+            .updated_at
+            .map(|at| at.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+            .unwrap_or_default();
+        format!("{updated_at}|{}", self.session_id)
+            .bytes()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    /// `None` for a token `encode` did not produce.
+    pub fn decode(token: &str) -> Option<Self> {
+        if !token.len().is_multiple_of(2) {
+            return None;
+        }
+        let bytes = (0..token.len())
+            .step_by(2)
+            .map(|at| u8::from_str_radix(token.get(at..at + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        let text = String::from_utf8(bytes).ok()?;
+        let (updated_at, session_id) = text.split_once('|')?;
+        let updated_at = match updated_at {
+            "" => None,
+            at => Some(DateTime::parse_from_rfc3339(at).ok()?.with_timezone(&Utc)),
+        };
+        Some(Self {
+            updated_at,
+            session_id: session_id.to_string(),
+        })
+    }
+}
+
+/// Up to `limit` of `transcripts` after `cursor`, newest first, and the
+/// cursor of the next page when there is one.
+pub fn page(
+    mut transcripts: Vec<Transcript>,
+    cursor: Option<&Cursor>,
+    limit: usize,
+) -> (Vec<Transcript>, Option<Cursor>) {
+    transcripts.sort_by(|a, b| {
+        (Reverse(a.updated_at), &a.session_id).cmp(&(Reverse(b.updated_at), &b.session_id))
+    });
+    if let Some(cursor) = cursor {
+        let last = (Reverse(cursor.updated_at), &cursor.session_id);
+        transcripts
+            .retain(|transcript| (Reverse(transcript.updated_at), &transcript.session_id) > last);
+    }
+    let next =
+        (transcripts.len() > limit && limit > 0).then(|| Cursor::after(&transcripts[limit - 1]));
+    transcripts.truncate(limit);
+    (transcripts, next)
+}
+
+/// Text of a message `content` field, which is either a plain string or a list of
+/// blocks. Non-text blocks such as tool calls and images are skipped.
+pub fn message_text(content: &Value) -> Option<String> {
+    if let Some(text) = content.as_str() {
+        return Some(text.to_string());
+    }
+
+    let segments: Vec<&str> = content
+        .as_array()?
+        .iter()
+        // Tool results also carry a `content` string, but they are not messages
+        .filter(|block| block.get("type").and_then(Value::as_str) != Some("tool_result"))
+        .filter_map(|block| {
+            block
+                .get("text")
+                .and_then(Value::as_str)
+                .or_else(|| block.get("content").and_then(Value::as_str))
+        })
+        .collect();
+    (!segments.is_empty()).then(|| segments.join("\n"))
+}
+
+/// Whether a "user" message was injected by the agent itself (slash command
+/// output, environment context, interruptions) rather than typed by the user.
+pub fn is_system_message(text: &str) -> bool {
+    let trimmed = text.trim_start();
+    trimmed.starts_with("<local-command")
+        || trimmed.starts_with("<command-")
+        || trimmed.starts_with("<environment_context>")
+        || trimmed.starts_with("<user_instructions>")
+        || trimmed.starts_with("<system-reminder>")
+        // Shell commands run with `!` and their output
+        || trimmed.starts_with("<bash-")
+        || trimmed.starts_with("Caveat:")
+        || trimmed.contains("[Request interrupted")
+}
+
+/// Directory holding one directory of sessions per Claude project.
+pub fn claude_projects_dir() -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    Some(Path::new(&home).join(".claude").join("projects"))
+}
+
+/// Directory where Claude stores the sessions of a project.
+pub fn claude_project_dir(project_path: &Path) -> Option<PathBuf> {
+    let canonical_path = project_path.canonicalize().ok()?;
+    // Claude encodes the project path by replacing / with -
+    let encoded_path = canonical_path.to_string_lossy().replace('/', "-");
+    Some(claude_projects_dir()?.join(encoded_path))
+}
+
+/// When an agent last wrote to a Claude or Codex session of a worktree, taken
+/// from the session files' modification times.
+pub fn last_session_activity(worktree_path: &Path) -> Option<DateTime<Utc>> {
+    let mut latest = None;
+    if let Some(entries) = claude_project_dir(worktree_path).and_then(|dir| fs::read_dir(dir).ok())
+    {
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_some_and(|ext| ext == "jsonl") {
+                latest = latest.max(modified_at(&path));
+            }
+        }
+    }
+    if let Ok(Some(session)) = codex::find_latest_session(worktree_path) {
+        latest = latest
+            .max(modified_at(&session.path))
+            .max(session.last_timestamp);
+    }
+    latest
+}
+
+fn modified_at(path: &Path) -> Option<DateTime<Utc>> {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .map(DateTime::<Utc>::from)
+}
+
+/// The most recent `limit` Claude and Codex transcripts recorded in a worktree,
+/// newest first.
+pub fn recent_transcripts(worktree_path: &Path, limit: usize) -> Result<Vec<Transcript>> {
+    transcripts_in(worktree_path, limit, &TimeWindow::default())
+}
+
+/// Like [`recent_transcripts`], keeping only messages sent inside `window`.
+/// Session files that cannot reach the window are skipped without being read.
+pub fn transcripts_in(
+    worktree_path: &Path,
+    limit: usize,
+    window: &TimeWindow,
+) -> Result<Vec<Transcript>> {
+    let mut transcripts = Vec::new();
+
+    if let Some(dir) = claude_project_dir(worktree_path)
+        && let Ok(entries) = fs::read_dir(&dir)
+    {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"))
+                && window.admits_file(&path)
+                && let Some(transcript) = read_claude_transcript(&path)?
+                && let Some(transcript) = transcript.within(window)
+            {
+                transcripts.push(transcript);
+            }
+        }
+    }
+
+    let (codex_sessions, _) = codex::recent_sessions_in(worktree_path, limit, window)?;
+    for session in codex_sessions {
+        if let Some(transcript) = read_codex_transcript(&session.path)?
+            && let Some(transcript) = transcript.within(window)
+        {
+            transcripts.push(transcript);
+        }
+    }
+
+    transcripts.sort_by_key(|transcript| std::cmp::Reverse(transcript.updated_at));
+    transcripts.truncate(limit);
+    Ok(transcripts)
+}
+
+pub fn read_claude_transcript(path: &Path) -> Result<Option<Transcript>> {
+    let session_id = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut turns = Vec::new();
+
+    for value in read_json_lines(path)? {
+        if let Some((role, text)) = claude::conversation_message(&value) {
+            push_turn(&mut turns, role, text, parse_timestamp(&value));
+        }
+    }
+
+    Ok(finish(turns, "Claude", session_id))
+}
+
+pub fn read_codex_transcript(path: &Path) -> Result<Option<Transcript>> {
+    let mut session_id = String::new();
+    let mut turns = Vec::new();
+
+    for value in read_json_lines(path)? {
+        let Some(payload) = value.get("payload") else {
+            continue;
+        };
+        match value.get("type").and_then(Value::as_str) {
+            Some("session_meta") => {
+                session_id = payload
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string();
+            }
+            Some("response_item")
+                if payload.get("type").and_then(Value::as_str) == Some("message") =>
+            {
+                let role = match payload.get("role").and_then(Value::as_str) {
+                    Some("user") => Role::User,
+                    Some("assistant") => Role::Assistant,
+                    _ => continue,
+                };
+                if let Some(text) = payload.get("content").and_then(message_text) {
+                    push_turn(&mut turns, role, text, parse_timestamp(&value));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(finish(turns, "Codex", session_id))
+}
+
+pub(crate) fn read_json_lines(path: &Path) -> Result<Vec<Value>> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open session file: {}", path.display()))?;
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect())
+}
+
+pub(crate) fn parse_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    value
+        .get("timestamp")
+        .and_then(Value::as_str)
+        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+        .map(|ts| ts.with_timezone(&Utc))
+}
+
+fn push_turn(turns: &mut Vec<Turn>, role: Role, text: String, timestamp: Option<DateTime<Utc>>) {
+    if text.trim().is_empty() || (role == Role::User && is_system_message(&text)) {
+        return;
+    }
+    turns.push(Turn {
+        role,
+        text,
+        timestamp,
+    });
+}
+
+fn finish(turns: Vec<Turn>, provider: &'static str, session_id: String) -> Option<Transcript> {
+    if turns.is_empty() {
+        return None;
+    }
+    Some(Transcript {
+        provider: provider.to_string(),
+        session_id,
+        started_at: turns.iter().find_map(|turn| turn.timestamp),
+        updated_at: turns.iter().rev().find_map(|turn| turn.timestamp),
+        turns,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+    use std::io::Write;
+
+    #[test]
+    fn pages_follow_cursors() {
+        let transcript = |id: &str, day: u32| Transcript {
+            provider: "Claude".into(),
+            session_id: id.into(),
+            started_at: None,
+            updated_at: Some(Utc.with_ymd_and_hms(2025, 1, day, 9, 0, 0).unwrap()),
+            turns: Vec::new(),
+        };
+        let ids = |page: &[Transcript]| -> Vec<String> {
+            page.iter().map(|t| t.session_id.clone()).collect()
+        };
+        let all = vec![
+            transcript("a", 1),
+            transcript("c", 3),
+            transcript("b", 3),
+            transcript("d", 2),
+        ];
+
+        let (first, next) = page(all.clone(), None, 2);
+        assert_eq!(ids(&first), ["b", "c"]);
+        let token = next.unwrap().encode();
+        let cursor = Cursor::decode(&token).unwrap();
+
+        // A session recorded meanwhile does not shift the next page
+        let mut grown = all.clone();
+        grown.push(transcript("e", 4));
+        let (second, next) = page(grown, Some(&cursor), 2);
+        assert_eq!(ids(&second), ["d", "a"]);
+        assert!(next.is_none());
+
+        assert!(Cursor::decode("zz").is_none());
+        assert!(Cursor::decode("abc").is_none());
+    }
+
+    #[test]
+    fn extracts_text_from_content_blocks() {
+        assert_eq!(message_text(&json!("hi")).as_deref(), Some("hi"));
+        assert_eq!(
+            message_text(&json!([
+                { "type": "text", "text": "one" },
+                { "type": "tool_use", "name": "Bash" },
+                { "type": "tool_result", "content": "output" },
+                { "type": "input_text", "text": "two" }
+            ]))
+            .as_deref(),
+            Some("one\ntwo")
+        );
+        assert_eq!(message_text(&json!([{ "type": "tool_use" }])), None);
+    }
+
+    #[test]
+    fn reads_claude_transcript_without_system_messages() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for line in [
+            json!({ "type": "user", "timestamp": "2025-01-01T10:00:00Z", "message": { "content": "fix the bug" } }),
+            json!({ "type": "assistant", "timestamp": "2025-01-01T10:00:05Z", "message": { "content": [{ "type": "text", "text": "Done" }] } }),
+            json!({ "type": "user", "timestamp": "2025-01-01T10:01:00Z", "message": { "content": "<command-name>/clear</command-name>" } }),
+            json!({ "type": "summary", "summary": "ignored" }),
+        ] {
+            writeln!(file, "{line}").unwrap();
+        }
+
+        let transcript = read_claude_transcript(file.path()).unwrap().unwrap();
+        assert_eq!(transcript.provider, "Claude");
+        assert_eq!(transcript.turns.len(), 2);
+        assert_eq!(transcript.turns[0].role, Role::User);
+        assert_eq!(transcript.turns[1].text, "Done");
+        assert_eq!(
+            transcript.updated_at.unwrap().to_rfc3339(),
+            "2025-01-01T10:00:05+00:00"
+        );
+    }
+
+    #[test]
+    fn reads_claude_transcript_without_injected_or_subagent_lines() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/claude");
+        let texts = |file: &str| -> Vec<(Role, String)> {
+            read_claude_transcript(&fixtures.join(file))
+                .unwrap()
+                .map(|transcript| {
+                    transcript
+                        .turns
+                        .into_iter()
+                        .map(|turn| (turn.role, turn.text))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        assert_eq!(
+            texts("v0.2.jsonl"),
+            [
+                (Role::User, "Add a retry to the upload client".to_string()),
+                (
+                    Role::Assistant,
+                    "I'll look at the client first.".to_string()
+                ),
+                (
+                    Role::Assistant,
+                    "Added a retry with exponential backoff around send().".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            texts("v2.0.jsonl"),
+            [
+                (Role::User, "Make the backoff configurable".to_string()),
+                (
+                    Role::Assistant,
+                    "Adding a backoff setting to config.rs.".to_string()
+                ),
+            ]
+        );
+        assert!(texts("v2.0-agent.jsonl").is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+use chrono::{DateTime, Duration, Utc};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::state::get_config_dir;
+
+// Turns the check off, like the `no_update_check` setting
+pub const DISABLE_ENV: &str = "XLAUDE_NO_UPDATE_CHECK";
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const RELEASES_URL: &str = "https://crates.io/api/v1/crates/xlaude";
+// The registry is asked, and an outdated version reported, at most this often
+const CHECK_INTERVAL_HOURS: i64 = 24;
+const FETCH_TIMEOUT_SECS: &str = "10";
+
+/// What the last check found, persisted in `update-check.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UpdateCache {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    checked_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    latest: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notified_at: Option<DateTime<Utc>>,
+}
+
+/// Tell the user about a newer release, at most once a day. The registry is
+/// queried by a detached `curl` whose answer is picked up by a later run, so
+/// commands never wait for the network and never fail because of it.
+pub fn check(disabled_by_setting: bool) {
+    if disabled_by_setting
+        || std::env::var_os(DISABLE_ENV).is_some()
+        || std::env::var_os("XLAUDE_TEST_MODE").is_some()
+        || !atty::is(atty::Stream::Stderr)
+    {
+        return;
+    }
+    let Ok(dir) = get_config_dir() else {
+        return;
+    };
+    let cache_path = dir.join("update-check.json");
+    let response_path = dir.join("update-check.response");
+    let mut cache: UpdateCache = fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    if let Some(latest) = fs::read_to_string(&response_path)
+        .ok()
+        .and_then(|body| latest_version(&body))
+    {
+        cache.latest = Some(latest);
+    }
+    let _ = fs::remove_file(&response_path);
+
+    let now = Utc::now();
+    let due = |at: Option<DateTime<Utc>>| {
+        at.is_none_or(|at| now - at >= Duration::hours(CHECK_INTERVAL_HOURS))
+    };
+    if let Some(latest) = cache.latest.as_deref()
+        && is_newer(latest, CURRENT_VERSION)
+        && due(cache.notified_at)
+    {
+        eprintln!(
+            "{} xlaude {} is available (you have {}); run {} to upgrade",
+            "💡".yellow(),
+            latest.green(),
+            CURRENT_VERSION,
+            "cargo install xlaude".cyan()
+        );
+        cache.notified_at = Some(now);
+    }
+    if due(cache.checked_at) && fetch(response_path).is_ok() {
+        cache.checked_at = Some(now);
+    }
+
+    if fs::create_dir_all(&dir).is_ok()
+        && let Ok(content) = serde_json::to_string_pretty(&cache)
+    {
+        let _ = fs::write(&cache_path, content);
+    }
+}
+
+/// Start downloading the registry's answer into `path` without waiting for it.
+fn fetch(path: PathBuf) -> std::io::Result<()> {
+    Command::new("curl")
+        .args(["-sS", "--fail", "--max-time", FETCH_TIMEOUT_SECS])
+        .args(["-A", &format!("xlaude/{CURRENT_VERSION} (update check)")])
+        .arg("-o")
+        .arg(path)
+        .arg(RELEASES_URL)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+}
+
+/// The newest stable version in a crates.io crate response.
+fn latest_version(body: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    value["crate"]["max_stable_version"]
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Compare `major.minor.patch`; anything unparsable is never newer.
+fn is_newer(candidate: &str, current: &str) -> bool {
+    let parse = |version: &str| -> Option<Vec<u64>> {
+        let core = version.trim().trim_start_matches('v');
+        let core = core.split(['-', '+']).next()?;
+        core.split('.').map(|part| part.parse().ok()).collect()
+    };
+    match (parse(candidate), parse(current)) {
+        (Some(candidate), Some(current)) => candidate > current,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newer_releases_are_detected() {
+        assert!(is_newer("0.8.0", "0.7.0"));
+        assert!(is_newer("0.7.10", "0.7.9"));
+        assert!(is_newer("v1.0.0", "0.7.0"));
+        assert!(!is_newer("0.7.0", "0.7.0"));
+        assert!(!is_newer("0.6.9", "0.7.0"));
+        assert!(!is_newer("not-a-version", "0.7.0"));
+        assert_eq!(
+            latest_version(r#"{"crate": {"max_stable_version": "0.8.1"}}"#).as_deref(),
+            Some("0.8.1")
+        );
+        assert_eq!(latest_version("<html>rate limited</html>"), None);
+    }
+}
@@ -0,0 +1,246 @@
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::ops::AddAssign;
+use std::path::Path;
+
+use crate::time_window::TimeWindow;
+use crate::transcript::{claude_project_dir, parse_timestamp, read_json_lines};
+
+// Prompt caching discounts relative to the model's input price
+const CACHE_READ_FACTOR: f64 = 0.1;
+const CACHE_WRITE_FACTOR: f64 = 1.25;
+
+/// Model tokens billed for a set of sessions. Cached prompt tokens count as
+/// input.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    // Estimated from list prices; see `model_prices`
+    pub cost_usd: f64,
+}
+
+impl TokenUsage {
+    pub fn total(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+impl AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cost_usd += other.cost_usd;
+    }
+}
+
+/// Tokens used by the Claude sessions of a worktree for messages sent
+/// inside `window`.
+pub fn claude_usage_in(worktree_path: &Path, window: &TimeWindow) -> Result<TokenUsage> {
+    let mut usage = TokenUsage::default();
+    if let Some(entries) = claude_project_dir(worktree_path).and_then(|dir| fs::read_dir(dir).ok())
+    {
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_some_and(|ext| ext == "jsonl") && window.admits_file(&path) {
+                usage += claude_usage(&read_json_lines(&path)?, window);
+            }
+        }
+    }
+    Ok(usage)
+}
+
+/// Tokens used by one Codex session for turns inside `window`.
+pub fn codex_usage_in(session_path: &Path, window: &TimeWindow) -> Result<TokenUsage> {
+    Ok(codex_usage(&read_json_lines(session_path)?, window))
+}
+
+/// Claude logs one line per content block of a response, each repeating the
+/// response's usage, so responses are counted once by message id.
+fn claude_usage(lines: &[Value], window: &TimeWindow) -> TokenUsage {
+    let mut seen = HashSet::new();
+    let mut usage = TokenUsage::default();
+    for line in lines {
+        if line.get("type").and_then(Value::as_str) != Some("assistant")
+            || !window.contains(parse_timestamp(line))
+        {
+            continue;
+        }
+        let Some(message) = line.get("message") else {
+            continue;
+        };
+        if let Some(id) = message.get("id").and_then(Value::as_str)
+            && !seen.insert(id.to_string())
+        {
+            continue;
+        }
+        let Some(counts) = message.get("usage") else {
+            continue;
+        };
+        let (input_price, output_price) =
+            model_prices(message.get("model").and_then(Value::as_str).unwrap_or(""));
+        let input = count(counts, "input_tokens");
+        let cache_write = count(counts, "cache_creation_input_tokens");
+        let cache_read = count(counts, "cache_read_input_tokens");
+        let output = count(counts, "output_tokens");
+        usage += TokenUsage {
+            input_tokens: input + cache_write + cache_read,
+            output_tokens: output,
+            cost_usd: (input as f64
+                + cache_write as f64 * CACHE_WRITE_FACTOR
+                + cache_read as f64 * CACHE_READ_FACTOR)
+                * input_price
+                / 1e6
+                + output as f64 * output_price / 1e6,
+        };
+    }
+    usage
+}
+
+/// Codex reports the usage of every turn in a `token_count` event, and the
+/// model in effect in the `turn_context` records before it.
+fn codex_usage(lines: &[Value], window: &TimeWindow) -> TokenUsage {
+    let mut usage = TokenUsage::default();
+    let mut model = "";
+    for line in lines {
+        let Some(payload) = line.get("payload") else {
+            continue;
+        };
+        match line.get("type").and_then(Value::as_str) {
+            Some("turn_context") => {
+                model = payload.get("model").and_then(Value::as_str).unwrap_or("");
+                continue;
+            }
+            Some("event_msg") if window.contains(parse_timestamp(line)) => {}
+            _ => continue,
+        }
+        if payload.get("type").and_then(Value::as_str) != Some("token_count") {
+            continue;
+        }
+        if let Some(counts) = payload.pointer("/info/last_token_usage") {
+            let (input_price, output_price) = model_prices(model);
+            let input = count(counts, "input_tokens");
+            // Cached tokens are part of `input_tokens`
+            let cached = count(counts, "cached_input_tokens").min(input);
+            let output = count(counts, "output_tokens");
+            usage += TokenUsage {
+                input_tokens: input,
+                output_tokens: output,
+                cost_usd: ((input - cached) as f64 + cached as f64 * CACHE_READ_FACTOR)
+                    * input_price
+                    / 1e6
+                    + output as f64 * output_price / 1e6,
+            };
+        }
+    }
+    usage
+}
+
+/// Approximate list prices in US dollars per million (input, output) tokens.
+/// Unknown models are priced like Claude Sonnet.
+fn model_prices(model: &str) -> (f64, f64) {
+    let model = model.to_ascii_lowercase();
+    if model.contains("opus-4-5") || model.contains("opus-4.5") {
+        (5.0, 25.0)
+    } else if model.contains("opus") {
+        (15.0, 75.0)
+    } else if model.contains("haiku") {
+        (1.0, 5.0)
+    } else if model.contains("gpt-5") && model.contains("mini") {
+        (0.25, 2.0)
+    } else if model.contains("gpt-5") || model.contains("codex") {
+        (1.25, 10.0)
+    } else if model.contains("o4-mini") {
+        (1.1, 4.4)
+    } else if model.contains("gpt-4.1") || model.starts_with("o3") {
+        (2.0, 8.0)
+    } else if model.contains("gemini") && model.contains("flash") {
+        (0.3, 2.5)
+    } else if model.contains("gemini") {
+        (1.25, 10.0)
+    } else {
+        (3.0, 15.0)
+    }
+}
+
+fn count(counts: &Value, field: &str) -> u64 {
+    counts.get(field).and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// Dollar amount for display, e.g. `$12.34`.
+pub fn format_dollars(amount: f64) -> String {
+    format!("${amount:.2}")
+}
+
+/// Compact token count for reports, e.g. `950`, `12.3k`, `4.1M`.
+pub fn format_tokens(tokens: u64) -> String {
+    match tokens {
+        0..1_000 => tokens.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", tokens as f64 / 1e3),
+        _ => format!("{:.1}M", tokens as f64 / 1e6),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn counts_claude_responses_once() {
+        let usage = json!({
+            "input_tokens": 10_000,
+            "cache_read_input_tokens": 90_000,
+            "output_tokens": 5_000
+        });
+        let lines = vec![
+            json!({ "type": "user", "timestamp": "2025-01-01T10:00:00Z" }),
+            json!({
+                "type": "assistant",
+                "timestamp": "2025-01-01T10:00:01Z",
+                "message": { "id": "msg_1", "model": "claude-sonnet-4-5", "usage": usage }
+            }),
+            json!({
+                "type": "assistant",
+                "timestamp": "2025-01-01T10:00:02Z",
+                "message": { "id": "msg_1", "usage": usage }
+            }),
+        ];
+        let total = claude_usage(&lines, &TimeWindow::default());
+        assert_eq!(total.input_tokens, 100_000);
+        assert_eq!(total.output_tokens, 5_000);
+        // (10k + 90k * 0.1) * $3/M + 5k * $15/M
+        assert!((total.cost_usd - 0.132).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sums_codex_turns_inside_the_window() {
+        let event = |timestamp: &str, input: u64| {
+            json!({
+                "type": "event_msg",
+                "timestamp": timestamp,
+                "payload": {
+                    "type": "token_count",
+                    "info": { "last_token_usage": { "input_tokens": input, "output_tokens": 1 } }
+                }
+            })
+        };
+        let lines = vec![
+            json!({ "type": "turn_context", "payload": { "model": "gpt-5-codex" } }),
+            event("2025-01-01T10:00:00Z", 100),
+            event("2025-01-03T10:00:00Z", 20),
+        ];
+        let window = TimeWindow {
+            since: Some("2025-01-02T00:00:00Z".parse().unwrap()),
+            until: None,
+        };
+        let usage = codex_usage(&lines, &window);
+        assert_eq!(usage.total(), 21);
+        assert!((usage.cost_usd - (20.0 * 1.25 + 10.0) / 1e6).abs() < 1e-12);
+        assert_eq!(format_tokens(950), "950");
+        assert_eq!(format_tokens(12_345), "12.3k");
+        assert_eq!(format_tokens(4_100_000), "4.1M");
+    }
+}
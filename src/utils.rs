@@ -1,9 +1,69 @@
 use anyhow::{Context, Result};
+use colored::Colorize;
 use rand::seq::IndexedRandom;
 use rand::{RngCore, SeedableRng};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::bail_with;
+use crate::error::{ErrorCategory, categorized};
+use crate::state::{NameGeneratorConfig, NameTheme, WorktreeInfo};
+use unicode_normalization::UnicodeNormalization;
+
+// Upper bound on `name_generator.words`; longer names stop being memorable
+const MAX_NAME_WORDS: usize = 5;
+
+const ADJECTIVES: &[&str] = &[
+    "amber", "bold", "brave", "bright", "calm", "clever", "cosmic", "crisp", "daring", "eager",
+    "fancy", "gentle", "glad", "golden", "happy", "humble", "jolly", "keen", "kind", "lively",
+    "lucky", "mellow", "merry", "mighty", "nimble", "noble", "polite", "proud", "quick", "quiet",
+    "rapid", "shiny", "silent", "sleek", "snowy", "steady", "sunny", "swift", "tidy", "witty",
+];
+
+const ANIMALS: &[&str] = &[
+    "badger", "beaver", "bison", "crane", "dingo", "dolphin", "eagle", "falcon", "ferret", "gecko",
+    "heron", "ibis", "jaguar", "koala", "lemur", "lynx", "marmot", "moose", "narwhal", "ocelot",
+    "orca", "otter", "panda", "pelican", "puffin", "quokka", "raven", "salmon", "seal", "sparrow",
+    "tapir", "tiger", "toucan", "turtle", "walrus", "weasel", "wombat", "yak", "zebra",
+];
+
+const SPACE: &[&str] = &[
+    "andromeda",
+    "apollo",
+    "asteroid",
+    "aurora",
+    "comet",
+    "corona",
+    "cosmos",
+    "eclipse",
+    "equinox",
+    "galaxy",
+    "gemini",
+    "halo",
+    "kepler",
+    "lunar",
+    "meteor",
+    "nebula",
+    "nova",
+    "orbit",
+    "orion",
+    "photon",
+    "pulsar",
+    "quasar",
+    "rocket",
+    "saturn",
+    "solstice",
+    "stellar",
+    "titan",
+    "vega",
+    "vortex",
+    "zenith",
+];
 
 pub fn generate_random_name() -> Result<String> {
+    let config = crate::state::XlaudeState::load()?
+        .name_generator
+        .unwrap_or_default();
+
     // Allow setting seed for testing
     let mut rng = if let Ok(seed_str) = std::env::var("XLAUDE_TEST_SEED") {
         let seed: u64 = seed_str.parse().unwrap_or(42);
@@ -12,32 +72,172 @@ pub fn generate_random_name() -> Result<String> {
         Box::new(rand::rng()) as Box<dyn RngCore>
     };
 
-    // Generate 128 bits of entropy for a 12-word mnemonic
-    let mut entropy = [0u8; 16];
-    rng.fill_bytes(&mut entropy);
+    generate_name_with(&config, &mut rng)
+}
 
-    let mnemonic = bip39::Mnemonic::from_entropy(&entropy)?;
-    let words: Vec<&str> = mnemonic.words().collect();
+/// Build a `-`-joined name from the configured word source. Themed lists put
+/// adjectives before the final noun (`brave-otter`).
+fn generate_name_with(config: &NameGeneratorConfig, rng: &mut dyn RngCore) -> Result<String> {
+    let count = config.words.unwrap_or(1);
+    if !(1..=MAX_NAME_WORDS).contains(&count) {
+        bail_with!(
+            ErrorCategory::Config,
+            "name_generator.words must be between 1 and {}, got {}",
+            MAX_NAME_WORDS,
+            count
+        );
+    }
 
-    // Use the same RNG for choosing the word
-    let mut chooser_rng = if let Ok(seed_str) = std::env::var("XLAUDE_TEST_SEED") {
-        let seed: u64 = seed_str.parse().unwrap_or(42);
-        rand::rngs::StdRng::seed_from_u64(seed)
+    let content;
+    let custom_words;
+    let (modifiers, nouns): (&[&str], &[&str]) = if let Some(path) = &config.wordlist {
+        content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read wordlist {}", path.display()))?;
+        custom_words = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect::<Vec<_>>();
+        if custom_words.is_empty() {
+            anyhow::bail!("Wordlist {} contains no words", path.display());
+        }
+        (&custom_words, &custom_words)
     } else {
-        let mut entropy_rng = rand::rng();
-        rand::rngs::StdRng::from_rng(&mut entropy_rng)
+        match config.theme.unwrap_or_default() {
+            NameTheme::Bip39 => {
+                let words = bip39::Language::English.word_list();
+                (words, words)
+            }
+            NameTheme::Animals => (ADJECTIVES, ANIMALS),
+            NameTheme::Space => (ADJECTIVES, SPACE),
+        }
     };
 
-    words
-        .choose(&mut chooser_rng)
-        .map(|&word| word.to_string())
-        .context("Failed to generate random name")
+    let mut words: Vec<&str> = Vec::with_capacity(count);
+    for index in 0..count {
+        let list = if index + 1 == count { nouns } else { modifiers };
+        // Avoid `otter-otter` unless the list is too small to do better
+        let word = (0..8)
+            .filter_map(|_| list.choose(rng).copied())
+            .find(|word| !words.contains(word))
+            .or_else(|| list.choose(rng).copied())
+            .context("Failed to generate random name")?;
+        words.push(word);
+    }
+
+    Ok(words.join("-"))
 }
 
+// Keeps `{repo}-{name}` well below the 255-byte file name limit
+const MAX_WORKTREE_NAME_BYTES: usize = 80;
+
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
 /// Sanitize a branch name for use in directory names
-/// Replaces forward slashes with hyphens to avoid creating subdirectories
+/// Path separators, characters invalid on common filesystems, and whitespace become
+/// hyphens; the result is NFC-normalized, free of `..`, not a Windows reserved name,
+/// and at most 80 bytes long.
 pub fn sanitize_branch_name(branch: &str) -> String {
-    branch.replace('/', "-")
+    let mut name = String::with_capacity(branch.len());
+    for ch in branch.nfc() {
+        let ch = match ch {
+            '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*' => '-',
+            ch if ch.is_control() || ch.is_whitespace() => '-',
+            ch => ch,
+        };
+        // Collapse runs of separators and dots so `a//b` and `x..y` stay readable
+        if matches!(ch, '-' | '.') && name.ends_with(ch) {
+            continue;
+        }
+        name.push(ch);
+    }
+
+    let mut name = trim_name(&name).to_string();
+    if name.len() > MAX_WORKTREE_NAME_BYTES {
+        let mut end = MAX_WORKTREE_NAME_BYTES;
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        name = trim_name(&name[..end]).to_string();
+    }
+
+    let stem = name.split('.').next().unwrap_or_default();
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+    {
+        name.insert(0, '_');
+    }
+
+    if name.is_empty() {
+        "worktree".to_string()
+    } else {
+        name
+    }
+}
+
+// Leading dots would hide the directory; trailing dots and hyphens are rejected or awkward on Windows
+fn trim_name(name: &str) -> &str {
+    name.trim_matches(|c| c == '-' || c == '.')
+}
+
+/// Return `base`, or `base-2`, `base-3`, ... for the first candidate that is not taken.
+pub fn with_numeric_suffix(base: &str, is_taken: impl Fn(&str) -> bool) -> String {
+    if !is_taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{base}-{n}"))
+        .find(|candidate| !is_taken(candidate))
+        .expect("unbounded suffix search")
+}
+
+/// Parse a human duration such as `90m`, `2h`, `7d`, or `2w`.
+pub fn parse_duration(input: &str) -> Result<chrono::Duration> {
+    let trimmed = input.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let value: i64 = number
+        .parse()
+        .with_context(|| format!("Invalid duration: {input}"))?;
+    let duration = match unit.trim().to_ascii_lowercase().as_str() {
+        "s" | "sec" | "secs" => chrono::TimeDelta::try_seconds(value),
+        "m" | "min" | "mins" => chrono::TimeDelta::try_minutes(value),
+        "h" | "hr" | "hrs" | "hour" | "hours" => chrono::TimeDelta::try_hours(value),
+        "d" | "day" | "days" => chrono::TimeDelta::try_days(value),
+        "w" | "week" | "weeks" => chrono::TimeDelta::try_weeks(value),
+        other => bail_with!(
+            ErrorCategory::Usage,
+            "Unknown duration unit '{}' in '{}' (use s, m, h, d, or w)",
+            other,
+            input
+        ),
+    };
+    match duration {
+        Some(duration) => Ok(duration),
+        None => bail_with!(ErrorCategory::Usage, "Duration '{}' is too long", input),
+    }
+}
+
+/// Match `text` against a pattern where `*` stands for any run of characters,
+/// including `/` (so `release/*` covers `release/1.2/hotfix`).
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((prefix, rest)) => {
+            let Some(text) = text.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=text.len())
+                .filter(|&start| text.is_char_boundary(start))
+                .any(|start| wildcard_match(rest, &text[start..]))
+        }
+    }
 }
 
 pub fn execute_in_dir<P, F, R>(path: P, f: F) -> Result<R>
@@ -57,16 +257,104 @@ where
 }
 
 /// Resolve agent command from state or default, and split into program + args.
-pub fn resolve_agent_command() -> Result<(String, Vec<String>)> {
+pub fn resolve_agent_command(repo_name: Option<&str>) -> Result<(String, Vec<String>)> {
     let state = crate::state::XlaudeState::load()?;
     let raw = state
-        .agent
-        .clone()
+        .agent_for(repo_name)
+        .map(str::to_string)
         .unwrap_or_else(crate::state::get_default_agent);
     let cmdline = normalize_agent_command(&raw);
     split_command_line(&cmdline)
 }
 
+/// The agent command for `worktree`, with the first installed entry of
+/// `agent_fallbacks` standing in when the configured agent is not installed,
+/// and the worktree's `agent_args` added.
+pub fn resolve_available_agent_command(worktree: &WorktreeInfo) -> Result<(String, Vec<String>)> {
+    let state = crate::state::XlaudeState::load()?;
+    let (program, mut args) = resolve_agent_command(Some(&worktree.repo_name))?;
+    args.extend(state.agent_args_for(worktree)?);
+    if find_executable(&program).is_some() {
+        return Ok((program, args));
+    }
+
+    for fallback in &state.agent_fallbacks {
+        let (fallback_program, mut fallback_args) =
+            split_command_line(&normalize_agent_command(fallback))?;
+        if find_executable(&fallback_program).is_some() {
+            eprintln!(
+                "{} Agent '{}' is not installed; falling back to '{}'",
+                "↪".yellow(),
+                program,
+                fallback.trim()
+            );
+            fallback_args.extend(state.agent_args_for(worktree)?);
+            return Ok((fallback_program, fallback_args));
+        }
+    }
+    Err(missing_agent_error(&program))
+}
+
+/// Error for an agent CLI that is not on PATH, with a hint on how to install it.
+pub fn missing_agent_error(program: &str) -> anyhow::Error {
+    categorized(
+        ErrorCategory::AgentFailure,
+        format!(
+            "Agent '{}' is not installed. {}",
+            program,
+            crate::agent::install_hint(program)
+        ),
+    )
+}
+
+/// Locate a program the way the shell would: paths are checked directly, bare
+/// names are searched on PATH.
+pub fn find_executable(program: &str) -> Option<PathBuf> {
+    let candidate = Path::new(program);
+    if candidate.components().count() > 1 {
+        return is_executable(candidate).then(|| candidate.to_path_buf());
+    }
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file() || path.with_extension("exe").is_file()
+}
+
+/// The xlaude binary: `XLAUDE_BIN` when set, which matters when xlaude runs
+/// inside another program (see `run_cli`), otherwise the running executable.
+pub fn xlaude_exe() -> Result<std::path::PathBuf> {
+    match std::env::var_os("XLAUDE_BIN").filter(|bin| !bin.is_empty()) {
+        Some(bin) => Ok(bin.into()),
+        None => std::env::current_exe().context("Failed to locate xlaude binary"),
+    }
+}
+
+/// Start another xlaude process in the background, detached from our stdio.
+pub fn spawn_detached_self(args: &[&str]) -> Result<()> {
+    let exe = xlaude_exe()?;
+    std::process::Command::new(exe)
+        .args(args)
+        .envs(crate::state::data_dir_env()?)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .context("Failed to start xlaude")?;
+    Ok(())
+}
+
 /// Split a command line string into executable and arguments using shell-style parsing.
 pub fn split_command_line(cmdline: &str) -> Result<(String, Vec<String>)> {
     let parts = shell_words::split(cmdline)
@@ -150,8 +438,11 @@ fn codex_has_positional_arguments(args: &[String]) -> bool {
     false
 }
 
-pub fn prepare_agent_command(worktree_path: &Path) -> Result<(String, Vec<String>)> {
-    let (program, args) = resolve_agent_command()?;
+/// The agent command for `worktree` with its `agent_args`, resuming the
+/// latest Codex session there when the command does not say otherwise.
+pub fn prepare_agent_command(worktree: &WorktreeInfo) -> Result<(String, Vec<String>)> {
+    let (program, mut args) = resolve_agent_command(Some(&worktree.repo_name))?;
+    args.extend(crate::state::XlaudeState::load()?.agent_args_for(worktree)?);
 
     if !program.eq_ignore_ascii_case("codex") {
         return Ok((program, args));
@@ -161,7 +452,7 @@ pub fn prepare_agent_command(worktree_path: &Path) -> Result<(String, Vec<String
         return Ok((program, args));
     }
 
-    let Some(session) = crate::codex::find_latest_session(worktree_path)? else {
+    let Some(session) = crate::codex::find_latest_session(&worktree.path)? else {
         return Ok((program, args));
     };
 
@@ -182,6 +473,108 @@ mod tests {
 
     static ENV_MUTEX: OnceLock<Mutex<()>> = OnceLock::new();
 
+    #[test]
+    fn sanitize_branch_name_handles_ugly_names() {
+        assert_eq!(sanitize_branch_name("feature/foo"), "feature-foo");
+        assert_eq!(sanitize_branch_name("feat/über//x..y"), "feat-über-x.y");
+        // Decomposed "u" + combining diaeresis is normalized to the composed form
+        assert_eq!(sanitize_branch_name("feat/u\u{308}ber"), "feat-über");
+        assert_eq!(sanitize_branch_name("fix: a|b <c>?"), "fix-a-b-c");
+        assert_eq!(sanitize_branch_name("../../etc"), "etc");
+        assert_eq!(sanitize_branch_name(".hidden."), "hidden");
+        assert_eq!(sanitize_branch_name("con"), "_con");
+        assert_eq!(sanitize_branch_name("LPT1.txt"), "_LPT1.txt");
+        assert_eq!(sanitize_branch_name("console"), "console");
+        assert_eq!(sanitize_branch_name("///"), "worktree");
+
+        let long = sanitize_branch_name(&"ü".repeat(100));
+        assert!(long.len() <= MAX_WORKTREE_NAME_BYTES);
+        assert!(long.chars().all(|c| c == 'ü'));
+    }
+
+    #[test]
+    fn parse_duration_accepts_common_units() {
+        assert_eq!(
+            parse_duration("90m").unwrap(),
+            chrono::Duration::minutes(90)
+        );
+        assert_eq!(parse_duration("2h").unwrap(), chrono::Duration::hours(2));
+        assert_eq!(parse_duration("7 days").unwrap(), chrono::Duration::days(7));
+        assert!(parse_duration("soon").is_err());
+        assert!(parse_duration("3y").is_err());
+        assert!(parse_duration("99999999999999d").is_err());
+        assert!(parse_duration("9223372036854775807w").is_err());
+    }
+
+    #[test]
+    fn wildcard_match_spans_path_separators() {
+        assert!(wildcard_match("main", "main"));
+        assert!(!wildcard_match("main", "main2"));
+        assert!(wildcard_match("release/*", "release/1.2/hotfix"));
+        assert!(!wildcard_match("release/*", "feature/release"));
+        assert!(wildcard_match("*-prod", "deploy-prod"));
+        assert!(wildcard_match("hot*fix*", "hotfix"));
+    }
+
+    #[test]
+    fn generate_name_uses_theme_and_word_count() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let config = NameGeneratorConfig {
+            words: Some(2),
+            theme: Some(NameTheme::Animals),
+            wordlist: None,
+        };
+        let name = generate_name_with(&config, &mut rng).unwrap();
+        let (adjective, animal) = name.split_once('-').unwrap();
+        assert!(ADJECTIVES.contains(&adjective));
+        assert!(ANIMALS.contains(&animal));
+
+        let default = generate_name_with(&NameGeneratorConfig::default(), &mut rng).unwrap();
+        assert!(
+            bip39::Language::English
+                .word_list()
+                .contains(&default.as_str())
+        );
+
+        let too_many = NameGeneratorConfig {
+            words: Some(MAX_NAME_WORDS + 1),
+            ..Default::default()
+        };
+        assert!(generate_name_with(&too_many, &mut rng).is_err());
+    }
+
+    #[test]
+    fn generate_name_reads_custom_wordlist() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("words.txt");
+        fs::write(&path, "# team names\n\nalpha\n  beta  \n").unwrap();
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let config = NameGeneratorConfig {
+            words: Some(2),
+            theme: Some(NameTheme::Space),
+            wordlist: Some(path.clone()),
+        };
+        let name = generate_name_with(&config, &mut rng).unwrap();
+        assert!(name == "alpha-beta" || name == "beta-alpha", "{name}");
+
+        fs::write(&path, "# nothing here\n").unwrap();
+        assert!(generate_name_with(&config, &mut rng).is_err());
+    }
+
+    #[test]
+    fn with_numeric_suffix_skips_taken_names() {
+        let taken = ["feat-a", "feat-a-2"];
+        assert_eq!(
+            with_numeric_suffix("feat-b", |n| taken.contains(&n)),
+            "feat-b"
+        );
+        assert_eq!(
+            with_numeric_suffix("feat-a", |n| taken.contains(&n)),
+            "feat-a-3"
+        );
+    }
+
     #[test]
     fn prepare_agent_command_resumes_latest_codex_session() {
         let _guard = ENV_MUTEX.get_or_init(|| Mutex::new(())).lock().unwrap();
@@ -251,7 +644,13 @@ mod tests {
                 ("XLAUDE_CODEX_SESSIONS_DIR", Some(sessions_dir_str.as_str())),
             ],
             || {
-                let (program, args) = prepare_agent_command(&worktree_path).unwrap();
+                let worktree = WorktreeInfo::new(
+                    "feature".to_string(),
+                    "feature".to_string(),
+                    worktree_path.clone(),
+                    "repo".to_string(),
+                );
+                let (program, args) = prepare_agent_command(&worktree).unwrap();
                 assert_eq!(program, "codex");
                 assert_eq!(args, vec!["resume".to_string(), "session-123".to_string()]);
             },
@@ -0,0 +1,222 @@
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::bail_with;
+use crate::commands::create::{CreateOptions, handle_create_in_dir_quiet};
+use crate::error::{ErrorCategory, WithCategory};
+use crate::git::{execute_git, find_main_repo};
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::tasks::{QueueLock, TaskQueue};
+use crate::utils::spawn_detached_self;
+
+// Overrides `webhook_token` from state.json, e.g. for secrets injected by a service manager
+const TOKEN_ENV: &str = "XLAUDE_WEBHOOK_TOKEN";
+
+/// Body of `POST /api/hooks/create`.
+#[derive(Debug, Deserialize)]
+pub struct CreateRequest {
+    // Repository name as known to xlaude, or an absolute path to its checkout
+    pub repo: String,
+    pub branch: String,
+    #[serde(default)]
+    pub base: Option<String>,
+    #[serde(default)]
+    pub open_agent: bool,
+    // Instruction for a headless agent run; without it the agent opens interactively
+    #[serde(default)]
+    pub prompt: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateResponse {
+    pub created: bool,
+    pub repo: String,
+    pub name: String,
+    pub branch: String,
+    pub path: PathBuf,
+    pub agent_started: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<u64>,
+}
+
+/// Check an `Authorization` header against the configured token.
+pub fn authorize(authorization: Option<&str>) -> Result<(), (StatusCode, String)> {
+    let configured = std::env::var(TOKEN_ENV)
+        .ok()
+        .or_else(|| {
+            XlaudeState::load()
+                .ok()
+                .and_then(|state| state.webhook_token)
+        })
+        .filter(|token| !token.is_empty());
+    let Some(expected) = configured else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Webhooks are disabled; set webhook_token in state.json".to_string(),
+        ));
+    };
+    let provided = authorization.and_then(|value| value.strip_prefix("Bearer "));
+    if !provided.is_some_and(|token| tokens_match(token.trim(), &expected)) {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid bearer token".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Compare without exiting early so response timing does not leak the token
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Create (or reuse) the worktree for `request.branch` and optionally start an agent in it.
+pub fn create_worktree(request: CreateRequest) -> Result<CreateResponse> {
+    let branch = request.branch.trim();
+    if branch.is_empty() {
+        bail_with!(ErrorCategory::Usage, "branch cannot be empty");
+    }
+    let state = XlaudeState::load()?;
+    let repo_path = resolve_repo(&state, &request.repo)?;
+    execute_git(&[
+        "-C",
+        &repo_path.to_string_lossy(),
+        "check-ref-format",
+        "--branch",
+        branch,
+    ])
+    .with_category(ErrorCategory::Usage, || {
+        format!("'{branch}' is not a valid branch name")
+    })?;
+
+    // Retried CI jobs should get the worktree created by the first attempt
+    let existing = state
+        .worktrees
+        .values()
+        .find(|info| {
+            info.branch == branch
+                && find_main_repo(&info.path, &info.repo_name)
+                    .and_then(|main| main.canonicalize().ok())
+                    .as_ref()
+                    == Some(&repo_path)
+        })
+        .cloned();
+    let created = existing.is_none();
+    let info = match existing {
+        Some(info) => info,
+        None => {
+            let name = handle_create_in_dir_quiet(
+                CreateOptions {
+                    name: Some(branch.to_string()),
+                    base: request.base.clone(),
+                    ..Default::default()
+                },
+                Some(repo_path.clone()),
+                true,
+            )?;
+            let state = XlaudeState::load()?;
+            state
+                .worktrees
+                .values()
+                .find(|info| info.name == name && info.branch == branch)
+                .cloned()
+                .with_category(
+                    ErrorCategory::General,
+                    || "Created worktree is missing from state",
+                )?
+        }
+    };
+
+    let mut task_id = None;
+    if request.open_agent {
+        task_id = start_agent(&info, request.prompt.as_deref())?;
+    }
+
+    Ok(CreateResponse {
+        created,
+        repo: info.repo_name,
+        name: info.name,
+        branch: info.branch,
+        path: info.path,
+        agent_started: request.open_agent,
+        task_id,
+    })
+}
+
+/// HTTP status for a failed webhook request.
+pub fn status_for(err: &anyhow::Error) -> StatusCode {
+    match ErrorCategory::of(err) {
+        ErrorCategory::NotFound => StatusCode::NOT_FOUND,
+        ErrorCategory::Usage => StatusCode::BAD_REQUEST,
+        ErrorCategory::Conflict => StatusCode::CONFLICT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn resolve_repo(state: &XlaudeState, repo: &str) -> Result<PathBuf> {
+    let path = Path::new(repo);
+    if path.is_absolute() {
+        if path.join(".git").exists() {
+            return path
+                .canonicalize()
+                .with_category(ErrorCategory::NotFound, || {
+                    format!("Cannot access '{}'", path.display())
+                });
+        }
+        bail_with!(
+            ErrorCategory::NotFound,
+            "'{}' is not a git repository",
+            path.display()
+        );
+    }
+    state
+        .worktrees
+        .values()
+        .filter(|info| info.repo_name == repo)
+        .find_map(|info| find_main_repo(&info.path, &info.repo_name)?.canonicalize().ok())
+        .with_category(ErrorCategory::NotFound, || {
+            format!(
+                "Repository '{repo}' is not known to xlaude; pass its absolute path or create a worktree in it first"
+            )
+        })
+}
+
+/// Queue `prompt` as a task and run it headlessly, or open the agent when there is
+/// no prompt. Returns the queued task id.
+fn start_agent(info: &WorktreeInfo, prompt: Option<&str>) -> Result<Option<u64>> {
+    let prompt = prompt.map(str::trim).filter(|prompt| !prompt.is_empty());
+    let Some(prompt) = prompt else {
+        spawn_detached_self(&["open", &info.name])?;
+        return Ok(None);
+    };
+
+    let lock = QueueLock::acquire()?;
+    let mut queue = TaskQueue::load()?;
+    let key = XlaudeState::make_key(&info.repo_name, &info.name);
+    let id = queue.push(key, prompt.to_string()).id;
+    queue.save()?;
+    drop(lock);
+    spawn_detached_self(&["run", &info.name])?;
+    Ok(Some(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_tokens_exactly() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secret", "secreT"));
+        assert!(!tokens_match("secret", "secret2"));
+        assert!(!tokens_match("", "secret"));
+    }
+}
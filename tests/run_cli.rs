@@ -0,0 +1,33 @@
+//! Output captured by `xlaude::run_cli`. The default test harness swaps in its
+//! own stdout and stderr, which the capture cannot see, so this test runs
+//! without it.
+
+use std::fs;
+
+fn main() {
+    let home = tempfile::tempdir().unwrap();
+    let config_dir = home.path().join(".config/xlaude");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(config_dir.join("state.json"), r#"{"worktrees": {}}"#).unwrap();
+    let env = [
+        ("HOME", home.path().to_str().unwrap()),
+        ("XLAUDE_CONFIG_DIR", config_dir.to_str().unwrap()),
+        ("XLAUDE_TEST_MODE", "1"),
+        ("NO_COLOR", "1"),
+        ("XLAUDE_NON_INTERACTIVE", "1"),
+        ("XLAUDE_LANG", "en"),
+    ];
+
+    let output = xlaude::run_cli(home.path(), ["list", "--json"], &env, "").unwrap();
+    assert!(output.success(), "{output:?}");
+    let listed: serde_json::Value = serde_json::from_str(&output.stdout).unwrap();
+    assert_eq!(listed["worktrees"], serde_json::json!([]));
+    assert_eq!(output.stderr, "");
+
+    let output = xlaude::run_cli(home.path(), ["list", "--bogus"], &env, "").unwrap();
+    assert_eq!(output.code, 8);
+    assert_eq!(output.stdout, "");
+    assert!(output.stderr.contains("'--bogus'"), "{output:?}");
+
+    println!("run_cli captures output: ok");
+}